@@ -1,5 +1,5 @@
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
@@ -9,11 +9,75 @@ use cargo_metadata::{Message, PackageId};
 /// Build the library for the specified package and return paths to the built cdylib files.
 ///
 /// If `target_triples` is provided, it can be used to specify custom target triples for cross-compilation.
-/// If `None`, the default host target is used.
+/// Each target is built by a separate cargo invocation, so that a failure can be attributed to
+/// its target. If `None`, the default host target is used.
+/// If `manifest_path` is provided, the package is built from that workspace instead of the current one.
 pub fn build_lib(
     package: &PackageId,
     target_triples: &Option<Vec<&'static platforms::Platform>>,
     release: bool,
+    manifest_path: Option<&Path>,
+) -> anyhow::Result<Vec<(Option<&'static platforms::Platform>, PathBuf)>> {
+    let Some(platforms) = target_triples else {
+        log::info!("No target triples specified, building for host platform");
+        return build_target(package, None, release, manifest_path);
+    };
+
+    check_installed_targets(platforms)?;
+
+    let mut dylib_paths = Vec::new();
+    for platform in platforms {
+        log::info!("Building for target {}", platform.target_triple);
+        let built =
+            build_target(package, Some(platform), release, manifest_path).with_context(|| {
+                format!(
+                    "Failed to build for target {triple}. Make sure its toolchain and linker are \
+                    installed, e.g. with `rustup target add {triple}` and a `linker` in the \
+                    `[target.{triple}]` section of `.cargo/config.toml`",
+                    triple = platform.target_triple
+                )
+            })?;
+        dylib_paths.extend(built);
+    }
+    Ok(dylib_paths)
+}
+
+/// Fail early if rustup is used and any of the targets is not installed.
+fn check_installed_targets(platforms: &[&'static platforms::Platform]) -> anyhow::Result<()> {
+    let output = match Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => {
+            log::debug!("rustup is not available, skipping the check for installed targets");
+            return Ok(());
+        }
+    };
+
+    let installed = String::from_utf8_lossy(&output.stdout);
+    let missing: Vec<&str> = platforms
+        .iter()
+        .map(|platform| platform.target_triple)
+        .filter(|triple| !installed.lines().any(|line| line.trim() == *triple))
+        .collect();
+
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Rust toolchain not installed for target(s): {}. Install with `rustup target add {}`",
+            missing.join(", "),
+            missing.join(" ")
+        );
+    }
+    Ok(())
+}
+
+/// Run `cargo build` for a single target, or for the host if `platform` is `None`.
+fn build_target(
+    package: &PackageId,
+    platform: Option<&'static platforms::Platform>,
+    release: bool,
+    manifest_path: Option<&Path>,
 ) -> anyhow::Result<Vec<(Option<&'static platforms::Platform>, PathBuf)>> {
     let mut command = Command::new("cargo");
 
@@ -21,12 +85,12 @@ pub fn build_lib(
         .args(&["build", "--lib", "--message-format=json-render-diagnostics"])
         .args(&["--package", &package.repr]);
 
-    if let Some(platforms) = &target_triples {
-        for platform in platforms {
-            command.args(&["--target", platform.target_triple]);
-        }
-    } else {
-        log::info!("No target triples specified, building for host platform");
+    if let Some(manifest_path) = manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+
+    if let Some(platform) = platform {
+        command.args(["--target", platform.target_triple]);
     }
 
     if release {
@@ -48,12 +112,6 @@ pub fn build_lib(
             Message::CompilerArtifact(artifact)
                 if artifact.target.is_cdylib() && artifact.package_id == *package =>
             {
-                // Heuristic to parse the target triple from the output filenames. When building for
-                // specific target triples, the outputs will be placed in subdirectories named after
-                // the target, for example "target/aarch64-apple-darwin/debug/libdahlquist.dylib".
-                // When not building for specific targets, the output will be in
-                // "target/debug/libdahlquist.dylib".
-
                 // Find the actual dylib file (not debug symbols like .pdb on Windows)
                 let path = artifact
                     .filenames
@@ -68,29 +126,7 @@ pub fn build_lib(
                         anyhow::anyhow!("No output filenames found for cdylib target")
                     })?;
 
-                let target = if let Some(_) = target_triples {
-                    // Extract the target triple from the path components. It should be the 3rd from last.
-                    Some(
-                        path.components()
-                            .nth_back(3)
-                            .and_then(|target_triple| {
-                                platforms::Platform::find(
-                                    &target_triple.as_os_str().to_string_lossy(),
-                                )
-                            })
-                            .ok_or_else(|| {
-                                anyhow::anyhow!(
-                                    "Could not determine target triple from path: {}",
-                                    path.display()
-                                )
-                            })?,
-                    )
-                } else {
-                    // No specific target triples were built, so this is the host platform
-                    None
-                };
-
-                dylib_paths.push((target, path));
+                dylib_paths.push((platform, path));
             }
             Message::BuildFinished(res) => {
                 build_success = res.success;
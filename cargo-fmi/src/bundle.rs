@@ -6,11 +6,13 @@ pub fn bundle(
     package: &Option<String>,
     target: &Option<Vec<String>>,
     release: bool,
+    source: bool,
 ) -> anyhow::Result<()> {
     let MetadataBuilder {
         package,
         model_identifier,
         target_dir,
+        workspace_metadata,
     } = MetadataBuilder::new(package.as_deref())?;
 
     let target_platforms = target
@@ -26,13 +28,35 @@ pub fn bundle(
         .transpose()?;
 
     // Build the cdylib for the specified targets or native if none specified
-    let cdylibs = crate::builder::build_lib(&package.id, &target_platforms, release)?;
+    let cdylibs = crate::builder::build_lib(&package.id, &target_platforms, release, None)?;
 
-    let mut model_data = crate::extractor::ModelData::new_from_dylib(&cdylibs[0].1)?;
+    let mut model_data = crate::extractor::extract_model_data(&cdylibs, || {
+        let host = crate::builder::build_lib(&package.id, &None, release, None)?;
+        Ok(host[0].1.clone())
+    })?;
     let terminals_and_icons = model_data.terminals_and_icons.take();
     let model_description =
         crate::metadata::create_model_description(&model_identifier, &package, model_data)?;
-    let build_description = crate::metadata::create_build_description(&model_identifier)?;
+    let manifest_dir = package
+        .manifest_path
+        .parent()
+        .expect("Manifest path has a parent directory");
+    let mut files =
+        crate::metadata::parse_package_files(&package)?.collect(manifest_dir.as_std_path())?;
+
+    // Source code FMUs additionally carry the vendored crates and a build description
+    let sources = source
+        .then(|| crate::sources::vendor(&workspace_metadata, &package))
+        .transpose()?;
+    let build_description = if let Some(sources) = &sources {
+        files.extend(sources.files.iter().cloned());
+        Some(crate::metadata::create_build_description(
+            &model_identifier,
+            &package,
+        )?)
+    } else {
+        None
+    };
 
     // Create FMU package
     let fmu_path = target_dir
@@ -47,6 +71,7 @@ pub fn bundle(
         terminals_and_icons,
         &fmu_path,
         &cdylibs,
+        &files,
     )?;
 
     Ok(())
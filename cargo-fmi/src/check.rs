@@ -0,0 +1,1299 @@
+//! Implements the `check` command to validate an FMU against rules of the FMI standard that are
+//! not enforced by the XML schema.
+//!
+//! The model description is checked for unique value references, resolvable `derivative`,
+//! `previous` and `clocks` references, valid combinations of causality, variability and initial,
+//! and a complete ModelStructure. The binaries of all platforms are checked for the required
+//! FMI functions, and the binary of the host platform is instantiated with the instantiation token
+//! of the model description, which it rejects if it was built for another model description.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet, btree_map::Entry},
+    fmt::Display,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use fmi::{
+    fmi2::schema as fmi2_schema,
+    fmi3::{
+        Fmi3Model,
+        schema::{
+            self as fmi3_schema, AbstractVariableTrait, ArrayableVariableTrait, Causality, Initial,
+            InitializableVariableTrait, Variability, Variable, VariableType,
+        },
+    },
+    schema::{MajorVersion, traits::FmiModelDescription},
+    traits::FmiImport,
+};
+
+/// Functions that every FMI 3.0 binary must export
+const FMI3_COMMON_FUNCTIONS: &[&str] = &[
+    "fmi3GetVersion",
+    "fmi3SetDebugLogging",
+    "fmi3FreeInstance",
+    "fmi3EnterInitializationMode",
+    "fmi3ExitInitializationMode",
+    "fmi3EnterEventMode",
+    "fmi3Terminate",
+    "fmi3Reset",
+    "fmi3GetFloat32",
+    "fmi3GetFloat64",
+    "fmi3GetInt8",
+    "fmi3GetUInt8",
+    "fmi3GetInt16",
+    "fmi3GetUInt16",
+    "fmi3GetInt32",
+    "fmi3GetUInt32",
+    "fmi3GetInt64",
+    "fmi3GetUInt64",
+    "fmi3GetBoolean",
+    "fmi3GetString",
+    "fmi3GetBinary",
+    "fmi3GetClock",
+    "fmi3SetFloat32",
+    "fmi3SetFloat64",
+    "fmi3SetInt8",
+    "fmi3SetUInt8",
+    "fmi3SetInt16",
+    "fmi3SetUInt16",
+    "fmi3SetInt32",
+    "fmi3SetUInt32",
+    "fmi3SetInt64",
+    "fmi3SetUInt64",
+    "fmi3SetBoolean",
+    "fmi3SetString",
+    "fmi3SetBinary",
+    "fmi3SetClock",
+    "fmi3GetNumberOfVariableDependencies",
+    "fmi3GetVariableDependencies",
+    "fmi3GetFMUState",
+    "fmi3SetFMUState",
+    "fmi3FreeFMUState",
+    "fmi3SerializedFMUStateSize",
+    "fmi3SerializeFMUState",
+    "fmi3DeserializeFMUState",
+    "fmi3GetDirectionalDerivative",
+    "fmi3GetAdjointDerivative",
+    "fmi3EnterConfigurationMode",
+    "fmi3ExitConfigurationMode",
+    "fmi3GetIntervalDecimal",
+    "fmi3GetIntervalFraction",
+    "fmi3GetShiftDecimal",
+    "fmi3GetShiftFraction",
+    "fmi3SetIntervalDecimal",
+    "fmi3SetIntervalFraction",
+    "fmi3SetShiftDecimal",
+    "fmi3SetShiftFraction",
+    "fmi3EvaluateDiscreteStates",
+    "fmi3UpdateDiscreteStates",
+];
+
+/// Functions of the FMI 3.0 Model Exchange interface
+const FMI3_ME_FUNCTIONS: &[&str] = &[
+    "fmi3InstantiateModelExchange",
+    "fmi3EnterContinuousTimeMode",
+    "fmi3CompletedIntegratorStep",
+    "fmi3SetTime",
+    "fmi3SetContinuousStates",
+    "fmi3GetContinuousStateDerivatives",
+    "fmi3GetEventIndicators",
+    "fmi3GetContinuousStates",
+    "fmi3GetNominalsOfContinuousStates",
+    "fmi3GetNumberOfEventIndicators",
+    "fmi3GetNumberOfContinuousStates",
+];
+
+/// Functions of the FMI 3.0 Co-Simulation interface
+const FMI3_CS_FUNCTIONS: &[&str] = &[
+    "fmi3InstantiateCoSimulation",
+    "fmi3EnterStepMode",
+    "fmi3GetOutputDerivatives",
+    "fmi3DoStep",
+];
+
+/// Functions of the FMI 3.0 Scheduled Execution interface
+const FMI3_SE_FUNCTIONS: &[&str] = &[
+    "fmi3InstantiateScheduledExecution",
+    "fmi3ActivateModelPartition",
+];
+
+/// Functions that every FMI 2.0 binary must export
+const FMI2_COMMON_FUNCTIONS: &[&str] = &[
+    "fmi2GetTypesPlatform",
+    "fmi2GetVersion",
+    "fmi2SetDebugLogging",
+    "fmi2Instantiate",
+    "fmi2FreeInstance",
+    "fmi2SetupExperiment",
+    "fmi2EnterInitializationMode",
+    "fmi2ExitInitializationMode",
+    "fmi2Terminate",
+    "fmi2Reset",
+    "fmi2GetReal",
+    "fmi2GetInteger",
+    "fmi2GetBoolean",
+    "fmi2GetString",
+    "fmi2SetReal",
+    "fmi2SetInteger",
+    "fmi2SetBoolean",
+    "fmi2SetString",
+    "fmi2GetFMUstate",
+    "fmi2SetFMUstate",
+    "fmi2FreeFMUstate",
+    "fmi2SerializedFMUstateSize",
+    "fmi2SerializeFMUstate",
+    "fmi2DeSerializeFMUstate",
+    "fmi2GetDirectionalDerivative",
+];
+
+/// Functions of the FMI 2.0 Model Exchange interface
+const FMI2_ME_FUNCTIONS: &[&str] = &[
+    "fmi2EnterEventMode",
+    "fmi2NewDiscreteStates",
+    "fmi2EnterContinuousTimeMode",
+    "fmi2CompletedIntegratorStep",
+    "fmi2SetTime",
+    "fmi2SetContinuousStates",
+    "fmi2GetDerivatives",
+    "fmi2GetEventIndicators",
+    "fmi2GetContinuousStates",
+    "fmi2GetNominalsOfContinuousStates",
+];
+
+/// Functions of the FMI 2.0 Co-Simulation interface
+const FMI2_CS_FUNCTIONS: &[&str] = &[
+    "fmi2SetRealInputDerivatives",
+    "fmi2GetRealOutputDerivatives",
+    "fmi2DoStep",
+    "fmi2CancelStep",
+    "fmi2GetStatus",
+    "fmi2GetRealStatus",
+    "fmi2GetIntegerStatus",
+    "fmi2GetBooleanStatus",
+    "fmi2GetStringStatus",
+];
+
+/// The rule that a [`Violation`] breaks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rule {
+    ValueReference,
+    Reference,
+    Initial,
+    ModelStructure,
+    Symbols,
+    InstantiationToken,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Rule::ValueReference => "value-reference",
+            Rule::Reference => "reference",
+            Rule::Initial => "initial",
+            Rule::ModelStructure => "model-structure",
+            Rule::Symbols => "symbols",
+            Rule::InstantiationToken => "instantiation-token",
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Violation {
+    rule: Rule,
+    message: String,
+}
+
+impl Violation {
+    fn new(rule: Rule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.rule, self.message)
+    }
+}
+
+pub fn check(fmu_path: &Path) -> Result<()> {
+    let min_desc = fmi::import::peek_descr_path(fmu_path)
+        .with_context(|| format!("Failed to read FMU at {}", fmu_path.display()))?;
+    let major = min_desc
+        .major_version()
+        .context("Failed to determine FMI version")?;
+
+    let violations = match major {
+        MajorVersion::FMI2 => {
+            let import: fmi::fmi2::import::Fmi2Import = fmi::import::from_path(fmu_path)
+                .with_context(|| format!("Failed to import FMU at {}", fmu_path.display()))?;
+            let model_desc = import.model_description();
+            let mut violations = check_fmi2_model_description(model_desc);
+            let mut functions: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            if let Some(me) = &model_desc.model_exchange {
+                functions
+                    .entry(me.model_identifier.as_str())
+                    .or_default()
+                    .extend(FMI2_COMMON_FUNCTIONS.iter().chain(FMI2_ME_FUNCTIONS));
+            }
+            if let Some(cs) = &model_desc.co_simulation {
+                functions
+                    .entry(cs.model_identifier.as_str())
+                    .or_default()
+                    .extend(FMI2_COMMON_FUNCTIONS.iter().chain(FMI2_CS_FUNCTIONS));
+            }
+            violations.extend(check_binaries(import.archive_path(), &functions)?);
+            violations.extend(check_instantiation(&model_desc.guid, || {
+                if model_desc.model_exchange.is_some() {
+                    import.instantiate_me("check", false, false).map(drop)
+                } else {
+                    import.instantiate_cs("check", false, false).map(drop)
+                }
+            }));
+            violations
+        }
+        MajorVersion::FMI3 => {
+            let import: fmi::fmi3::import::Fmi3Import = fmi::import::from_path(fmu_path)
+                .with_context(|| format!("Failed to import FMU at {}", fmu_path.display()))?;
+            let model_desc = import.model_description();
+            let mut violations = check_fmi3_model_description(model_desc);
+            let mut functions: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            let interfaces = [
+                (
+                    model_desc
+                        .model_exchange
+                        .as_ref()
+                        .map(|me| &me.model_identifier),
+                    FMI3_ME_FUNCTIONS,
+                ),
+                (
+                    model_desc
+                        .co_simulation
+                        .as_ref()
+                        .map(|cs| &cs.model_identifier),
+                    FMI3_CS_FUNCTIONS,
+                ),
+                (
+                    model_desc
+                        .scheduled_execution
+                        .as_ref()
+                        .map(|se| &se.model_identifier),
+                    FMI3_SE_FUNCTIONS,
+                ),
+            ];
+            for (model_identifier, interface_functions) in interfaces {
+                if let Some(model_identifier) = model_identifier {
+                    functions
+                        .entry(model_identifier.as_str())
+                        .or_default()
+                        .extend(FMI3_COMMON_FUNCTIONS.iter().chain(interface_functions));
+                }
+            }
+            violations.extend(check_binaries(import.archive_path(), &functions)?);
+            violations.extend(check_instantiation(&model_desc.instantiation_token, || {
+                if model_desc.model_exchange.is_some() {
+                    import.instantiate_me("check", false, false).map(drop)
+                } else if model_desc.co_simulation.is_some() {
+                    import
+                        .instantiate_cs("check", false, false, false, false, &[])
+                        .map(drop)
+                } else {
+                    import.instantiate_se("check", false, false).map(drop)
+                }
+            }));
+            violations
+        }
+        MajorVersion::FMI1 => anyhow::bail!("FMI 1.0 is not supported"),
+    };
+
+    if violations.is_empty() {
+        log::info!("No violations found in {}", fmu_path.display());
+        return Ok(());
+    }
+    for violation in &violations {
+        println!("{violation}");
+    }
+    anyhow::bail!(
+        "Found {} violation(s) in {}",
+        violations.len(),
+        fmu_path.display()
+    )
+}
+
+/// The variability a variable has if the attribute is omitted: `continuous` for floating point
+/// variables and `discrete` for all others.
+fn default_variability(is_float: bool) -> Variability {
+    if is_float {
+        Variability::Continuous
+    } else {
+        Variability::Discrete
+    }
+}
+
+/// The initial a variable has if the attribute is omitted, or `None` if it has none.
+fn default_initial(causality: Causality, variability: Variability) -> Option<Initial> {
+    match (causality, variability) {
+        (Causality::Parameter | Causality::StructuralParameter, _) => Some(Initial::Exact),
+        (Causality::CalculatedParameter, _) => Some(Initial::Calculated),
+        (Causality::Output | Causality::Local, Variability::Constant) => Some(Initial::Exact),
+        (Causality::Output | Causality::Local, _) => Some(Initial::Calculated),
+        _ => None,
+    }
+}
+
+/// Check the combination of causality, variability and the given `initial` of a variable against
+/// the tables of the FMI standard.
+fn check_combination(
+    major: MajorVersion,
+    name: &str,
+    causality: Causality,
+    variability: Variability,
+    initial: Option<Initial>,
+    is_float: bool,
+) -> Option<Violation> {
+    use Causality as C;
+    use Variability as V;
+
+    let valid = match (causality, variability) {
+        (C::Output | C::Local, V::Constant) => true,
+        (C::Parameter | C::CalculatedParameter | C::StructuralParameter | C::Local, V::Fixed)
+        | (C::Parameter | C::CalculatedParameter | C::StructuralParameter | C::Local, V::Tunable) => {
+            true
+        }
+        (C::Input | C::Output | C::Local, V::Discrete) => true,
+        (C::Input | C::Output | C::Local | C::Independent, V::Continuous) => true,
+        // Not part of the standard, nothing to check against
+        (C::Dependent, _) => return None,
+        _ => false,
+    };
+    if !valid {
+        return Some(Violation::new(
+            Rule::Initial,
+            format!(
+                "Variable '{name}': causality {causality} can not have variability {variability}"
+            ),
+        ));
+    }
+    if variability == V::Continuous && !is_float {
+        return Some(Violation::new(
+            Rule::Initial,
+            format!("Variable '{name}': only floating point variables can be continuous"),
+        ));
+    }
+
+    let allowed: &[Initial] = match (causality, variability) {
+        (C::Parameter | C::StructuralParameter, _) => &[Initial::Exact],
+        (C::CalculatedParameter, _) => &[Initial::Calculated, Initial::Approx],
+        (C::Output | C::Local, V::Constant) => &[Initial::Exact],
+        (C::Output, _) => &[Initial::Calculated, Initial::Exact],
+        (C::Local, V::Fixed | V::Tunable) => &[Initial::Calculated, Initial::Approx],
+        (C::Local, _) => &[Initial::Calculated, Initial::Exact, Initial::Approx],
+        // FMI 2.0 does not allow initial for inputs
+        (C::Input, _) if major == MajorVersion::FMI3 => &[Initial::Exact],
+        _ => &[],
+    };
+    match initial {
+        Some(initial) if !allowed.contains(&initial) => Some(Violation::new(
+            Rule::Initial,
+            format!(
+                "Variable '{name}': initial {initial:?} is not allowed for causality {causality} and variability {variability}"
+            ),
+        )),
+        _ => None,
+    }
+}
+
+/// The attributes of an FMI 3.0 variable the checks look at
+struct Fmi3Variable<'a> {
+    variable: &'a dyn AbstractVariableTrait,
+    variability: Variability,
+    /// The `initial` attribute as given in the model description
+    given_initial: Option<Initial>,
+    /// The `initial` attribute, or its default
+    initial: Option<Initial>,
+    derivative: Option<u32>,
+    previous: Option<u32>,
+}
+
+impl<'a> Fmi3Variable<'a> {
+    fn new(variable: &'a Variable) -> Self {
+        macro_rules! attributes {
+            ($var:expr, $derivative:expr) => {
+                (
+                    $var as &dyn AbstractVariableTrait,
+                    $var.variability,
+                    $var.initial(),
+                    $derivative,
+                    $var.previous(),
+                )
+            };
+        }
+        let (abstract_variable, variability, given_initial, derivative, previous) = match variable {
+            Variable::Float32(var) => attributes!(var, var.derivative()),
+            Variable::Float64(var) => attributes!(var, var.derivative()),
+            Variable::Int8(var) => attributes!(var, None),
+            Variable::UInt8(var) => attributes!(var, None),
+            Variable::Int16(var) => attributes!(var, None),
+            Variable::UInt16(var) => attributes!(var, None),
+            Variable::Int32(var) => attributes!(var, None),
+            Variable::UInt32(var) => attributes!(var, None),
+            Variable::Int64(var) => attributes!(var, None),
+            Variable::UInt64(var) => attributes!(var, None),
+            Variable::Boolean(var) => attributes!(var, None),
+            Variable::String(var) => attributes!(var, None),
+            Variable::Binary(var) => attributes!(var, None),
+            Variable::Clock(var) => (
+                var as &dyn AbstractVariableTrait,
+                var.variability,
+                None,
+                None,
+                None,
+            ),
+        };
+
+        let causality = abstract_variable.causality();
+        let variability = variability
+            .unwrap_or_else(|| default_variability(is_fmi3_float(abstract_variable.data_type())));
+        let initial = match abstract_variable.data_type() {
+            VariableType::FmiClock => None,
+            _ => given_initial.or_else(|| default_initial(causality, variability)),
+        };
+        Self {
+            variable: abstract_variable,
+            variability,
+            given_initial,
+            initial,
+            derivative,
+            previous,
+        }
+    }
+
+    /// Whether the variable must be listed as InitialUnknown if it is an output or state. Clocked
+    /// variables are excluded, since they are only defined while their clock ticks.
+    fn is_initial_unknown(&self) -> bool {
+        matches!(self.initial, Some(Initial::Approx | Initial::Calculated))
+            && self.variable.clocks().is_none_or(<[u32]>::is_empty)
+    }
+}
+
+fn is_fmi3_float(data_type: VariableType) -> bool {
+    matches!(
+        data_type,
+        VariableType::FmiFloat32 | VariableType::FmiFloat64
+    )
+}
+
+fn check_fmi3_model_description(model_desc: &fmi3_schema::Fmi3ModelDescription) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let variables: Vec<Fmi3Variable> = model_desc
+        .model_variables
+        .variables
+        .iter()
+        .map(Fmi3Variable::new)
+        .collect();
+
+    let mut by_vr: BTreeMap<u32, &Fmi3Variable> = BTreeMap::new();
+    for variable in &variables {
+        let vr = variable.variable.value_reference();
+        match by_vr.entry(vr) {
+            Entry::Occupied(other) => violations.push(Violation::new(
+                Rule::ValueReference,
+                format!(
+                    "Variables '{}' and '{}' share the value reference {vr}",
+                    other.get().variable.name(),
+                    variable.variable.name()
+                ),
+            )),
+            Entry::Vacant(entry) => {
+                entry.insert(variable);
+            }
+        }
+    }
+
+    for variable in &variables {
+        let name = variable.variable.name();
+        if let Some(derivative) = variable.derivative {
+            match by_vr.get(&derivative) {
+                None => violations.push(Violation::new(
+                    Rule::Reference,
+                    format!(
+                        "Variable '{name}': derivative references the unknown value reference {derivative}"
+                    ),
+                )),
+                Some(state) if !is_fmi3_float(state.variable.data_type()) => {
+                    violations.push(Violation::new(
+                        Rule::Reference,
+                        format!(
+                            "Variable '{name}': derivative references '{}', which is not a floating point variable",
+                            state.variable.name()
+                        ),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(previous) = variable.previous
+            && !by_vr.contains_key(&previous)
+        {
+            violations.push(Violation::new(
+                Rule::Reference,
+                format!(
+                    "Variable '{name}': previous references the unknown value reference {previous}"
+                ),
+            ));
+        }
+        for clock in variable.variable.clocks().unwrap_or_default() {
+            match by_vr.get(clock) {
+                None => violations.push(Violation::new(
+                    Rule::Reference,
+                    format!(
+                        "Variable '{name}': clocks references the unknown value reference {clock}"
+                    ),
+                )),
+                Some(clock) if clock.variable.data_type() != VariableType::FmiClock => violations
+                    .push(Violation::new(
+                        Rule::Reference,
+                        format!(
+                            "Variable '{name}': clocks references '{}', which is not a Clock",
+                            clock.variable.name()
+                        ),
+                    )),
+                Some(_) => {}
+            }
+        }
+
+        violations.extend(check_combination(
+            MajorVersion::FMI3,
+            name,
+            variable.variable.causality(),
+            variable.variability,
+            variable.given_initial,
+            is_fmi3_float(variable.variable.data_type()),
+        ));
+    }
+
+    let structure = &model_desc.model_structure;
+    for (kind, unknowns) in [
+        ("Output", structure.outputs().collect::<Vec<_>>()),
+        (
+            "ContinuousStateDerivative",
+            structure.continuous_state_derivatives().collect(),
+        ),
+        ("ClockedState", structure.clocked_states().collect()),
+        ("InitialUnknown", structure.initial_unknowns().collect()),
+        ("EventIndicator", structure.event_indicators().collect()),
+    ] {
+        for unknown in unknowns {
+            let dependencies = unknown
+                .dependencies
+                .as_ref()
+                .map(|dependencies| dependencies.0.as_slice())
+                .unwrap_or_default();
+            for vr in std::iter::once(&unknown.value_reference).chain(dependencies) {
+                if !by_vr.contains_key(vr) {
+                    violations.push(Violation::new(
+                        Rule::ModelStructure,
+                        format!(
+                            "{kind} {}: references the unknown value reference {vr}",
+                            unknown.value_reference
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    let listed = |unknowns: &mut dyn Iterator<Item = &fmi3_schema::Fmi3Unknown>| {
+        unknowns
+            .map(|unknown| unknown.value_reference)
+            .collect::<BTreeSet<_>>()
+    };
+    let outputs = listed(&mut structure.outputs());
+    let derivatives = listed(&mut structure.continuous_state_derivatives());
+    let initial_unknowns = listed(&mut structure.initial_unknowns());
+
+    for vr in &outputs {
+        if let Some(variable) = by_vr.get(vr)
+            && variable.variable.causality() != Causality::Output
+        {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "Output {vr}: variable '{}' does not have causality output",
+                    variable.variable.name()
+                ),
+            ));
+        }
+    }
+    for vr in &derivatives {
+        if let Some(variable) = by_vr.get(vr)
+            && variable.derivative.is_none()
+        {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "ContinuousStateDerivative {vr}: variable '{}' has no derivative attribute",
+                    variable.variable.name()
+                ),
+            ));
+        }
+    }
+
+    let mut missing = |kind: &str, listed: &BTreeSet<u32>, variable: &Fmi3Variable| {
+        let vr = variable.variable.value_reference();
+        if !listed.contains(&vr) {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "Variable '{}' ({vr}) is not listed as {kind}",
+                    variable.variable.name()
+                ),
+            ));
+        }
+    };
+    for variable in &variables {
+        let causality = variable.variable.causality();
+        if causality == Causality::Output {
+            missing("Output", &outputs, variable);
+        }
+        if model_desc.model_exchange.is_some()
+            && variable.derivative.is_some()
+            && variable.variability == Variability::Continuous
+        {
+            missing("ContinuousStateDerivative", &derivatives, variable);
+        }
+        if causality == Causality::CalculatedParameter
+            || causality == Causality::Output && variable.is_initial_unknown()
+        {
+            missing("InitialUnknown", &initial_unknowns, variable);
+        }
+    }
+    // Continuous states and their derivatives
+    for vr in &derivatives {
+        let Some(derivative) = by_vr.get(vr) else {
+            continue;
+        };
+        let state = derivative.derivative.and_then(|state| by_vr.get(&state));
+        for variable in std::iter::once(derivative).chain(state) {
+            if variable.is_initial_unknown() {
+                missing("InitialUnknown", &initial_unknowns, variable);
+            }
+        }
+    }
+
+    dedup(violations)
+}
+
+/// The FMI 3.0 equivalent of an FMI 2.0 causality
+fn fmi2_causality(causality: &fmi2_schema::Causality) -> Causality {
+    match causality {
+        fmi2_schema::Causality::Parameter => Causality::Parameter,
+        fmi2_schema::Causality::CalculatedParameter => Causality::CalculatedParameter,
+        fmi2_schema::Causality::Input => Causality::Input,
+        fmi2_schema::Causality::Output => Causality::Output,
+        fmi2_schema::Causality::Local => Causality::Local,
+        fmi2_schema::Causality::Independent => Causality::Independent,
+    }
+}
+
+/// The FMI 3.0 equivalent of an FMI 2.0 variability
+fn fmi2_variability(variability: fmi2_schema::Variability) -> Variability {
+    match variability {
+        fmi2_schema::Variability::Constant => Variability::Constant,
+        fmi2_schema::Variability::Fixed => Variability::Fixed,
+        fmi2_schema::Variability::Tunable => Variability::Tunable,
+        fmi2_schema::Variability::Discrete => Variability::Discrete,
+        fmi2_schema::Variability::Continuous => Variability::Continuous,
+    }
+}
+
+/// The FMI 3.0 equivalent of an FMI 2.0 initial
+fn fmi2_initial(initial: &fmi2_schema::Initial) -> Initial {
+    match initial {
+        fmi2_schema::Initial::Exact => Initial::Exact,
+        fmi2_schema::Initial::Approx => Initial::Approx,
+        fmi2_schema::Initial::Calculated => Initial::Calculated,
+    }
+}
+
+fn fmi2_type_name(elem: &fmi2_schema::ScalarVariableElement) -> &'static str {
+    match elem {
+        fmi2_schema::ScalarVariableElement::Real(_) => "Real",
+        fmi2_schema::ScalarVariableElement::Integer(_) => "Integer",
+        fmi2_schema::ScalarVariableElement::Boolean(_) => "Boolean",
+        fmi2_schema::ScalarVariableElement::String => "String",
+        fmi2_schema::ScalarVariableElement::Enumeration => "Enumeration",
+    }
+}
+
+fn check_fmi2_model_description(model_desc: &fmi2_schema::Fmi2ModelDescription) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let variables = &model_desc.model_variables.variables;
+    // ScalarVariable indices are 1-based
+    let by_index = |index: u32| {
+        index
+            .checked_sub(1)
+            .and_then(|index| variables.get(index as usize))
+    };
+    let is_real = |variable: &fmi2_schema::ScalarVariable| fmi2_type_name(&variable.elem) == "Real";
+    let variability = |variable: &fmi2_schema::ScalarVariable| {
+        variable
+            .variability
+            .map(fmi2_variability)
+            .unwrap_or_else(|| default_variability(is_real(variable)))
+    };
+    let initial =
+        |variable: &fmi2_schema::ScalarVariable| {
+            variable.initial.as_ref().map(fmi2_initial).or_else(|| {
+                default_initial(fmi2_causality(&variable.causality), variability(variable))
+            })
+        };
+
+    // Aliases share a value reference, but only with variables of the same type
+    let mut by_vr: BTreeMap<u32, &fmi2_schema::ScalarVariable> = BTreeMap::new();
+    for variable in variables {
+        let vr = variable.value_reference;
+        match by_vr.get(&vr) {
+            Some(other) if fmi2_type_name(&other.elem) != fmi2_type_name(&variable.elem) => {
+                violations.push(Violation::new(
+                    Rule::ValueReference,
+                    format!(
+                        "Variables '{}' ({}) and '{}' ({}) share the value reference {vr}",
+                        other.name,
+                        fmi2_type_name(&other.elem),
+                        variable.name,
+                        fmi2_type_name(&variable.elem)
+                    ),
+                ))
+            }
+            Some(_) => {}
+            None => {
+                by_vr.insert(vr, variable);
+            }
+        }
+    }
+
+    for variable in variables {
+        let name = &variable.name;
+        if let fmi2_schema::ScalarVariableElement::Real(real) = &variable.elem
+            && let Some(derivative) = real.derivative
+        {
+            match by_index(derivative) {
+                None => violations.push(Violation::new(
+                    Rule::Reference,
+                    format!(
+                        "Variable '{name}': derivative references the unknown index {derivative}"
+                    ),
+                )),
+                Some(state) if !is_real(state) => violations.push(Violation::new(
+                    Rule::Reference,
+                    format!(
+                        "Variable '{name}': derivative references '{}', which is not a Real variable",
+                        state.name
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+
+        violations.extend(check_combination(
+            MajorVersion::FMI2,
+            name,
+            fmi2_causality(&variable.causality),
+            variability(variable),
+            variable.initial.as_ref().map(fmi2_initial),
+            is_real(variable),
+        ));
+    }
+
+    let structure = &model_desc.model_structure;
+    for (kind, unknowns) in [
+        ("Output", &structure.outputs.unknowns),
+        ("Derivative", &structure.derivatives.unknowns),
+        ("InitialUnknown", &structure.initial_unknowns.unknowns),
+    ] {
+        for unknown in unknowns {
+            for index in std::iter::once(&unknown.index).chain(&unknown.dependencies) {
+                if by_index(*index).is_none() {
+                    violations.push(Violation::new(
+                        Rule::ModelStructure,
+                        format!(
+                            "{kind} {}: references the unknown index {index}",
+                            unknown.index
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    let listed = |unknowns: &[fmi2_schema::Fmi2VariableDependency]| {
+        unknowns
+            .iter()
+            .map(|unknown| unknown.index)
+            .collect::<BTreeSet<_>>()
+    };
+    let outputs = listed(&structure.outputs.unknowns);
+    let derivatives = listed(&structure.derivatives.unknowns);
+    let initial_unknowns = listed(&structure.initial_unknowns.unknowns);
+    let is_derivative = |variable: &fmi2_schema::ScalarVariable| {
+        matches!(
+            &variable.elem,
+            fmi2_schema::ScalarVariableElement::Real(real) if real.derivative.is_some()
+        )
+    };
+
+    for index in &outputs {
+        if let Some(variable) = by_index(*index)
+            && variable.causality != fmi2_schema::Causality::Output
+        {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "Output {index}: variable '{}' does not have causality output",
+                    variable.name
+                ),
+            ));
+        }
+    }
+    for index in &derivatives {
+        if let Some(variable) = by_index(*index)
+            && !is_derivative(variable)
+        {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "Derivative {index}: variable '{}' has no derivative attribute",
+                    variable.name
+                ),
+            ));
+        }
+    }
+
+    let is_initial_unknown = |variable: &fmi2_schema::ScalarVariable| {
+        matches!(
+            initial(variable),
+            Some(Initial::Approx | Initial::Calculated)
+        )
+    };
+    let mut missing = |kind: &str, listed: &BTreeSet<u32>, index: u32| {
+        if !listed.contains(&index) {
+            violations.push(Violation::new(
+                Rule::ModelStructure,
+                format!(
+                    "Variable '{}' (index {index}) is not listed as {kind}",
+                    variables[index as usize - 1].name
+                ),
+            ));
+        }
+    };
+    for (index, variable) in (1..).zip(variables) {
+        if variable.causality == fmi2_schema::Causality::Output {
+            missing("Output", &outputs, index);
+        }
+        if model_desc.model_exchange.is_some()
+            && is_derivative(variable)
+            && variability(variable) == Variability::Continuous
+        {
+            missing("Derivative", &derivatives, index);
+        }
+        if variable.causality == fmi2_schema::Causality::CalculatedParameter
+            || variable.causality == fmi2_schema::Causality::Output && is_initial_unknown(variable)
+        {
+            missing("InitialUnknown", &initial_unknowns, index);
+        }
+    }
+    // Continuous states and their derivatives
+    for index in &derivatives {
+        let Some(derivative) = by_index(*index) else {
+            continue;
+        };
+        let state = match &derivative.elem {
+            fmi2_schema::ScalarVariableElement::Real(real) => {
+                real.derivative.filter(|state| by_index(*state).is_some())
+            }
+            _ => None,
+        };
+        for index in std::iter::once(*index).chain(state) {
+            if is_initial_unknown(&variables[index as usize - 1]) {
+                missing("InitialUnknown", &initial_unknowns, index);
+            }
+        }
+    }
+
+    dedup(violations)
+}
+
+/// Remove repeated violations, e.g. a state that is also an output missing as InitialUnknown
+fn dedup(violations: Vec<Violation>) -> Vec<Violation> {
+    let mut seen = HashSet::new();
+    violations
+        .into_iter()
+        .filter(|violation| seen.insert(violation.to_string()))
+        .collect()
+}
+
+/// Names of the FMI functions found in a binary of any platform.
+///
+/// The binary is not loaded; instead its bytes are scanned for NUL-terminated identifiers
+/// starting with `prefix`, as they appear in the export tables of ELF, Mach-O and PE files.
+fn exported_functions<'a>(bytes: &'a [u8], prefix: &str) -> HashSet<&'a [u8]> {
+    let prefix = prefix.as_bytes();
+    let mut functions = HashSet::new();
+    let mut start = 0;
+    while let Some(offset) = bytes[start..]
+        .windows(prefix.len())
+        .position(|window| window == prefix)
+    {
+        let begin = start + offset;
+        let len = bytes[begin..]
+            .iter()
+            .position(|byte| !byte.is_ascii_alphanumeric())
+            .unwrap_or(bytes.len() - begin);
+        let preceded_by_identifier = begin > 0 && bytes[begin - 1].is_ascii_alphanumeric();
+        if !preceded_by_identifier && bytes.get(begin + len) == Some(&0) {
+            functions.insert(&bytes[begin..begin + len]);
+        }
+        start = begin + len.max(1);
+    }
+    functions
+}
+
+/// Check the binary of each model identifier in every platform folder below `binaries/` for the
+/// required functions.
+fn check_binaries(
+    archive_path: &Path,
+    functions: &BTreeMap<&str, Vec<&str>>,
+) -> Result<Vec<Violation>> {
+    let binaries_dir = archive_path.join("binaries");
+    if !binaries_dir.is_dir() {
+        log::warn!("The FMU contains no binaries, skipping the binary checks");
+        return Ok(Vec::new());
+    }
+
+    let mut platforms = std::fs::read_dir(&binaries_dir)
+        .context("Failed to read the binaries folder")?
+        .collect::<Result<Vec<_>, _>>()?;
+    platforms.sort_by_key(|entry| entry.file_name());
+
+    let mut violations = Vec::new();
+    for platform in platforms.iter().filter(|entry| entry.path().is_dir()) {
+        let platform_name = platform.file_name();
+        let platform_name = platform_name.to_string_lossy();
+        for (model_identifier, required) in functions {
+            let binary = ["so", "dylib", "dll"]
+                .iter()
+                .map(|extension| {
+                    platform
+                        .path()
+                        .join(format!("{model_identifier}.{extension}"))
+                })
+                .find(|path| path.is_file());
+            let Some(binary) = binary else {
+                violations.push(Violation::new(
+                    Rule::Symbols,
+                    format!("binaries/{platform_name}: no binary for model identifier '{model_identifier}'"),
+                ));
+                continue;
+            };
+            let binary_name = format!(
+                "binaries/{platform_name}/{}",
+                binary.file_name().unwrap_or_default().to_string_lossy()
+            );
+            log::info!("Checking {binary_name}");
+
+            let bytes = std::fs::read(&binary)
+                .with_context(|| format!("Failed to read {}", binary.display()))?;
+            let exported = exported_functions(&bytes, "fmi");
+            let missing: BTreeSet<&str> = required
+                .iter()
+                .copied()
+                .filter(|function| !exported.contains(function.as_bytes()))
+                .collect();
+            if !missing.is_empty() {
+                violations.push(Violation::new(
+                    Rule::Symbols,
+                    format!(
+                        "{binary_name}: missing functions {}",
+                        missing.into_iter().collect::<Vec<_>>().join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(violations)
+}
+
+/// Instantiate the binary of the host platform with the instantiation token of the model
+/// description. The FMU rejects a token it was not built for, so a failed instantiation is a
+/// violation. If there is no binary for the host platform, the check is skipped.
+fn check_instantiation(
+    instantiation_token: &str,
+    instantiate: impl FnOnce() -> Result<(), fmi::Error>,
+) -> Option<Violation> {
+    match instantiate() {
+        Ok(()) => None,
+        Err(fmi::Error::Instantiation) => Some(Violation::new(
+            Rule::InstantiationToken,
+            format!(
+                "The binary for the host platform can not be instantiated with the instantiation token '{instantiation_token}'"
+            ),
+        )),
+        Err(err) => {
+            log::warn!("Skipping the instantiation token check: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmi3_violations(variables: &str, structure: &str) -> Vec<(Rule, String)> {
+        let xml = format!(
+            r#"<fmiModelDescription fmiVersion="3.0" modelName="Test" instantiationToken="{{test}}">
+    <ModelExchange modelIdentifier="test"/>
+    <ModelVariables>{variables}</ModelVariables>
+    <ModelStructure>{structure}</ModelStructure>
+</fmiModelDescription>"#
+        );
+        let model_desc: fmi3_schema::Fmi3ModelDescription =
+            fmi::schema::deserialize(&xml).expect("valid model description");
+        check_fmi3_model_description(&model_desc)
+            .into_iter()
+            .map(|violation| (violation.rule, violation.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid_fmi3_model_description() {
+        let violations = fmi3_violations(
+            r#"
+        <Float64 name="time" valueReference="0" causality="independent" variability="continuous"/>
+        <Float64 name="h" valueReference="1" causality="output" initial="exact" start="1"/>
+        <Float64 name="der(h)" valueReference="2" derivative="1"/>
+        <Float64 name="g" valueReference="3" causality="parameter" variability="fixed" start="-9.81"/>
+        <Float64 name="k" valueReference="4" causality="calculatedParameter" variability="fixed"/>"#,
+            r#"
+        <Output valueReference="1"/>
+        <ContinuousStateDerivative valueReference="2"/>
+        <InitialUnknown valueReference="2"/>
+        <InitialUnknown valueReference="4"/>"#,
+        );
+        assert_eq!(violations, vec![]);
+    }
+
+    #[test]
+    fn fmi3_violations_are_reported() {
+        let violations = fmi3_violations(
+            r#"
+        <Float64 name="x" valueReference="1" causality="input" initial="approx" start="0"/>
+        <Float64 name="y" valueReference="1" causality="output"/>
+        <Float64 name="der(x)" valueReference="2" derivative="7"/>
+        <Int32 name="n" valueReference="3" causality="output" variability="continuous" clocks="1"/>"#,
+            r#"<Output valueReference="1"/>"#,
+        );
+        let rules: Vec<Rule> = violations.iter().map(|(rule, _)| *rule).collect();
+        assert!(rules.contains(&Rule::ValueReference), "{violations:?}");
+        assert!(
+            violations.contains(&(
+                Rule::Reference,
+                "Variable 'der(x)': derivative references the unknown value reference 7"
+                    .to_string()
+            )),
+            "{violations:?}"
+        );
+        assert!(
+            violations.contains(&(
+                Rule::Reference,
+                "Variable 'n': clocks references 'x', which is not a Clock".to_string()
+            )),
+            "{violations:?}"
+        );
+        assert!(
+            violations.contains(&(
+                Rule::Initial,
+                "Variable 'x': initial Approx is not allowed for causality input and variability continuous"
+                    .to_string()
+            )),
+            "{violations:?}"
+        );
+        assert!(
+            violations.contains(&(
+                Rule::Initial,
+                "Variable 'n': only floating point variables can be continuous".to_string()
+            )),
+            "{violations:?}"
+        );
+        assert!(
+            violations.contains(&(
+                Rule::ModelStructure,
+                "Variable 'n' (3) is not listed as Output".to_string()
+            )),
+            "{violations:?}"
+        );
+        assert!(
+            violations.contains(&(
+                Rule::ModelStructure,
+                "Variable 'der(x)' (2) is not listed as ContinuousStateDerivative".to_string()
+            )),
+            "{violations:?}"
+        );
+    }
+
+    #[test]
+    fn default_variability_depends_on_the_type() {
+        // Without a variability attribute, floating point variables are continuous and all
+        // others discrete, whatever their causality, so parameters must declare it
+        let violations = fmi3_violations(
+            r#"
+        <Float64 name="p" valueReference="1" causality="parameter" start="1"/>
+        <Int32 name="n" valueReference="2" causality="parameter" start="1"/>
+        <Int32 name="m" valueReference="3" causality="output"/>"#,
+            r#"<Output valueReference="3"/><InitialUnknown valueReference="3"/>"#,
+        );
+        assert_eq!(
+            violations,
+            vec![
+                (
+                    Rule::Initial,
+                    "Variable 'p': causality parameter can not have variability continuous"
+                        .to_string()
+                ),
+                (
+                    Rule::Initial,
+                    "Variable 'n': causality parameter can not have variability discrete"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fmi2_violations_are_reported() {
+        let xml = r#"<fmiModelDescription fmiVersion="2.0" modelName="Test" guid="{test}">
+    <ModelExchange modelIdentifier="test"/>
+    <ModelVariables>
+        <ScalarVariable name="x" valueReference="0" initial="exact"><Real start="1"/></ScalarVariable>
+        <ScalarVariable name="der(x)" valueReference="1"><Real derivative="1"/></ScalarVariable>
+        <ScalarVariable name="y" valueReference="0" causality="output" variability="continuous"><Integer/></ScalarVariable>
+        <ScalarVariable name="p" valueReference="2" causality="parameter" variability="fixed" initial="calculated"><Real/></ScalarVariable>
+    </ModelVariables>
+    <ModelStructure>
+        <Outputs><Unknown index="3"/></Outputs>
+        <Derivatives><Unknown index="2"/></Derivatives>
+        <InitialUnknowns><Unknown index="9"/></InitialUnknowns>
+    </ModelStructure>
+</fmiModelDescription>"#;
+        let model_desc: fmi2_schema::Fmi2ModelDescription =
+            fmi::schema::deserialize(xml).expect("valid model description");
+        let violations: Vec<String> = check_fmi2_model_description(&model_desc)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            violations,
+            vec![
+                "[value-reference] Variables 'x' (Real) and 'y' (Integer) share the value reference 0",
+                "[initial] Variable 'y': only floating point variables can be continuous",
+                "[initial] Variable 'p': initial Calculated is not allowed for causality parameter and variability fixed",
+                "[model-structure] InitialUnknown 9: references the unknown index 9",
+                "[model-structure] Variable 'y' (index 3) is not listed as InitialUnknown",
+                "[model-structure] Variable 'der(x)' (index 2) is not listed as InitialUnknown",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_exported_functions() {
+        let bytes = b"\x7fELF\0fmi3GetVersion\0_fmi3DoStep\0xfmi3Reset\0fmi3SetTime is a string";
+        let functions = exported_functions(bytes, "fmi3");
+        assert!(functions.contains(b"fmi3GetVersion".as_slice()));
+        assert!(functions.contains(b"fmi3DoStep".as_slice()));
+        assert!(!functions.contains(b"fmi3Reset".as_slice()));
+        assert!(!functions.contains(b"fmi3SetTime".as_slice()));
+    }
+
+    #[test]
+    fn missing_functions_are_violations() {
+        let archive = tempfile::tempdir().unwrap();
+        let platform = archive.path().join("binaries").join("x86_64-linux");
+        std::fs::create_dir_all(&platform).unwrap();
+        std::fs::write(platform.join("test.so"), b"\0fmi3GetVersion\0").unwrap();
+
+        let functions = BTreeMap::from([("test", vec!["fmi3GetVersion"])]);
+        let violations = check_binaries(archive.path(), &functions).unwrap();
+        assert!(violations.is_empty(), "{violations:?}");
+
+        let functions = BTreeMap::from([("test", vec!["fmi3GetVersion", "fmi3Reset"])]);
+        let violations = check_binaries(archive.path(), &functions).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::Symbols);
+    }
+
+    #[test]
+    fn rejected_instantiation_token_is_a_violation() {
+        assert!(check_instantiation("{test}", || Ok(())).is_none());
+
+        let violation = check_instantiation("{test}", || Err(fmi::Error::Instantiation));
+        assert_eq!(
+            violation.map(|violation| violation.rule),
+            Some(Rule::InstantiationToken)
+        );
+
+        // Without a binary for the host platform the token can not be checked
+        let violation = check_instantiation("{test}", || {
+            Err(fmi::Error::UnsupportedPlatform {
+                os: "os".to_owned(),
+                arch: "arch".to_owned(),
+            })
+        });
+        assert!(violation.is_none());
+    }
+
+    #[test]
+    fn function_lists_match_the_headers() {
+        for (header, lists) in [
+            (
+                "fmi3Functions.h",
+                [
+                    FMI3_COMMON_FUNCTIONS,
+                    FMI3_ME_FUNCTIONS,
+                    FMI3_CS_FUNCTIONS,
+                    FMI3_SE_FUNCTIONS,
+                ]
+                .concat(),
+            ),
+            (
+                "fmi2Functions.h",
+                [FMI2_COMMON_FUNCTIONS, FMI2_ME_FUNCTIONS, FMI2_CS_FUNCTIONS].concat(),
+            ),
+        ] {
+            let standard = if header.starts_with("fmi3") {
+                "fmi-standard3"
+            } else {
+                "fmi-standard2"
+            };
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../fmi-sys")
+                .join(standard)
+                .join("headers")
+                .join(header);
+            let content = std::fs::read_to_string(&path).expect("read header");
+            let declared: BTreeSet<&str> = content
+                .lines()
+                .filter_map(|line| line.strip_prefix("#define "))
+                .filter(|line| line.contains("FullName("))
+                .filter_map(|line| line.split_whitespace().next())
+                .filter(|name| !name.contains('('))
+                .collect();
+            let listed: BTreeSet<&str> = lists.into_iter().collect();
+            assert_eq!(declared, listed, "{header}");
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use anyhow::{Context, Result};
 use libloading::Library;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use fmi::fmi3::{binding, schema};
 use fmi_export::fmi3::ModelMetadata;
@@ -12,15 +12,20 @@ const INSTANTIATION_TOKEN_SYM: &[u8] = b"FMI3_INSTANTIATION_TOKEN";
 const SUPPORTS_ME_SYM: &[u8] = b"fmi3SupportsModelExchange";
 const SUPPORTS_CS_SYM: &[u8] = b"fmi3SupportsCoSimulation";
 const SUPPORTS_SE_SYM: &[u8] = b"fmi3SupportsScheduledExecution";
+const SUPPORTS_FMU_STATE_SYM: &[u8] = b"fmi3SupportsFMUState";
 
 pub struct ModelData {
     pub model_variables: schema::ModelVariables,
     pub model_structure: schema::ModelStructure,
     pub terminals_and_icons: Option<schema::Fmi3TerminalsAndIcons>,
+    pub unit_definitions: Option<schema::UnitDefinitions>,
+    pub type_definitions: Option<schema::TypeDefinitions>,
+    pub log_categories: Option<schema::LogCategories>,
     pub instantiation_token: String,
     pub supports_model_exchange: bool,
     pub supports_co_simulation: bool,
     pub supports_scheduled_execution: bool,
+    pub supports_fmu_state: bool,
 }
 
 impl ModelData {
@@ -67,15 +72,164 @@ impl ModelData {
                 symbol()
             };
 
+            // Not part of the standard, so FMUs built by older versions of fmi-export or by other
+            // exporters don't provide it
+            let supports_fmu_state = match lib
+                .get::<fn() -> binding::fmi3Boolean>(SUPPORTS_FMU_STATE_SYM)
+            {
+                Ok(symbol) => symbol(),
+                Err(_) => {
+                    log::debug!("No fmi3SupportsFMUState symbol, assuming no FMU state support");
+                    false
+                }
+            };
+
             Ok(ModelData {
                 model_variables: metadata.model_variables,
                 model_structure: metadata.model_structure,
                 terminals_and_icons: metadata.terminals,
+                unit_definitions: metadata.unit_definitions,
+                type_definitions: metadata.type_definitions,
+                log_categories: metadata.log_categories,
                 instantiation_token,
                 supports_model_exchange,
                 supports_co_simulation,
                 supports_scheduled_execution,
+                supports_fmu_state,
             })
         }
     }
 }
+
+/// Whether a binary built for `platform` (or the host if `None`) can be loaded on the host.
+pub fn is_host_loadable(platform: Option<&platforms::Platform>) -> bool {
+    platform.is_none_or(|platform| {
+        platform.target_os.as_str() == std::env::consts::OS
+            && platform.target_arch.as_str() == std::env::consts::ARCH
+    })
+}
+
+impl ModelData {
+    /// Check that `other`, extracted from another binary, describes the same model.
+    fn ensure_consistent(&self, other: &ModelData) -> Result<()> {
+        let mut mismatches = Vec::new();
+        if self.instantiation_token != other.instantiation_token {
+            mismatches.push("instantiation token");
+        }
+        if self.model_variables != other.model_variables {
+            mismatches.push("model variables");
+        }
+        if self.model_structure != other.model_structure {
+            mismatches.push("model structure");
+        }
+        if self.terminals_and_icons != other.terminals_and_icons {
+            mismatches.push("terminals");
+        }
+        if self.unit_definitions != other.unit_definitions
+            || self.type_definitions != other.type_definitions
+        {
+            mismatches.push("unit or type definitions");
+        }
+        if self.log_categories != other.log_categories {
+            mismatches.push("log categories");
+        }
+        if (
+            self.supports_model_exchange,
+            self.supports_co_simulation,
+            self.supports_scheduled_execution,
+            self.supports_fmu_state,
+        ) != (
+            other.supports_model_exchange,
+            other.supports_co_simulation,
+            other.supports_scheduled_execution,
+            other.supports_fmu_state,
+        ) {
+            mismatches.push("supported interface types");
+        }
+
+        if !mismatches.is_empty() {
+            anyhow::bail!("differs in {}", mismatches.join(", "));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the binary at `path` embeds the instantiation token.
+fn contains_instantiation_token(path: &Path, token: &str) -> Result<bool> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(bytes
+        .windows(token.len())
+        .any(|window| window == token.as_bytes()))
+}
+
+/// Extract the model data from the built binaries and check that they all export the same model.
+///
+/// The metadata is read from the first binary that can be loaded on the host, calling
+/// `build_host` if there is none. Further loadable binaries are compared in full; binaries for
+/// foreign platforms are never loaded, but must embed the same instantiation token.
+pub fn extract_model_data(
+    dylibs: &[(Option<&'static platforms::Platform>, PathBuf)],
+    build_host: impl FnOnce() -> Result<PathBuf>,
+) -> Result<ModelData> {
+    let host_dylib = match dylibs
+        .iter()
+        .find(|(platform, _)| is_host_loadable(*platform))
+    {
+        Some((_, path)) => path.clone(),
+        None => {
+            log::info!(
+                "None of the targets runs on the host, building for the host to extract the model metadata"
+            );
+            build_host()?
+        }
+    };
+    let model_data = ModelData::new_from_dylib(&host_dylib)?;
+
+    for (platform, path) in dylibs {
+        let target = platform.map_or("host", |platform| platform.target_triple);
+        if *path == host_dylib {
+            continue;
+        }
+        if is_host_loadable(*platform) {
+            ModelData::new_from_dylib(path)?
+                .ensure_consistent(&model_data)
+                .with_context(|| {
+                    format!("The binary for target {target} exports a different model")
+                })?;
+        } else if !contains_instantiation_token(path, &model_data.instantiation_token)? {
+            anyhow::bail!(
+                "The binary for target {target} does not contain the instantiation token {}",
+                model_data.instantiation_token
+            );
+        } else {
+            log::debug!("Found the instantiation token in the binary for target {target}");
+        }
+    }
+
+    Ok(model_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foreign_platforms_are_not_loadable() {
+        assert!(is_host_loadable(None));
+        let foreign = platforms::Platform::ALL
+            .iter()
+            .find(|platform| platform.target_os.as_str() != std::env::consts::OS)
+            .expect("foreign platform");
+        assert!(!is_host_loadable(Some(foreign)));
+    }
+
+    #[test]
+    fn finds_instantiation_token_in_binary() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("libmodel.so");
+        std::fs::write(&path, b"\x7fELF\0\0{6ba7b810-9dad}\0").expect("write binary");
+        assert!(contains_instantiation_token(&path, "{6ba7b810-9dad}").unwrap());
+        assert!(!contains_instantiation_token(&path, "{00000000-0000}").unwrap());
+    }
+}
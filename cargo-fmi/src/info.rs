@@ -21,8 +21,11 @@ pub fn info(package: &Option<String>, target: &Option<Vec<String>>, release: boo
         })
         .transpose()?;
 
-    let cdylibs = crate::builder::build_lib(&package.id, &target_platforms, release)?;
-    let model_data = crate::extractor::ModelData::new_from_dylib(&cdylibs[0].1)?;
+    let cdylibs = crate::builder::build_lib(&package.id, &target_platforms, release, None)?;
+    let model_data = crate::extractor::extract_model_data(&cdylibs, || {
+        let host = crate::builder::build_lib(&package.id, &None, release, None)?;
+        Ok(host[0].1.clone())
+    })?;
     let model_description =
         crate::metadata::create_model_description(&model_identifier, &package, model_data)?;
 
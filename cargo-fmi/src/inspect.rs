@@ -59,10 +59,26 @@ fn inspect_debug<Imp: FmiImport>(fmu_path: &Path, major: MajorVersion) -> Result
     let mut entries = list_archive_entries(import.archive_path())
         .context("Failed to list extracted FMU contents")?;
     entries.sort_by(|(a, _), (b, _)| a.cmp(b));
-    for (path, size) in entries {
+    for (path, size) in &entries {
         println!("  - {} ({} bytes)", path.display(), size);
     }
 
+    for (title, folder) in [
+        ("Resources", "resources"),
+        ("Documentation", "documentation"),
+    ] {
+        let files = files_in_folder(&entries, folder);
+        if !files.is_empty() {
+            println!("{title}:");
+            for file in files {
+                println!("  - {}", file.display());
+            }
+        }
+    }
+    if let Some(icon) = find_icon(&entries) {
+        println!("Icon: {}", icon.display());
+    }
+
     if major == MajorVersion::FMI3 {
         let build_path = import
             .archive_path()
@@ -90,6 +106,27 @@ fn inspect_debug<Imp: FmiImport>(fmu_path: &Path, major: MajorVersion) -> Result
     Ok(())
 }
 
+/// Paths of the entries below the given top-level archive folder, relative to it.
+fn files_in_folder<'a>(entries: &'a [(PathBuf, u64)], folder: &str) -> Vec<&'a Path> {
+    entries
+        .iter()
+        .filter_map(|(path, _)| path.strip_prefix(folder).ok())
+        .collect()
+}
+
+/// The model icon, `terminalsAndIcons/icon.{png,svg}` in FMI 3.0 or `model.png` in FMI 2.0.
+fn find_icon(entries: &[(PathBuf, u64)]) -> Option<&Path> {
+    let candidates = [
+        Path::new("terminalsAndIcons").join("icon.png"),
+        Path::new("terminalsAndIcons").join("icon.svg"),
+        PathBuf::from("model.png"),
+    ];
+    entries
+        .iter()
+        .map(|(path, _)| path.as_path())
+        .find(|path| candidates.iter().any(|candidate| candidate == path))
+}
+
 fn list_archive_entries(root: &Path) -> Result<Vec<(PathBuf, u64)>> {
     let mut entries = Vec::new();
     collect_entries(root, root, &mut entries)?;
@@ -133,4 +170,34 @@ mod tests {
         assert!(names.contains(&"modelDescription.xml".to_string()));
         assert!(names.contains(&format!("nested{}resources.bin", std::path::MAIN_SEPARATOR)));
     }
+
+    #[test]
+    fn packaged_files_are_grouped() {
+        let entries = vec![
+            (PathBuf::from("modelDescription.xml"), 1),
+            (Path::new("resources").join("table.csv"), 2),
+            (Path::new("documentation").join("index.html"), 3),
+            (
+                Path::new("documentation").join("licenses").join("LICENSE"),
+                4,
+            ),
+            (Path::new("terminalsAndIcons").join("icon.svg"), 5),
+        ];
+
+        assert_eq!(
+            files_in_folder(&entries, "resources"),
+            vec![Path::new("table.csv")]
+        );
+        assert_eq!(
+            files_in_folder(&entries, "documentation"),
+            vec![
+                Path::new("index.html"),
+                &Path::new("licenses").join("LICENSE")
+            ]
+        );
+        assert_eq!(
+            find_icon(&entries),
+            Some(Path::new("terminalsAndIcons").join("icon.svg").as_path())
+        );
+    }
 }
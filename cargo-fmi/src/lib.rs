@@ -7,12 +7,15 @@ use std::ffi::OsString;
 
 mod builder;
 mod bundle;
+mod check;
 mod extractor;
 mod info;
 mod inspect;
 mod metadata;
 mod new;
 mod packager;
+mod rebuild;
+mod sources;
 
 #[derive(Parser, Debug)]
 #[command(name = "cargo-fmi", bin_name = "cargo-fmi")]
@@ -47,6 +50,21 @@ enum Commands {
         /// Build in release mode
         #[arg(long, default_value_t = false)]
         release: bool,
+        /// Include the crate sources and a buildDescription.xml (source code FMU)
+        #[arg(long, default_value_t = false)]
+        source: bool,
+    },
+    /// Compile a source code FMU for the host platform
+    Rebuild {
+        /// Path to the source code FMU
+        #[arg(value_name = "FMU_PATH")]
+        fmu: std::path::PathBuf,
+        /// Write the rebuilt FMU to this path instead of updating it in place
+        #[arg(long, value_name = "PATH")]
+        output: Option<std::path::PathBuf>,
+        /// Build in release mode
+        #[arg(long, default_value_t = false)]
+        release: bool,
     },
     /// Inspect a packaged FMU (.fmu)
     Inspect {
@@ -57,6 +75,12 @@ enum Commands {
         #[arg(long, value_enum, default_value_t = InspectFormat::ModelDescription)]
         format: InspectFormat,
     },
+    /// Check a packaged FMU (.fmu) against the rules of the FMI standard
+    Check {
+        /// Path to the FMU file to check
+        #[arg(value_name = "FMU_PATH")]
+        fmu: std::path::PathBuf,
+    },
     /// Print the model description struct for a package
     Info {
         /// Name of the package
@@ -103,8 +127,15 @@ where
             package,
             target,
             release,
-        } => bundle::bundle(&package.or(global_package), &target, release)?,
+            source,
+        } => bundle::bundle(&package.or(global_package), &target, release, source)?,
+        Commands::Rebuild {
+            fmu,
+            output,
+            release,
+        } => rebuild::rebuild(&fmu, output.as_deref(), release)?,
         Commands::Inspect { fmu, format } => inspect::inspect(&fmu, format)?,
+        Commands::Check { fmu } => check::check(&fmu)?,
         Commands::Info {
             package,
             target,
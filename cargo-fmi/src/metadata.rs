@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Context;
 use cargo_metadata::{CrateType, MetadataCommand, Package, Target};
 
@@ -7,6 +9,8 @@ pub struct MetadataBuilder {
     pub model_identifier: String,
     pub package: Package,
     pub target_dir: std::path::PathBuf,
+    /// Metadata of the whole workspace, used to vendor path dependencies
+    pub workspace_metadata: cargo_metadata::Metadata,
 }
 
 impl MetadataBuilder {
@@ -45,7 +49,11 @@ impl MetadataBuilder {
         Ok(Self {
             model_identifier: target.name.to_string(),
             package: package.clone(),
-            target_dir: workspace_metadata.target_directory.into_std_path_buf(),
+            target_dir: workspace_metadata
+                .target_directory
+                .clone()
+                .into_std_path_buf(),
+            workspace_metadata,
         })
     }
 }
@@ -107,6 +115,102 @@ pub fn parse_package_metadata(
     Ok(None)
 }
 
+/// Additional files to include in the FMU archive, from `[package.metadata.fmu]`.
+///
+/// Paths are relative to the package manifest directory. Files are placed at the top of their
+/// archive folder, while the contents of directories are copied recursively.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct PackageFiles {
+    /// Files or directories copied into `resources/`
+    #[serde(default)]
+    pub resources: Vec<PathBuf>,
+    /// Files or directories copied into `documentation/`, e.g. `index.html`
+    #[serde(default)]
+    pub documentation: Vec<PathBuf>,
+    /// Files or directories copied into `documentation/licenses/`
+    #[serde(default)]
+    pub licenses: Vec<PathBuf>,
+    /// PNG or SVG icon, stored as `terminalsAndIcons/icon.{png,svg}`
+    pub icon: Option<PathBuf>,
+}
+
+impl PackageFiles {
+    /// Resolve the configured files to `(archive path, source path)` pairs.
+    pub fn collect(&self, manifest_dir: &Path) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+        let mut files = Vec::new();
+        for (folder, sources) in [
+            ("resources", &self.resources),
+            ("documentation", &self.documentation),
+            ("documentation/licenses", &self.licenses),
+        ] {
+            for source in sources {
+                collect_files(&manifest_dir.join(source), Path::new(folder), &mut files)?;
+            }
+        }
+
+        if let Some(icon) = &self.icon {
+            let source = manifest_dir.join(icon);
+            let extension = source
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(str::to_ascii_lowercase);
+            let name = match extension.as_deref() {
+                Some("png") => "icon.png",
+                Some("svg") => "icon.svg",
+                _ => anyhow::bail!(
+                    "Unsupported icon format '{}', expected a .png or .svg file",
+                    icon.display()
+                ),
+            };
+            if !source.is_file() {
+                anyhow::bail!("Icon file not found: {}", source.display());
+            }
+            files.push((Path::new("terminalsAndIcons").join(name), source));
+        }
+
+        Ok(files)
+    }
+}
+
+/// Add `source` under `folder`, recursing into directories.
+fn collect_files(
+    source: &Path,
+    folder: &Path,
+    files: &mut Vec<(PathBuf, PathBuf)>,
+) -> anyhow::Result<()> {
+    if source.is_dir() {
+        let mut entries = std::fs::read_dir(source)
+            .with_context(|| format!("Failed to read directory: {}", source.display()))?
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_files(&path, &folder.join(entry.file_name()), files)?;
+            } else {
+                files.push((folder.join(entry.file_name()), path));
+            }
+        }
+    } else if source.is_file() {
+        let name = source.file_name().context("Invalid file name")?;
+        files.push((folder.join(name), source.to_path_buf()));
+    } else {
+        anyhow::bail!("File or directory not found: {}", source.display());
+    }
+    Ok(())
+}
+
+/// Parse the additional package files from package.metadata.fmu
+pub fn parse_package_files(package: &Package) -> anyhow::Result<PackageFiles> {
+    match package.metadata.get("fmu") {
+        Some(fmu_metadata_value) => serde_json::from_value(fmu_metadata_value.clone()).context(
+            "Failed to parse FMU package files. Please check the `resources`, `documentation`, \
+            `licenses` and `icon` keys of your Cargo.toml [package.metadata.fmu] section.",
+        ),
+        None => Ok(PackageFiles::default()),
+    }
+}
+
 pub fn create_model_description(
     model_identifier: &str,
     package: &Package,
@@ -119,6 +223,8 @@ pub fn create_model_description(
     let fmi_version =
         unsafe { std::ffi::CStr::from_ptr(binding::fmi3Version.as_ptr() as _) }.to_string_lossy();
 
+    let supports_fmu_state = Some(model_data.supports_fmu_state);
+
     // Build model description from package metadata
     Ok(schema::Fmi3ModelDescription {
         fmi_version: fmi_version.to_string(),
@@ -131,6 +237,9 @@ pub fn create_model_description(
         license: package.license.clone(),
         generation_tool: Some("rust-fmi".to_string()),
         generation_date_and_time: Some(chrono::Utc::now().to_rfc3339()),
+        unit_definitions: model_data.unit_definitions,
+        type_definitions: model_data.type_definitions,
+        log_categories: model_data.log_categories,
         // Set the extracted model variables and structure
         model_variables: model_data.model_variables,
         model_structure: model_data.model_structure,
@@ -140,8 +249,10 @@ pub fn create_model_description(
             .supports_model_exchange
             .then(|| schema::Fmi3ModelExchange {
                 model_identifier: model_identifier.to_string(),
-                can_get_and_set_fmu_state: Some(false),
-                can_serialize_fmu_state: Some(false),
+                can_get_and_set_fmu_state: supports_fmu_state,
+                can_serialize_fmu_state: supports_fmu_state,
+                provides_directional_derivatives: Some(true),
+                provides_adjoint_derivatives: Some(true),
                 ..Default::default()
             }),
         co_simulation: model_data
@@ -149,12 +260,20 @@ pub fn create_model_description(
             .then(|| schema::Fmi3CoSimulation {
                 model_identifier: model_identifier.to_string(),
                 can_handle_variable_communication_step_size: Some(true),
-                has_event_mode: Some(false),
+                has_event_mode: Some(true),
+                can_get_and_set_fmu_state: supports_fmu_state,
+                can_serialize_fmu_state: supports_fmu_state,
+                provides_directional_derivatives: Some(true),
+                provides_adjoint_derivatives: Some(true),
                 ..Default::default()
             }),
         scheduled_execution: model_data.supports_scheduled_execution.then(|| {
             schema::Fmi3ScheduledExecution {
                 model_identifier: model_identifier.to_string(),
+                can_get_and_set_fmu_state: supports_fmu_state,
+                can_serialize_fmu_state: supports_fmu_state,
+                provides_directional_derivatives: Some(true),
+                provides_adjoint_derivatives: Some(true),
                 ..Default::default()
             }
         }),
@@ -162,27 +281,67 @@ pub fn create_model_description(
     })
 }
 
-/// Create a basic BuildDescription for the FMU
+/// Create the BuildDescription of a source code FMU.
+///
+/// Source file names are relative to the `sources` folder of the FMU, as laid out by
+/// [`crate::sources::vendor`]. The Cargo features of the package are listed as optional
+/// preprocessor definitions.
 pub fn create_build_description(
     model_identifier: &str,
+    package: &Package,
 ) -> anyhow::Result<schema::Fmi3BuildDescription> {
     let fmi_version =
         unsafe { std::ffi::CStr::from_ptr(binding::fmi3Version.as_ptr() as _) }.to_string_lossy();
+
+    let target = find_cdylib_target(package)?;
+    let package_dir = package
+        .manifest_path
+        .parent()
+        .expect("Manifest path has a parent directory");
+    let lib_path = target
+        .src_path
+        .strip_prefix(package_dir)
+        .with_context(|| format!("Library source {} is outside the package", target.src_path))?;
+
+    let default_features = package.features.get("default");
+    let preprocessor_definitions = package
+        .features
+        .keys()
+        .filter(|feature| *feature != "default")
+        .map(|feature| schema::PreprocessorDefinition {
+            name: feature.clone(),
+            optional: Some(true),
+            description: default_features
+                .is_some_and(|default| default.contains(feature))
+                .then(|| "Cargo feature, enabled by default".to_string()),
+            ..Default::default()
+        })
+        .collect();
+
     Ok(schema::Fmi3BuildDescription {
         fmi_version: fmi_version.to_string(),
         build_configurations: vec![schema::BuildConfiguration {
             model_identifier: model_identifier.to_string(),
-            platform: Some("generic".to_string()),
             description: Some(format!(
-                "Build configuration for {model_identifier} FMU generated from Rust"
+                "Build {model_identifier} with Cargo from the sources folder, \
+                or with `cargo fmi rebuild`"
             )),
             source_file_sets: vec![schema::SourceFileSet {
-                name: Some("documentation".to_string()),
-                language: Some("Markdown".to_string()),
-                source_files: vec![schema::SourceFile {
-                    name: "README.md".to_string(),
-                    ..Default::default()
-                }],
+                name: Some(package.name.to_string()),
+                language: Some("Rust".to_string()),
+                compiler: Some("cargo".to_string()),
+                compiler_options: Some(format!("build --lib --release --package {}", package.name)),
+                source_files: vec![
+                    schema::SourceFile {
+                        name: "Cargo.toml".to_string(),
+                        ..Default::default()
+                    },
+                    schema::SourceFile {
+                        name: format!("{}/{}", package.name, lib_path.as_str().replace('\\', "/")),
+                        ..Default::default()
+                    },
+                ],
+                preprocessor_definitions,
                 ..Default::default()
             }],
             ..Default::default()
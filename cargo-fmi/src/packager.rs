@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     io::Write,
     path::{Path, PathBuf},
 };
@@ -7,13 +8,17 @@ use anyhow::Context;
 use fmi::fmi3::schema;
 
 /// Create the FMU ZIP package
+///
+/// `files` are additional `(archive path, source path)` pairs, such as resources, documentation
+/// and vendored sources. The build description is only written for source code FMUs.
 pub fn package_fmu(
     model_identifier: &str,
     model_description: schema::Fmi3ModelDescription,
-    build_description: schema::Fmi3BuildDescription,
+    build_description: Option<schema::Fmi3BuildDescription>,
     terminals_and_icons: Option<schema::Fmi3TerminalsAndIcons>,
     fmu_path: &Path,
     dylibs: &[(Option<&'static platforms::Platform>, PathBuf)],
+    files: &[(PathBuf, PathBuf)],
 ) -> anyhow::Result<()> {
     log::debug!("Creating FMU package at: {}", fmu_path.display());
 
@@ -25,7 +30,7 @@ pub fn package_fmu(
 
     let mut zw = zip::ZipWriter::new(std::fs::File::create(&fmu_path)?);
 
-    let binaries_path = PathBuf::from("binaries");
+    let mut directories = BTreeSet::new();
 
     zw.set_comment("Created by rust-fmi");
 
@@ -39,19 +44,21 @@ pub fn package_fmu(
     zw.write_all(xml.as_bytes())?;
 
     // Write the buildDescription.xml file (under sources/)
-    let sources_dir = PathBuf::from("sources");
-    zw.add_directory_from_path(&sources_dir, zip::write::SimpleFileOptions::default())?;
-    zw.start_file_from_path(
-        sources_dir.join("buildDescription.xml"),
-        zip::write::SimpleFileOptions::default(),
-    )?;
-    let build_xml = fmi::schema::serialize(&build_description, false)
-        .context("Failed to serialize build description")?;
-    zw.write_all(build_xml.as_bytes())?;
+    if let Some(build_description) = build_description {
+        let sources_dir = PathBuf::from("sources");
+        add_directory(&mut zw, &mut directories, &sources_dir)?;
+        zw.start_file_from_path(
+            sources_dir.join("buildDescription.xml"),
+            zip::write::SimpleFileOptions::default(),
+        )?;
+        let build_xml = fmi::schema::serialize(&build_description, false)
+            .context("Failed to serialize build description")?;
+        zw.write_all(build_xml.as_bytes())?;
+    }
 
     if let Some(terminals) = terminals_and_icons {
         let terminals_dir = PathBuf::from("terminalsAndIcons");
-        zw.add_directory_from_path(&terminals_dir, zip::write::SimpleFileOptions::default())?;
+        add_directory(&mut zw, &mut directories, &terminals_dir)?;
         zw.start_file_from_path(
             terminals_dir.join("terminalsAndIcons.xml"),
             zip::write::SimpleFileOptions::default(),
@@ -61,37 +68,268 @@ pub fn package_fmu(
         zw.write_all(terminals_xml.as_bytes())?;
     }
 
-    for (platform, dylib_path) in dylibs {
-        let (os, arch) = if let Some(p) = platform {
-            (p.target_os.as_str(), p.target_arch.as_str())
-        } else {
-            (std::env::consts::OS, std::env::consts::ARCH)
-        };
-        let folder = fmi::fmi3::platform_folder(os, arch)?;
-        let path = binaries_path.join(folder);
-
-        // Create a filename for the archive entry consisting of the model identifier and the extension
-        let filename = format!(
-            "{}.{}",
-            model_identifier,
-            dylib_path
-                .extension()
-                .expect("Dylib must have an extension")
-                .to_str()
-                .unwrap()
-        );
+    add_binaries(&mut zw, &mut directories, model_identifier, dylibs)?;
 
-        zw.add_directory_from_path(&path, zip::write::SimpleFileOptions::default())?;
-        zw.start_file_from_path(
-            path.join(&filename),
-            zip::write::SimpleFileOptions::default(),
-        )?;
+    for (archive_path, source) in files {
+        log::debug!("Adding {} as {}", source.display(), archive_path.display());
+        if let Some(parent) = archive_path.parent() {
+            add_directory(&mut zw, &mut directories, parent)?;
+        }
+        zw.start_file_from_path(archive_path, zip::write::SimpleFileOptions::default())?;
+        let mut f = std::fs::File::open(source)
+            .with_context(|| format!("Failed to open {}", source.display()))?;
+        std::io::copy(&mut f, &mut zw)?;
+    }
+
+    zw.finish()?;
+
+    Ok(())
+}
+
+/// Archive path of the binary built for `platform`, or for the host if `None`.
+fn binary_path(
+    model_identifier: &str,
+    platform: Option<&'static platforms::Platform>,
+    dylib_path: &Path,
+) -> anyhow::Result<PathBuf> {
+    let (os, arch) = if let Some(p) = platform {
+        (p.target_os.as_str(), p.target_arch.as_str())
+    } else {
+        (std::env::consts::OS, std::env::consts::ARCH)
+    };
+    let folder = fmi::fmi3::platform_folder(os, arch)?;
+
+    // Create a filename for the archive entry consisting of the model identifier and the extension
+    let filename = format!(
+        "{}.{}",
+        model_identifier,
+        dylib_path
+            .extension()
+            .expect("Dylib must have an extension")
+            .to_str()
+            .unwrap()
+    );
+    Ok(PathBuf::from("binaries").join(folder).join(filename))
+}
+
+/// Add the built dylibs below `binaries/<platform>/`.
+fn add_binaries<W: std::io::Write + std::io::Seek>(
+    zw: &mut zip::ZipWriter<W>,
+    directories: &mut BTreeSet<PathBuf>,
+    model_identifier: &str,
+    dylibs: &[(Option<&'static platforms::Platform>, PathBuf)],
+) -> anyhow::Result<()> {
+    let mut added = BTreeSet::new();
+    for (platform, dylib_path) in dylibs {
+        let path = binary_path(model_identifier, *platform, dylib_path)?;
+        if !added.insert(path.clone()) {
+            anyhow::bail!(
+                "More than one target maps to {}, build only one of them",
+                path.display()
+            );
+        }
+        if let Some(parent) = path.parent() {
+            add_directory(zw, directories, parent)?;
+        }
+        zw.start_file_from_path(&path, zip::write::SimpleFileOptions::default())?;
 
         let mut f = std::fs::File::open(dylib_path)?;
-        std::io::copy(&mut f, &mut zw)?;
+        std::io::copy(&mut f, zw)?;
     }
+    Ok(())
+}
 
+/// Copy the FMU at `fmu_path` to `output_path`, adding or replacing the given binaries.
+pub fn replace_binaries(
+    fmu_path: &Path,
+    output_path: &Path,
+    model_identifier: &str,
+    dylibs: &[(Option<&'static platforms::Platform>, PathBuf)],
+) -> anyhow::Result<()> {
+    let mut archive = zip::ZipArchive::new(
+        std::fs::File::open(fmu_path)
+            .with_context(|| format!("Failed to open {}", fmu_path.display()))?,
+    )?;
+
+    let replaced = dylibs
+        .iter()
+        .map(|(platform, dylib_path)| binary_path(model_identifier, *platform, dylib_path))
+        .collect::<anyhow::Result<BTreeSet<_>>>()?;
+
+    // Write next to the output first, so the input may be overwritten
+    let output_dir = output_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(output_dir)?;
+    let temp = tempfile::NamedTempFile::new_in(output_dir)?;
+
+    let mut zw = zip::ZipWriter::new(temp.reopen()?);
+    zw.set_comment("Created by rust-fmi");
+    let mut directories = BTreeSet::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let path = PathBuf::from(entry.name());
+        if replaced.contains(&path) {
+            continue;
+        }
+        if entry.is_dir() {
+            directories.insert(path.components().collect());
+        }
+        zw.raw_copy_file(entry)?;
+    }
+    add_binaries(&mut zw, &mut directories, model_identifier, dylibs)?;
     zw.finish()?;
 
+    temp.persist(output_path)
+        .with_context(|| format!("Failed to write {}", output_path.display()))?;
     Ok(())
 }
+
+/// Add a directory entry and its parents to the archive, unless already present.
+fn add_directory<W: std::io::Write + std::io::Seek>(
+    zw: &mut zip::ZipWriter<W>,
+    directories: &mut BTreeSet<PathBuf>,
+    path: &Path,
+) -> anyhow::Result<()> {
+    if path.as_os_str().is_empty() || directories.contains(path) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        add_directory(zw, directories, parent)?;
+    }
+    zw.add_directory_from_path(path, zip::write::SimpleFileOptions::default())?;
+    directories.insert(path.to_path_buf());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::PackageFiles;
+    use tempfile::tempdir;
+
+    #[test]
+    fn package_fmu_includes_package_files() {
+        let root = tempdir().expect("temp dir");
+        let root_path = root.path();
+        std::fs::create_dir_all(root_path.join("data").join("maps")).expect("create data");
+        std::fs::write(root_path.join("data").join("table.csv"), "t,y").expect("write table");
+        std::fs::write(root_path.join("data").join("maps").join("map.bin"), "map")
+            .expect("write map");
+        std::fs::write(root_path.join("index.html"), "<html/>").expect("write index");
+        std::fs::write(root_path.join("LICENSE"), "MIT").expect("write license");
+        std::fs::write(root_path.join("logo.png"), "png").expect("write icon");
+
+        let package_files = PackageFiles {
+            resources: vec![PathBuf::from("data")],
+            documentation: vec![PathBuf::from("index.html")],
+            licenses: vec![PathBuf::from("LICENSE")],
+            icon: Some(PathBuf::from("logo.png")),
+        };
+        let files = package_files.collect(root_path).expect("collect files");
+
+        let fmu_path = root_path.join("out").join("model.fmu");
+        package_fmu(
+            "model",
+            schema::Fmi3ModelDescription::default(),
+            None,
+            None,
+            &fmu_path,
+            &[],
+            &files,
+        )
+        .expect("package fmu");
+
+        let mut archive =
+            zip::ZipArchive::new(std::fs::File::open(&fmu_path).expect("open fmu")).expect("zip");
+        let names: Vec<_> = archive.file_names().map(str::to_owned).collect();
+        for name in [
+            "resources/table.csv",
+            "resources/maps/map.bin",
+            "documentation/index.html",
+            "documentation/licenses/LICENSE",
+            "terminalsAndIcons/icon.png",
+        ] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "{name} missing in {names:?}"
+            );
+        }
+
+        let mut table = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("resources/table.csv").expect("table"),
+            &mut table,
+        )
+        .expect("read table");
+        assert_eq!(table, "t,y");
+    }
+
+    #[test]
+    fn replace_binaries_keeps_other_entries() {
+        let root = tempdir().expect("temp dir");
+        let root_path = root.path();
+        std::fs::write(root_path.join("old.so"), "old").expect("write old dylib");
+        std::fs::write(root_path.join("new.so"), "new").expect("write new dylib");
+        std::fs::write(root_path.join("table.csv"), "t,y").expect("write table");
+
+        let fmu_path = root_path.join("model.fmu");
+        package_fmu(
+            "model",
+            schema::Fmi3ModelDescription::default(),
+            Some(schema::Fmi3BuildDescription::default()),
+            None,
+            &fmu_path,
+            &[(None, root_path.join("old.so"))],
+            &[(
+                PathBuf::from("resources/table.csv"),
+                root_path.join("table.csv"),
+            )],
+        )
+        .expect("package fmu");
+
+        let output_path = root_path.join("out").join("model.fmu");
+        replace_binaries(
+            &fmu_path,
+            &output_path,
+            "model",
+            &[(None, root_path.join("new.so"))],
+        )
+        .expect("replace binaries");
+
+        let mut archive =
+            zip::ZipArchive::new(std::fs::File::open(&output_path).expect("open")).expect("zip");
+        let binary = binary_path("model", None, Path::new("new.so")).expect("binary path");
+        let mut contents = String::new();
+        std::io::Read::read_to_string(
+            &mut archive
+                .by_name(binary.to_str().expect("utf-8"))
+                .expect("binary"),
+            &mut contents,
+        )
+        .expect("read binary");
+        assert_eq!(contents, "new");
+        for name in ["sources/buildDescription.xml", "resources/table.csv"] {
+            assert!(archive.by_name(name).is_ok(), "{name} missing");
+        }
+        assert_eq!(
+            archive
+                .file_names()
+                .filter(|name| name.ends_with(".so"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn package_files_reject_unsupported_icon() {
+        let root = tempdir().expect("temp dir");
+        std::fs::write(root.path().join("logo.bmp"), "bmp").expect("write icon");
+        let package_files = PackageFiles {
+            icon: Some(PathBuf::from("logo.bmp")),
+            ..Default::default()
+        };
+        assert!(package_files.collect(root.path()).is_err());
+    }
+}
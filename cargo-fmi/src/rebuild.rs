@@ -0,0 +1,63 @@
+//! Implements the `rebuild` command to compile a source code FMU for the host platform.
+
+use std::path::Path;
+
+use anyhow::Context;
+use cargo_metadata::{CrateType, MetadataCommand};
+use fmi::fmi3::schema;
+
+pub fn rebuild(fmu: &Path, output: Option<&Path>, release: bool) -> anyhow::Result<()> {
+    let extracted = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let mut archive = zip::ZipArchive::new(
+        std::fs::File::open(fmu).with_context(|| format!("Failed to open {}", fmu.display()))?,
+    )?;
+    archive
+        .extract(extracted.path())
+        .context("Failed to extract the FMU")?;
+
+    let sources_dir = extracted.path().join("sources");
+    let build_xml = std::fs::read_to_string(sources_dir.join("buildDescription.xml"))
+        .context("Not a source code FMU: sources/buildDescription.xml is missing")?;
+    let build_description: schema::Fmi3BuildDescription =
+        fmi::schema::deserialize(&build_xml).context("Failed to parse buildDescription.xml")?;
+
+    let configuration = build_description
+        .build_configurations
+        .iter()
+        .find(|configuration| {
+            configuration
+                .source_file_sets
+                .iter()
+                .any(|set| set.language.as_deref() == Some("Rust"))
+        })
+        .context("The build description has no Rust build configuration")?;
+    let model_identifier = &configuration.model_identifier;
+
+    let manifest_path = sources_dir.join("Cargo.toml");
+    let metadata = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .exec()
+        .context("Failed to execute cargo metadata on the FMU sources")?;
+    let package = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|package| {
+            package.targets.iter().any(|target| {
+                target.name == *model_identifier && target.crate_types.contains(&CrateType::CDyLib)
+            })
+        })
+        .with_context(|| format!("No cdylib target '{model_identifier}' in the FMU sources"))?;
+
+    log::info!(
+        "Rebuilding '{}' from package '{}'",
+        model_identifier,
+        package.name
+    );
+    let cdylibs = crate::builder::build_lib(&package.id, &None, release, Some(&manifest_path))?;
+
+    let output = output.unwrap_or(fmu);
+    crate::packager::replace_binaries(fmu, output, model_identifier, &cdylibs)?;
+    log::info!("Wrote {}", output.display());
+
+    Ok(())
+}
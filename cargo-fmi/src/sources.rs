@@ -0,0 +1,373 @@
+//! Vendor the crate sources of a package for a source code FMU.
+//!
+//! The package and all of its path dependencies are copied below `sources/<package name>/`. A
+//! generated `sources/Cargo.toml` ties them together as a workspace, so that the FMU can be
+//! rebuilt with `cargo build` from the `sources` folder.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use cargo_metadata::{DependencyKind, Metadata, Package};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Files of a source code FMU
+pub struct SourceTree {
+    /// Holds the generated manifests until the FMU is packaged
+    _generated: tempfile::TempDir,
+    /// `(archive path, source path)` pairs of all files below `sources/`
+    pub files: Vec<(PathBuf, PathBuf)>,
+}
+
+/// The package and its transitive path dependencies, keyed by package name
+fn path_packages<'a>(
+    metadata: &'a Metadata,
+    package: &'a Package,
+) -> BTreeMap<&'a str, &'a Package> {
+    let mut packages = BTreeMap::new();
+    let mut stack = vec![package];
+    while let Some(package) = stack.pop() {
+        if packages.insert(package.name.as_str(), package).is_some() {
+            continue;
+        }
+        for dependency in &package.dependencies {
+            if dependency.kind == DependencyKind::Development {
+                continue;
+            }
+            let Some(path) = &dependency.path else {
+                continue;
+            };
+            if let Some(dep_package) = metadata.packages.iter().find(|p| {
+                p.name.as_str() == dependency.name && p.manifest_path.parent() == Some(path)
+            }) {
+                stack.push(dep_package);
+            }
+        }
+    }
+    packages
+}
+
+/// Vendor `package` and its path dependencies.
+pub fn vendor(metadata: &Metadata, package: &Package) -> anyhow::Result<SourceTree> {
+    let packages = path_packages(metadata, package);
+    let sources_dir = PathBuf::from("sources");
+    let generated = tempfile::tempdir().context("Failed to create temporary directory")?;
+
+    // Vendored directory of each package, keyed by its original directory
+    let vendored: BTreeMap<PathBuf, String> = packages
+        .iter()
+        .map(|(name, package)| {
+            let dir = package
+                .manifest_path
+                .parent()
+                .expect("Manifest path has a parent directory");
+            (normalize(dir.as_std_path()), name.to_string())
+        })
+        .collect();
+
+    let mut files = Vec::new();
+    for (name, package) in &packages {
+        let package_dir = package
+            .manifest_path
+            .parent()
+            .expect("Manifest path has a parent directory")
+            .as_std_path();
+
+        let mut package_files = Vec::new();
+        collect_files(package_dir, package_dir, &mut package_files)?;
+        for relative in package_files {
+            files.push((
+                sources_dir.join(name).join(&relative),
+                package_dir.join(&relative),
+            ));
+        }
+
+        let manifest = std::fs::read_to_string(&package.manifest_path)
+            .with_context(|| format!("Failed to read {}", package.manifest_path))?;
+        let manifest = rewrite_package_manifest(&manifest, package_dir, &vendored)
+            .with_context(|| format!("Failed to rewrite {}", package.manifest_path))?;
+        let generated_manifest = generated.path().join(format!("{name}.toml"));
+        std::fs::write(&generated_manifest, manifest)?;
+        files.push((
+            sources_dir.join(name).join("Cargo.toml"),
+            generated_manifest,
+        ));
+    }
+
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let workspace_manifest =
+        std::fs::read_to_string(workspace_root.join("Cargo.toml")).unwrap_or_default();
+    let manifest = workspace_manifest_for(&workspace_manifest, workspace_root, &vendored)
+        .context("Failed to generate the workspace manifest")?;
+    let generated_manifest = generated.path().join("Cargo.toml");
+    std::fs::write(&generated_manifest, manifest)?;
+    files.push((sources_dir.join("Cargo.toml"), generated_manifest));
+
+    // Pin the dependency versions the FMU was built with
+    let lock_file = workspace_root.join("Cargo.lock");
+    if lock_file.exists() {
+        files.push((sources_dir.join("Cargo.lock"), lock_file));
+    }
+
+    log::info!(
+        "Vendored {} packages with {} files",
+        packages.len(),
+        files.len()
+    );
+
+    Ok(SourceTree {
+        _generated: generated,
+        files,
+    })
+}
+
+/// Collect the files of a package relative to `root`, skipping build output, hidden entries,
+/// nested packages and the manifest itself.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if dir == root && file_name == "target" || path.join("Cargo.toml").exists() {
+                continue;
+            }
+            collect_files(root, &path, files)?;
+        } else if !(dir == root && (file_name == "Cargo.toml" || file_name == "Cargo.lock")) {
+            files.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Lexically resolve `.` and `..` components of a path
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Point the `path` of a dependency at its vendored directory relative to `base`, or return
+/// `false` if the dependency is not vendored.
+fn rewrite_dependency_path(
+    dependency: &mut Item,
+    dir: &Path,
+    vendored: &BTreeMap<PathBuf, String>,
+    base: &str,
+) -> bool {
+    let Some(path) = dependency.get("path").and_then(Item::as_str) else {
+        return true;
+    };
+    match vendored.get(&normalize(&dir.join(path))) {
+        Some(name) => {
+            dependency["path"] = toml_edit::value(format!("{base}{name}"));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Rewrite the path dependencies of a table of dependencies, dropping and returning those not
+/// vendored.
+fn rewrite_dependencies(
+    table: &mut Item,
+    dir: &Path,
+    vendored: &BTreeMap<PathBuf, String>,
+    base: &str,
+) -> Vec<String> {
+    let Some(table) = table.as_table_like_mut() else {
+        return Vec::new();
+    };
+    let unresolved: Vec<String> = table
+        .iter_mut()
+        .filter_map(|(key, dependency)| {
+            (!rewrite_dependency_path(dependency, dir, vendored, base)).then(|| key.to_string())
+        })
+        .collect();
+    for key in &unresolved {
+        table.remove(key);
+    }
+    unresolved
+}
+
+/// Rewrite a package manifest for the vendored layout. Dev-dependencies are removed, since
+/// they are not needed to build the FMU and may not be vendored.
+pub fn rewrite_package_manifest(
+    manifest: &str,
+    package_dir: &Path,
+    vendored: &BTreeMap<PathBuf, String>,
+) -> anyhow::Result<String> {
+    let mut doc: DocumentMut = manifest.parse()?;
+    let root = doc.as_table_mut();
+    root.remove("workspace");
+    root.remove("dev-dependencies");
+    let mut dropped = Vec::new();
+    for key in ["dependencies", "build-dependencies"] {
+        if let Some(table) = root.get_mut(key) {
+            dropped.extend(rewrite_dependencies(table, package_dir, vendored, "../"));
+        }
+    }
+    if let Some(targets) = root.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            let Some(target) = target.as_table_like_mut() else {
+                continue;
+            };
+            target.remove("dev-dependencies");
+            for key in ["dependencies", "build-dependencies"] {
+                if let Some(table) = target.get_mut(key) {
+                    dropped.extend(rewrite_dependencies(table, package_dir, vendored, "../"));
+                }
+            }
+        }
+    }
+    for key in dropped {
+        log::warn!("Dropping path dependency '{key}' that is not vendored");
+    }
+    Ok(doc.to_string())
+}
+
+/// Generate the `sources/Cargo.toml` workspace manifest from the original workspace manifest,
+/// keeping the inherited package keys, dependencies, lints and profiles.
+pub fn workspace_manifest_for(
+    workspace_manifest: &str,
+    workspace_root: &Path,
+    vendored: &BTreeMap<PathBuf, String>,
+) -> anyhow::Result<String> {
+    let original: DocumentMut = workspace_manifest.parse()?;
+    let mut workspace = original
+        .get("workspace")
+        .and_then(Item::as_table)
+        .cloned()
+        .unwrap_or_else(Table::new);
+
+    workspace.remove("exclude");
+    workspace.remove("default-members");
+    if let Some(dependencies) = workspace.get_mut("dependencies") {
+        // Path dependencies of other workspace members are not needed
+        rewrite_dependencies(dependencies, workspace_root, vendored, "");
+    }
+    let mut members = toml_edit::Array::new();
+    members.extend(vendored.values().map(String::as_str));
+    workspace.insert("members", toml_edit::value(members));
+    if !workspace.contains_key("resolver") {
+        workspace.insert("resolver", toml_edit::value("2"));
+    }
+    workspace.set_implicit(false);
+
+    let mut doc = DocumentMut::new();
+    doc.insert("workspace", Item::Table(workspace));
+    if let Some(profile) = original.get("profile") {
+        doc.insert("profile", profile.clone());
+    }
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vendored() -> BTreeMap<PathBuf, String> {
+        BTreeMap::from([
+            (PathBuf::from("/ws/model"), "model".to_string()),
+            (PathBuf::from("/ws/crates/helper"), "helper".to_string()),
+        ])
+    }
+
+    #[test]
+    fn package_manifest_uses_vendored_paths() {
+        let manifest = r#"
+[package]
+name = "model"
+edition.workspace = true
+
+[dependencies]
+helper = { path = "../crates/helper" }
+log = "0.4"
+
+[dev-dependencies]
+tester = { path = "../tester" }
+
+[target.'cfg(unix)'.dependencies]
+other = { path = "../other" }
+"#;
+        let rewritten =
+            rewrite_package_manifest(manifest, Path::new("/ws/model"), &vendored()).unwrap();
+        let doc: DocumentMut = rewritten.parse().unwrap();
+
+        assert_eq!(
+            doc["dependencies"]["helper"]["path"].as_str(),
+            Some("../helper")
+        );
+        assert_eq!(doc["dependencies"]["log"].as_str(), Some("0.4"));
+        assert!(doc.get("dev-dependencies").is_none());
+        assert!(
+            doc["target"]["cfg(unix)"]["dependencies"]
+                .get("other")
+                .is_none()
+        );
+        assert!(rewritten.contains("edition.workspace = true"));
+    }
+
+    #[test]
+    fn workspace_manifest_keeps_inherited_keys() {
+        let manifest = r#"
+[workspace]
+members = ["model", "crates/*", "tools/cli"]
+exclude = ["old"]
+
+[workspace.package]
+edition = "2024"
+
+[workspace.dependencies]
+helper = { path = "crates/helper", version = "0.1" }
+cli = { path = "tools/cli" }
+log = "0.4"
+
+[profile.release]
+lto = true
+
+[package]
+name = "root"
+"#;
+        let generated = workspace_manifest_for(manifest, Path::new("/ws"), &vendored()).unwrap();
+        let doc: DocumentMut = generated.parse().unwrap();
+
+        let members: Vec<_> = doc["workspace"]["members"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|m| m.as_str())
+            .collect();
+        assert_eq!(members, vec!["helper", "model"]);
+        assert!(doc["workspace"].get("exclude").is_none());
+        assert_eq!(
+            doc["workspace"]["package"]["edition"].as_str(),
+            Some("2024")
+        );
+        assert_eq!(
+            doc["workspace"]["dependencies"]["helper"]["path"].as_str(),
+            Some("helper")
+        );
+        assert!(doc["workspace"]["dependencies"].get("cli").is_none());
+        assert_eq!(doc["profile"]["release"]["lto"].as_bool(), Some(true));
+        assert!(doc.get("package").is_none());
+    }
+}
@@ -15,8 +15,13 @@ use fmi_export::{
 ///
 /// This is a simple first-order linear ODE that demonstrates basic
 /// Model Exchange and Co-Simulation capabilities.
-#[derive(FmuModel, Default, Debug)]
-#[model(model_exchange = true, co_simulation = true, user_model = false)]
+#[derive(FmuModel, Default, Debug, Clone)]
+#[model(
+    model_exchange = true,
+    co_simulation = true,
+    user_model = false,
+    fmu_state = true
+)]
 struct Dahlquist {
     /// The state variable
     #[variable(causality = Output, variability = Continuous, start = 1.0, initial = Exact)]
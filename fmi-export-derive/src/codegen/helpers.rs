@@ -0,0 +1,24 @@
+//! Field and type inspection shared by the code generators
+
+use crate::model::{Field, FieldAttributeOuter};
+
+/// Check if a field has the skip attribute set to true
+pub fn has_skip_attribute(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|attr| matches!(attr, FieldAttributeOuter::Variable(var_attr) if var_attr.skip))
+}
+
+/// Check if a type is a `Clock`
+pub fn is_clock_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Clock")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
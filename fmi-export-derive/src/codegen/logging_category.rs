@@ -0,0 +1,77 @@
+//! Code generation for `#[derive(LoggingCategory)]`
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+
+use crate::model::LoggingCategoryEnum;
+
+/// Generates `Display`, `FromStr` and `ModelLoggingCategory` for a logging category enum
+pub struct LoggingCategoryImpl<'a>(&'a LoggingCategoryEnum);
+
+impl<'a> LoggingCategoryImpl<'a> {
+    pub fn new(model: &'a LoggingCategoryEnum) -> Self {
+        Self(model)
+    }
+}
+
+impl ToTokens for LoggingCategoryImpl<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let enum_name = &self.0.ident;
+        let (Some(trace), Some(error)) = (self.0.trace_category(), self.0.error_category()) else {
+            return;
+        };
+        let trace = &trace.ident;
+        let error = &error.ident;
+
+        let idents: Vec<_> = self.0.categories.iter().map(|c| &c.ident).collect();
+        let names: Vec<_> = self.0.categories.iter().map(|c| &c.name).collect();
+        let descriptions = self
+            .0
+            .categories
+            .iter()
+            .map(|category| match &category.description {
+                Some(description) => quote! { Some(#description) },
+                None => quote! { None },
+            });
+
+        tokens.extend(quote! {
+            #[automatically_derived]
+            impl ::std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        #(Self::#idents => f.write_str(#names),)*
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl ::std::str::FromStr for #enum_name {
+                type Err = String;
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    match s {
+                        #(#names => Ok(Self::#idents),)*
+                        _ => Err(format!("Unknown logging category: {}", s)),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl ::fmi_export::fmi3::ModelLoggingCategory for #enum_name {
+                fn all_categories() -> impl Iterator<Item = Self> {
+                    [#(Self::#idents),*].into_iter()
+                }
+                fn trace_category() -> Self {
+                    Self::#trace
+                }
+                fn error_category() -> Self {
+                    Self::#error
+                }
+                fn description(&self) -> Option<&'static str> {
+                    match self {
+                        #(Self::#idents => #descriptions,)*
+                    }
+                }
+            }
+        });
+    }
+}
@@ -5,6 +5,10 @@ use quote::{ToTokens, quote};
 
 use crate::model::Model;
 
+pub use logging_category::LoggingCategoryImpl;
+
+mod helpers;
+mod logging_category;
 mod model_get_set;
 mod model_get_set_states;
 mod model_impl;
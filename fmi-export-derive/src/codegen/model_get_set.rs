@@ -2,6 +2,7 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::{ToTokens, format_ident, quote};
 use syn::{Ident, parse_quote};
 
+use crate::codegen::helpers::has_skip_attribute;
 use crate::model::{Field, FieldAttributeOuter, Model};
 
 pub struct ModelGetSetImpl<'a> {
@@ -9,14 +10,6 @@ pub struct ModelGetSetImpl<'a> {
     pub model: &'a Model,
 }
 
-/// Check if a field has the skip attribute set to true
-fn has_skip_attribute(field: &Field) -> bool {
-    field
-        .attrs
-        .iter()
-        .any(|attr| matches!(attr, FieldAttributeOuter::Variable(var_attr) if var_attr.skip))
-}
-
 /// Filter out fields that have the skip attribute
 fn filter_non_skipped_fields(fields: &[Field]) -> Vec<&Field> {
     fields
@@ -556,6 +549,81 @@ fn build_clock_set_fn(model: &crate::model::Model) -> proc_macro2::TokenStream {
     }
 }
 
+fn build_clock_interval_get_fn(model: &crate::model::Model) -> proc_macro2::TokenStream {
+    // Filter out skipped fields
+    let non_skipped_fields = filter_non_skipped_fields(&model.fields);
+
+    // Create scalar count variables:
+    let scalar_var_counts = non_skipped_fields.iter().map(|f| {
+        let count_name = format_ident!("{}_count", f.ident);
+        let field_type = &f.rust_type;
+        quote! {
+            let #count_name = <#field_type as ::fmi_export::fmi3::ModelGetSet<M>>::FIELD_COUNT as u32;
+        }
+    });
+
+    // Generate if-else conditions to route to the correct field. The interval variability is
+    // checked by the instance, so every field is routed.
+    let mut conditions = Vec::new();
+
+    for (i, field) in non_skipped_fields.iter().enumerate() {
+        let field_name = &field.ident;
+        let field_type = &field.rust_type;
+        let count_name = format_ident!("{}_count", field.ident);
+
+        let cumulative_sum = if i == 0 {
+            quote! { #count_name }
+        } else {
+            let prev_sums: Vec<_> = non_skipped_fields
+                .iter()
+                .take(i)
+                .map(|f| format_ident!("{}_count", f.ident))
+                .collect();
+            quote! { #(#prev_sums)+* + #count_name }
+        };
+
+        let vr_offset = if i == 0 {
+            quote! { vr }
+        } else {
+            let prev_sums: Vec<_> = non_skipped_fields
+                .iter()
+                .take(i)
+                .map(|f| format_ident!("{}_count", f.ident))
+                .collect();
+            quote! { vr - (#(#prev_sums)+*) }
+        };
+
+        conditions.push(quote! {
+            if vr < #cumulative_sum {
+                <#field_type as ::fmi_export::fmi3::ModelGetSet<M>>::get_clock_interval(&mut self.#field_name, #vr_offset, interval, context)
+            }
+        });
+    }
+
+    // Chain all conditions together with else if
+    let chained_conditions = if conditions.is_empty() {
+        quote! { Err(::fmi::fmi3::Fmi3Error::Error) }
+    } else {
+        let mut result = quote! { { Err(::fmi::fmi3::Fmi3Error::Error) } };
+        for condition in conditions.into_iter().rev() {
+            result = quote! { #condition else #result };
+        }
+        result
+    };
+
+    quote! {
+        fn get_clock_interval(
+            &mut self,
+            vr: ::fmi::fmi3::binding::fmi3ValueReference,
+            interval: &mut Option<f64>,
+            context: &dyn ::fmi_export::fmi3::Context<M>
+        ) -> Result<(), ::fmi::fmi3::Fmi3Error> {
+            #(#scalar_var_counts)*
+            #chained_conditions
+        }
+    }
+}
+
 impl ToTokens for ModelGetSetImpl<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let struct_name = self.struct_name;
@@ -624,6 +692,7 @@ impl ToTokens for ModelGetSetImpl<'_> {
         // Generate Clock-specific methods
         let clock_get_fn = build_clock_get_fn(self.model);
         let clock_set_fn = build_clock_set_fn(self.model);
+        let clock_interval_get_fn = build_clock_interval_get_fn(self.model);
 
         tokens.extend(quote! {
             #[allow(non_snake_case)]
@@ -660,6 +729,7 @@ impl ToTokens for ModelGetSetImpl<'_> {
                 #binary_set_fn
                 #clock_get_fn
                 #clock_set_fn
+                #clock_interval_get_fn
             }
         });
     }
@@ -0,0 +1,87 @@
+//! Code generation for FMU state snapshots
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+use syn::Ident;
+
+use crate::model::Model;
+
+/// Generates the FMU state items of the `Model` impl.
+///
+/// Snapshots are taken by cloning the model, so opting in requires the struct to implement
+/// `Clone`.
+pub struct FmuStateGen<'a>(&'a Model);
+
+impl<'a> FmuStateGen<'a> {
+    pub fn new(model: &'a Model) -> Self {
+        Self(model)
+    }
+}
+
+impl ToTokens for FmuStateGen<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        if !self.0.supports_fmu_state() {
+            return;
+        }
+
+        tokens.extend(quote! {
+            const SUPPORTS_FMU_STATE: bool = true;
+
+            fn snapshot_fmu_state(&self) -> Result<Self, ::fmi::fmi3::Fmi3Error> {
+                Ok(::std::clone::Clone::clone(self))
+            }
+
+            fn serialize_fmu_state(
+                &self,
+                buffer: &mut Vec<u8>,
+            ) -> Result<(), ::fmi::fmi3::Fmi3Error> {
+                ::fmi_export::fmi3::FmuStateValue::write_state(self, buffer);
+                Ok(())
+            }
+
+            fn deserialize_fmu_state(buffer: &mut &[u8]) -> Result<Self, ::fmi::fmi3::Fmi3Error> {
+                <Self as ::fmi_export::fmi3::FmuStateValue>::read_state(buffer)
+            }
+        });
+    }
+}
+
+/// Generates the `FmuStateValue` impl, encoding every struct field in declaration order.
+///
+/// This also makes the model usable as a `#[child]` of another model with FMU state support.
+pub struct FmuStateValueImpl<'a> {
+    struct_name: &'a Ident,
+    model: &'a Model,
+}
+
+impl<'a> FmuStateValueImpl<'a> {
+    pub fn new(struct_name: &'a Ident, model: &'a Model) -> Self {
+        Self { struct_name, model }
+    }
+}
+
+impl ToTokens for FmuStateValueImpl<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        if !self.model.supports_fmu_state() {
+            return;
+        }
+
+        let struct_name = self.struct_name;
+        let field_idents = &self.model.all_field_idents;
+
+        tokens.extend(quote! {
+            #[automatically_derived]
+            impl ::fmi_export::fmi3::FmuStateValue for #struct_name {
+                fn write_state(&self, buffer: &mut Vec<u8>) {
+                    #(::fmi_export::fmi3::FmuStateValue::write_state(&self.#field_idents, buffer);)*
+                }
+
+                fn read_state(buffer: &mut &[u8]) -> Result<Self, ::fmi::fmi3::Fmi3Error> {
+                    Ok(Self {
+                        #(#field_idents: ::fmi_export::fmi3::FmuStateValue::read_state(buffer)?,)*
+                    })
+                }
+            }
+        });
+    }
+}
@@ -4,6 +4,7 @@ use quote::{ToTokens, format_ident, quote};
 use std::collections::HashMap;
 
 use crate::Model;
+use crate::codegen::helpers::{has_skip_attribute, is_clock_type};
 use crate::model::{AliasAttribute, Field, FieldAttributeOuter};
 
 pub struct BuildMetadataGen<'a> {
@@ -15,18 +16,6 @@ impl<'a> BuildMetadataGen<'a> {
         Self { model }
     }
 
-    fn is_clock_type(&self, ty: &syn::Type) -> bool {
-        match ty {
-            syn::Type::Path(type_path) => type_path
-                .path
-                .segments
-                .last()
-                .map(|segment| segment.ident == "Clock")
-                .unwrap_or(false),
-            _ => false,
-        }
-    }
-
     fn is_float_type(&self, ty: &syn::Type) -> bool {
         match ty {
             syn::Type::Path(type_path) => {
@@ -83,7 +72,7 @@ impl ToTokens for BuildMetadataGen<'_> {
 
         // First pass: assign VRs to all fields with variable attributes (excluding skipped ones)
         for field in &self.model.fields {
-            if self.has_skip_attribute(field) {
+            if has_skip_attribute(field) {
                 // Skip fields with skip=true
                 continue;
             }
@@ -116,7 +105,7 @@ impl ToTokens for BuildMetadataGen<'_> {
 
         // Second pass: generate the actual variable definitions
         for field in &self.model.fields {
-            if self.has_skip_attribute(field) {
+            if has_skip_attribute(field) {
                 // For fields with skip=true, don't generate anything
                 continue;
             } else if self.is_child_field(field) {
@@ -272,7 +261,7 @@ impl ToTokens for BuildMetadataGen<'_> {
                                 if matches!(
                                     causality_schema,
                                     ::fmi::fmi3::schema::Causality::Output
-                                ) && self.is_clock_type(&field.rust_type)
+                                ) && is_clock_type(&field.rust_type)
                                 {
                                     let current_vr = field_name_to_vr[&field.ident.to_string()];
                                     model_structure_tokens.push(quote! {
@@ -361,14 +350,6 @@ impl ToTokens for BuildMetadataGen<'_> {
 }
 
 impl BuildMetadataGen<'_> {
-    /// Check if a field has the skip attribute set to true
-    fn has_skip_attribute(&self, field: &Field) -> bool {
-        field
-            .attrs
-            .iter()
-            .any(|attr| matches!(attr, FieldAttributeOuter::Variable(var_attr) if var_attr.skip))
-    }
-
     /// Check if a field has no variable attributes (ignoring docstrings and aliases)
     fn has_no_variable_attributes(&self, field: &Field) -> bool {
         !field
@@ -475,6 +456,47 @@ impl BuildMetadataGen<'_> {
             });
         }
 
+        // Set unit and display unit if specified (for Float variables)
+        if let Some(unit) = &var_attr.unit {
+            builder_calls.push(quote! {
+                .with_unit(#unit)
+            });
+        }
+        if let Some(display_unit) = &var_attr.display_unit {
+            builder_calls.push(quote! {
+                .with_display_unit(#display_unit)
+            });
+        }
+
+        // Set declared type if specified
+        if let Some(declared_type) = &var_attr.declared_type {
+            builder_calls.push(quote! {
+                .with_declared_type(#declared_type)
+            });
+        }
+
+        // Set interval, shift and resolution if specified (for Clock variables)
+        if let Some(interval) = var_attr.interval {
+            builder_calls.push(quote! {
+                .with_interval(#interval)
+            });
+        }
+        if let Some(shift) = var_attr.shift {
+            builder_calls.push(quote! {
+                .with_shift(#shift)
+            });
+        }
+        if let Some(resolution) = var_attr.resolution {
+            builder_calls.push(quote! {
+                .with_resolution(#resolution)
+            });
+        }
+        if let Some(supports_fraction) = var_attr.supports_fraction {
+            builder_calls.push(quote! {
+                .with_supports_fraction(#supports_fraction)
+            });
+        }
+
         // Set start value if specified
         if let Some(start) = &var_attr.start {
             builder_calls.push(quote! {
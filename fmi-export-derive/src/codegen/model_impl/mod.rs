@@ -5,9 +5,12 @@ use uuid;
 
 use crate::model::{FieldAttributeOuter, Model};
 
+mod fmu_state;
 mod metadata;
+mod partitions;
 mod start_values;
 mod terminals;
+mod units;
 
 /// Generate the Model trait implementation
 pub struct ModelImpl<'a> {
@@ -37,6 +40,10 @@ impl ToTokens for ModelImpl<'_> {
         let set_start_values_body = start_values::SetStartValuesGen::new(&self.model);
         let build_terminals_body = terminals::BuildTerminalsGen::new(&self.model);
         let terminal_provider_impl = terminals::TerminalProviderImpl::new(struct_name, &self.model);
+        let fmu_state_items = fmu_state::FmuStateGen::new(self.model);
+        let fmu_state_value_impl = fmu_state::FmuStateValueImpl::new(struct_name, self.model);
+        let partition_items = partitions::PartitionsGen::new(self.model);
+        let unit_items = units::UnitsGen::new(self.model);
 
         let number_of_event_indicators = count_event_indicators(&self.model);
 
@@ -83,8 +90,13 @@ impl ToTokens for ModelImpl<'_> {
                     //#variable_validation_body
                     Ok(())
                 }
+
+                #fmu_state_items
+                #partition_items
+                #unit_items
             }
             #terminal_provider_impl
+            #fmu_state_value_impl
         });
     }
 }
@@ -0,0 +1,79 @@
+//! Code generation for Scheduled Execution model partitions
+
+use proc_macro_error2::emit_error;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+
+use crate::codegen::helpers::{has_skip_attribute, is_clock_type};
+use crate::model::{Field, FieldAttributeOuter, Model};
+
+/// Generates `Model::activate_partition`, dispatching each input Clock with a `partition`
+/// attribute to its method.
+pub struct PartitionsGen<'a>(&'a Model);
+
+impl<'a> PartitionsGen<'a> {
+    pub fn new(model: &'a Model) -> Self {
+        Self(model)
+    }
+}
+
+impl ToTokens for PartitionsGen<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let fields: Vec<&Field> = self
+            .0
+            .fields
+            .iter()
+            .filter(|field| !has_skip_attribute(field))
+            .collect();
+
+        let mut arms = Vec::new();
+
+        for (i, field) in fields.iter().enumerate() {
+            let Some((partition, causality)) = field.attrs.iter().find_map(|attr| match attr {
+                FieldAttributeOuter::Variable(var_attr) => var_attr
+                    .partition
+                    .as_ref()
+                    .map(|partition| (partition, var_attr.causality)),
+                _ => None,
+            }) else {
+                continue;
+            };
+
+            if !is_clock_type(&field.rust_type) {
+                emit_error!(field.ident, "partition is only allowed on Clock variables");
+                continue;
+            }
+            if causality.map(Into::into) != Some(fmi::fmi3::schema::Causality::Input) {
+                emit_error!(field.ident, "partition requires causality = Input");
+                continue;
+            }
+
+            // Value references as seen by `ModelGetSet`, counting all preceding fields
+            let preceding_types = fields.iter().take(i).map(|f| &f.rust_type);
+            arms.push(quote! {
+                if vr == 0 #(+ <#preceding_types as ::fmi_export::fmi3::ModelGetSet<Self>>::FIELD_COUNT as u32)* {
+                    return self.#partition(context, activation_time);
+                }
+            });
+        }
+
+        if arms.is_empty() {
+            return;
+        }
+
+        tokens.extend(quote! {
+            fn activate_partition(
+                &mut self,
+                context: &dyn ::fmi_export::fmi3::Context<Self>,
+                vr: ::fmi::fmi3::binding::fmi3ValueReference,
+                activation_time: f64,
+            ) -> Result<::fmi::fmi3::Fmi3Res, ::fmi::fmi3::Fmi3Error>
+            where
+                Self: ::fmi_export::fmi3::UserModel,
+            {
+                #(#arms)*
+                Err(::fmi::fmi3::Fmi3Error::Error)
+            }
+        });
+    }
+}
@@ -3,7 +3,8 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{ToTokens, quote};
 
-use crate::model::{Field, FieldAttributeOuter, Model};
+use crate::codegen::helpers::has_skip_attribute;
+use crate::model::{FieldAttributeOuter, Model};
 
 pub struct SetStartValuesGen<'a>(&'a Model);
 
@@ -13,14 +14,6 @@ impl<'a> SetStartValuesGen<'a> {
     }
 }
 
-/// Check if a field has the skip attribute set to true
-fn has_skip_attribute(field: &Field) -> bool {
-    field
-        .attrs
-        .iter()
-        .any(|attr| matches!(attr, FieldAttributeOuter::Variable(var_attr) if var_attr.skip))
-}
-
 impl ToTokens for SetStartValuesGen<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let mut assignments = Vec::new();
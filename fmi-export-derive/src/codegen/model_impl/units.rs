@@ -0,0 +1,340 @@
+//! Code generation for the `UnitDefinitions` and `TypeDefinitions` of the model description
+
+use std::collections::HashMap;
+
+use proc_macro_error2::emit_error;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+
+use crate::model::{Field, FieldAttributeOuter, Model, TypeDefinitionAttr};
+
+/// Generates `Model::build_unit_definitions` and `Model::build_type_definitions` from the
+/// struct-level `#[unit]`, `#[display_unit]` and `#[type_definition]` attributes, and checks
+/// that every unit referenced in the struct is declared.
+pub struct UnitsGen<'a>(&'a Model);
+
+impl<'a> UnitsGen<'a> {
+    pub fn new(model: &'a Model) -> Self {
+        Self(model)
+    }
+}
+
+/// The float type (`f32` or `f64`) of a field, looking through arrays, `Vec`, `Option` and `Box`
+fn float_kind(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            match segment.ident.to_string().as_str() {
+                ident @ ("f32" | "f64") => Some(ident.to_owned()),
+                "Vec" | "Option" | "Box" => match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) => {
+                        args.args.iter().find_map(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => float_kind(ty),
+                            _ => None,
+                        })
+                    }
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        syn::Type::Array(array) => float_kind(&array.elem),
+        syn::Type::Reference(reference) => float_kind(&reference.elem),
+        _ => None,
+    }
+}
+
+/// The float type of a type definition, `f64` unless `base_type` is given
+fn base_kind(type_definition: &TypeDefinitionAttr) -> Option<String> {
+    match &type_definition.base_type {
+        Some(ty) => float_kind(ty),
+        None => Some("f64".to_owned()),
+    }
+}
+
+/// Check if a field should be treated as a child component
+fn is_component_field(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|attr| matches!(attr, FieldAttributeOuter::Child(_)))
+        || !field
+            .attrs
+            .iter()
+            .any(|attr| matches!(attr, FieldAttributeOuter::Variable(_)))
+}
+
+impl UnitsGen<'_> {
+    /// Emit errors for references to units, display units and types that are not declared.
+    fn check_references(&self) {
+        let model = self.0;
+
+        // Display units declared for each unit
+        let mut units: HashMap<String, Vec<String>> = HashMap::new();
+        for unit in model.iter_units() {
+            if units.insert(unit.name.clone(), Vec::new()).is_some() {
+                emit_error!(model.ident, "Duplicate unit '{}'", unit.name);
+            }
+        }
+        for display_unit in model.iter_display_units() {
+            match units.get_mut(&display_unit.unit) {
+                Some(display_units) => display_units.push(display_unit.name.clone()),
+                None => emit_error!(
+                    model.ident,
+                    "Display unit '{}' refers to undeclared unit '{}'",
+                    display_unit.name,
+                    display_unit.unit
+                ),
+            }
+        }
+
+        let check_unit = |unit: Option<&String>, display_unit: Option<&String>, owner: &str| {
+            if let Some(unit) = unit
+                && !units.contains_key(unit)
+            {
+                emit_error!(
+                    model.ident,
+                    "Unit '{}' of {} is not declared, add #[unit(name = \"{}\", ...)]",
+                    unit,
+                    owner,
+                    unit
+                );
+            }
+            if let Some(display_unit) = display_unit {
+                let declared = unit
+                    .and_then(|unit| units.get(unit))
+                    .is_some_and(|display_units| display_units.contains(display_unit));
+                if !declared {
+                    emit_error!(
+                        model.ident,
+                        "Display unit '{}' of {} is not declared for its unit",
+                        display_unit,
+                        owner
+                    );
+                }
+            }
+        };
+
+        let mut types = HashMap::new();
+        for type_definition in model.iter_type_definitions() {
+            if base_kind(type_definition).is_none() {
+                emit_error!(
+                    model.ident,
+                    "Type '{}' must have base_type f32 or f64",
+                    type_definition.name
+                );
+            }
+            check_unit(
+                type_definition.unit.as_ref(),
+                type_definition.display_unit.as_ref(),
+                &format!("type '{}'", type_definition.name),
+            );
+            if types
+                .insert(type_definition.name.clone(), type_definition)
+                .is_some()
+            {
+                emit_error!(model.ident, "Duplicate type '{}'", type_definition.name);
+            }
+        }
+
+        for field in &model.fields {
+            for attr in &field.attrs {
+                let FieldAttributeOuter::Variable(var_attr) = attr else {
+                    continue;
+                };
+                let kind = float_kind(&field.rust_type);
+                if kind.is_none() && (var_attr.unit.is_some() || var_attr.display_unit.is_some()) {
+                    emit_error!(
+                        field.ident,
+                        "unit and display_unit are only allowed for Float32/Float64 variables"
+                    );
+                }
+
+                let type_definition = match &var_attr.declared_type {
+                    Some(declared_type) => match types.get(declared_type) {
+                        Some(type_definition) => {
+                            if kind.is_some() && base_kind(type_definition) != kind {
+                                emit_error!(
+                                    field.ident,
+                                    "declared_type '{}' does not match the field type",
+                                    declared_type
+                                );
+                            }
+                            Some(*type_definition)
+                        }
+                        None => {
+                            emit_error!(
+                                field.ident,
+                                "Type '{}' is not declared, add #[type_definition(name = \"{}\", ...)]",
+                                declared_type,
+                                declared_type
+                            );
+                            None
+                        }
+                    },
+                    None => None,
+                };
+
+                // A display unit without a unit on the variable refers to the unit of its type
+                let unit = var_attr
+                    .unit
+                    .as_ref()
+                    .or(type_definition.and_then(|ty| ty.unit.as_ref()));
+                check_unit(
+                    unit.filter(|_| var_attr.unit.is_some() || var_attr.display_unit.is_some()),
+                    var_attr.display_unit.as_ref(),
+                    &format!("field '{}'", field.ident),
+                );
+            }
+        }
+    }
+
+    fn unit_tokens(&self) -> Vec<TokenStream2> {
+        self.0
+            .iter_units()
+            .map(|unit| {
+                let name = &unit.name;
+                let opt = |value: Option<i32>| match value {
+                    Some(value) => quote! { Some(#value) },
+                    None => quote! { None },
+                };
+                let opt_f64 = |value: Option<f64>| match value {
+                    Some(value) => quote! { Some(#value) },
+                    None => quote! { None },
+                };
+                let (kg, m, s, a, k, mol, cd, rad) = (
+                    opt(unit.kg),
+                    opt(unit.m),
+                    opt(unit.s),
+                    opt(unit.a),
+                    opt(unit.k),
+                    opt(unit.mol),
+                    opt(unit.cd),
+                    opt(unit.rad),
+                );
+                let (factor, offset) = (opt_f64(unit.factor), opt_f64(unit.offset));
+                let display_units = self
+                    .0
+                    .iter_display_units()
+                    .filter(|display_unit| &display_unit.unit == name)
+                    .map(|display_unit| {
+                        let display_name = &display_unit.name;
+                        let factor = opt_f64(display_unit.factor);
+                        let offset = opt_f64(display_unit.offset);
+                        let inverse = match display_unit.inverse {
+                            Some(inverse) => quote! { Some(#inverse) },
+                            None => quote! { None },
+                        };
+                        quote! {
+                            ::fmi::schema::fmi3::DisplayUnit {
+                                name: #display_name.to_string(),
+                                factor: #factor,
+                                offset: #offset,
+                                inverse: #inverse,
+                                annotations: None,
+                            }
+                        }
+                    });
+                quote! {
+                    units.push(::fmi::schema::fmi3::Fmi3Unit {
+                        name: #name.to_string(),
+                        base_unit: Some(::fmi::schema::fmi3::BaseUnit {
+                            kg: #kg,
+                            m: #m,
+                            s: #s,
+                            a: #a,
+                            k: #k,
+                            mol: #mol,
+                            cd: #cd,
+                            rad: #rad,
+                            factor: #factor,
+                            offset: #offset,
+                        }),
+                        display_unit: vec![#(#display_units),*],
+                        annotations: None,
+                    });
+                }
+            })
+            .collect()
+    }
+
+    fn type_tokens(&self) -> Vec<TokenStream2> {
+        self.0
+            .iter_type_definitions()
+            .map(|type_definition| {
+                let opt_str = |value: &Option<String>| match value {
+                    Some(value) => quote! { Some(#value.to_string()) },
+                    None => quote! { None },
+                };
+                let opt_bool = |value: Option<bool>| match value {
+                    Some(value) => quote! { Some(#value) },
+                    None => quote! { None },
+                };
+                let (variant, struct_name, float) = match base_kind(type_definition).as_deref() {
+                    Some("f32") => (quote! { Float32 }, quote! { Float32Type }, quote! { f32 }),
+                    _ => (quote! { Float64 }, quote! { Float64Type }, quote! { f64 }),
+                };
+                let opt_float = |value: Option<f64>| match value {
+                    Some(value) => quote! { Some(#value as #float) },
+                    None => quote! { None },
+                };
+                let name = &type_definition.name;
+                let description = opt_str(&type_definition.description);
+                let quantity = opt_str(&type_definition.quantity);
+                let unit = opt_str(&type_definition.unit);
+                let display_unit = opt_str(&type_definition.display_unit);
+                let relative_quantity = opt_bool(type_definition.relative_quantity);
+                let unbounded = opt_bool(type_definition.unbounded);
+                let min = opt_float(type_definition.min);
+                let max = opt_float(type_definition.max);
+                let nominal = opt_float(type_definition.nominal);
+                quote! {
+                    types.push(::fmi::schema::fmi3::TypeDefinition::#variant(
+                        ::fmi::schema::fmi3::#struct_name {
+                            name: #name.to_string(),
+                            description: #description,
+                            annotations: None,
+                            quantity: #quantity,
+                            unit: #unit,
+                            display_unit: #display_unit,
+                            relative_quantity: #relative_quantity,
+                            unbounded: #unbounded,
+                            min: #min,
+                            max: #max,
+                            nominal: #nominal,
+                        },
+                    ));
+                }
+            })
+            .collect()
+    }
+}
+
+impl ToTokens for UnitsGen<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.check_references();
+
+        let components: Vec<&syn::Type> = self
+            .0
+            .fields
+            .iter()
+            .filter(|field| is_component_field(field))
+            .map(|field| &field.rust_type)
+            .collect();
+
+        let unit_tokens = self.unit_tokens();
+        let type_tokens = self.type_tokens();
+
+        tokens.extend(quote! {
+            fn build_unit_definitions(units: &mut Vec<::fmi::schema::fmi3::Fmi3Unit>) {
+                #(#unit_tokens)*
+                #(<#components as ::fmi_export::fmi3::Model>::build_unit_definitions(units);)*
+            }
+
+            fn build_type_definitions(types: &mut Vec<::fmi::schema::fmi3::TypeDefinition>) {
+                #(#type_tokens)*
+                #(<#components as ::fmi_export::fmi3::Model>::build_type_definitions(types);)*
+            }
+        });
+    }
+}
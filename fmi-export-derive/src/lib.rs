@@ -12,8 +12,8 @@ mod util;
 //#[cfg(test)]
 //mod tests;
 
-use codegen::CodeGenerator;
-use model::Model;
+use codegen::{CodeGenerator, LoggingCategoryImpl};
+use model::{LoggingCategoryEnum, Model};
 
 //TODO: move this into `fmi` crate?
 const RUST_FMI_NAMESPACE: uuid::Uuid = uuid::uuid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
@@ -39,7 +39,19 @@ const RUST_FMI_NAMESPACE: uuid::Uuid = uuid::uuid!("6ba7b810-9dad-11d1-80b4-00c0
 ///     v: f64,
 /// }
 /// ```
-#[proc_macro_derive(FmuModel, attributes(model, variable, alias, child, terminal))]
+#[proc_macro_derive(
+    FmuModel,
+    attributes(
+        model,
+        variable,
+        alias,
+        child,
+        terminal,
+        unit,
+        display_unit,
+        type_definition
+    )
+)]
 #[proc_macro_error]
 pub fn derive_fmu_model(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -53,3 +65,42 @@ pub fn derive_fmu_model(input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+/// Derive macro for custom logging categories of a `UserModel`
+///
+/// Implements `Display`, `FromStr` and `ModelLoggingCategory`. Category names default to the
+/// variant name in camelCase and the variant docstrings become the category descriptions in
+/// the model description.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use fmi_export::LoggingCategory;
+///
+/// #[derive(LoggingCategory, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+/// enum Category {
+///     /// Log all events
+///     #[default]
+///     LogAll,
+///     /// Trace FMI API calls
+///     #[category(trace)]
+///     Trace,
+///     /// Log the steps of the solver
+///     #[category(name = "logSolver")]
+///     Solver,
+/// }
+/// ```
+#[proc_macro_derive(LoggingCategory, attributes(category))]
+#[proc_macro_error]
+pub fn derive_logging_category(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let model = LoggingCategoryEnum::from(input);
+
+    proc_macro_error2::abort_if_dirty();
+
+    let logging_category_impl = LoggingCategoryImpl::new(&model);
+    quote::quote! {
+        #logging_category_impl
+    }
+    .into()
+}
@@ -27,6 +27,22 @@ pub struct FieldAttribute {
     /// If present, this variable is clocked. The value of the attribute clocks is a non-empty list of value references
     /// of Clocks this variable belongs to.
     pub clocks: Option<Vec<syn::Ident>>,
+    /// Method implementing the model partition of this input Clock in Scheduled Execution.
+    pub partition: Option<syn::Ident>,
+    /// Clock interval in seconds (Constant, Fixed and Tunable Clocks)
+    pub interval: Option<f64>,
+    /// Clock shift in seconds (Constant, Fixed and Tunable Clocks)
+    pub shift: Option<f64>,
+    /// Clock resolution in ticks per second, enabling the fractional interval representation
+    pub resolution: Option<u64>,
+    /// Whether the Clock supports the fractional interval representation
+    pub supports_fraction: Option<bool>,
+    /// Unit of a float variable, declared with a struct-level `#[unit(...)]`
+    pub unit: Option<String>,
+    /// Default display unit of a float variable, declared with `#[display_unit(...)]`
+    pub display_unit: Option<String>,
+    /// Name of a type declared with a struct-level `#[type_definition(...)]`
+    pub declared_type: Option<String>,
     pub max_size: Option<usize>,
     pub mime_type: Option<String>,
 }
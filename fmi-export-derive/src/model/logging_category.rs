@@ -0,0 +1,163 @@
+//! Parsing of `#[derive(LoggingCategory)]` enums
+
+use attribute_derive::FromAttr;
+use convert_case::{Case, Casing};
+use proc_macro_error2::emit_error;
+
+use super::parse_doc_attribute;
+
+/// Variant-level `#[category(...)]` attribute
+#[derive(Debug, FromAttr, PartialEq, Clone, Default)]
+#[attribute(ident = category)]
+pub struct CategoryAttr {
+    /// Category name as seen by the importer, defaults to the variant name in camelCase
+    pub name: Option<String>,
+    /// Use this category for tracing FMI API calls
+    pub trace: bool,
+    /// Use this category for logging errors
+    pub error: bool,
+}
+
+/// A single logging category
+#[derive(Debug, PartialEq, Clone)]
+pub struct Category {
+    pub ident: syn::Ident,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_default: bool,
+    pub attr: CategoryAttr,
+}
+
+/// Representation of a `#[derive(LoggingCategory)]` enum
+#[derive(Debug, PartialEq, Clone)]
+pub struct LoggingCategoryEnum {
+    pub ident: syn::Ident,
+    pub categories: Vec<Category>,
+}
+
+impl LoggingCategoryEnum {
+    /// The category marked with `#[category(trace)]`
+    pub fn trace_category(&self) -> Option<&Category> {
+        self.categories.iter().find(|category| category.attr.trace)
+    }
+
+    /// The category marked with `#[category(error)]`, or the `#[default]` one
+    pub fn error_category(&self) -> Option<&Category> {
+        self.categories
+            .iter()
+            .find(|category| category.attr.error)
+            .or_else(|| self.categories.iter().find(|category| category.is_default))
+    }
+}
+
+impl From<syn::DeriveInput> for LoggingCategoryEnum {
+    fn from(input: syn::DeriveInput) -> Self {
+        let syn::Data::Enum(data) = input.data else {
+            emit_error!(input.ident, "LoggingCategory can only be derived for enums");
+            return Self {
+                ident: input.ident,
+                categories: vec![],
+            };
+        };
+
+        let categories: Vec<Category> = data
+            .variants
+            .into_iter()
+            .filter_map(|variant| {
+                if !matches!(variant.fields, syn::Fields::Unit) {
+                    emit_error!(variant.ident, "Logging categories must be unit variants");
+                    return None;
+                }
+
+                let mut attr = CategoryAttr::default();
+                let mut doc_lines = Vec::new();
+                let mut is_default = false;
+                for variant_attr in &variant.attrs {
+                    let path = variant_attr.meta.path();
+                    if path.is_ident("doc") {
+                        doc_lines.extend(parse_doc_attribute(variant_attr));
+                    } else if path.is_ident("default") {
+                        is_default = true;
+                    } else if path.is_ident("category") {
+                        match CategoryAttr::from_attribute(variant_attr) {
+                            Ok(parsed) => attr = parsed,
+                            Err(e) => emit_error!(variant_attr, e.to_string()),
+                        }
+                    }
+                }
+
+                let name = attr
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| variant.ident.to_string().to_case(Case::Camel));
+                let description = (!doc_lines.is_empty()).then(|| doc_lines.join(" "));
+
+                Some(Category {
+                    ident: variant.ident,
+                    name,
+                    description,
+                    is_default,
+                    attr,
+                })
+            })
+            .collect();
+
+        let model = Self {
+            ident: input.ident,
+            categories,
+        };
+
+        if model.categories.iter().filter(|c| c.attr.trace).count() != 1 {
+            emit_error!(
+                model.ident,
+                "Exactly one variant must be marked with #[category(trace)]"
+            );
+        }
+        if model.categories.iter().filter(|c| c.attr.error).count() > 1 {
+            emit_error!(
+                model.ident,
+                "At most one variant can be marked with #[category(error)]"
+            );
+        }
+        if model.error_category().is_none() {
+            emit_error!(
+                model.ident,
+                "Mark the category for errors with #[category(error)] or #[default]"
+            );
+        }
+
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logging_categories() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            enum Category {
+                /// Log all events
+                #[default]
+                LogAll,
+                /// Trace FMI API calls
+                #[category(trace)]
+                Trace,
+                #[category(name = "solver")]
+                LogSolverSteps,
+            }
+        };
+        let model = LoggingCategoryEnum::from(input);
+
+        let names: Vec<_> = model.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["logAll", "trace", "solver"]);
+        assert_eq!(
+            model.categories[0].description.as_deref(),
+            Some("Log all events")
+        );
+        assert_eq!(model.categories[2].description, None);
+        assert_eq!(model.trace_category().unwrap().ident, "Trace");
+        assert_eq!(model.error_category().unwrap().ident, "LogAll");
+    }
+}
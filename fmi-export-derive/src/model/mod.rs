@@ -2,9 +2,11 @@ use attribute_derive::FromAttr;
 use proc_macro_error2::emit_error;
 
 mod field_attr;
+mod logging_category;
 pub use field_attr::{
     AliasAttribute, ChildAttribute, FieldAttribute, FieldAttributeOuter, TerminalAttribute,
 };
+pub use logging_category::LoggingCategoryEnum;
 
 /// Helper function to extract docstring from a syn::Attribute
 /// Follows DRY principles by centralizing doc attribute parsing logic
@@ -50,6 +52,10 @@ pub struct StructAttr {
     /// Whether to auto-generate a UserModel impl (default: true)
     #[attribute(optional)]
     pub user_model: Option<bool>,
+
+    /// Enable getting, setting and serializing the FMU state (default: false)
+    #[attribute(optional)]
+    pub fmu_state: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -57,6 +63,9 @@ pub enum StructAttrOuter {
     Docstring(String),
     Model(StructAttr),
     Terminal(TerminalStructAttr),
+    Unit(UnitAttr),
+    DisplayUnit(DisplayUnitAttr),
+    TypeDefinition(TypeDefinitionAttr),
 }
 
 /// Struct-level attributes for terminal generation
@@ -74,6 +83,61 @@ pub struct TerminalStructAttr {
     pub terminal_kind: Option<String>,
 }
 
+/// Struct-level unit declaration, with the exponents of the SI base units
+#[derive(Debug, attribute_derive::FromAttr, PartialEq, Clone, Default)]
+#[attribute(ident = unit)]
+#[attribute(error(missing_field = "`{field}` was not specified"))]
+pub struct UnitAttr {
+    /// Unit name as referenced by variables, e.g. "m/s"
+    pub name: String,
+    pub kg: Option<i32>,
+    pub m: Option<i32>,
+    pub s: Option<i32>,
+    pub a: Option<i32>,
+    pub k: Option<i32>,
+    pub mol: Option<i32>,
+    pub cd: Option<i32>,
+    pub rad: Option<i32>,
+    /// Conversion to the SI base units: `base = factor * unit + offset`
+    pub factor: Option<f64>,
+    pub offset: Option<f64>,
+}
+
+/// Struct-level display unit declaration for a declared unit
+#[derive(Debug, attribute_derive::FromAttr, PartialEq, Clone, Default)]
+#[attribute(ident = display_unit)]
+#[attribute(error(missing_field = "`{field}` was not specified"))]
+pub struct DisplayUnitAttr {
+    /// Name of the unit this display unit belongs to
+    pub unit: String,
+    /// Display unit name, e.g. "km/h"
+    pub name: String,
+    /// Conversion from the unit: `display = factor * unit + offset`
+    pub factor: Option<f64>,
+    pub offset: Option<f64>,
+    pub inverse: Option<bool>,
+}
+
+/// Struct-level float type definition, referenced by `declared_type` on variables
+#[derive(Debug, attribute_derive::FromAttr, PartialEq, Clone, Default)]
+#[attribute(ident = type_definition)]
+#[attribute(error(missing_field = "`{field}` was not specified"))]
+pub struct TypeDefinitionAttr {
+    /// Type name, e.g. "Velocity"
+    pub name: String,
+    /// Rust type of the variables using this type, `f32` or `f64` (default)
+    pub base_type: Option<syn::Type>,
+    pub description: Option<String>,
+    pub quantity: Option<String>,
+    pub unit: Option<String>,
+    pub display_unit: Option<String>,
+    pub relative_quantity: Option<bool>,
+    pub unbounded: Option<bool>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub nominal: Option<f64>,
+}
+
 /// Representation of an FmuModel field with it's parsed attributes
 #[derive(Debug, PartialEq, Clone)]
 pub struct Field {
@@ -87,6 +151,8 @@ pub struct Model {
     pub ident: syn::Ident,
     pub attrs: Vec<StructAttrOuter>,
     pub fields: Vec<Field>,
+    /// Identifiers of all named struct fields, including those without FMU attributes
+    pub all_field_idents: Vec<syn::Ident>,
 }
 
 impl Field {
@@ -161,6 +227,30 @@ impl Model {
         })
     }
 
+    /// Iterator over the struct-level unit declarations
+    pub fn iter_units(&self) -> impl Iterator<Item = &UnitAttr> {
+        self.attrs.iter().filter_map(|attr| match attr {
+            StructAttrOuter::Unit(unit) => Some(unit),
+            _ => None,
+        })
+    }
+
+    /// Iterator over the struct-level display unit declarations
+    pub fn iter_display_units(&self) -> impl Iterator<Item = &DisplayUnitAttr> {
+        self.attrs.iter().filter_map(|attr| match attr {
+            StructAttrOuter::DisplayUnit(display_unit) => Some(display_unit),
+            _ => None,
+        })
+    }
+
+    /// Iterator over the struct-level type definitions
+    pub fn iter_type_definitions(&self) -> impl Iterator<Item = &TypeDefinitionAttr> {
+        self.attrs.iter().filter_map(|attr| match attr {
+            StructAttrOuter::TypeDefinition(type_definition) => Some(type_definition),
+            _ => None,
+        })
+    }
+
     /// Check if Model Exchange is supported
     pub fn supports_model_exchange(&self) -> bool {
         self.get_model_attr()
@@ -188,6 +278,13 @@ impl Model {
             .and_then(|attr| attr.user_model)
             .unwrap_or(true)
     }
+
+    /// Check if FMU state snapshots are supported
+    pub fn supports_fmu_state(&self) -> bool {
+        self.get_model_attr()
+            .and_then(|attr| attr.fmu_state)
+            .unwrap_or(false)
+    }
 }
 
 impl TryFrom<syn::Field> for Field {
@@ -301,6 +398,11 @@ impl From<syn::DeriveInput> for Model {
     fn from(item: syn::DeriveInput) -> Self {
         if let syn::Data::Struct(struct_data) = item.data {
             let attrs = build_attrs(item.attrs);
+            let all_field_idents = struct_data
+                .fields
+                .iter()
+                .filter_map(|field| field.ident.clone())
+                .collect();
             let fields = build_fields(struct_data.fields);
 
             // Check for time variable name conflicts
@@ -310,6 +412,7 @@ impl From<syn::DeriveInput> for Model {
                 ident: item.ident,
                 attrs,
                 fields,
+                all_field_idents,
             }
         } else {
             emit_error!(item, "FmuModel can only be derived for structs");
@@ -317,6 +420,7 @@ impl From<syn::DeriveInput> for Model {
                 ident: item.ident,
                 attrs: vec![],
                 fields: vec![],
+                all_field_idents: vec![],
             }
         }
     }
@@ -354,6 +458,34 @@ pub fn build_attrs(attrs: Vec<syn::Attribute>) -> Vec<StructAttrOuter> {
                 }
             }
 
+            Some(ident) if ident == "unit" => match UnitAttr::from_attribute(attr.clone()) {
+                Ok(attr) => Some(StructAttrOuter::Unit(attr)),
+                Err(e) => {
+                    emit_error!(attr, format!("{e}"));
+                    None
+                }
+            },
+
+            Some(ident) if ident == "display_unit" => {
+                match DisplayUnitAttr::from_attribute(attr.clone()) {
+                    Ok(attr) => Some(StructAttrOuter::DisplayUnit(attr)),
+                    Err(e) => {
+                        emit_error!(attr, format!("{e}"));
+                        None
+                    }
+                }
+            }
+
+            Some(ident) if ident == "type_definition" => {
+                match TypeDefinitionAttr::from_attribute(attr.clone()) {
+                    Ok(attr) => Some(StructAttrOuter::TypeDefinition(attr)),
+                    Err(e) => {
+                        emit_error!(attr, format!("{e}"));
+                        None
+                    }
+                }
+            }
+
             _ => None,
         })
         .collect()
@@ -459,6 +591,24 @@ fn parse_model_attr_bool(attr: syn::Attribute) -> Result<StructAttr, String> {
                 let _ = list;
                 return Err("user_model expects a boolean value, e.g. user_model = false".into());
             }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("fmu_state") => {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Bool(lit_bool),
+                    ..
+                }) = nv.value
+                {
+                    model_attr.fmu_state = Some(lit_bool.value);
+                } else {
+                    return Err("fmu_state expects a boolean".into());
+                }
+            }
+            syn::Meta::Path(path) if path.is_ident("fmu_state") => {
+                return Err("fmu_state expects a boolean value, e.g. fmu_state = true".into());
+            }
+            syn::Meta::List(list) if list.path.is_ident("fmu_state") => {
+                let _ = list;
+                return Err("fmu_state expects a boolean value, e.g. fmu_state = true".into());
+            }
             // Ignore unknown entries here and let the main parser report errors elsewhere
             _ => {}
         }
@@ -519,6 +669,39 @@ mod tests {
         let _attr = FieldAttribute::from_attribute(input).unwrap();
     }
 
+    #[test]
+    fn test_unit_attributes() {
+        let attrs = build_attrs(vec![
+            syn::parse_quote! { #[unit(name = "m/s", m = 1, s = -1)] },
+            syn::parse_quote! { #[display_unit(unit = "m/s", name = "km/h", factor = 0.2777777777777778)] },
+            syn::parse_quote! { #[type_definition(name = "Velocity", base_type = f32, unit = "m/s", min = 0.0)] },
+        ]);
+        assert_eq!(
+            attrs,
+            vec![
+                StructAttrOuter::Unit(UnitAttr {
+                    name: "m/s".to_string(),
+                    m: Some(1),
+                    s: Some(-1),
+                    ..Default::default()
+                }),
+                StructAttrOuter::DisplayUnit(DisplayUnitAttr {
+                    unit: "m/s".to_string(),
+                    name: "km/h".to_string(),
+                    factor: Some(0.2777777777777778),
+                    ..Default::default()
+                }),
+                StructAttrOuter::TypeDefinition(TypeDefinitionAttr {
+                    name: "Velocity".to_string(),
+                    base_type: Some(syn::parse_quote!(f32)),
+                    unit: Some("m/s".to_string()),
+                    min: Some(0.0),
+                    ..Default::default()
+                }),
+            ]
+        );
+    }
+
     #[test]
     fn test_fields_and_attributes() {
         let input: syn::ItemStruct = syn::parse_quote! {
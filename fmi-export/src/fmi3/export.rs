@@ -78,7 +78,9 @@ macro_rules! export_fmu {
         /// Export the model components
         #[unsafe(export_name = "model_metadata")]
         pub fn model_metadata() -> ::fmi_export::fmi3::ModelMetadata {
-            <$ty as ::fmi_export::fmi3::Model>::build_toplevel_metadata()
+            let mut metadata = <$ty as ::fmi_export::fmi3::Model>::build_toplevel_metadata();
+            metadata.log_categories = Some(<<$ty as ::fmi_export::fmi3::UserModel>::LoggingCategory as ::fmi_export::fmi3::ModelLoggingCategory>::log_categories());
+            metadata
         }
 
         #[unsafe(export_name = "FMI3_INSTANTIATION_TOKEN")]
@@ -106,6 +108,12 @@ macro_rules! export_fmu {
             <$ty as ::fmi_export::fmi3::Model>::SUPPORTS_SCHEDULED_EXECUTION as _
         }
 
+        #[unsafe(export_name = "fmi3SupportsFMUState")]
+        #[cfg_attr(coverage_nightly, coverage(off))]
+        pub unsafe extern "C" fn fmi3_supports_fmu_state() -> ::fmi::fmi3::binding::fmi3Boolean {
+            <$ty as ::fmi_export::fmi3::Model>::SUPPORTS_FMU_STATE as _
+        }
+
         // Inquire version numbers and set debug logging
 
         #[unsafe(export_name = "fmi3GetVersion")]
@@ -0,0 +1,358 @@
+//! Runtime state of Clock intervals and shifts
+
+use std::collections::BTreeMap;
+
+use fmi::fmi3::{Fmi3Error, Fmi3Res, binding, schema};
+
+use crate::fmi3::{
+    Context, Model, ModelGetSet, ModelInstance, ModelState, UserModel,
+    traits::{FmuStateValue, ModelLoggingCategory},
+};
+
+/// Interval and shift of a single Clock, as seen by the importer
+#[derive(Debug, Clone)]
+pub(crate) struct ClockState {
+    interval_variability: schema::IntervalVariability,
+    /// Interval in seconds, `None` until it is known
+    interval: Option<f64>,
+    /// Shift in seconds
+    shift: f64,
+    /// Resolution in ticks per second of the fractional representation
+    resolution: Option<u64>,
+    /// Whether the interval changed since it was last read by the importer
+    interval_changed: bool,
+}
+
+impl ClockState {
+    fn new(clock: &schema::FmiClock) -> Self {
+        let resolution = clock.resolution;
+        let from_counter =
+            |counter: Option<u64>| Some(counter? as f64 / resolution.filter(|&r| r > 0)? as f64);
+        let interval = clock
+            .interval_decimal
+            .or_else(|| from_counter(clock.interval_counter));
+        Self {
+            interval_variability: clock.interval_variability(),
+            interval,
+            shift: clock
+                .shift_decimal
+                .or_else(|| from_counter(clock.shift_counter))
+                .unwrap_or(0.0),
+            resolution,
+            interval_changed: interval.is_some(),
+        }
+    }
+
+    /// Append the parts of the Clock state that change at runtime to an FMU state buffer.
+    pub(crate) fn write_state(&self, buffer: &mut Vec<u8>) {
+        self.interval.write_state(buffer);
+        self.shift.write_state(buffer);
+        self.interval_changed.write_state(buffer);
+    }
+
+    /// Restore the parts of the Clock state written by [`ClockState::write_state`].
+    pub(crate) fn read_state(&mut self, buffer: &mut &[u8]) -> Result<(), Fmi3Error> {
+        self.interval = Option::read_state(buffer)?;
+        self.shift = f64::read_state(buffer)?;
+        self.interval_changed = bool::read_state(buffer)?;
+        Ok(())
+    }
+}
+
+/// Build the Clock table of a model from its metadata, keyed by value reference.
+pub(crate) fn build_clock_states<M: Model>() -> BTreeMap<binding::fmi3ValueReference, ClockState> {
+    M::build_toplevel_metadata()
+        .model_variables
+        .clock()
+        .into_iter()
+        .map(|clock| (clock.value_reference, ClockState::new(clock)))
+        .collect()
+}
+
+impl<M, C> ModelInstance<M, C>
+where
+    M: Model + UserModel + ModelGetSet<M>,
+    C: Context<M>,
+{
+    /// Look up a Clock whose interval can be queried by the importer.
+    fn queryable_clock(
+        &mut self,
+        vr: binding::fmi3ValueReference,
+    ) -> Result<&mut ClockState, Fmi3Error> {
+        match self.clocks.get(&vr).map(|clock| clock.interval_variability) {
+            Some(schema::IntervalVariability::Triggered) => {
+                self.context.log(
+                    Fmi3Error::Error.into(),
+                    M::LoggingCategory::default(),
+                    format_args!("The interval of triggered Clock {vr} cannot be queried"),
+                );
+                Err(Fmi3Error::Error)
+            }
+            Some(_) => Ok(self.clocks.get_mut(&vr).expect("Clock exists")),
+            None => {
+                self.context.log(
+                    Fmi3Error::Error.into(),
+                    M::LoggingCategory::default(),
+                    format_args!("Value reference {vr} is not a Clock"),
+                );
+                Err(Fmi3Error::Error)
+            }
+        }
+    }
+
+    /// Look up a Clock whose interval and shift can be set by the importer in the current state.
+    fn settable_clock(
+        &mut self,
+        vr: binding::fmi3ValueReference,
+    ) -> Result<&mut ClockState, Fmi3Error> {
+        let Some(interval_variability) =
+            self.clocks.get(&vr).map(|clock| clock.interval_variability)
+        else {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!("Value reference {vr} is not a Clock"),
+            );
+            return Err(Fmi3Error::Error);
+        };
+
+        let settable = match interval_variability {
+            schema::IntervalVariability::Fixed => matches!(
+                self.state,
+                ModelState::Instantiated
+                    | ModelState::ConfigurationMode
+                    | ModelState::InitializationMode
+            ),
+            schema::IntervalVariability::Tunable => matches!(
+                self.state,
+                ModelState::Instantiated
+                    | ModelState::ConfigurationMode
+                    | ModelState::InitializationMode
+                    | ModelState::ReconfigurationMode
+                    | ModelState::EventMode
+                    | ModelState::ClockActivationMode
+            ),
+            _ => false,
+        };
+
+        if !settable {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!(
+                    "The interval of {interval_variability:?} Clock {vr} cannot be set in state {:?}",
+                    self.state
+                ),
+            );
+            return Err(Fmi3Error::Error);
+        }
+
+        Ok(self.clocks.get_mut(&vr).expect("Clock exists"))
+    }
+
+    /// Pick up the interval the model set for a `Changing` or `Countdown` Clock.
+    fn update_clock_interval(&mut self, vr: binding::fmi3ValueReference) -> Result<(), Fmi3Error> {
+        let Some(clock) = self.clocks.get_mut(&vr) else {
+            return Ok(());
+        };
+        if !matches!(
+            clock.interval_variability,
+            schema::IntervalVariability::Changing | schema::IntervalVariability::Countdown
+        ) {
+            return Ok(());
+        }
+        let mut interval = None;
+        // Model value references don't include 'time'
+        self.model
+            .get_clock_interval(vr - 1, &mut interval, &self.context)?;
+        if let Some(interval) = interval {
+            clock.interval = Some(interval);
+            clock.interval_changed = true;
+        }
+        Ok(())
+    }
+
+    fn clock_resolution(
+        &self,
+        vr: binding::fmi3ValueReference,
+        resolution: Option<u64>,
+    ) -> Result<u64, Fmi3Error> {
+        resolution.filter(|&r| r > 0).ok_or_else(|| {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!("Clock {vr} has no resolution for the fractional representation"),
+            );
+            Fmi3Error::Error
+        })
+    }
+
+    pub fn get_interval_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        intervals: &mut [f64],
+        qualifiers: &mut [binding::fmi3IntervalQualifier],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_interval_decimal(vrs: {vrs:?})"),
+        );
+        for ((&vr, interval), qualifier) in vrs.iter().zip(intervals).zip(qualifiers) {
+            self.update_clock_interval(vr)?;
+            let clock = self.queryable_clock(vr)?;
+            match clock.interval {
+                Some(value) => {
+                    *interval = value;
+                    *qualifier = if std::mem::take(&mut clock.interval_changed) {
+                        binding::fmi3IntervalQualifier_fmi3IntervalChanged
+                    } else {
+                        binding::fmi3IntervalQualifier_fmi3IntervalUnchanged
+                    };
+                }
+                None => {
+                    *interval = 0.0;
+                    *qualifier = binding::fmi3IntervalQualifier_fmi3IntervalNotYetKnown;
+                }
+            }
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn get_interval_fraction(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        counters: &mut [u64],
+        resolutions: &mut [u64],
+        qualifiers: &mut [binding::fmi3IntervalQualifier],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_interval_fraction(vrs: {vrs:?})"),
+        );
+        for (i, &vr) in vrs.iter().enumerate() {
+            let resolution = self.queryable_clock(vr)?.resolution;
+            let resolution = self.clock_resolution(vr, resolution)?;
+            let mut interval = [0.0];
+            self.get_interval_decimal(&[vr], &mut interval, &mut qualifiers[i..=i])?;
+            counters[i] = (interval[0] * resolution as f64).round() as u64;
+            resolutions[i] = resolution;
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn get_shift_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        shifts: &mut [f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_shift_decimal(vrs: {vrs:?})"),
+        );
+        for (&vr, shift) in vrs.iter().zip(shifts) {
+            *shift = self.queryable_clock(vr)?.shift;
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn get_shift_fraction(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        counters: &mut [u64],
+        resolutions: &mut [u64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_shift_fraction(vrs: {vrs:?})"),
+        );
+        for ((&vr, counter), res) in vrs.iter().zip(counters).zip(resolutions) {
+            let clock = self.queryable_clock(vr)?;
+            let (shift, resolution) = (clock.shift, clock.resolution);
+            let resolution = self.clock_resolution(vr, resolution)?;
+            *counter = (shift * resolution as f64).round() as u64;
+            *res = resolution;
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn set_interval_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        intervals: &[f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("set_interval_decimal(vrs: {vrs:?}, intervals: {intervals:?})"),
+        );
+        for (&vr, &interval) in vrs.iter().zip(intervals) {
+            if interval <= 0.0 {
+                self.context.log(
+                    Fmi3Error::Error.into(),
+                    M::LoggingCategory::default(),
+                    format_args!("Invalid interval {interval} for Clock {vr}"),
+                );
+                return Err(Fmi3Error::Error);
+            }
+            let clock = self.settable_clock(vr)?;
+            clock.interval_changed |= clock.interval != Some(interval);
+            clock.interval = Some(interval);
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn set_interval_fraction(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        counters: &[u64],
+        resolutions: &[u64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        let intervals = fractions_to_decimal(counters, resolutions)?;
+        self.set_interval_decimal(vrs, &intervals)
+    }
+
+    pub fn set_shift_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        shifts: &[f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("set_shift_decimal(vrs: {vrs:?}, shifts: {shifts:?})"),
+        );
+        for (&vr, &shift) in vrs.iter().zip(shifts) {
+            self.settable_clock(vr)?.shift = shift;
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    pub fn set_shift_fraction(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        counters: &[u64],
+        resolutions: &[u64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        let shifts = fractions_to_decimal(counters, resolutions)?;
+        self.set_shift_decimal(vrs, &shifts)
+    }
+}
+
+/// Convert `counter / resolution` pairs to seconds.
+fn fractions_to_decimal(counters: &[u64], resolutions: &[u64]) -> Result<Vec<f64>, Fmi3Error> {
+    counters
+        .iter()
+        .zip(resolutions)
+        .map(|(&counter, &resolution)| {
+            if resolution == 0 {
+                Err(Fmi3Error::Error)
+            } else {
+                Ok(counter as f64 / resolution as f64)
+            }
+        })
+        .collect()
+}
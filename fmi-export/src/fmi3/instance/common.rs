@@ -90,9 +90,7 @@ where
                 self.state = ModelState::EventMode;
             }
             InterfaceType::CoSimulation => {
-                //TODO support event mode switch
-                let event_mode_used = false;
-                if event_mode_used {
+                if self.event_mode_used {
                     self.state = ModelState::EventMode;
                 } else {
                     self.state = ModelState::StepMode;
@@ -122,6 +120,7 @@ where
         self.state = ModelState::Instantiated;
         self.context.initialize(0.0, None);
         self.model.set_start_values();
+        self.clocks = super::clocks::build_clock_states::<M>();
         Ok(Fmi3Res::OK)
     }
 
@@ -156,25 +155,21 @@ where
                 self.state = ModelState::Instantiated;
             }
 
-            ModelState::ReconfigurationMode => {
-                match self.instance_type {
-                    InterfaceType::ModelExchange => {
+            ModelState::ReconfigurationMode => match self.instance_type {
+                InterfaceType::ModelExchange => {
+                    self.state = ModelState::EventMode;
+                }
+                InterfaceType::CoSimulation => {
+                    if self.event_mode_used {
                         self.state = ModelState::EventMode;
-                    }
-                    InterfaceType::CoSimulation => {
-                        //TODO support event mode switch
-                        let event_mode_used = false;
-                        if event_mode_used {
-                            self.state = ModelState::EventMode;
-                        } else {
-                            self.state = ModelState::StepMode;
-                        }
-                    }
-                    InterfaceType::ScheduledExecution => {
-                        self.state = ModelState::ClockActivationMode;
+                    } else {
+                        self.state = ModelState::StepMode;
                     }
                 }
-            }
+                InterfaceType::ScheduledExecution => {
+                    self.state = ModelState::ClockActivationMode;
+                }
+            },
 
             _ => {
                 self.context.log(
@@ -198,6 +193,22 @@ where
             M::LoggingCategory::trace_category(),
             format_args!("enter_event_mode()"),
         );
+
+        // Co-Simulation only has an Event Mode if the importer asked for it at instantiation
+        if self.instance_type == InterfaceType::CoSimulation
+            && !(self.event_mode_used && matches!(self.state, ModelState::StepMode))
+        {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!(
+                    "enter_event_mode() called in invalid state {:?} (event_mode_used: {})",
+                    self.state, self.event_mode_used
+                ),
+            );
+            return Err(Fmi3Error::Error);
+        }
+
         self.state = ModelState::EventMode;
         Ok(Fmi3Res::OK)
     }
@@ -211,8 +222,24 @@ where
             M::LoggingCategory::trace_category(),
             format_args!("update_discrete_states()"),
         );
-        //UserModel::event_update(&mut self.model, &self.context, event_flags)
-        self.model.event_update(&self.context, event_flags)
+
+        if self.instance_type == InterfaceType::CoSimulation
+            && !matches!(self.state, ModelState::EventMode)
+        {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!(
+                    "update_discrete_states() called in invalid state {:?}",
+                    self.state
+                ),
+            );
+            return Err(Fmi3Error::Error);
+        }
+
+        let res = self.model.event_update(&self.context, event_flags)?;
+        self.is_dirty_values = true;
+        Ok(res)
     }
 
     fn get_number_of_variable_dependencies(
@@ -4,7 +4,9 @@ use fmi::fmi3::Fmi3Status;
 
 use crate::fmi3::{
     UserModel,
-    instance::{IntermediateUpdateClosure, LogMessageClosure},
+    instance::{
+        ClockUpdateClosure, IntermediateUpdateClosure, LogMessageClosure, PreemptionClosure,
+    },
     traits::{Context, ModelLoggingCategory},
 };
 
@@ -25,6 +27,12 @@ pub struct BasicContext<M: UserModel> {
     early_return_allowed: bool,
     /// Optional FMI intermediate update callback (CS).
     intermediate_update: Option<IntermediateUpdateClosure>,
+    /// Optional FMI clock update callback (SE).
+    clock_update: Option<ClockUpdateClosure>,
+    /// Optional FMI lock preemption callback (SE).
+    lock_preemption: Option<PreemptionClosure>,
+    /// Optional FMI unlock preemption callback (SE).
+    unlock_preemption: Option<PreemptionClosure>,
 }
 
 impl<M: UserModel> BasicContext<M> {
@@ -46,9 +54,25 @@ impl<M: UserModel> BasicContext<M> {
             time: 0.0,
             early_return_allowed,
             intermediate_update,
+            clock_update: None,
+            lock_preemption: None,
+            unlock_preemption: None,
         }
     }
 
+    /// Set the Scheduled Execution callbacks passed to `fmi3InstantiateScheduledExecution`.
+    pub fn with_scheduled_execution(
+        mut self,
+        clock_update: Option<ClockUpdateClosure>,
+        lock_preemption: Option<PreemptionClosure>,
+        unlock_preemption: Option<PreemptionClosure>,
+    ) -> Self {
+        self.clock_update = clock_update;
+        self.lock_preemption = lock_preemption;
+        self.unlock_preemption = unlock_preemption;
+        self
+    }
+
     pub fn intermediate_update(&self) -> Option<&IntermediateUpdateClosure> {
         self.intermediate_update.as_ref()
     }
@@ -100,6 +124,24 @@ where
         self.early_return_allowed
     }
 
+    fn clock_update(&self) {
+        if let Some(clock_update) = &self.clock_update {
+            clock_update();
+        }
+    }
+
+    fn lock_preemption(&self) {
+        if let Some(lock_preemption) = &self.lock_preemption {
+            lock_preemption();
+        }
+    }
+
+    fn unlock_preemption(&self) {
+        if let Some(unlock_preemption) = &self.unlock_preemption {
+            unlock_preemption();
+        }
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
@@ -0,0 +1,186 @@
+use fmi::fmi3::{Fmi3Error, Fmi3Res, binding};
+
+use super::ModelInstance;
+use crate::fmi3::{
+    Model, UserModel,
+    traits::{Context, ModelGetSet, ModelLoggingCategory},
+};
+
+/// Relative step size of the central-difference fallback, approximately `cbrt(f64::EPSILON)`.
+const FD_RELATIVE_STEP: f64 = 6.055_454_452_393_343e-6;
+
+impl<M, C> ModelInstance<M, C>
+where
+    M: Model + UserModel + ModelGetSet<M>,
+    C: Context<M>,
+{
+    /// Compute the directional derivative of the `unknowns` with respect to the `knowns`.
+    ///
+    /// Delegates to [`UserModel::get_directional_derivative`], falling back to central finite
+    /// differences if the model doesn't provide an analytic implementation.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetDirectionalDerivative>
+    pub fn get_directional_derivative(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_directional_derivative(unknowns: {unknowns:?}, knowns: {knowns:?})"),
+        );
+        self.update_values()?;
+        if !self.model.get_directional_derivative(
+            &self.context,
+            unknowns,
+            knowns,
+            seed,
+            sensitivity,
+        )? {
+            self.finite_difference(unknowns, knowns, seed, sensitivity)?;
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    /// Compute the adjoint derivative of the `unknowns` with respect to the `knowns`.
+    ///
+    /// Delegates to [`UserModel::get_adjoint_derivative`]. The finite-difference fallback builds
+    /// the partial derivative matrix one column per element of the knowns.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetAdjointDerivative>
+    pub fn get_adjoint_derivative(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_adjoint_derivative(unknowns: {unknowns:?}, knowns: {knowns:?})"),
+        );
+        self.update_values()?;
+        if !self
+            .model
+            .get_adjoint_derivative(&self.context, unknowns, knowns, seed, sensitivity)?
+        {
+            let mut unit_seed = vec![0.0; sensitivity.len()];
+            let mut column = vec![0.0; seed.len()];
+            for j in 0..sensitivity.len() {
+                unit_seed[j] = 1.0;
+                self.finite_difference(unknowns, knowns, &unit_seed, &mut column)?;
+                unit_seed[j] = 0.0;
+                sensitivity[j] = column.iter().zip(seed).map(|(c, s)| c * s).sum();
+            }
+        }
+        Ok(Fmi3Res::OK)
+    }
+
+    /// Re-evaluate the model equations if any inputs changed since the last evaluation.
+    fn update_values(&mut self) -> Result<(), Fmi3Error> {
+        if self.is_dirty_values {
+            self.model.calculate_values(&self.context)?;
+            self.is_dirty_values = false;
+        }
+        Ok(())
+    }
+
+    /// Read the Float64 values of `vrs`, which must exactly fill `values`.
+    fn read_float64(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        values: &mut [f64],
+    ) -> Result<(), Fmi3Error> {
+        self.update_values()?;
+        let mut value_index = 0;
+        for vr in vrs {
+            // 'time' is neither a valid known nor unknown
+            if *vr == 0 {
+                return Err(Fmi3Error::Error);
+            }
+            value_index +=
+                self.model
+                    .get_float64(*vr - 1, &mut values[value_index..], &self.context)?;
+        }
+        if value_index == values.len() {
+            Ok(())
+        } else {
+            Err(Fmi3Error::Error)
+        }
+    }
+
+    /// Write the Float64 values of `vrs`, bypassing the variable setting restrictions.
+    fn write_float64(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        values: &[f64],
+    ) -> Result<(), Fmi3Error> {
+        self.is_dirty_values = true;
+        let mut value_index = 0;
+        for vr in vrs {
+            if *vr == 0 {
+                return Err(Fmi3Error::Error);
+            }
+            value_index +=
+                self.model
+                    .set_float64(*vr - 1, &values[value_index..], &self.context)?;
+        }
+        Ok(())
+    }
+
+    /// Evaluate the `unknowns` into `values` with the knowns set to `x0 + step * seed`.
+    fn evaluate_perturbed(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        x0: &[f64],
+        seed: &[f64],
+        step: f64,
+        values: &mut [f64],
+    ) -> Result<(), Fmi3Error> {
+        let x: Vec<f64> = x0.iter().zip(seed).map(|(x, s)| x + step * s).collect();
+        self.write_float64(knowns, &x)?;
+        self.read_float64(unknowns, values)
+    }
+
+    /// Approximate `sensitivity = J * seed` with central differences along `seed`.
+    ///
+    /// The knowns are restored afterwards, and the model equations are re-evaluated on the next
+    /// access.
+    fn finite_difference(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<(), Fmi3Error> {
+        let mut x0 = vec![0.0; seed.len()];
+        self.read_float64(knowns, &mut x0)?;
+
+        let seed_norm = seed.iter().fold(0.0_f64, |acc, s| acc.max(s.abs()));
+        if seed_norm == 0.0 {
+            sensitivity.fill(0.0);
+            return Ok(());
+        }
+        let x_norm = x0.iter().fold(1.0_f64, |acc, x| acc.max(x.abs()));
+        let h = FD_RELATIVE_STEP * x_norm / seed_norm;
+
+        let mut y_minus = vec![0.0; sensitivity.len()];
+        let result = self
+            .evaluate_perturbed(unknowns, knowns, &x0, seed, h, sensitivity)
+            .and_then(|_| self.evaluate_perturbed(unknowns, knowns, &x0, seed, -h, &mut y_minus));
+
+        // Always restore the knowns, even if an evaluation failed
+        self.write_float64(knowns, &x0)?;
+        result?;
+
+        for (y_plus, y_minus) in sensitivity.iter_mut().zip(&y_minus) {
+            *y_plus = (*y_plus - y_minus) / (2.0 * h);
+        }
+        Ok(())
+    }
+}
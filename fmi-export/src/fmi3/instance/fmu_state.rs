@@ -0,0 +1,165 @@
+use std::{cell::OnceCell, collections::BTreeMap};
+
+use fmi::fmi3::{Fmi3Error, Fmi3Res, binding};
+
+use super::{
+    ModelInstance,
+    clocks::{ClockState, build_clock_states},
+};
+use crate::fmi3::{
+    Model, UserModel,
+    traits::{Context, FmuStateValue, ModelLoggingCategory},
+};
+
+/// A snapshot of a [`ModelInstance`], as handed out by `fmi3GetFMUState`.
+///
+/// Captures the user model together with the time-keeping of the instance context and the
+/// intervals and shifts of its Clocks. The instance
+/// state machine ([`crate::fmi3::ModelState`]) is not part of the snapshot, so restoring a state
+/// does not change the mode the instance is in.
+///
+/// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetFMUState>
+pub struct FmuState<M> {
+    model: M,
+    time: f64,
+    stop_time: Option<f64>,
+    is_dirty_values: bool,
+    clocks: BTreeMap<binding::fmi3ValueReference, ClockState>,
+    /// Serialized form, computed by `fmi3SerializedFMUStateSize` and reused by
+    /// `fmi3SerializeFMUState`
+    serialized: OnceCell<Vec<u8>>,
+}
+
+impl<M: Model> FmuState<M> {
+    fn write(&self, buffer: &mut Vec<u8>) -> Result<(), Fmi3Error> {
+        M::INSTANTIATION_TOKEN.to_string().write_state(buffer);
+        self.time.write_state(buffer);
+        self.stop_time.write_state(buffer);
+        self.is_dirty_values.write_state(buffer);
+        (self.clocks.len() as u64).write_state(buffer);
+        for (vr, clock) in &self.clocks {
+            vr.write_state(buffer);
+            clock.write_state(buffer);
+        }
+        self.model.serialize_fmu_state(buffer)
+    }
+
+    fn read(mut buffer: &[u8]) -> Result<Self, Fmi3Error> {
+        if String::read_state(&mut buffer)? != M::INSTANTIATION_TOKEN {
+            return Err(Fmi3Error::Error);
+        }
+        let time = f64::read_state(&mut buffer)?;
+        let stop_time = Option::read_state(&mut buffer)?;
+        let is_dirty_values = bool::read_state(&mut buffer)?;
+        let mut clocks = build_clock_states::<M>();
+        if u64::read_state(&mut buffer)? != clocks.len() as u64 {
+            return Err(Fmi3Error::Error);
+        }
+        for _ in 0..clocks.len() {
+            let vr = u32::read_state(&mut buffer)?;
+            clocks
+                .get_mut(&vr)
+                .ok_or(Fmi3Error::Error)?
+                .read_state(&mut buffer)?;
+        }
+        let fmu_state = Self {
+            model: M::deserialize_fmu_state(&mut buffer)?,
+            time,
+            stop_time,
+            is_dirty_values,
+            clocks,
+            serialized: OnceCell::new(),
+        };
+        if !buffer.is_empty() {
+            return Err(Fmi3Error::Error);
+        }
+        Ok(fmu_state)
+    }
+}
+
+impl<M, C> ModelInstance<M, C>
+where
+    M: Model + UserModel,
+    C: Context<M>,
+{
+    fn check_fmu_state_support(&self, function: &str) -> Result<(), Fmi3Error> {
+        if M::SUPPORTS_FMU_STATE {
+            Ok(())
+        } else {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!("{function}() is not supported by this model"),
+            );
+            Err(Fmi3Error::Error)
+        }
+    }
+
+    /// Take a snapshot of the current instance state.
+    pub fn get_fmu_state(&mut self) -> Result<FmuState<M>, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("get_fmu_state()"),
+        );
+        self.check_fmu_state_support("fmi3GetFMUState")?;
+        Ok(FmuState {
+            model: self.model.snapshot_fmu_state()?,
+            time: self.context.time(),
+            stop_time: self.context.stop_time(),
+            is_dirty_values: self.is_dirty_values,
+            clocks: self.clocks.clone(),
+            serialized: OnceCell::new(),
+        })
+    }
+
+    /// Restore the instance to a previously taken snapshot.
+    pub fn set_fmu_state(&mut self, fmu_state: &FmuState<M>) -> Result<Fmi3Res, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("set_fmu_state()"),
+        );
+        self.check_fmu_state_support("fmi3SetFMUState")?;
+        self.model = fmu_state.model.snapshot_fmu_state()?;
+        self.context.initialize(fmu_state.time, fmu_state.stop_time);
+        self.is_dirty_values = fmu_state.is_dirty_values;
+        self.clocks = fmu_state.clocks.clone();
+        Ok(Fmi3Res::OK)
+    }
+
+    /// Serialize a snapshot into bytes.
+    ///
+    /// The bytes are kept in the snapshot, so that querying the size and then serializing only
+    /// serializes the snapshot once.
+    pub fn serialize_fmu_state<'s>(
+        &mut self,
+        fmu_state: &'s FmuState<M>,
+    ) -> Result<&'s [u8], Fmi3Error> {
+        self.check_fmu_state_support("fmi3SerializeFMUState")?;
+        if let Some(buffer) = fmu_state.serialized.get() {
+            return Ok(buffer);
+        }
+        let mut buffer = Vec::new();
+        fmu_state.write(&mut buffer)?;
+        Ok(fmu_state.serialized.get_or_init(|| buffer))
+    }
+
+    /// Reconstruct a snapshot from bytes written by [`ModelInstance::serialize_fmu_state`].
+    pub fn deserialize_fmu_state(&mut self, buffer: &[u8]) -> Result<FmuState<M>, Fmi3Error> {
+        self.context.log(
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!("deserialize_fmu_state(size: {})", buffer.len()),
+        );
+        self.check_fmu_state_support("fmi3DeserializeFMUState")?;
+
+        FmuState::read(buffer).inspect_err(|_| {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!("Serialized FMU state is invalid or belongs to another model"),
+            );
+        })
+    }
+}
@@ -2,7 +2,10 @@ use crate::fmi3::{
     Model, ModelGetSetStates, ModelState, UserModel,
     traits::{Context, ModelGetSet, ModelLoggingCategory},
 };
-use fmi::fmi3::{CoSimulation, Fmi3Error, Fmi3Res};
+use fmi::{
+    EventFlags,
+    fmi3::{CoSimulation, Fmi3Error, Fmi3Res},
+};
 
 use super::ModelInstance;
 
@@ -95,8 +98,25 @@ where
             no_set_fmu_state_prior_to_current_point,
         )?;
 
-        *event_handling_needed = result.event_handling_needed;
+        *event_handling_needed = false;
         *terminate_simulation = result.terminate_simulation;
+
+        if result.event_handling_needed {
+            if self.event_mode_used {
+                // The importer enters Event Mode and calls `update_discrete_states`
+                *event_handling_needed = true;
+            } else {
+                // Without Event Mode the FMU has to handle the event internally
+                let mut event_flags = EventFlags {
+                    discrete_states_need_update: true,
+                    ..Default::default()
+                };
+                while event_flags.discrete_states_need_update && !event_flags.terminate_simulation {
+                    self.model.event_update(&self.context, &mut event_flags)?;
+                }
+                *terminate_simulation |= event_flags.terminate_simulation;
+            }
+        }
         *early_return = self.context.early_return_allowed() && result.early_return;
         *last_successful_time = result.last_successful_time;
 
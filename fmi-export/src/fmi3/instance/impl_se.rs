@@ -1,6 +1,9 @@
-use fmi::fmi3::ScheduledExecution;
+use fmi::fmi3::{Fmi3Error, Fmi3Res, ScheduledExecution, binding};
 
-use crate::fmi3::{Context, Model, ModelGetSet, ModelGetSetStates, ModelInstance, UserModel};
+use crate::fmi3::{
+    Context, Model, ModelGetSet, ModelGetSetStates, ModelInstance, ModelState, UserModel,
+    traits::ModelLoggingCategory,
+};
 
 impl<M, C> ScheduledExecution for ModelInstance<M, C>
 where
@@ -9,15 +12,52 @@ where
 {
     fn activate_model_partition(
         &mut self,
-        _clock_reference: fmi::fmi3::binding::fmi3ValueReference,
-        _activation_time: f64,
-    ) -> Result<fmi::fmi3::Fmi3Res, fmi::fmi3::Fmi3Error> {
-        //TODO: implement activate_model_partition. For now, report and return Error.
+        clock_reference: binding::fmi3ValueReference,
+        activation_time: f64,
+    ) -> Result<Fmi3Res, Fmi3Error> {
         self.context.log(
-            fmi::fmi3::Fmi3Error::Error.into(),
-            M::LoggingCategory::default(),
-            format_args!("activate_model_partition() is not implemented yet."),
+            Fmi3Res::OK.into(),
+            M::LoggingCategory::trace_category(),
+            format_args!(
+                "activate_model_partition(clock_reference: {clock_reference}, activation_time: {activation_time})"
+            ),
         );
-        Err(fmi::fmi3::Fmi3Error::Error)
+        self.assert_instance_type(fmi::InterfaceType::ScheduledExecution)?;
+
+        match self.state {
+            ModelState::ClockActivationMode => {}
+            _ => {
+                self.context.log(
+                    Fmi3Error::Error.into(),
+                    M::LoggingCategory::default(),
+                    format_args!(
+                        "activate_model_partition() called in invalid state {:?}",
+                        self.state
+                    ),
+                );
+                return Err(Fmi3Error::Error);
+            }
+        }
+
+        // 'time' (VR 0) is not a Clock
+        if clock_reference == 0 {
+            return Err(Fmi3Error::Error);
+        }
+
+        self.context.set_time(activation_time);
+        let result =
+            self.model
+                .activate_partition(&self.context, clock_reference - 1, activation_time);
+
+        // Partitions update discrete states, so calculated values have to be re-evaluated
+        self.is_dirty_values = true;
+
+        result.inspect_err(|_| {
+            self.context.log(
+                Fmi3Error::Error.into(),
+                M::LoggingCategory::default(),
+                format_args!("activate_model_partition() failed for Clock {clock_reference}"),
+            );
+        })
     }
 }
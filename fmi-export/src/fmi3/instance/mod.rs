@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use fmi::fmi3::{Fmi3Error, Fmi3Status, binding};
 
 use crate::fmi3::{
@@ -5,16 +7,23 @@ use crate::fmi3::{
     traits::{Context, Model},
 };
 
+mod clocks;
 mod common;
 pub mod context;
+mod derivatives;
+mod fmu_state;
 mod get_set;
 mod impl_cs;
 mod impl_me;
 mod impl_se;
 
+pub use fmu_state::FmuState;
+
 pub type LogMessageClosure = Box<dyn Fn(Fmi3Status, &str, std::fmt::Arguments<'_>) + Send + Sync>;
 pub type IntermediateUpdateClosure =
     Box<dyn Fn(f64, bool, bool, bool, bool) -> Option<f64> + Send + Sync>;
+pub type ClockUpdateClosure = Box<dyn Fn() + Send + Sync>;
+pub type PreemptionClosure = Box<dyn Fn() + Send + Sync>;
 
 /// An exportable FMU instance, generic over model type M and context type C
 #[repr(C)]
@@ -33,6 +42,10 @@ where
     state: ModelState,
     /// Do we need to re-evaluate the model equations?
     is_dirty_values: bool,
+    /// Whether the importer supports Event Mode (Co-Simulation only)
+    event_mode_used: bool,
+    /// Intervals and shifts of the model Clocks, keyed by value reference
+    clocks: BTreeMap<binding::fmi3ValueReference, clocks::ClockState>,
     /// The user-defined model
     model: M,
 }
@@ -64,6 +77,8 @@ where
             state: ModelState::Instantiated,
             instance_type,
             is_dirty_values: true,
+            event_mode_used: false,
+            clocks: clocks::build_clock_states::<M>(),
             model: M::default(),
         };
 
@@ -73,6 +88,16 @@ where
         Ok(instance)
     }
 
+    /// Set whether the importer supports Event Mode, as passed to `fmi3InstantiateCoSimulation`.
+    pub fn with_event_mode_used(mut self, event_mode_used: bool) -> Self {
+        self.event_mode_used = event_mode_used;
+        self
+    }
+
+    pub fn event_mode_used(&self) -> bool {
+        self.event_mode_used
+    }
+
     pub fn instance_name(&self) -> &str {
         &self.instance_name
     }
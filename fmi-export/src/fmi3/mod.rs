@@ -22,11 +22,11 @@ mod variable_builder;
 use std::{fmt::Display, str::FromStr};
 
 // Re-exports
-pub use instance::{ModelInstance, context::BasicContext};
+pub use instance::{FmuState, ModelInstance, context::BasicContext};
 pub use traits::{
     CSDoStepResult, Context, Fmi3CoSimulation, Fmi3Common, Fmi3ModelExchange,
-    Fmi3ScheduledExecution, Model, ModelGetSet, ModelGetSetStates, ModelLoggingCategory,
-    ModelMetadata, TerminalProvider, UserModel,
+    Fmi3ScheduledExecution, FmuStateValue, Model, ModelGetSet, ModelGetSetStates,
+    ModelLoggingCategory, ModelMetadata, TerminalProvider, UserModel,
 };
 pub use types::{Binary, Clock, InitializeFromStart};
 pub use variable_builder::{FmiVariableBuilder, VariableBuilder};
@@ -102,4 +102,10 @@ impl ModelLoggingCategory for DefaultLoggingCategory {
     fn error_category() -> Self {
         Self::LogAll
     }
+    fn description(&self) -> Option<&'static str> {
+        match self {
+            DefaultLoggingCategory::LogAll => Some("Log all events"),
+            DefaultLoggingCategory::Trace => Some("Trace FMI API calls"),
+        }
+    }
 }
@@ -0,0 +1,159 @@
+use fmi::fmi3::Fmi3Error;
+
+use crate::fmi3::{Binary, Clock};
+
+/// Byte-level encoding of values stored in a serialized FMU state.
+///
+/// This is implemented for the primitive FMI types, `String`, [`Binary`], [`Clock`], arrays and
+/// `Vec`s thereof. `#[derive(FmuModel)]` implements it for the model struct when
+/// `#[model(fmu_state = true)]` is set, so every field of such a model (including non-variable
+/// fields and `#[child]` components) must implement it as well.
+///
+/// All values are encoded little-endian; variable-length values are prefixed with their length
+/// as a `u64`.
+pub trait FmuStateValue: Sized {
+    /// Append the encoded value to `buffer`.
+    fn write_state(&self, buffer: &mut Vec<u8>);
+
+    /// Decode a value from the front of `buffer`, advancing it past the consumed bytes.
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error>;
+}
+
+/// Split `len` bytes off the front of `buffer`.
+fn take_bytes<'a>(buffer: &mut &'a [u8], len: usize) -> Result<&'a [u8], Fmi3Error> {
+    if buffer.len() < len {
+        return Err(Fmi3Error::Error);
+    }
+    let (head, tail) = buffer.split_at(len);
+    *buffer = tail;
+    Ok(head)
+}
+
+fn read_len(buffer: &mut &[u8]) -> Result<usize, Fmi3Error> {
+    usize::try_from(u64::read_state(buffer)?).map_err(|_| Fmi3Error::Error)
+}
+
+macro_rules! impl_fmu_state_value_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl FmuStateValue for $ty {
+                fn write_state(&self, buffer: &mut Vec<u8>) {
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+                    let bytes = take_bytes(buffer, std::mem::size_of::<$ty>())?;
+                    Ok(<$ty>::from_le_bytes(
+                        bytes.try_into().map_err(|_| Fmi3Error::Error)?,
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+impl_fmu_state_value_primitive!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl FmuStateValue for bool {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        buffer.push(*self as u8);
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        match take_bytes(buffer, 1)? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(Fmi3Error::Error),
+        }
+    }
+}
+
+impl FmuStateValue for String {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        (self.len() as u64).write_state(buffer);
+        buffer.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        let len = read_len(buffer)?;
+        let bytes = take_bytes(buffer, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Fmi3Error::Error)
+    }
+}
+
+impl FmuStateValue for Binary {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        (self.0.len() as u64).write_state(buffer);
+        buffer.extend_from_slice(&self.0);
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        let len = read_len(buffer)?;
+        Ok(Binary(take_bytes(buffer, len)?.to_vec()))
+    }
+}
+
+impl FmuStateValue for Clock {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        self.0.write_state(buffer);
+        self.1.write_state(buffer);
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        Ok(Clock(
+            bool::read_state(buffer)?,
+            Option::read_state(buffer)?,
+        ))
+    }
+}
+
+impl<T: FmuStateValue> FmuStateValue for Option<T> {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        self.is_some().write_state(buffer);
+        if let Some(value) = self {
+            value.write_state(buffer);
+        }
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        if bool::read_state(buffer)? {
+            T::read_state(buffer).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: FmuStateValue> FmuStateValue for Vec<T> {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        (self.len() as u64).write_state(buffer);
+        for value in self {
+            value.write_state(buffer);
+        }
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        let len = read_len(buffer)?;
+        // Cap the pre-allocation so a corrupt length can't request an arbitrarily large buffer.
+        let mut values = Vec::with_capacity(len.min(buffer.len()));
+        for _ in 0..len {
+            values.push(T::read_state(buffer)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<T: FmuStateValue, const N: usize> FmuStateValue for [T; N] {
+    fn write_state(&self, buffer: &mut Vec<u8>) {
+        for value in self {
+            value.write_state(buffer);
+        }
+    }
+
+    fn read_state(buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        let values = (0..N)
+            .map(|_| T::read_state(buffer))
+            .collect::<Result<Vec<_>, _>>()?;
+        values.try_into().map_err(|_| Fmi3Error::Error)
+    }
+}
@@ -8,9 +8,11 @@ use fmi::{
 
 use crate::fmi3::ModelState;
 
+mod fmu_state;
 mod model_get_set;
 mod wrappers;
 
+pub use fmu_state::FmuStateValue;
 pub use model_get_set::{ModelGetSet, ModelGetSetStates};
 pub use wrappers::{Fmi3CoSimulation, Fmi3Common, Fmi3ModelExchange, Fmi3ScheduledExecution};
 
@@ -44,6 +46,15 @@ pub trait Context<M: UserModel> {
         false
     }
 
+    /// Signal the importer that an output or countdown Clock ticked (relevant for SE).
+    fn clock_update(&self) {}
+
+    /// Prevent the current model partition from being preempted (relevant for SE).
+    fn lock_preemption(&self) {}
+
+    /// Allow preemption again after [`Context::lock_preemption`] (relevant for SE).
+    fn unlock_preemption(&self) {}
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
 
@@ -67,6 +78,9 @@ pub trait Model: Default {
     /// Whether this model supports Scheduled Execution interface
     const SUPPORTS_SCHEDULED_EXECUTION: bool;
 
+    /// Whether this model supports getting, setting and serializing the FMU state
+    const SUPPORTS_FMU_STATE: bool = false;
+
     /// Recursively build the model variables and structure by appending to the provided
     /// `ModelVariables` and `ModelStructure` instances.
     ///
@@ -81,6 +95,12 @@ pub trait Model: Default {
     /// Recursively build terminal definitions by appending to the provided list.
     fn build_terminals(_terminals: &mut Vec<fmi::schema::fmi3::Terminal>, _prefix: Option<&str>) {}
 
+    /// Recursively build the unit definitions by appending to the provided list.
+    fn build_unit_definitions(_units: &mut Vec<fmi::schema::fmi3::Fmi3Unit>) {}
+
+    /// Recursively build the type definitions by appending to the provided list.
+    fn build_type_definitions(_types: &mut Vec<fmi::schema::fmi3::TypeDefinition>) {}
+
     /// Build top-level terminals metadata, if any.
     fn build_toplevel_terminals() -> Option<fmi::schema::fmi3::Fmi3TerminalsAndIcons> {
         let mut terminals = Vec::new();
@@ -113,10 +133,27 @@ pub trait Model: Default {
         let mut structure = fmi::schema::fmi3::ModelStructure::default();
         let _num_vars = Self::build_metadata(&mut variables, &mut structure, 1, None);
         let terminals = Self::build_toplevel_terminals();
+
+        // Child components of the same type declare the same units and types
+        let mut units = Vec::new();
+        Self::build_unit_definitions(&mut units);
+        let mut unit_names = std::collections::HashSet::new();
+        units.retain(|unit| unit_names.insert(unit.name.clone()));
+
+        let mut type_definitions = Vec::new();
+        Self::build_type_definitions(&mut type_definitions);
+        let mut type_names = std::collections::HashSet::new();
+        type_definitions.retain(|ty| type_names.insert(ty.name().to_owned()));
+
         ModelMetadata {
             model_variables: variables,
             model_structure: structure,
             terminals,
+            unit_definitions: (!units.is_empty())
+                .then_some(fmi::schema::fmi3::UnitDefinitions { units }),
+            type_definitions: (!type_definitions.is_empty())
+                .then_some(fmi::schema::fmi3::TypeDefinitions { type_definitions }),
+            log_categories: None,
         }
     }
 
@@ -135,6 +172,43 @@ pub trait Model: Default {
         let _ = (vr, state);
         Ok(())
     }
+
+    /// Run the model partition of the input Clock `vr` for `fmi3ActivateModelPartition`.
+    ///
+    /// As for [`ModelGetSet`], `vr` does not count the `time` variable. The derive macro
+    /// dispatches to the method given by the `partition` attribute of the Clock.
+    fn activate_partition(
+        &mut self,
+        _context: &dyn Context<Self>,
+        _vr: binding::fmi3ValueReference,
+        _activation_time: f64,
+    ) -> Result<Fmi3Res, Fmi3Error>
+    where
+        Self: UserModel,
+    {
+        Err(Fmi3Error::Error)
+    }
+
+    /// Take a snapshot of the model for `fmi3GetFMUState`.
+    ///
+    /// Only called if [`Model::SUPPORTS_FMU_STATE`] is set.
+    fn snapshot_fmu_state(&self) -> Result<Self, Fmi3Error> {
+        Err(Fmi3Error::Error)
+    }
+
+    /// Append the serialized model to `buffer` for `fmi3SerializeFMUState`.
+    ///
+    /// Only called if [`Model::SUPPORTS_FMU_STATE`] is set.
+    fn serialize_fmu_state(&self, _buffer: &mut Vec<u8>) -> Result<(), Fmi3Error> {
+        Err(Fmi3Error::Error)
+    }
+
+    /// Reconstruct a model previously written by [`Model::serialize_fmu_state`].
+    ///
+    /// Only called if [`Model::SUPPORTS_FMU_STATE`] is set.
+    fn deserialize_fmu_state(_buffer: &mut &[u8]) -> Result<Self, Fmi3Error> {
+        Err(Fmi3Error::Error)
+    }
 }
 
 /// Aggregated metadata for an FMI 3.0 model.
@@ -142,6 +216,10 @@ pub struct ModelMetadata {
     pub model_variables: fmi::schema::fmi3::ModelVariables,
     pub model_structure: fmi::schema::fmi3::ModelStructure,
     pub terminals: Option<fmi::schema::fmi3::Fmi3TerminalsAndIcons>,
+    pub unit_definitions: Option<fmi::schema::fmi3::UnitDefinitions>,
+    pub type_definitions: Option<fmi::schema::fmi3::TypeDefinitions>,
+    /// Logging categories of the [`UserModel::LoggingCategory`], filled in by `export_fmu!`
+    pub log_categories: Option<fmi::schema::fmi3::LogCategories>,
 }
 
 /// Trait for providing FMI 3.0 terminal definitions.
@@ -157,11 +235,31 @@ pub trait ModelLoggingCategory: Display + FromStr + Ord + Copy + Default {
     fn trace_category() -> Self;
     /// Get the category for logging errors
     fn error_category() -> Self;
+
+    /// Description of the category for the model description
+    fn description(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Build the `<LogCategories>` of the model description from [`Self::all_categories`]
+    fn log_categories() -> fmi::schema::fmi3::LogCategories {
+        fmi::schema::fmi3::LogCategories {
+            categories: Self::all_categories()
+                .map(|category| fmi::schema::fmi3::Category {
+                    annotations: None,
+                    name: category.to_string(),
+                    description: category.description().map(str::to_owned),
+                })
+                .collect(),
+        }
+    }
 }
 
 /// Result payload for a Co-Simulation `do_step` implementation.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CSDoStepResult {
+    /// An event occurred during the step. If the importer uses Event Mode it is asked to enter it,
+    /// otherwise [`UserModel::event_update`] is called before `do_step` returns.
     pub event_handling_needed: bool,
     pub terminate_simulation: bool,
     pub early_return: bool,
@@ -237,6 +335,45 @@ pub trait UserModel: Sized {
         Ok(true)
     }
 
+    /// Compute the directional derivative `sensitivity = J * seed` of the `unknowns` with respect
+    /// to the `knowns`, where `J` is their partial derivative matrix.
+    ///
+    /// Value references are those of the model description, and `seed`/`sensitivity` hold one
+    /// entry per (array) element of the knowns/unknowns respectively.
+    ///
+    /// # Returns
+    /// - `Ok(true)` if `sensitivity` was computed analytically
+    /// - `Ok(false)` (the default) to fall back to finite differences, which perturb the knowns
+    ///   through [`ModelGetSet`] and re-evaluate [`UserModel::calculate_values`]
+    /// - `Err(Fmi3Error)` if the derivative could not be computed
+    fn get_directional_derivative(
+        &mut self,
+        _context: &dyn Context<Self>,
+        _unknowns: &[binding::fmi3ValueReference],
+        _knowns: &[binding::fmi3ValueReference],
+        _seed: &[f64],
+        _sensitivity: &mut [f64],
+    ) -> Result<bool, Fmi3Error> {
+        Ok(false)
+    }
+
+    /// Compute the adjoint derivative `sensitivity = seed^T * J` of the `unknowns` with respect to
+    /// the `knowns`, where `J` is their partial derivative matrix.
+    ///
+    /// Here `seed` holds one entry per element of the unknowns and `sensitivity` one entry per
+    /// element of the knowns. Return values are as for
+    /// [`UserModel::get_directional_derivative`].
+    fn get_adjoint_derivative(
+        &mut self,
+        _context: &dyn Context<Self>,
+        _unknowns: &[binding::fmi3ValueReference],
+        _knowns: &[binding::fmi3ValueReference],
+        _seed: &[f64],
+        _sensitivity: &mut [f64],
+    ) -> Result<bool, Fmi3Error> {
+        Ok(false)
+    }
+
     /// Co-Simulation step implementation.
     ///
     /// Default behavior advances time and reports a completed step.
@@ -161,6 +161,16 @@ pub trait ModelGetSet<M: Model> {
     ) -> Result<(), Fmi3Error> {
         Err(Fmi3Error::Error)
     }
+
+    /// Take the interval set by the model with [`Clock::set_interval`] since it was last taken
+    fn get_clock_interval(
+        &mut self,
+        _vr: binding::fmi3ValueReference,
+        _interval: &mut Option<f64>,
+        _context: &dyn Context<M>,
+    ) -> Result<(), Fmi3Error> {
+        Err(Fmi3Error::Error)
+    }
 }
 
 impl_model_get_set_primitive!(boolean, bool, schema::DataType::Boolean);
@@ -238,6 +248,19 @@ impl<M: Model> ModelGetSet<M> for Clock {
             Err(Fmi3Error::Error)
         }
     }
+    fn get_clock_interval(
+        &mut self,
+        vr: binding::fmi3ValueReference,
+        interval: &mut Option<f64>,
+        _context: &dyn Context<M>,
+    ) -> Result<(), Fmi3Error> {
+        if vr == 0 {
+            *interval = self.1.take();
+            Ok(())
+        } else {
+            Err(Fmi3Error::Error)
+        }
+    }
 }
 
 impl<M: Model> ModelGetSet<M> for Binary {
@@ -9,8 +9,11 @@
 use ::std::ffi::CString;
 
 use crate::fmi3::{
-    ModelGetSetStates, ModelInstance, UserModel,
-    instance::{IntermediateUpdateClosure, LogMessageClosure, context::BasicContext},
+    FmuState, ModelGetSetStates, ModelInstance, UserModel,
+    instance::{
+        ClockUpdateClosure, IntermediateUpdateClosure, LogMessageClosure, PreemptionClosure,
+        context::BasicContext,
+    },
     traits::ModelGetSet,
 };
 
@@ -157,6 +160,21 @@ macro_rules! wrapper_getset_functions {
     };
 }
 
+/// Store `state` behind an FMI state handle, overwriting the existing state if there is one.
+///
+/// # Safety
+/// `fmu_state` must be a valid pointer, holding either null or a state previously stored here.
+unsafe fn store_fmu_state<M>(fmu_state: *mut binding::fmi3FMUState, state: FmuState<M>) {
+    unsafe {
+        let existing = *fmu_state as *mut FmuState<M>;
+        if existing.is_null() {
+            *fmu_state = ::std::boxed::Box::into_raw(::std::boxed::Box::new(state)) as _;
+        } else {
+            *existing = state;
+        }
+    }
+}
+
 pub trait Fmi3Common: Model + UserModel + ModelGetSet<Self> + ModelGetSetStates + Sized
 where
     Self: 'static,
@@ -267,7 +285,7 @@ where
         resource_path: binding::fmi3String,
         _visible: binding::fmi3Boolean,
         _logging_on: binding::fmi3Boolean,
-        _event_mode_used: binding::fmi3Boolean,
+        event_mode_used: binding::fmi3Boolean,
         _early_return_allowed: binding::fmi3Boolean,
         _required_intermediate_variables: *const binding::fmi3ValueReference,
         _n_required_intermediate_variables: usize,
@@ -371,8 +389,9 @@ where
             context,
             fmi::InterfaceType::CoSimulation,
         ) {
-            Ok(instance) => ::std::boxed::Box::into_raw(::std::boxed::Box::new(instance))
-                as binding::fmi3Instance,
+            Ok(instance) => ::std::boxed::Box::into_raw(::std::boxed::Box::new(
+                instance.with_event_mode_used(event_mode_used),
+            )) as binding::fmi3Instance,
             Err(_) => {
                 eprintln!("Failed to instantiate FMU: invalid instantiation token");
                 ::std::ptr::null_mut()
@@ -386,24 +405,92 @@ where
         instantiation_token: binding::fmi3String,
         resource_path: binding::fmi3String,
         _visible: binding::fmi3Boolean,
-        _logging_on: binding::fmi3Boolean,
-        _instance_environment: binding::fmi3InstanceEnvironment,
-        _log_message: binding::fmi3LogMessageCallback,
-        _clock_update: binding::fmi3ClockUpdateCallback,
-        _lock_preemption: binding::fmi3LockPreemptionCallback,
-        _unlock_preemption: binding::fmi3UnlockPreemptionCallback,
+        logging_on: binding::fmi3Boolean,
+        instance_environment: binding::fmi3InstanceEnvironment,
+        log_message: binding::fmi3LogMessageCallback,
+        clock_update: binding::fmi3ClockUpdateCallback,
+        lock_preemption: binding::fmi3LockPreemptionCallback,
+        unlock_preemption: binding::fmi3UnlockPreemptionCallback,
     ) -> binding::fmi3Instance {
-        let _name = unsafe { ::std::ffi::CStr::from_ptr(instance_name) }
+        let name = unsafe { ::std::ffi::CStr::from_ptr(instance_name) }
             .to_string_lossy()
             .into_owned();
-        let _token = unsafe { ::std::ffi::CStr::from_ptr(instantiation_token) }.to_string_lossy();
-        let _resource_path = ::std::path::PathBuf::from(
+        let token = unsafe { ::std::ffi::CStr::from_ptr(instantiation_token) }.to_string_lossy();
+        let resource_path = ::std::path::PathBuf::from(
             unsafe { ::std::ffi::CStr::from_ptr(resource_path) }
                 .to_string_lossy()
                 .into_owned(),
         );
 
-        todo!("Scheduled-Execution not yet implemented");
+        let log_message: LogMessageClosure = if let Some(cb) = log_message {
+            Box::new(
+                move |status: Fmi3Status, category: &str, args: std::fmt::Arguments<'_>| {
+                    let category_c = CString::new(category).unwrap_or_default();
+                    let message_c = CString::new(args.to_string()).unwrap_or_default();
+                    unsafe {
+                        cb(
+                            std::ptr::null_mut() as binding::fmi3InstanceEnvironment,
+                            status.into(),
+                            category_c.as_ptr(),
+                            message_c.as_ptr(),
+                        )
+                    };
+                },
+            )
+        } else {
+            Box::new(
+                move |status: Fmi3Status, category: &str, args: std::fmt::Arguments<'_>| {
+                    let category_c = CString::new(category).unwrap_or_default();
+                    let message_c = CString::new(args.to_string()).unwrap_or_default();
+                    eprintln!(
+                        "Log (status: {:?}, category: {}): {}",
+                        status,
+                        category_c.to_string_lossy(),
+                        message_c.to_string_lossy()
+                    );
+                },
+            )
+        };
+
+        // The importer identifies the instance by its environment in the clock update callback.
+        // The pointer is only passed back to the importer, so store it as an address.
+        let instance_environment = instance_environment as usize;
+        let clock_update: Option<ClockUpdateClosure> = clock_update.map(|cb| {
+            let closure: ClockUpdateClosure = Box::new(move || unsafe {
+                cb(instance_environment as binding::fmi3InstanceEnvironment)
+            });
+            closure
+        });
+        let lock_preemption: Option<PreemptionClosure> = lock_preemption.map(|cb| {
+            let closure: PreemptionClosure = Box::new(move || unsafe { cb() });
+            closure
+        });
+        let unlock_preemption: Option<PreemptionClosure> = unlock_preemption.map(|cb| {
+            let closure: PreemptionClosure = Box::new(move || unsafe { cb() });
+            closure
+        });
+
+        if !Self::SUPPORTS_SCHEDULED_EXECUTION {
+            eprintln!("Scheduled Execution not supported by this FMU");
+            return ::std::ptr::null_mut();
+        }
+
+        let context = BasicContext::new(logging_on, log_message, resource_path, false, None)
+            .with_scheduled_execution(clock_update, lock_preemption, unlock_preemption);
+
+        match crate::fmi3::ModelInstance::<Self, BasicContext<Self>>::new(
+            name,
+            &token,
+            context,
+            fmi::InterfaceType::ScheduledExecution,
+        ) {
+            Ok(instance) => ::std::boxed::Box::into_raw(::std::boxed::Box::new(instance))
+                as binding::fmi3Instance,
+            Err(_) => {
+                eprintln!("Failed to instantiate FMU: invalid instantiation token");
+                ::std::ptr::null_mut()
+            }
+        }
     }
 
     #[inline(always)]
@@ -461,8 +548,21 @@ where
                 // _this dropped here
             }
             fmi::InterfaceType::ScheduledExecution => {
-                // TODO: Add SEContext when implemented
-                eprintln!("Scheduled Execution not yet implemented");
+                let _this = unsafe {
+                    ::std::boxed::Box::from_raw(
+                        instance
+                            as *mut crate::fmi3::ModelInstance<
+                                Self,
+                                crate::fmi3::instance::context::BasicContext<Self>,
+                            >,
+                    )
+                };
+                _this.context().log(
+                    Fmi3Res::OK.into(),
+                    Default::default(),
+                    format_args!("{}: fmi3FreeInstance()", _this.instance_name()),
+                );
+                // _this dropped here
             }
         }
     }
@@ -543,88 +643,197 @@ where
     // FMU State functions
     #[inline(always)]
     unsafe fn fmi3_get_fmu_state(
-        _instance: binding::fmi3Instance,
-        _fmu_state: *mut binding::fmi3FMUState,
+        instance: binding::fmi3Instance,
+        fmu_state: *mut binding::fmi3FMUState,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        if fmu_state.is_null() {
+            eprintln!("Invalid FMU state pointer");
+            return binding::fmi3Status_fmi3Error;
+        }
+        match dispatch_by_instance_type!(instance, Self, get_fmu_state) {
+            Ok(state) => {
+                unsafe { store_fmu_state(fmu_state, state) };
+                binding::fmi3Status_fmi3OK
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_set_fmu_state(
-        _instance: binding::fmi3Instance,
-        _fmu_state: binding::fmi3FMUState,
+        instance: binding::fmi3Instance,
+        fmu_state: binding::fmi3FMUState,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        if fmu_state.is_null() {
+            eprintln!("Invalid FMU state");
+            return binding::fmi3Status_fmi3Error;
+        }
+        let state = unsafe { &*(fmu_state as *const FmuState<Self>) };
+        match dispatch_by_instance_type!(instance, Self, set_fmu_state, state) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_free_fmu_state(
         _instance: binding::fmi3Instance,
-        _fmu_state: *mut binding::fmi3FMUState,
+        fmu_state: *mut binding::fmi3FMUState,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        // Freeing a null state is a no-op
+        if fmu_state.is_null() || unsafe { *fmu_state }.is_null() {
+            return binding::fmi3Status_fmi3OK;
+        }
+        unsafe {
+            drop(::std::boxed::Box::from_raw(
+                *fmu_state as *mut FmuState<Self>,
+            ));
+            *fmu_state = ::std::ptr::null_mut();
+        }
+        binding::fmi3Status_fmi3OK
     }
 
     #[inline(always)]
     unsafe fn fmi3_serialized_fmu_state_size(
-        _instance: binding::fmi3Instance,
-        _fmu_state: binding::fmi3FMUState,
-        _size: *mut usize,
+        instance: binding::fmi3Instance,
+        fmu_state: binding::fmi3FMUState,
+        size: *mut usize,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        if fmu_state.is_null() {
+            eprintln!("Invalid FMU state");
+            return binding::fmi3Status_fmi3Error;
+        }
+        let state = unsafe { &*(fmu_state as *const FmuState<Self>) };
+        match dispatch_by_instance_type!(instance, Self, serialize_fmu_state, state) {
+            Ok(bytes) => {
+                unsafe {
+                    *size = bytes.len();
+                }
+                binding::fmi3Status_fmi3OK
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_serialize_fmu_state(
-        _instance: binding::fmi3Instance,
-        _fmu_state: binding::fmi3FMUState,
-        _serialized_state: *mut binding::fmi3Byte,
-        _size: usize,
+        instance: binding::fmi3Instance,
+        fmu_state: binding::fmi3FMUState,
+        serialized_state: *mut binding::fmi3Byte,
+        size: usize,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        if fmu_state.is_null() {
+            eprintln!("Invalid FMU state");
+            return binding::fmi3Status_fmi3Error;
+        }
+        let state = unsafe { &*(fmu_state as *const FmuState<Self>) };
+        match dispatch_by_instance_type!(instance, Self, serialize_fmu_state, state) {
+            Ok(bytes) => {
+                if bytes.len() > size {
+                    eprintln!(
+                        "Buffer too small: serialized FMU state needs {} bytes but only {} allocated",
+                        bytes.len(),
+                        size
+                    );
+                    return binding::fmi3Status_fmi3Error;
+                }
+                let buffer = unsafe { ::std::slice::from_raw_parts_mut(serialized_state, size) };
+                buffer[..bytes.len()].copy_from_slice(bytes);
+                binding::fmi3Status_fmi3OK
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_deserialize_fmu_state(
-        _instance: binding::fmi3Instance,
-        _serialized_state: *const binding::fmi3Byte,
-        _size: usize,
-        _fmu_state: *mut binding::fmi3FMUState,
+        instance: binding::fmi3Instance,
+        serialized_state: *const binding::fmi3Byte,
+        size: usize,
+        fmu_state: *mut binding::fmi3FMUState,
     ) -> binding::fmi3Status {
-        todo!("FMU state not yet implemented");
+        if fmu_state.is_null() || serialized_state.is_null() {
+            eprintln!("Invalid FMU state pointer");
+            return binding::fmi3Status_fmi3Error;
+        }
+        let bytes = unsafe { ::std::slice::from_raw_parts(serialized_state, size) };
+        match dispatch_by_instance_type!(instance, Self, deserialize_fmu_state, bytes) {
+            Ok(state) => {
+                unsafe { store_fmu_state(fmu_state, state) };
+                binding::fmi3Status_fmi3OK
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     // Derivative functions
     #[inline(always)]
     unsafe fn fmi3_get_directional_derivative(
-        _instance: binding::fmi3Instance,
-        _unknowns: *const binding::fmi3ValueReference,
-        _n_unknowns: usize,
-        _knowns: *const binding::fmi3ValueReference,
-        _n_knowns: usize,
-        _seed: *const binding::fmi3Float64,
-        _n_seed: usize,
-        _sensitivity: *mut binding::fmi3Float64,
-        _n_sensitivity: usize,
+        instance: binding::fmi3Instance,
+        unknowns: *const binding::fmi3ValueReference,
+        n_unknowns: usize,
+        knowns: *const binding::fmi3ValueReference,
+        n_knowns: usize,
+        seed: *const binding::fmi3Float64,
+        n_seed: usize,
+        sensitivity: *mut binding::fmi3Float64,
+        n_sensitivity: usize,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Directional derivative not yet implemented");
+        let unknowns = unsafe { ::std::slice::from_raw_parts(unknowns, n_unknowns) };
+        let knowns = unsafe { ::std::slice::from_raw_parts(knowns, n_knowns) };
+        let seed = unsafe { ::std::slice::from_raw_parts(seed, n_seed) };
+        let sensitivity = unsafe { ::std::slice::from_raw_parts_mut(sensitivity, n_sensitivity) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            get_directional_derivative,
+            unknowns,
+            knowns,
+            seed,
+            sensitivity
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_get_adjoint_derivative(
-        _instance: binding::fmi3Instance,
-        _unknowns: *const binding::fmi3ValueReference,
-        _n_unknowns: usize,
-        _knowns: *const binding::fmi3ValueReference,
-        _n_knowns: usize,
-        _seed: *const binding::fmi3Float64,
-        _n_seed: usize,
-        _sensitivity: *mut binding::fmi3Float64,
-        _n_sensitivity: usize,
+        instance: binding::fmi3Instance,
+        unknowns: *const binding::fmi3ValueReference,
+        n_unknowns: usize,
+        knowns: *const binding::fmi3ValueReference,
+        n_knowns: usize,
+        seed: *const binding::fmi3Float64,
+        n_seed: usize,
+        sensitivity: *mut binding::fmi3Float64,
+        n_sensitivity: usize,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Adjoint derivative not yet implemented");
+        let unknowns = unsafe { ::std::slice::from_raw_parts(unknowns, n_unknowns) };
+        let knowns = unsafe { ::std::slice::from_raw_parts(knowns, n_knowns) };
+        let seed = unsafe { ::std::slice::from_raw_parts(seed, n_seed) };
+        let sensitivity = unsafe { ::std::slice::from_raw_parts_mut(sensitivity, n_sensitivity) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            get_adjoint_derivative,
+            unknowns,
+            knowns,
+            seed,
+            sensitivity
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     // Configuration mode functions
@@ -655,96 +864,212 @@ where
     // Clock related functions
     #[inline(always)]
     unsafe fn fmi3_get_interval_decimal(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _intervals: *mut binding::fmi3Float64,
-        _qualifiers: *mut binding::fmi3IntervalQualifier,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        intervals: *mut binding::fmi3Float64,
+        qualifiers: *mut binding::fmi3IntervalQualifier,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let intervals = unsafe { ::std::slice::from_raw_parts_mut(intervals, n_value_references) };
+        let qualifiers =
+            unsafe { ::std::slice::from_raw_parts_mut(qualifiers, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            get_interval_decimal,
+            value_refs,
+            intervals,
+            qualifiers
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_get_interval_fraction(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _counters: *mut binding::fmi3UInt64,
-        _resolutions: *mut binding::fmi3UInt64,
-        _qualifiers: *mut binding::fmi3IntervalQualifier,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        counters: *mut binding::fmi3UInt64,
+        resolutions: *mut binding::fmi3UInt64,
+        qualifiers: *mut binding::fmi3IntervalQualifier,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let counters = unsafe { ::std::slice::from_raw_parts_mut(counters, n_value_references) };
+        let resolutions =
+            unsafe { ::std::slice::from_raw_parts_mut(resolutions, n_value_references) };
+        let qualifiers =
+            unsafe { ::std::slice::from_raw_parts_mut(qualifiers, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            get_interval_fraction,
+            value_refs,
+            counters,
+            resolutions,
+            qualifiers
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_get_shift_decimal(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _shifts: *mut binding::fmi3Float64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        shifts: *mut binding::fmi3Float64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let shifts = unsafe { ::std::slice::from_raw_parts_mut(shifts, n_value_references) };
+        match dispatch_by_instance_type!(instance, Self, get_shift_decimal, value_refs, shifts) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_get_shift_fraction(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _counters: *mut binding::fmi3UInt64,
-        _resolutions: *mut binding::fmi3UInt64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        counters: *mut binding::fmi3UInt64,
+        resolutions: *mut binding::fmi3UInt64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let counters = unsafe { ::std::slice::from_raw_parts_mut(counters, n_value_references) };
+        let resolutions =
+            unsafe { ::std::slice::from_raw_parts_mut(resolutions, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            get_shift_fraction,
+            value_refs,
+            counters,
+            resolutions
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_set_interval_decimal(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _intervals: *const binding::fmi3Float64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        intervals: *const binding::fmi3Float64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let intervals = unsafe { ::std::slice::from_raw_parts(intervals, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            set_interval_decimal,
+            value_refs,
+            intervals
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_set_interval_fraction(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _counters: *const binding::fmi3UInt64,
-        _resolutions: *const binding::fmi3UInt64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        counters: *const binding::fmi3UInt64,
+        resolutions: *const binding::fmi3UInt64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let counters = unsafe { ::std::slice::from_raw_parts(counters, n_value_references) };
+        let resolutions = unsafe { ::std::slice::from_raw_parts(resolutions, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            set_interval_fraction,
+            value_refs,
+            counters,
+            resolutions
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_set_shift_decimal(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _shifts: *const binding::fmi3Float64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        shifts: *const binding::fmi3Float64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let shifts = unsafe { ::std::slice::from_raw_parts(shifts, n_value_references) };
+        match dispatch_by_instance_type!(instance, Self, set_shift_decimal, value_refs, shifts) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
     unsafe fn fmi3_set_shift_fraction(
-        _instance: binding::fmi3Instance,
-        _value_references: *const binding::fmi3ValueReference,
-        _n_value_references: usize,
-        _counters: *const binding::fmi3UInt64,
-        _resolutions: *const binding::fmi3UInt64,
+        instance: binding::fmi3Instance,
+        value_references: *const binding::fmi3ValueReference,
+        n_value_references: usize,
+        counters: *const binding::fmi3UInt64,
+        resolutions: *const binding::fmi3UInt64,
     ) -> binding::fmi3Status {
-        //let _instance = checked_deref_me!(instance, Self);
-        todo!("Clock interval not yet implemented");
+        let value_refs =
+            unsafe { ::std::slice::from_raw_parts(value_references, n_value_references) };
+        let counters = unsafe { ::std::slice::from_raw_parts(counters, n_value_references) };
+        let resolutions = unsafe { ::std::slice::from_raw_parts(resolutions, n_value_references) };
+        match dispatch_by_instance_type!(
+            instance,
+            Self,
+            set_shift_fraction,
+            value_refs,
+            counters,
+            resolutions
+        ) {
+            Ok(res) => {
+                let status: Fmi3Status = res.into();
+                status.into()
+            }
+            Err(_) => binding::fmi3Status_fmi3Error,
+        }
     }
 
     #[inline(always)]
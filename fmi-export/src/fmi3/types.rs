@@ -1,6 +1,19 @@
 /// A runtime clock type for FMU variables
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub struct Clock(pub bool);
+///
+/// The first field is whether the Clock is active. The model of a `Changing` or `Countdown` Clock
+/// schedules its next tick with [`Clock::set_interval`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Clock(pub bool, pub(crate) Option<f64>);
+
+impl Clock {
+    /// Set the interval until the next tick of a `Changing` or `Countdown` Clock, in seconds.
+    ///
+    /// The importer sees the new interval with `fmi3IntervalChanged` on its next call to
+    /// `fmi3GetIntervalDecimal`, typically in Event Mode. Ignored for other Clocks.
+    pub fn set_interval(&mut self, interval: f64) {
+        self.1 = Some(interval);
+    }
+}
 
 impl std::ops::Deref for Clock {
     type Target = bool;
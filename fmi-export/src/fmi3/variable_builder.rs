@@ -80,6 +80,8 @@ where
     min: Option<f64>,
     max: Option<f64>,
     nominal: Option<f64>,
+    unit: Option<String>,
+    display_unit: Option<String>,
 
     // Integer attributes
     quantity: Option<String>,
@@ -91,6 +93,10 @@ where
     // Clock attributes
     clocks: Option<Vec<u32>>,
     interval_variability: Option<schema::IntervalVariability>,
+    interval: Option<f64>,
+    shift: Option<f64>,
+    resolution: Option<u64>,
+    supports_fraction: Option<bool>,
 
     // Dimensions for array variables
     dimensions: Vec<schema::Dimension>,
@@ -121,11 +127,17 @@ where
             min: None,
             max: None,
             nominal: None,
+            unit: None,
+            display_unit: None,
             quantity: None,
             max_size: None,
             mime_type: None,
             clocks: None,
             interval_variability: None,
+            interval: None,
+            shift: None,
+            resolution: None,
+            supports_fraction: None,
             dimensions: Vec::new(),
             _phantom: std::marker::PhantomData,
         }
@@ -219,9 +231,21 @@ where
         self
     }
 
+    /// Set the unit of the variable (float types), which must be declared in `UnitDefinitions`.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Set the default display unit of the variable (float types).
+    pub fn with_display_unit(mut self, display_unit: impl Into<String>) -> Self {
+        self.display_unit = Some(display_unit.into());
+        self
+    }
+
     // Integer-specific attribute setters
 
-    /// Set the quantity for the variable (integer types).
+    /// Set the quantity for the variable (float and integer types).
     pub fn with_quantity(mut self, quantity: impl Into<String>) -> Self {
         self.quantity = Some(quantity.into());
         self
@@ -266,6 +290,30 @@ where
         self
     }
 
+    /// Set the interval of a periodic clock in seconds.
+    pub fn with_interval(mut self, interval: f64) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Set the shift of a periodic clock in seconds.
+    pub fn with_shift(mut self, shift: f64) -> Self {
+        self.shift = Some(shift);
+        self
+    }
+
+    /// Set the resolution of the fractional interval and shift of a clock, in ticks per second.
+    pub fn with_resolution(mut self, resolution: u64) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Set whether the interval and shift of a clock can be accessed as fractions.
+    pub fn with_supports_fraction(mut self, supports_fraction: bool) -> Self {
+        self.supports_fraction = Some(supports_fraction);
+        self
+    }
+
     /// Build the final FMI variable.
     ///
     /// This delegates to the type-specific `finish` implementation which will
@@ -320,6 +368,11 @@ macro_rules! impl_fmi_variable_builder_float {
                     builder.initial,
                 );
 
+                var.declared_type = builder.declared_type;
+                var.quantity = builder.quantity;
+                var.unit = builder.unit;
+                var.display_unit = builder.display_unit;
+
                 // Set float-specific attributes if present
                 if let Some(derivative) = builder.derivative {
                     var.derivative = Some(derivative);
@@ -361,6 +414,9 @@ macro_rules! impl_fmi_variable_builder_int {
                     builder.initial,
                 );
 
+                var.declared_type = builder.declared_type;
+                var.quantity = builder.quantity;
+
                 // Apply dimensions if any
                 if !builder.dimensions.is_empty() {
                     var.dimensions = builder.dimensions;
@@ -474,11 +530,17 @@ where
             min: builder.min,
             max: builder.max,
             nominal: builder.nominal,
+            unit: builder.unit,
+            display_unit: builder.display_unit,
             quantity: builder.quantity,
             max_size: builder.max_size,
             mime_type: builder.mime_type,
             clocks: builder.clocks,
             interval_variability: builder.interval_variability,
+            interval: builder.interval,
+            shift: builder.shift,
+            resolution: builder.resolution,
+            supports_fraction: builder.supports_fraction,
             dimensions: builder.dimensions,
             _phantom: std::marker::PhantomData,
         };
@@ -520,11 +582,17 @@ where
             min: builder.min,
             max: builder.max,
             nominal: builder.nominal,
+            unit: builder.unit,
+            display_unit: builder.display_unit,
             quantity: builder.quantity,
             max_size: builder.max_size,
             mime_type: builder.mime_type,
             clocks: builder.clocks,
             interval_variability: builder.interval_variability,
+            interval: builder.interval,
+            shift: builder.shift,
+            resolution: builder.resolution,
+            supports_fraction: builder.supports_fraction,
             dimensions: builder.dimensions,
             _phantom: std::marker::PhantomData,
         };
@@ -553,6 +621,16 @@ impl FmiVariableBuilder for Clock {
                 .interval_variability
                 .unwrap_or(schema::IntervalVariability::Triggered),
         );
+        var.interval_decimal = builder.interval;
+        var.shift_decimal = builder.shift;
+        var.supports_fraction = builder.supports_fraction;
+        var.resolution = builder.resolution;
+        // The fractional representation is derived from the decimal one
+        if let Some(resolution) = builder.resolution {
+            let to_counter = |seconds: f64| (seconds * resolution as f64).round() as u64;
+            var.interval_counter = builder.interval.map(to_counter);
+            var.shift_counter = builder.shift.map(to_counter);
+        }
 
         var
     }
@@ -613,7 +691,7 @@ impl FmiVariableBuilder for Binary {
 
 #[cfg(test)]
 mod tests {
-    use fmi::schema::fmi3::{AbstractVariableTrait, ArrayableVariableTrait};
+    use fmi::schema::fmi3::{AbstractVariableTrait, ArrayableVariableTrait, TypedVariableTrait};
 
     use super::*;
 
@@ -729,4 +807,36 @@ mod tests {
         assert_eq!(var.causality(), schema::Causality::Parameter);
         assert_eq!(var.variability(), schema::Variability::Tunable);
     }
+
+    #[test]
+    fn test_unit_attributes() {
+        let var = <f64 as FmiVariableBuilder>::variable("v", 3)
+            .with_declared_type("Velocity")
+            .with_unit("m/s")
+            .with_display_unit("km/h")
+            .finish();
+
+        assert_eq!(var.declared_type(), Some("Velocity"));
+        assert_eq!(var.unit.as_deref(), Some("m/s"));
+        assert_eq!(var.display_unit.as_deref(), Some("km/h"));
+    }
+
+    #[test]
+    fn test_clock_attributes() {
+        let var = <Clock as FmiVariableBuilder>::variable("tick", 3)
+            .with_causality(schema::Causality::Input)
+            .with_interval_variability(schema::IntervalVariability::Constant)
+            .with_interval(0.01)
+            .with_shift(0.005)
+            .with_resolution(1000)
+            .with_supports_fraction(true)
+            .finish();
+
+        assert_eq!(var.interval_decimal, Some(0.01));
+        assert_eq!(var.shift_decimal, Some(0.005));
+        assert_eq!(var.resolution, Some(1000));
+        assert_eq!(var.interval_counter, Some(10));
+        assert_eq!(var.shift_counter, Some(5));
+        assert_eq!(var.supports_fraction, Some(true));
+    }
 }
@@ -19,6 +19,7 @@ pub mod rumoca;
 // Re-export the derive macro
 #[doc = include_str!("fmu_model_docs.md")]
 pub use fmi_export_derive::FmuModel;
+pub use fmi_export_derive::LoggingCategory;
 
 // Re-export paste for use in macros
 #[doc(hidden)]
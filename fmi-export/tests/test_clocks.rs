@@ -0,0 +1,343 @@
+use std::ffi::CString;
+
+use fmi::{
+    fmi3::{Fmi3Error, Fmi3Res, Fmi3Status, binding},
+    traits::FmiStatus,
+};
+use fmi_export::{
+    FmuModel,
+    fmi3::{Clock, Context, DefaultLoggingCategory, Fmi3Common, Model, UserModel},
+};
+
+/// Clocks with the different kinds of interval variability
+#[derive(FmuModel, Default, Debug, Clone)]
+#[model(
+    model_exchange = false,
+    scheduled_execution = true,
+    user_model = false,
+    fmu_state = true
+)]
+struct Timers {
+    #[variable(
+        causality = Input,
+        interval_variability = Constant,
+        interval = 0.01,
+        shift = 0.005,
+        resolution = 1000,
+        supports_fraction = true
+    )]
+    periodic: Clock,
+
+    #[variable(causality = Input, interval_variability = Tunable, interval = 0.1)]
+    tunable: Clock,
+
+    #[variable(causality = Input, interval_variability = Fixed)]
+    fixed: Clock,
+
+    #[variable(causality = Input, interval_variability = Triggered)]
+    triggered: Clock,
+
+    #[variable(causality = Output, interval_variability = Countdown)]
+    countdown: Clock,
+}
+
+impl UserModel for Timers {
+    type LoggingCategory = DefaultLoggingCategory;
+
+    fn configurate(&mut self, _context: &dyn Context<Self>) -> Result<(), Fmi3Error> {
+        self.countdown.set_interval(0.3);
+        Ok(())
+    }
+}
+
+fn get_interval(
+    inst: binding::fmi3Instance,
+    vr: u32,
+) -> Result<(f64, binding::fmi3IntervalQualifier), Fmi3Error> {
+    let mut interval = [0.0];
+    let mut qualifier = [0];
+    Fmi3Status::from(unsafe {
+        <Timers as Fmi3Common>::fmi3_get_interval_decimal(
+            inst,
+            [vr].as_ptr(),
+            1,
+            interval.as_mut_ptr(),
+            qualifier.as_mut_ptr(),
+        )
+    })
+    .ok()
+    .map(|_| (interval[0], qualifier[0]))
+}
+
+fn set_interval(inst: binding::fmi3Instance, vr: u32, interval: f64) -> Result<Fmi3Res, Fmi3Error> {
+    Fmi3Status::from(unsafe {
+        <Timers as Fmi3Common>::fmi3_set_interval_decimal(
+            inst,
+            [vr].as_ptr(),
+            1,
+            [interval].as_ptr(),
+        )
+    })
+    .ok()
+}
+
+#[test]
+fn test_clock_metadata() {
+    let vars = Timers::build_toplevel_metadata().model_variables;
+    let periodic = vars
+        .clock()
+        .into_iter()
+        .find(|clock| clock.name == "periodic")
+        .unwrap();
+    assert_eq!(periodic.interval_decimal, Some(0.01));
+    assert_eq!(periodic.shift_decimal, Some(0.005));
+    assert_eq!(periodic.resolution, Some(1000));
+    assert_eq!(periodic.interval_counter, Some(10));
+    assert_eq!(periodic.shift_counter, Some(5));
+    assert_eq!(periodic.supports_fraction, Some(true));
+}
+
+#[test]
+fn test_clock_intervals() {
+    let vars = Timers::build_toplevel_metadata().model_variables;
+    let vr = |name: &str| vars.find_by_name(name).unwrap().value_reference();
+
+    let inst = unsafe {
+        <Timers as Fmi3Common>::fmi3_instantiate_scheduled_execution(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(Timers::INSTANTIATION_TOKEN).unwrap().as_ptr(),
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            std::ptr::null_mut(),
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+    assert!(!inst.is_null());
+
+    // The interval is reported as changed only on the first read
+    assert_eq!(
+        get_interval(inst, vr("periodic")),
+        Ok((0.01, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+    assert_eq!(
+        get_interval(inst, vr("periodic")),
+        Ok((0.01, binding::fmi3IntervalQualifier_fmi3IntervalUnchanged))
+    );
+    assert_eq!(
+        get_interval(inst, vr("fixed")),
+        Ok((0.0, binding::fmi3IntervalQualifier_fmi3IntervalNotYetKnown))
+    );
+    assert_eq!(get_interval(inst, vr("triggered")), Err(Fmi3Error::Error));
+    assert_eq!(get_interval(inst, 0), Err(Fmi3Error::Error));
+    assert_eq!(
+        get_interval(inst, vr("countdown")),
+        Ok((0.0, binding::fmi3IntervalQualifier_fmi3IntervalNotYetKnown))
+    );
+
+    let mut counters = [0; 2];
+    let mut resolutions = [0; 2];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_get_shift_fraction(
+                inst,
+                [vr("periodic")].as_ptr(),
+                1,
+                counters.as_mut_ptr(),
+                resolutions.as_mut_ptr(),
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!((counters[0], resolutions[0]), (5, 1000));
+
+    // Clocks without a resolution have no fractional representation
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_get_shift_fraction(
+                inst,
+                [vr("tunable")].as_ptr(),
+                1,
+                counters.as_mut_ptr(),
+                resolutions.as_mut_ptr(),
+            )
+        })
+        .ok(),
+        Err(Fmi3Error::Error)
+    );
+
+    // Constant Clocks cannot be set, Fixed Clocks only before initialization is done
+    assert_eq!(
+        set_interval(inst, vr("periodic"), 0.02),
+        Err(Fmi3Error::Error)
+    );
+    assert_eq!(set_interval(inst, vr("fixed"), 0.5), Ok(Fmi3Res::OK));
+    assert_eq!(
+        get_interval(inst, vr("fixed")),
+        Ok((0.5, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_enter_initialization_mode(
+                inst, false, 0.0, 0.0, false, 0.0,
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Timers as Fmi3Common>::fmi3_exit_initialization_mode(inst) })
+            .ok(),
+        Ok(Fmi3Res::OK),
+    );
+
+    assert_eq!(set_interval(inst, vr("fixed"), 0.25), Err(Fmi3Error::Error));
+
+    // The interval set by the model is seen once as changed, and cannot be set by the importer
+    assert_eq!(
+        get_interval(inst, vr("countdown")),
+        Ok((0.3, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+    assert_eq!(
+        get_interval(inst, vr("countdown")),
+        Ok((0.3, binding::fmi3IntervalQualifier_fmi3IntervalUnchanged))
+    );
+    assert_eq!(
+        set_interval(inst, vr("countdown"), 0.1),
+        Err(Fmi3Error::Error)
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_set_interval_fraction(
+                inst,
+                [vr("tunable")].as_ptr(),
+                1,
+                [1].as_ptr(),
+                [20].as_ptr(),
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(
+        get_interval(inst, vr("tunable")),
+        Ok((0.05, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+
+    // Resetting restores the intervals from the model description
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Timers as Fmi3Common>::fmi3_reset(inst) }).ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(
+        get_interval(inst, vr("tunable")),
+        Ok((0.1, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+
+    unsafe { <Timers as Fmi3Common>::fmi3_free_instance(inst) };
+}
+
+#[test]
+fn test_clock_fmu_state() {
+    let vars = Timers::build_toplevel_metadata().model_variables;
+    let tunable = vars.find_by_name("tunable").unwrap().value_reference();
+
+    let inst = unsafe {
+        <Timers as Fmi3Common>::fmi3_instantiate_scheduled_execution(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(Timers::INSTANTIATION_TOKEN).unwrap().as_ptr(),
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            std::ptr::null_mut(),
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+    assert!(!inst.is_null());
+
+    assert_eq!(set_interval(inst, tunable, 0.2), Ok(Fmi3Res::OK));
+    let mut fmu_state: binding::fmi3FMUState = std::ptr::null_mut();
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_get_fmu_state(inst, &mut fmu_state)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+
+    // Rolling back restores the interval set before the state was taken
+    assert_eq!(set_interval(inst, tunable, 0.4), Ok(Fmi3Res::OK));
+    assert_eq!(
+        get_interval(inst, tunable),
+        Ok((0.4, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Timers as Fmi3Common>::fmi3_set_fmu_state(inst, fmu_state) })
+            .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(
+        get_interval(inst, tunable),
+        Ok((0.2, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+
+    // The Clock state also survives serialization
+    let mut size = 0;
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_serialized_fmu_state_size(inst, fmu_state, &mut size)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    let mut bytes = vec![0; size];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_serialize_fmu_state(
+                inst,
+                fmu_state,
+                bytes.as_mut_ptr(),
+                size,
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    let mut restored: binding::fmi3FMUState = std::ptr::null_mut();
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Timers as Fmi3Common>::fmi3_deserialize_fmu_state(
+                inst,
+                bytes.as_ptr(),
+                size,
+                &mut restored,
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(set_interval(inst, tunable, 0.4), Ok(Fmi3Res::OK));
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Timers as Fmi3Common>::fmi3_set_fmu_state(inst, restored) })
+            .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(
+        get_interval(inst, tunable),
+        Ok((0.2, binding::fmi3IntervalQualifier_fmi3IntervalChanged))
+    );
+
+    unsafe {
+        <Timers as Fmi3Common>::fmi3_free_fmu_state(inst, &mut fmu_state);
+        <Timers as Fmi3Common>::fmi3_free_fmu_state(inst, &mut restored);
+        <Timers as Fmi3Common>::fmi3_free_instance(inst);
+    }
+}
@@ -173,3 +173,99 @@ fn test_model_cs_wrapper() {
         Ok(Fmi3Res::OK),
     );
 }
+
+#[test]
+fn test_model_cs_event_mode() {
+    let instantiate = |event_mode_used: bool| unsafe {
+        <Dahlquist as Fmi3Common>::fmi3_instantiate_co_simulation(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(Dahlquist::INSTANTIATION_TOKEN)
+                .unwrap()
+                .as_ptr() as *mut i8,
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            event_mode_used as _,
+            false as _,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            None,
+            None,
+        )
+    };
+    let initialize = |inst| unsafe {
+        <Dahlquist as Fmi3Common>::fmi3_enter_initialization_mode(
+            inst, false, 0.0, 0.0, false, 0.0,
+        );
+        Fmi3Status::from(<Dahlquist as Fmi3Common>::fmi3_exit_initialization_mode(
+            inst,
+        ))
+        .ok()
+    };
+    let do_step = |inst| unsafe {
+        Fmi3Status::from(<Dahlquist as Fmi3CoSimulation>::fmi3_do_step(
+            inst,
+            0.0,
+            0.1,
+            false as _,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        ))
+        .ok()
+    };
+    let update_discrete_states = |inst| {
+        let mut flags = [false; 5];
+        let mut next_event_time = 0.0;
+        let [
+            need_update,
+            terminate,
+            nominals_changed,
+            values_changed,
+            next_time_defined,
+        ] = &mut flags;
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_update_discrete_states(
+                inst,
+                need_update,
+                terminate,
+                nominals_changed,
+                values_changed,
+                next_time_defined,
+                &mut next_event_time,
+            )
+        })
+        .ok()
+    };
+
+    // With Event Mode, the instance starts in Event Mode after initialization
+    let inst = instantiate(true);
+    assert_eq!(initialize(inst), Ok(Fmi3Res::OK));
+    assert_eq!(do_step(inst), Err(Fmi3Error::Error));
+    assert_eq!(update_discrete_states(inst), Ok(Fmi3Res::OK));
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Dahlquist as Fmi3CoSimulation>::fmi3_enter_step_mode(inst) })
+            .ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(do_step(inst), Ok(Fmi3Res::OK));
+    assert_eq!(update_discrete_states(inst), Err(Fmi3Error::Error));
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Dahlquist as Fmi3Common>::fmi3_enter_event_mode(inst) }).ok(),
+        Ok(Fmi3Res::OK)
+    );
+    assert_eq!(update_discrete_states(inst), Ok(Fmi3Res::OK));
+    unsafe { <Dahlquist as Fmi3Common>::fmi3_free_instance(inst) };
+
+    // Without Event Mode, the instance goes straight to Step Mode and can't enter Event Mode
+    let inst = instantiate(false);
+    assert_eq!(initialize(inst), Ok(Fmi3Res::OK));
+    assert_eq!(do_step(inst), Ok(Fmi3Res::OK));
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Dahlquist as Fmi3Common>::fmi3_enter_event_mode(inst) }).ok(),
+        Err(Fmi3Error::Error)
+    );
+    unsafe { <Dahlquist as Fmi3Common>::fmi3_free_instance(inst) };
+}
@@ -0,0 +1,231 @@
+use std::{
+    ffi::CString,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use fmi::{
+    fmi3::{Fmi3Error, Fmi3Res, Fmi3Status, binding},
+    traits::FmiStatus,
+};
+use fmi_export::{
+    FmuModel,
+    fmi3::{Context, DefaultLoggingCategory, Fmi3Common, Model, UserModel},
+};
+
+/// Van der Pol oscillator without an analytic Jacobian, exercising the finite-difference fallback
+#[derive(FmuModel, Default, Debug)]
+#[model(user_model = false)]
+struct VanDerPol {
+    #[variable(causality = Output, variability = Continuous, start = [2.0, 0.0], initial = Exact)]
+    x: [f64; 2],
+
+    #[variable(causality = Local, variability = Continuous, derivative = x, initial = Calculated)]
+    der_x: [f64; 2],
+
+    #[variable(causality = Parameter, variability = Fixed, start = 1.0, initial = Exact)]
+    mu: f64,
+}
+
+impl UserModel for VanDerPol {
+    type LoggingCategory = DefaultLoggingCategory;
+
+    fn calculate_values(&mut self, _context: &dyn Context<Self>) -> Result<Fmi3Res, Fmi3Error> {
+        self.der_x[0] = self.x[1];
+        self.der_x[1] = self.mu * ((1.0 - self.x[0] * self.x[0]) * self.x[1]) - self.x[0];
+        Ok(Fmi3Res::OK)
+    }
+}
+
+static ANALYTIC_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Dahlquist test equation with an analytic Jacobian
+#[derive(FmuModel, Default, Debug)]
+#[model(user_model = false)]
+struct Dahlquist {
+    #[variable(causality = Output, variability = Continuous, start = 1.0, initial = Exact)]
+    x: f64,
+
+    #[variable(causality = Local, variability = Continuous, derivative = x, initial = Calculated)]
+    der_x: f64,
+
+    #[variable(causality = Parameter, variability = Fixed, start = 2.0, initial = Exact)]
+    k: f64,
+}
+
+impl UserModel for Dahlquist {
+    type LoggingCategory = DefaultLoggingCategory;
+
+    fn calculate_values(&mut self, _context: &dyn Context<Self>) -> Result<Fmi3Res, Fmi3Error> {
+        self.der_x = -self.k * self.x;
+        Ok(Fmi3Res::OK)
+    }
+
+    fn get_directional_derivative(
+        &mut self,
+        _context: &dyn Context<Self>,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<bool, Fmi3Error> {
+        // Only d(der_x)/dx is provided analytically
+        if unknowns != [2] || knowns != [1] {
+            return Ok(false);
+        }
+        ANALYTIC_CALLS.fetch_add(1, Ordering::SeqCst);
+        sensitivity[0] = -self.k * seed[0];
+        Ok(true)
+    }
+}
+
+fn instantiate_me<M: Model + Fmi3Common>() -> binding::fmi3Instance {
+    let inst = unsafe {
+        <M as Fmi3Common>::fmi3_instantiate_model_exchange(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(M::INSTANTIATION_TOKEN).unwrap().as_ptr(),
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            std::ptr::null_mut(),
+            None,
+        )
+    };
+    assert!(!inst.is_null());
+
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <M as Fmi3Common>::fmi3_enter_initialization_mode(inst, false, 0.0, 0.0, false, 0.0)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <M as Fmi3Common>::fmi3_exit_initialization_mode(inst) }).ok(),
+        Ok(Fmi3Res::OK),
+    );
+    inst
+}
+
+fn directional_derivative<M: Fmi3Common>(
+    inst: binding::fmi3Instance,
+    unknowns: &[binding::fmi3ValueReference],
+    knowns: &[binding::fmi3ValueReference],
+    seed: &[f64],
+    sensitivity: &mut [f64],
+) -> Result<Fmi3Res, Fmi3Error> {
+    Fmi3Status::from(unsafe {
+        <M as Fmi3Common>::fmi3_get_directional_derivative(
+            inst,
+            unknowns.as_ptr(),
+            unknowns.len(),
+            knowns.as_ptr(),
+            knowns.len(),
+            seed.as_ptr(),
+            seed.len(),
+            sensitivity.as_mut_ptr(),
+            sensitivity.len(),
+        )
+    })
+    .ok()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() < 1e-6,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+#[test]
+fn test_directional_derivative_finite_difference() {
+    let inst = instantiate_me::<VanDerPol>();
+
+    // Jacobian of der_x w.r.t. x at x = [2, 0], mu = 1 is [[0, 1], [-1, -3]]
+    let mut sensitivity = [0.0; 2];
+    assert_eq!(
+        directional_derivative::<VanDerPol>(inst, &[2], &[1], &[1.0, 0.0], &mut sensitivity),
+        Ok(Fmi3Res::OK),
+    );
+    assert_close(&sensitivity, &[0.0, -1.0]);
+
+    assert_eq!(
+        directional_derivative::<VanDerPol>(inst, &[2], &[1], &[0.0, 1.0], &mut sensitivity),
+        Ok(Fmi3Res::OK),
+    );
+    assert_close(&sensitivity, &[1.0, -3.0]);
+
+    // The perturbed knowns are restored afterwards
+    let mut x = [0.0; 2];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <VanDerPol as Fmi3Common>::fmi3_get_float64(inst, [1].as_ptr(), 1, x.as_mut_ptr(), 2)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(x, [2.0, 0.0]);
+
+    // 'time' cannot be used as a known
+    assert_eq!(
+        directional_derivative::<VanDerPol>(inst, &[2], &[0], &[1.0], &mut sensitivity),
+        Err(Fmi3Error::Error),
+    );
+
+    unsafe { <VanDerPol as Fmi3Common>::fmi3_free_instance(inst) };
+}
+
+#[test]
+fn test_adjoint_derivative_finite_difference() {
+    let inst = instantiate_me::<VanDerPol>();
+
+    let unknowns = [2];
+    let knowns = [1];
+    let seed = [1.0, 1.0];
+    let mut sensitivity = [0.0; 2];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <VanDerPol as Fmi3Common>::fmi3_get_adjoint_derivative(
+                inst,
+                unknowns.as_ptr(),
+                unknowns.len(),
+                knowns.as_ptr(),
+                knowns.len(),
+                seed.as_ptr(),
+                seed.len(),
+                sensitivity.as_mut_ptr(),
+                sensitivity.len(),
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_close(&sensitivity, &[-1.0, -2.0]);
+
+    unsafe { <VanDerPol as Fmi3Common>::fmi3_free_instance(inst) };
+}
+
+#[test]
+fn test_directional_derivative_analytic() {
+    let inst = instantiate_me::<Dahlquist>();
+
+    let mut sensitivity = [0.0];
+    let calls = ANALYTIC_CALLS.load(Ordering::SeqCst);
+    assert_eq!(
+        directional_derivative::<Dahlquist>(inst, &[2], &[1], &[0.5], &mut sensitivity),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(sensitivity, [-1.0]);
+    assert_eq!(ANALYTIC_CALLS.load(Ordering::SeqCst), calls + 1);
+
+    // Not covered analytically: d(der_x)/dk = -x, via finite differences
+    assert_eq!(
+        directional_derivative::<Dahlquist>(inst, &[2], &[3], &[1.0], &mut sensitivity),
+        Ok(Fmi3Res::OK),
+    );
+    assert_close(&sensitivity, &[-1.0]);
+    assert_eq!(ANALYTIC_CALLS.load(Ordering::SeqCst), calls + 1);
+
+    unsafe { <Dahlquist as Fmi3Common>::fmi3_free_instance(inst) };
+}
@@ -0,0 +1,258 @@
+use std::ffi::CString;
+
+use fmi::{
+    fmi3::{Fmi3Error, Fmi3Res, Fmi3Status, binding},
+    traits::FmiStatus,
+};
+use fmi_export::{
+    FmuModel,
+    fmi3::{
+        CSDoStepResult, Context, DefaultLoggingCategory, Fmi3CoSimulation, Fmi3Common, Model,
+        UserModel,
+    },
+};
+
+#[derive(FmuModel, Default, Debug, Clone)]
+#[model(co_simulation = true, user_model = false, fmu_state = true)]
+struct Dahlquist {
+    #[variable(causality = Output, variability = Continuous, start = 1.0, initial = Exact)]
+    x: f64,
+
+    #[variable(causality = Local, variability = Continuous, derivative = x, initial = Calculated)]
+    der_x: f64,
+
+    #[variable(causality = Parameter, variability = Fixed, start = 1.0, initial = Exact)]
+    k: f64,
+
+    /// Internal bookkeeping that is not exposed as a variable but must still be captured
+    steps: u32,
+}
+
+impl UserModel for Dahlquist {
+    type LoggingCategory = DefaultLoggingCategory;
+
+    fn calculate_values(&mut self, _context: &dyn Context<Self>) -> Result<Fmi3Res, Fmi3Error> {
+        self.der_x = -self.k * self.x;
+        Ok(Fmi3Res::OK)
+    }
+
+    fn do_step(
+        &mut self,
+        context: &mut dyn Context<Self>,
+        current_communication_point: f64,
+        communication_step_size: f64,
+        _no_set_fmu_state_prior_to_current_point: bool,
+    ) -> Result<CSDoStepResult, Fmi3Error> {
+        self.calculate_values(context)?;
+        self.x += self.der_x * communication_step_size;
+        self.steps += 1;
+        let last_time = current_communication_point + communication_step_size;
+        context.set_time(last_time);
+        Ok(CSDoStepResult::completed(last_time))
+    }
+}
+
+#[derive(FmuModel, Default, Debug)]
+#[model(co_simulation = true)]
+struct Stateless {
+    #[variable(causality = Output, start = 1.0)]
+    y: f64,
+}
+
+fn instantiate_cs<M: Model + Fmi3Common>() -> binding::fmi3Instance {
+    let inst = unsafe {
+        <M as Fmi3Common>::fmi3_instantiate_co_simulation(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(M::INSTANTIATION_TOKEN).unwrap().as_ptr(),
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            false as _,
+            false as _,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            None,
+            None,
+        )
+    };
+    assert!(!inst.is_null());
+
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <M as Fmi3Common>::fmi3_enter_initialization_mode(inst, false, 0.0, 0.0, false, 0.0)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <M as Fmi3Common>::fmi3_exit_initialization_mode(inst) }).ok(),
+        Ok(Fmi3Res::OK),
+    );
+    inst
+}
+
+fn do_step(inst: binding::fmi3Instance, current_communication_point: f64) {
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3CoSimulation>::fmi3_do_step(
+                inst,
+                current_communication_point,
+                0.1,
+                false as _,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+}
+
+fn get_x(inst: binding::fmi3Instance) -> f64 {
+    let mut x = [0.0];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_get_float64(inst, [1].as_ptr(), 1, x.as_mut_ptr(), 1)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    x[0]
+}
+
+#[test]
+fn test_fmu_state_roundtrip() {
+    let inst = instantiate_cs::<Dahlquist>();
+    do_step(inst, 0.0);
+    let x0 = get_x(inst);
+
+    let mut state: binding::fmi3FMUState = std::ptr::null_mut();
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_get_fmu_state(inst, &mut state)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert!(!state.is_null());
+
+    do_step(inst, 0.1);
+    assert_ne!(get_x(inst), x0);
+
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Dahlquist as Fmi3Common>::fmi3_set_fmu_state(inst, state) })
+            .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(get_x(inst), x0);
+
+    // Serialize, advance, then restore from the deserialized copy
+    let mut size = 0;
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_serialized_fmu_state_size(inst, state, &mut size)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    let mut bytes = vec![0u8; size];
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_serialize_fmu_state(
+                inst,
+                state,
+                bytes.as_mut_ptr(),
+                bytes.len(),
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+
+    do_step(inst, 0.1);
+    do_step(inst, 0.2);
+
+    let mut restored: binding::fmi3FMUState = std::ptr::null_mut();
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_deserialize_fmu_state(
+                inst,
+                bytes.as_ptr(),
+                bytes.len(),
+                &mut restored,
+            )
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Dahlquist as Fmi3Common>::fmi3_set_fmu_state(inst, restored) })
+            .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(get_x(inst), x0);
+
+    // A truncated buffer must be rejected
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Dahlquist as Fmi3Common>::fmi3_deserialize_fmu_state(
+                inst,
+                bytes.as_ptr(),
+                bytes.len() - 1,
+                &mut restored,
+            )
+        })
+        .ok(),
+        Err(Fmi3Error::Error),
+    );
+
+    for mut fmu_state in [state, restored] {
+        assert_eq!(
+            Fmi3Status::from(unsafe {
+                <Dahlquist as Fmi3Common>::fmi3_free_fmu_state(inst, &mut fmu_state)
+            })
+            .ok(),
+            Ok(Fmi3Res::OK),
+        );
+        assert!(fmu_state.is_null());
+    }
+
+    unsafe { <Dahlquist as Fmi3Common>::fmi3_free_instance(inst) };
+}
+
+#[test]
+fn test_fmu_state_serialization_captures_internal_fields() {
+    let model = Dahlquist {
+        x: 0.5,
+        der_x: -0.5,
+        k: 1.0,
+        steps: 7,
+    };
+
+    let mut buffer = Vec::new();
+    model.serialize_fmu_state(&mut buffer).unwrap();
+    let restored = Dahlquist::deserialize_fmu_state(&mut buffer.as_slice()).unwrap();
+
+    assert_eq!(restored.x, 0.5);
+    assert_eq!(restored.der_x, -0.5);
+    assert_eq!(restored.steps, 7);
+}
+
+#[test]
+fn test_fmu_state_unsupported() {
+    let inst = instantiate_cs::<Stateless>();
+    let mut state: binding::fmi3FMUState = std::ptr::null_mut();
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Stateless as Fmi3Common>::fmi3_get_fmu_state(inst, &mut state)
+        })
+        .ok(),
+        Err(Fmi3Error::Error),
+    );
+    assert!(state.is_null());
+
+    unsafe { <Stateless as Fmi3Common>::fmi3_free_instance(inst) };
+}
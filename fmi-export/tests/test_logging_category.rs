@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use fmi_export::{
+    FmuModel, LoggingCategory,
+    fmi3::{ModelLoggingCategory, UserModel},
+};
+
+/// Logging categories of the model
+#[derive(LoggingCategory, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+enum Category {
+    /// Log all events
+    #[default]
+    LogAll,
+    /// Trace FMI API calls
+    #[category(trace)]
+    Trace,
+    /// Log the steps of the solver
+    #[category(name = "logSolver")]
+    Solver,
+    #[category(error)]
+    LogError,
+}
+
+#[derive(FmuModel, Default, Debug)]
+#[model(model_exchange = true, user_model = false)]
+struct Logged {
+    #[variable(causality = Output, start = 0.0)]
+    y: f64,
+}
+
+impl UserModel for Logged {
+    type LoggingCategory = Category;
+}
+
+fmi_export::export_fmu!(Logged);
+
+#[test]
+fn test_derived_categories() {
+    assert_eq!(Category::LogAll.to_string(), "logAll");
+    assert_eq!(Category::Solver.to_string(), "logSolver");
+    assert_eq!(Category::from_str("logError"), Ok(Category::LogError));
+    assert!(Category::from_str("logSolverSteps").is_err());
+
+    assert_eq!(Category::trace_category(), Category::Trace);
+    assert_eq!(Category::error_category(), Category::LogError);
+    assert_eq!(Category::Trace.description(), Some("Trace FMI API calls"));
+    assert_eq!(Category::LogError.description(), None);
+}
+
+#[test]
+fn test_exported_log_categories() {
+    let log_categories = model_metadata().log_categories.unwrap();
+    let categories: Vec<_> = log_categories
+        .categories
+        .iter()
+        .map(|category| (category.name.as_str(), category.description.as_deref()))
+        .collect();
+    assert_eq!(
+        categories,
+        vec![
+            ("logAll", Some("Log all events")),
+            ("trace", Some("Trace FMI API calls")),
+            ("logSolver", Some("Log the steps of the solver")),
+            ("logError", None),
+        ]
+    );
+}
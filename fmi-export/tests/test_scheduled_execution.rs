@@ -0,0 +1,158 @@
+use std::{
+    ffi::CString,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use fmi::{
+    fmi3::{Fmi3Error, Fmi3Res, Fmi3Status, binding},
+    traits::FmiStatus,
+};
+use fmi_export::{
+    FmuModel,
+    fmi3::{
+        Clock, Context, DefaultLoggingCategory, Fmi3Common, Fmi3ScheduledExecution, Model,
+        UserModel,
+    },
+};
+
+/// Controller software with a fast and a slow task
+#[derive(FmuModel, Default, Debug)]
+#[model(model_exchange = false, scheduled_execution = true, user_model = false)]
+struct Ecu {
+    #[variable(causality = Input, interval_variability = Triggered, partition = fast_task)]
+    fast: Clock,
+
+    #[variable(causality = Input, interval_variability = Triggered, partition = slow_task)]
+    slow: Clock,
+
+    #[variable(causality = Output, variability = Discrete, clocks = [fast], start = 0)]
+    counter: i32,
+
+    #[variable(causality = Output, variability = Discrete, clocks = [slow], start = 0.0)]
+    last_slow_time: f64,
+}
+
+impl Ecu {
+    fn fast_task(
+        &mut self,
+        context: &dyn Context<Self>,
+        _activation_time: f64,
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        context.lock_preemption();
+        self.counter += 1;
+        context.unlock_preemption();
+        context.clock_update();
+        Ok(Fmi3Res::OK)
+    }
+
+    fn slow_task(
+        &mut self,
+        context: &dyn Context<Self>,
+        activation_time: f64,
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        assert_eq!(context.time(), activation_time);
+        self.last_slow_time = activation_time;
+        Ok(Fmi3Res::OK)
+    }
+}
+
+impl UserModel for Ecu {
+    type LoggingCategory = DefaultLoggingCategory;
+}
+
+static CLOCK_UPDATES: AtomicUsize = AtomicUsize::new(0);
+static LOCKS: AtomicUsize = AtomicUsize::new(0);
+static UNLOCKS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn clock_update(instance_environment: binding::fmi3InstanceEnvironment) {
+    assert_eq!(instance_environment as usize, 0x1234);
+    CLOCK_UPDATES.fetch_add(1, Ordering::SeqCst);
+}
+
+unsafe extern "C" fn lock_preemption() {
+    LOCKS.fetch_add(1, Ordering::SeqCst);
+}
+
+unsafe extern "C" fn unlock_preemption() {
+    UNLOCKS.fetch_add(1, Ordering::SeqCst);
+}
+
+fn activate(inst: binding::fmi3Instance, vr: u32, time: f64) -> Result<Fmi3Res, Fmi3Error> {
+    Fmi3Status::from(unsafe {
+        <Ecu as Fmi3ScheduledExecution>::fmi3_activate_model_partition(inst, vr, time)
+    })
+    .ok()
+}
+
+#[test]
+fn test_activate_model_partitions() {
+    let vars = Ecu::build_toplevel_metadata().model_variables;
+    let vr = |name: &str| vars.find_by_name(name).unwrap().value_reference();
+
+    let inst = unsafe {
+        <Ecu as Fmi3Common>::fmi3_instantiate_scheduled_execution(
+            CString::new("test").unwrap().as_ptr(),
+            CString::new(Ecu::INSTANTIATION_TOKEN).unwrap().as_ptr(),
+            CString::new("path/to/fmu").unwrap().as_ptr(),
+            false as _,
+            false as _,
+            0x1234 as binding::fmi3InstanceEnvironment,
+            None,
+            Some(clock_update),
+            Some(lock_preemption),
+            Some(unlock_preemption),
+        )
+    };
+    assert!(!inst.is_null());
+
+    // Partitions can only be activated in Clock Activation Mode
+    assert_eq!(activate(inst, vr("fast"), 0.0), Err(Fmi3Error::Error));
+
+    assert_eq!(
+        Fmi3Status::from(unsafe {
+            <Ecu as Fmi3Common>::fmi3_enter_initialization_mode(inst, false, 0.0, 0.0, false, 0.0)
+        })
+        .ok(),
+        Ok(Fmi3Res::OK),
+    );
+    assert_eq!(
+        Fmi3Status::from(unsafe { <Ecu as Fmi3Common>::fmi3_exit_initialization_mode(inst) }).ok(),
+        Ok(Fmi3Res::OK),
+    );
+
+    for i in 0..3 {
+        assert_eq!(activate(inst, vr("fast"), i as f64 * 0.01), Ok(Fmi3Res::OK));
+    }
+    assert_eq!(activate(inst, vr("slow"), 0.1), Ok(Fmi3Res::OK));
+
+    // Only input Clocks with a partition can be activated
+    assert_eq!(activate(inst, vr("counter"), 0.1), Err(Fmi3Error::Error));
+    assert_eq!(activate(inst, 0, 0.1), Err(Fmi3Error::Error));
+
+    let mut counter = [0];
+    let mut last_slow_time = [0.0];
+    unsafe {
+        <Ecu as Fmi3Common>::fmi3_get_int32(
+            inst,
+            [vr("counter")].as_ptr(),
+            1,
+            counter.as_mut_ptr(),
+            1,
+        );
+        <Ecu as Fmi3Common>::fmi3_get_float64(
+            inst,
+            [vr("last_slow_time")].as_ptr(),
+            1,
+            last_slow_time.as_mut_ptr(),
+            1,
+        );
+    }
+    assert_eq!(counter, [3]);
+    assert_eq!(last_slow_time, [0.1]);
+
+    assert_eq!(CLOCK_UPDATES.load(Ordering::SeqCst), 3);
+    assert_eq!(LOCKS.load(Ordering::SeqCst), 3);
+    assert_eq!(UNLOCKS.load(Ordering::SeqCst), 3);
+
+    unsafe { <Ecu as Fmi3Common>::fmi3_free_instance(inst) };
+}
@@ -0,0 +1,85 @@
+use fmi::schema::fmi3::{AbstractVariableTrait, TypeDefinition, TypedVariableTrait};
+use fmi_export::{FmuModel, fmi3::Model};
+
+/// Motor declaring its own units and types
+#[derive(FmuModel, Default, Debug)]
+#[unit(name = "rad/s", rad = 1, s = -1)]
+#[display_unit(unit = "rad/s", name = "rpm", factor = 9.549296585513721)]
+#[unit(name = "N.m", kg = 1, m = 2, s = -2)]
+#[type_definition(
+    name = "AngularVelocity",
+    quantity = "AngularVelocity",
+    unit = "rad/s",
+    display_unit = "rpm"
+)]
+struct Motor {
+    #[variable(causality = Output, start = 0.0, declared_type = "AngularVelocity")]
+    w: f64,
+
+    #[variable(causality = Input, start = 0.0, unit = "N.m")]
+    tau: f64,
+}
+
+#[derive(FmuModel, Default, Debug)]
+#[model(model_exchange = true)]
+#[unit(name = "m/s", m = 1, s = -1)]
+#[display_unit(unit = "m/s", name = "km/h", factor = 3.6)]
+#[type_definition(name = "Speed", base_type = f32, unit = "m/s", min = 0.0)]
+struct Vehicle {
+    #[variable(causality = Output, start = 0.0, declared_type = "Speed", display_unit = "km/h")]
+    speed: f32,
+
+    #[child]
+    front: Motor,
+
+    #[child]
+    rear: Motor,
+}
+
+#[test]
+fn test_unit_definitions() {
+    let metadata = Vehicle::build_toplevel_metadata();
+
+    let units = metadata.unit_definitions.unwrap().units;
+    let names: Vec<_> = units.iter().map(|unit| unit.name.as_str()).collect();
+    assert_eq!(names, vec!["m/s", "rad/s", "N.m"]);
+
+    let base_unit = units[2].base_unit.as_ref().unwrap();
+    assert_eq!(
+        (base_unit.kg, base_unit.m, base_unit.s),
+        (Some(1), Some(2), Some(-2))
+    );
+    assert_eq!(units[1].display_unit[0].name, "rpm");
+    assert_eq!(units[0].display_unit[0].factor, Some(3.6));
+}
+
+#[test]
+fn test_type_definitions() {
+    let metadata = Vehicle::build_toplevel_metadata();
+
+    let types = metadata.type_definitions.unwrap().type_definitions;
+    let names: Vec<_> = types.iter().map(TypeDefinition::name).collect();
+    assert_eq!(names, vec!["Speed", "AngularVelocity"]);
+
+    let TypeDefinition::Float32(speed) = &types[0] else {
+        panic!("Speed should be a Float32Type");
+    };
+    assert_eq!(speed.unit.as_deref(), Some("m/s"));
+    assert_eq!(speed.min, Some(0.0));
+}
+
+#[test]
+fn test_variable_units() {
+    let vars = Vehicle::build_toplevel_metadata().model_variables;
+
+    let speed = &vars.float32()[0];
+    assert_eq!(speed.declared_type(), Some("Speed"));
+    assert_eq!(speed.display_unit.as_deref(), Some("km/h"));
+
+    let tau = vars
+        .float64()
+        .into_iter()
+        .find(|var| var.name() == "rear.tau")
+        .unwrap();
+    assert_eq!(tau.unit.as_deref(), Some("N.m"));
+}
@@ -3,6 +3,7 @@ use fmi::fmi3::binding;
 use fmi_sys::ls_bus;
 use std::borrow::Cow;
 
+pub mod simulation;
 #[cfg(test)]
 mod tests;
 
@@ -205,6 +206,41 @@ fn check_buffer_capacity(buffer: &[u8], needed_size: usize) -> Result<(), FmiLsB
     }
 }
 
+/// Offset of the parameter type in a configuration operation, right after the operation header
+const CONFIGURATION_PARAMETER_OFFSET: usize =
+    std::mem::size_of::<ls_bus::fmi3LsBusOperationHeader>();
+
+/// Write a configuration operation for a single parameter.
+///
+/// Like the `FMI3_LS_BUS_CAN_CREATE_OP_CONFIGURATION_*` macros, the operation length only covers
+/// the header, the parameter type and the configured parameter, not the whole union.
+fn transmit_configuration(
+    buffer: &mut [u8],
+    parameter_type: ls_bus::fmi3LsBusCanConfigParameterType,
+    parameter: &[u8],
+) -> Result<usize, FmiLsBusError> {
+    let op_size = CONFIGURATION_PARAMETER_OFFSET + 1 + parameter.len();
+
+    check_buffer_capacity(buffer, op_size)?;
+
+    let header = ls_bus::fmi3LsBusOperationHeader {
+        opCode: ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION,
+        length: op_size as binding::fmi3UInt32,
+    };
+    let header_bytes = unsafe {
+        std::slice::from_raw_parts(
+            &header as *const _ as *const u8,
+            CONFIGURATION_PARAMETER_OFFSET,
+        )
+    };
+
+    buffer[..CONFIGURATION_PARAMETER_OFFSET].copy_from_slice(header_bytes);
+    buffer[CONFIGURATION_PARAMETER_OFFSET] = parameter_type;
+    buffer[CONFIGURATION_PARAMETER_OFFSET + 1..op_size].copy_from_slice(parameter);
+
+    Ok(op_size)
+}
+
 /// CAN bus operations that can be transmitted over FMI-LS-BUS.
 ///
 /// This enum represents the different types of CAN operations that can be
@@ -238,7 +274,7 @@ fn check_buffer_capacity(buffer: &[u8], needed_size: usize) -> Result<(), FmiLsB
 ///     _ => {}
 /// }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LsBusCanOp<'a> {
     /// CAN transmit operation
     Transmit {
@@ -269,10 +305,12 @@ pub enum LsBusCanOp<'a> {
     Confirm(ls_bus::fmi3LsBusCanId),
     /// CAN configuration operation baud rate setting
     ConfigBaudrate(ls_bus::fmi3LsBusCanBaudrate),
-    /// CAN configuration operation FD baud rate setting
-    ConfigFdBaudrate,
-    /// CAN configuration operation XL baud rate setting
-    ConfigXlBaudrate,
+    /// CAN configuration operation FD baud rate setting, i.e. the baud rate of the data phase of
+    /// CAN FD frames with bit rate switching
+    ConfigFdBaudrate(ls_bus::fmi3LsBusCanBaudrate),
+    /// CAN configuration operation XL baud rate setting, i.e. the baud rate of the data phase of
+    /// CAN XL frames
+    ConfigXlBaudrate(ls_bus::fmi3LsBusCanBaudrate),
     /// CAN configuration operation for the arbitration lost behavior setting
     ConfigArbitrationLost(LsBusCanArbitrationLostBehavior),
     /// CAN arbitration lost operation
@@ -291,7 +329,73 @@ pub enum LsBusCanOp<'a> {
     Wakeup,
 }
 
-#[derive(Debug)]
+impl LsBusCanOp<'_> {
+    /// Convert into an operation that owns its payload, e.g. to keep it after the buffer it was
+    /// read from has been reset.
+    pub fn into_owned(self) -> LsBusCanOp<'static> {
+        match self {
+            LsBusCanOp::Transmit { id, ide, rtr, data } => LsBusCanOp::Transmit {
+                id,
+                ide,
+                rtr,
+                data: Cow::Owned(data.into_owned()),
+            },
+            LsBusCanOp::FdTransmit {
+                id,
+                ide,
+                brs,
+                esi,
+                data,
+            } => LsBusCanOp::FdTransmit {
+                id,
+                ide,
+                brs,
+                esi,
+                data: Cow::Owned(data.into_owned()),
+            },
+            LsBusCanOp::XlTransmit {
+                id,
+                ide,
+                sec,
+                sdt,
+                vcid,
+                af,
+                data,
+            } => LsBusCanOp::XlTransmit {
+                id,
+                ide,
+                sec,
+                sdt,
+                vcid,
+                af,
+                data: Cow::Owned(data.into_owned()),
+            },
+            LsBusCanOp::Confirm(id) => LsBusCanOp::Confirm(id),
+            LsBusCanOp::ConfigBaudrate(baud_rate) => LsBusCanOp::ConfigBaudrate(baud_rate),
+            LsBusCanOp::ConfigFdBaudrate(baud_rate) => LsBusCanOp::ConfigFdBaudrate(baud_rate),
+            LsBusCanOp::ConfigXlBaudrate(baud_rate) => LsBusCanOp::ConfigXlBaudrate(baud_rate),
+            LsBusCanOp::ConfigArbitrationLost(behavior) => {
+                LsBusCanOp::ConfigArbitrationLost(behavior)
+            }
+            LsBusCanOp::ArbitrationLost { id } => LsBusCanOp::ArbitrationLost { id },
+            LsBusCanOp::BusError {
+                id,
+                error_code,
+                error_flags,
+                is_sender,
+            } => LsBusCanOp::BusError {
+                id,
+                error_code,
+                error_flags,
+                is_sender,
+            },
+            LsBusCanOp::Status(kind) => LsBusCanOp::Status(kind),
+            LsBusCanOp::Wakeup => LsBusCanOp::Wakeup,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum LsBusCanArbitrationLostBehavior {
     /// On arbitration lost, buffer the message and retransmit later.
@@ -302,7 +406,7 @@ pub enum LsBusCanArbitrationLostBehavior {
         ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_ARBITRATION_LOST_BEHAVIOR_DISCARD_AND_NOTIFY,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum LsBusCanErrorCode {
     /// Represents a CAN bus error of type 'BIT_ERROR'.
@@ -346,7 +450,7 @@ impl TryFrom<ls_bus::fmi3LsBusCanErrorCode> for LsBusCanErrorCode {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum LsBusCanErrorFlag {
     /// Indicates that a specified Network FMU is detecting the given Bus Error first.
@@ -371,7 +475,7 @@ impl TryFrom<ls_bus::fmi3LsBusCanErrorFlag> for LsBusCanErrorFlag {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum LsBusCanStatusKind {
     /// Indicates that the CAN node is in state 'ERROR_ACTIVE'.
@@ -526,55 +630,26 @@ impl<'a> LsBusOperation<'a> for LsBusCanOp<'a> {
 
                 Ok(op_size)
             }
-            LsBusCanOp::ConfigBaudrate(baud_rate) => {
-                let op_size = std::mem::size_of::<ls_bus::fmi3LsBusCanOperationConfiguration>();
-
-                check_buffer_capacity(buffer, op_size)?;
-
-                let op = ls_bus::fmi3LsBusCanOperationConfiguration {
-                    header: ls_bus::fmi3LsBusOperationHeader {
-                        opCode: ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION,
-                        length: op_size as binding::fmi3UInt32,
-                    },
-                    parameterType: ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CAN_BAUDRATE,
-                    __bindgen_anon_1: ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 {
-                        baudrate: baud_rate,
-                    },
-                };
-
-                let op_bytes =
-                    unsafe { std::slice::from_raw_parts(&op as *const _ as *const u8, op_size) };
-
-                buffer[0..op_size].copy_from_slice(op_bytes);
-
-                Ok(op_size)
-            }
-            LsBusCanOp::ConfigFdBaudrate => todo!(),
-            LsBusCanOp::ConfigXlBaudrate => todo!(),
-            LsBusCanOp::ConfigArbitrationLost(behavior) => {
-                let op_size = std::mem::size_of::<ls_bus::fmi3LsBusCanOperationConfiguration>();
-
-                check_buffer_capacity(buffer, op_size)?;
-
-                let op = ls_bus::fmi3LsBusCanOperationConfiguration {
-                    header: ls_bus::fmi3LsBusOperationHeader {
-                        opCode: ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION,
-                        length: op_size as binding::fmi3UInt32,
-                    },
-                    parameterType:
-                        ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_ARBITRATION_LOST_BEHAVIOR,
-                    __bindgen_anon_1: ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 {
-                        arbitrationLostBehavior: behavior as binding::fmi3UInt8,
-                    },
-                };
-
-                let op_bytes =
-                    unsafe { std::slice::from_raw_parts(&op as *const _ as *const u8, op_size) };
-
-                buffer[0..op_size].copy_from_slice(op_bytes);
-
-                Ok(op_size)
-            }
+            LsBusCanOp::ConfigBaudrate(baud_rate) => transmit_configuration(
+                buffer,
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CAN_BAUDRATE,
+                &baud_rate.to_ne_bytes(),
+            ),
+            LsBusCanOp::ConfigFdBaudrate(baud_rate) => transmit_configuration(
+                buffer,
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANFD_BAUDRATE,
+                &baud_rate.to_ne_bytes(),
+            ),
+            LsBusCanOp::ConfigXlBaudrate(baud_rate) => transmit_configuration(
+                buffer,
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANXL_BAUDRATE,
+                &baud_rate.to_ne_bytes(),
+            ),
+            LsBusCanOp::ConfigArbitrationLost(behavior) => transmit_configuration(
+                buffer,
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_ARBITRATION_LOST_BEHAVIOR,
+                &[behavior as ls_bus::fmi3LsBusCanArbitrationLostBehavior],
+            ),
             LsBusCanOp::ArbitrationLost { id } => {
                 let op_size = std::mem::size_of::<ls_bus::fmi3LsBusCanOperationArbitrationLost>();
 
@@ -799,38 +874,49 @@ impl<'a> LsBusOperation<'a> for LsBusCanOp<'a> {
                 Ok(Some(LsBusCanOp::Wakeup))
             }
 
-            ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION
-                if size >= std::mem::size_of::<ls_bus::fmi3LsBusCanOperationConfiguration>() =>
-            {
+            ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION if size > CONFIGURATION_PARAMETER_OFFSET => {
                 let op_bytes = &buffer[*read_pos..*read_pos + size];
-                let operation: &ls_bus::fmi3LsBusCanOperationConfiguration = unsafe {
-                    &*(op_bytes.as_ptr() as *const ls_bus::fmi3LsBusCanOperationConfiguration)
+                let parameter_type = op_bytes[CONFIGURATION_PARAMETER_OFFSET];
+                let parameter = &op_bytes[CONFIGURATION_PARAMETER_OFFSET + 1..];
+                let baud_rate = || {
+                    parameter
+                        .get(..std::mem::size_of::<ls_bus::fmi3LsBusCanBaudrate>())
+                        .map(|bytes| {
+                            ls_bus::fmi3LsBusCanBaudrate::from_ne_bytes(
+                                bytes.try_into().expect("Slice has the size of a baud rate"),
+                            )
+                        })
+                        .ok_or(FmiLsBusError::InvalidOperation(op))
                 };
-                *read_pos += size;
 
-                match operation.parameterType {
+                let operation = match parameter_type {
                     ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CAN_BAUDRATE => {
-                        Ok(Some(LsBusCanOp::ConfigBaudrate(unsafe {
-                            operation.__bindgen_anon_1.baudrate
-                        })))
+                        LsBusCanOp::ConfigBaudrate(baud_rate()?)
+                    }
+                    ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANFD_BAUDRATE => {
+                        LsBusCanOp::ConfigFdBaudrate(baud_rate()?)
+                    }
+                    ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANXL_BAUDRATE => {
+                        LsBusCanOp::ConfigXlBaudrate(baud_rate()?)
                     }
                     ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_ARBITRATION_LOST_BEHAVIOR => {
-                        let behavior =
-                            unsafe { operation.__bindgen_anon_1.arbitrationLostBehavior };
+                        let behavior = *parameter
+                            .first()
+                            .ok_or(FmiLsBusError::InvalidOperation(op))?;
                         match behavior {
                             ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_ARBITRATION_LOST_BEHAVIOR_BUFFER_AND_RETRANSMIT => {
-                                Ok(Some(LsBusCanOp::ConfigArbitrationLost(crate::can::LsBusCanArbitrationLostBehavior::BufferAndRetransmit)))
+                                LsBusCanOp::ConfigArbitrationLost(LsBusCanArbitrationLostBehavior::BufferAndRetransmit)
                             }
                             ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_ARBITRATION_LOST_BEHAVIOR_DISCARD_AND_NOTIFY => {
-                                Ok(Some(LsBusCanOp::ConfigArbitrationLost(crate::can::LsBusCanArbitrationLostBehavior::DiscardAndNotify)))
+                                LsBusCanOp::ConfigArbitrationLost(LsBusCanArbitrationLostBehavior::DiscardAndNotify)
                             }
-                            _ => Err(FmiLsBusError::InvalidVariant(behavior as u32))
+                            _ => return Err(FmiLsBusError::InvalidVariant(behavior as u32)),
                         }
                     }
-                    _ => Err(FmiLsBusError::InvalidOperation(
-                        operation.parameterType as u32,
-                    )),
-                }
+                    _ => return Err(FmiLsBusError::InvalidOperation(parameter_type as u32)),
+                };
+                *read_pos += size;
+                Ok(Some(operation))
             }
 
             _ => {
@@ -0,0 +1,553 @@
+//! Importer-side simulation of a CAN bus that connects several Network FMUs.
+//!
+//! [`CanBusSimulation`] takes the role of the Bus Simulation described by FMI-LS-BUS. It collects
+//! the operations that the Network FMUs write into their `Tx_Data` variables, arbitrates pending
+//! frames by ID priority and delivers the winning frame to the `Rx_Data` variables of all other
+//! nodes once it has been transmitted at the configured baud rates. The sender receives a
+//! `Confirm` operation at the same time.
+//!
+//! The importer drives the simulation from its master algorithm:
+//!
+//! 1. When the `Tx_Clock` of an FMU ticks, pass its `Tx_Data` to [`CanBusSimulation::process_tx`]
+//!    (or use [`CanBusSimulation::transmit_from`]).
+//! 2. Call [`CanBusSimulation::update`] with the current time, and schedule the next
+//!    communication point no later than [`CanBusSimulation::next_event_time`].
+//! 3. Hand the operations returned by [`CanBusSimulation::take_rx`] to the FMUs and activate
+//!    their `Rx_Clock` (or use [`CanBusSimulation::deliver_to`]).
+//!
+//! # Example
+//!
+//! ```rust
+//! use fmi_ls_bus::{
+//!     FmiLsBus,
+//!     can::{LsBusCanOp, simulation::CanBusSimulation},
+//! };
+//! use std::borrow::Cow;
+//!
+//! let mut bus = CanBusSimulation::new(500_000);
+//! let ecu1 = bus.add_node();
+//! let ecu2 = bus.add_node();
+//!
+//! // ECU 1 transmits a frame at t = 0
+//! let mut tx = FmiLsBus::new();
+//! let mut tx_data = vec![0u8; 64];
+//! tx.write_operation(
+//!     LsBusCanOp::Transmit {
+//!         id: 0x10,
+//!         ide: 0,
+//!         rtr: 0,
+//!         data: Cow::Borrowed(&[1, 2]),
+//!     },
+//!     &mut tx_data,
+//! )
+//! .unwrap();
+//! bus.process_tx(ecu1, &tx_data[..tx.write_pos]).unwrap();
+//! bus.update(0.0).unwrap();
+//!
+//! // The frame arrives at ECU 2 once it has been transmitted
+//! let arrival = bus.next_event_time().unwrap();
+//! assert!(arrival > 0.0);
+//! bus.update(arrival).unwrap();
+//! assert!(bus.take_rx(ecu2).is_some());
+//! ```
+
+use std::collections::VecDeque;
+
+use fmi::fmi3::{GetSet, binding, schema};
+use fmi_sys::ls_bus;
+
+use super::{DEFAULT_CAN_BUFFER_SIZE, LsBusCanArbitrationLostBehavior, LsBusCanOp};
+use crate::{
+    FmiLsBus, FmiLsBusError,
+    can::{LsBusCanErrorCode, LsBusCanErrorFlag},
+};
+
+/// Index of a node of a [`CanBusSimulation`], as returned by [`CanBusSimulation::add_node`]
+pub type CanNodeId = usize;
+
+/// Upper bound of the size of a single serialized CAN operation
+const MAX_OPERATION_SIZE: usize =
+    std::mem::size_of::<ls_bus::fmi3LsBusCanOperationCanXlTransmit>() + DEFAULT_CAN_BUFFER_SIZE;
+
+/// Arbitration priority of a frame, lower values win.
+///
+/// The base identifier is compared first. A standard frame wins against an extended frame with
+/// the same base identifier, since its IDE bit is dominant.
+type Priority = (
+    ls_bus::fmi3LsBusCanId,
+    ls_bus::fmi3LsBusCanIde,
+    ls_bus::fmi3LsBusCanId,
+);
+
+/// Identifier of a transmit operation
+fn frame_id(op: &LsBusCanOp) -> Option<ls_bus::fmi3LsBusCanId> {
+    match op {
+        LsBusCanOp::Transmit { id, .. }
+        | LsBusCanOp::FdTransmit { id, .. }
+        | LsBusCanOp::XlTransmit { id, .. } => Some(*id),
+        _ => None,
+    }
+}
+
+fn priority(op: &LsBusCanOp) -> Priority {
+    match op {
+        LsBusCanOp::Transmit { id, ide, .. } | LsBusCanOp::FdTransmit { id, ide, .. }
+            if *ide != 0 =>
+        {
+            (id >> 18, 1, id & 0x3_FFFF)
+        }
+        _ => (frame_id(op).unwrap_or(ls_bus::fmi3LsBusCanId::MAX), 0, 0),
+    }
+}
+
+/// Number of bits of a frame in the arbitration and in the data phase, including the
+/// interframe space.
+///
+/// Stuff bits are not counted, so the result is a lower bound of the actual frame length.
+fn frame_bits(op: &LsBusCanOp) -> (u32, u32) {
+    match op {
+        LsBusCanOp::Transmit { ide, rtr, data, .. } => {
+            let data_bits = if *rtr != 0 { 0 } else { 8 * data.len() as u32 };
+            let overhead = if *ide != 0 { 67 } else { 47 };
+            (overhead + data_bits, 0)
+        }
+        LsBusCanOp::FdTransmit { ide, data, .. } => {
+            // SOF, identifier, RRS, IDE, FDF, res, BRS; CRC delimiter, ACK, EOF and IFS
+            let arbitration = if *ide != 0 { 36 } else { 17 } + 13;
+            // ESI, DLC, stuff count and CRC
+            let crc = if data.len() <= 16 { 17 } else { 21 };
+            (arbitration, 9 + crc + 8 * data.len() as u32)
+        }
+        LsBusCanOp::XlTransmit { data, .. } => {
+            // SOF, priority identifier, RRS, IDE, FDF, XLF, resXLF, ADS; DAS, ACK, EOF and IFS
+            let arbitration = 20 + 15;
+            // SDT, SEC, DLC, SBC, PCRC, VCID, AF, FCRC and FCP
+            (arbitration, 112 + 8 * data.len() as u32)
+        }
+        _ => (0, 0),
+    }
+}
+
+/// A node of the bus, i.e. the CAN terminal of one Network FMU
+#[derive(Debug)]
+struct CanNode {
+    arbitration_lost_behavior: LsBusCanArbitrationLostBehavior,
+    /// Frames waiting for transmission
+    pending: VecDeque<LsBusCanOp<'static>>,
+    rx_bus: FmiLsBus,
+    rx_data: Vec<u8>,
+}
+
+impl CanNode {
+    fn new() -> Self {
+        Self {
+            arbitration_lost_behavior: LsBusCanArbitrationLostBehavior::BufferAndRetransmit,
+            pending: VecDeque::new(),
+            rx_bus: FmiLsBus::new(),
+            rx_data: Vec::new(),
+        }
+    }
+
+    /// The pending frame with the highest priority
+    fn contender(&self) -> Option<(usize, Priority)> {
+        self.pending
+            .iter()
+            .enumerate()
+            .map(|(index, op)| (index, priority(op)))
+            .min_by_key(|(_, priority)| *priority)
+    }
+
+    /// Append an operation to the operations to be delivered to the FMU
+    fn push_rx(&mut self, op: LsBusCanOp) -> Result<(), FmiLsBusError> {
+        let needed_len = self.rx_bus.write_pos + MAX_OPERATION_SIZE;
+        if self.rx_data.len() < needed_len {
+            self.rx_data.resize(needed_len, 0);
+        }
+        self.rx_bus.write_operation(op, &mut self.rx_data)
+    }
+}
+
+/// A frame on the bus
+#[derive(Debug)]
+struct Transmission {
+    sender: CanNodeId,
+    op: LsBusCanOp<'static>,
+    end_time: f64,
+}
+
+/// Simulation of a CAN bus connecting the CAN terminals of several Network FMUs.
+///
+/// See the [module documentation](self) for how to drive it.
+#[derive(Debug)]
+pub struct CanBusSimulation {
+    baud_rate: ls_bus::fmi3LsBusCanBaudrate,
+    fd_baud_rate: ls_bus::fmi3LsBusCanBaudrate,
+    xl_baud_rate: ls_bus::fmi3LsBusCanBaudrate,
+    nodes: Vec<CanNode>,
+    transmission: Option<Transmission>,
+}
+
+impl CanBusSimulation {
+    /// Create a bus without nodes, transmitting at `baud_rate` bit/s. A baud rate of 0 transmits
+    /// frames without delay.
+    ///
+    /// The data phases of CAN FD and CAN XL frames are transmitted at the same baud rate until
+    /// their baud rates are configured.
+    pub fn new(baud_rate: ls_bus::fmi3LsBusCanBaudrate) -> Self {
+        Self {
+            baud_rate,
+            fd_baud_rate: 0,
+            xl_baud_rate: 0,
+            nodes: Vec::new(),
+            transmission: None,
+        }
+    }
+
+    /// Add a node to the bus and return its id.
+    pub fn add_node(&mut self) -> CanNodeId {
+        self.nodes.push(CanNode::new());
+        self.nodes.len() - 1
+    }
+
+    /// The current baud rate in bit/s, as last configured by a node.
+    pub fn baud_rate(&self) -> ls_bus::fmi3LsBusCanBaudrate {
+        self.baud_rate
+    }
+
+    /// Set the baud rate in bit/s.
+    pub fn set_baud_rate(&mut self, baud_rate: ls_bus::fmi3LsBusCanBaudrate) {
+        self.baud_rate = baud_rate;
+    }
+
+    /// The baud rate of the data phase of CAN FD frames with bit rate switching in bit/s, or 0 if
+    /// not configured.
+    pub fn fd_baud_rate(&self) -> ls_bus::fmi3LsBusCanBaudrate {
+        self.fd_baud_rate
+    }
+
+    /// Set the baud rate of the data phase of CAN FD frames with bit rate switching in bit/s.
+    pub fn set_fd_baud_rate(&mut self, baud_rate: ls_bus::fmi3LsBusCanBaudrate) {
+        self.fd_baud_rate = baud_rate;
+    }
+
+    /// The baud rate of the data phase of CAN XL frames in bit/s, or 0 if not configured.
+    pub fn xl_baud_rate(&self) -> ls_bus::fmi3LsBusCanBaudrate {
+        self.xl_baud_rate
+    }
+
+    /// Set the baud rate of the data phase of CAN XL frames in bit/s.
+    pub fn set_xl_baud_rate(&mut self, baud_rate: ls_bus::fmi3LsBusCanBaudrate) {
+        self.xl_baud_rate = baud_rate;
+    }
+
+    /// Time it takes to transmit a frame at the current baud rates
+    fn transmission_time(&self, op: &LsBusCanOp) -> f64 {
+        if self.baud_rate == 0 {
+            return 0.0;
+        }
+        let data_baud_rate = match op {
+            LsBusCanOp::FdTransmit { brs, .. } if *brs != 0 && self.fd_baud_rate != 0 => {
+                self.fd_baud_rate
+            }
+            LsBusCanOp::XlTransmit { .. } if self.xl_baud_rate != 0 => self.xl_baud_rate,
+            _ => self.baud_rate,
+        };
+        let (arbitration, data) = frame_bits(op);
+        arbitration as f64 / self.baud_rate as f64 + data as f64 / data_baud_rate as f64
+    }
+
+    /// Process the operations that a node has written into its `Tx_Data` variable.
+    ///
+    /// Transmit operations are queued for arbitration at the next call to [`Self::update`],
+    /// configuration operations take effect immediately and wakeup operations are forwarded to
+    /// all other nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` has not been added to the bus.
+    pub fn process_tx(&mut self, node: CanNodeId, tx_data: &[u8]) -> Result<(), FmiLsBusError> {
+        let mut tx_bus = FmiLsBus::new();
+        while let Some(op) = tx_bus.read_next_operation::<LsBusCanOp>(tx_data)? {
+            match op {
+                LsBusCanOp::Transmit { .. }
+                | LsBusCanOp::FdTransmit { .. }
+                | LsBusCanOp::XlTransmit { .. } => {
+                    self.nodes[node].pending.push_back(op.into_owned());
+                }
+                LsBusCanOp::ConfigBaudrate(baud_rate) => self.baud_rate = baud_rate,
+                LsBusCanOp::ConfigFdBaudrate(baud_rate) => self.fd_baud_rate = baud_rate,
+                LsBusCanOp::ConfigXlBaudrate(baud_rate) => self.xl_baud_rate = baud_rate,
+                LsBusCanOp::ConfigArbitrationLost(behavior) => {
+                    self.nodes[node].arbitration_lost_behavior = behavior;
+                }
+                LsBusCanOp::Wakeup => {
+                    for (index, other) in self.nodes.iter_mut().enumerate() {
+                        if index != node {
+                            other.push_rx(LsBusCanOp::Wakeup)?;
+                        }
+                    }
+                }
+                // Status reports and operations of other directions do not affect the bus
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Advance the bus to `time`.
+    ///
+    /// A frame whose transmission has ended is delivered to all other nodes and confirmed to its
+    /// sender, then the pending frames of all nodes are arbitrated. Nodes that lose arbitration
+    /// either keep their frame for the next arbitration or discard it and receive an
+    /// `ArbitrationLost` operation, depending on their configured behavior. If several nodes
+    /// transmit the same identifier, their frames are discarded and all nodes receive a
+    /// `BusError` operation.
+    pub fn update(&mut self, time: f64) -> Result<(), FmiLsBusError> {
+        let mut start_time = time;
+        loop {
+            if self
+                .transmission
+                .as_ref()
+                .is_some_and(|transmission| transmission.end_time > time)
+            {
+                return Ok(());
+            }
+            if let Some(transmission) = self.transmission.take() {
+                // Frames that were waiting are arbitrated as soon as the bus is idle
+                start_time = transmission.end_time;
+                self.complete(transmission)?;
+            }
+            if !self.arbitrate(start_time)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The time at which the frame currently on the bus has been transmitted.
+    pub fn next_event_time(&self) -> Option<f64> {
+        self.transmission
+            .as_ref()
+            .map(|transmission| transmission.end_time)
+    }
+
+    /// Take the operations to be delivered to a node, if any.
+    ///
+    /// The returned buffer is meant to be set as the node's `Rx_Data` together with a tick of
+    /// its `Rx_Clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` has not been added to the bus.
+    pub fn take_rx(&mut self, node: CanNodeId) -> Option<Vec<u8>> {
+        let node = &mut self.nodes[node];
+        if node.rx_bus.write_pos == 0 {
+            return None;
+        }
+        node.rx_data.truncate(node.rx_bus.write_pos);
+        node.rx_bus.reset();
+        Some(std::mem::take(&mut node.rx_data))
+    }
+
+    /// Deliver the frame on the bus and confirm it to its sender
+    fn complete(&mut self, transmission: Transmission) -> Result<(), FmiLsBusError> {
+        let id = frame_id(&transmission.op).expect("Only transmit operations are on the bus");
+        log::trace!(
+            "CAN frame {id:#x} of node {} transmitted at t = {}",
+            transmission.sender,
+            transmission.end_time
+        );
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            if index == transmission.sender {
+                node.push_rx(LsBusCanOp::Confirm(id))?;
+            } else {
+                node.push_rx(transmission.op.clone())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start transmitting the pending frame with the highest priority.
+    ///
+    /// Returns `false` if no frame is pending.
+    fn arbitrate(&mut self, start_time: f64) -> Result<bool, FmiLsBusError> {
+        let contenders: Vec<_> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(node, state)| state.contender().map(|(index, prio)| (node, index, prio)))
+            .collect();
+        let Some(winning) = contenders.iter().map(|(_, _, prio)| *prio).min() else {
+            return Ok(false);
+        };
+        let winners: Vec<_> = contenders
+            .iter()
+            .filter(|(_, _, prio)| *prio == winning)
+            .map(|(node, index, _)| (*node, *index))
+            .collect();
+
+        if let [(sender, index)] = winners[..] {
+            let op = self.nodes[sender]
+                .pending
+                .remove(index)
+                .expect("Contender is pending");
+            for &(node, index, _) in contenders.iter().filter(|(node, ..)| *node != sender) {
+                let state = &mut self.nodes[node];
+                if state.arbitration_lost_behavior
+                    == LsBusCanArbitrationLostBehavior::DiscardAndNotify
+                {
+                    let lost = state.pending.remove(index).expect("Contender is pending");
+                    let id = frame_id(&lost).expect("Only transmit operations are pending");
+                    state.push_rx(LsBusCanOp::ArbitrationLost { id })?;
+                }
+            }
+            let end_time = start_time + self.transmission_time(&op);
+            self.transmission = Some(Transmission {
+                sender,
+                op,
+                end_time,
+            });
+        } else {
+            // Several nodes won the arbitration with the same identifier, so their frames
+            // collide after the arbitration field.
+            let id = frame_id_of(winning);
+            log::debug!("CAN frames with identifier {id:#x} collided at t = {start_time}");
+            for &(node, index) in &winners {
+                self.nodes[node].pending.remove(index);
+            }
+            for (node, state) in self.nodes.iter_mut().enumerate() {
+                let is_sender = winners.iter().any(|(sender, _)| *sender == node);
+                state.push_rx(LsBusCanOp::BusError {
+                    id,
+                    error_code: LsBusCanErrorCode::BitError,
+                    error_flags: if is_sender {
+                        LsBusCanErrorFlag::Primary
+                    } else {
+                        LsBusCanErrorFlag::Secondary
+                    },
+                    is_sender,
+                })?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Collect the operations of a node from its FMU instance, if its `Tx_Clock` ticked.
+    ///
+    /// Must be called in Event Mode. Returns whether the clock ticked.
+    pub fn transmit_from<Inst: GetSet>(
+        &mut self,
+        node: CanNodeId,
+        variables: &CanNodeVariables,
+        inst: &mut Inst,
+    ) -> Result<bool, FmiLsBusError> {
+        match variables.read_tx(inst)? {
+            Some(tx_data) => {
+                self.process_tx(node, &tx_data)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Hand the pending operations of a node to its FMU instance and activate its `Rx_Clock`.
+    ///
+    /// Must be called in Event Mode. Returns whether there were any operations.
+    pub fn deliver_to<Inst: GetSet>(
+        &mut self,
+        node: CanNodeId,
+        variables: &CanNodeVariables,
+        inst: &mut Inst,
+    ) -> Result<bool, FmiLsBusError> {
+        match self.take_rx(node) {
+            Some(rx_data) => {
+                variables.write_rx(inst, &rx_data)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// The identifier of a frame from its arbitration priority
+fn frame_id_of((base, ide, extension): Priority) -> ls_bus::fmi3LsBusCanId {
+    if ide != 0 {
+        base << 18 | extension
+    } else {
+        base
+    }
+}
+
+/// Value references of the LS-BUS CAN variables of a Network FMU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanNodeVariables {
+    pub tx_data: binding::fmi3ValueReference,
+    pub rx_data: binding::fmi3ValueReference,
+    pub tx_clock: binding::fmi3ValueReference,
+    pub rx_clock: binding::fmi3ValueReference,
+    /// Size of the buffer used to read `Tx_Data`
+    pub tx_max_size: usize,
+}
+
+impl CanNodeVariables {
+    /// Look up the variables `<prefix>.Tx_Data`, `<prefix>.Rx_Data`, `<prefix>.Tx_Clock` and
+    /// `<prefix>.Rx_Clock`, e.g. with the prefix `CanChannel`.
+    pub fn from_model_description(
+        model_description: &schema::Fmi3ModelDescription,
+        prefix: &str,
+    ) -> Result<Self, FmiLsBusError> {
+        let variables = &model_description.model_variables;
+        let find = |name: &str| {
+            let name = format!("{prefix}.{name}");
+            variables
+                .find_by_name(&name)
+                .map(|var| var.value_reference())
+                .ok_or(FmiLsBusError::UnknownVariable(name))
+        };
+        let tx_data = find("Tx_Data")?;
+        let tx_max_size = variables
+            .variables
+            .iter()
+            .find_map(|var| match var {
+                schema::Variable::Binary(var) if var.value_reference == tx_data => var.max_size,
+                _ => None,
+            })
+            .map_or(DEFAULT_CAN_BUFFER_SIZE, |max_size| max_size as usize);
+
+        Ok(Self {
+            tx_data,
+            rx_data: find("Rx_Data")?,
+            tx_clock: find("Tx_Clock")?,
+            rx_clock: find("Rx_Clock")?,
+            tx_max_size,
+        })
+    }
+
+    /// Read `Tx_Data` if `Tx_Clock` ticked.
+    pub fn read_tx<Inst: GetSet>(&self, inst: &mut Inst) -> Result<Option<Vec<u8>>, FmiLsBusError> {
+        let mut ticked = [false];
+        inst.get_clock(&[self.tx_clock], &mut ticked)
+            .map_err(fmi::Error::from)?;
+        if !ticked[0] {
+            return Ok(None);
+        }
+        let mut tx_data = vec![0u8; self.tx_max_size];
+        let sizes = inst
+            .get_binary(&[self.tx_data], &mut [&mut tx_data])
+            .map_err(fmi::Error::from)?;
+        tx_data.truncate(sizes[0]);
+        Ok(Some(tx_data))
+    }
+
+    /// Set `Rx_Data` and activate `Rx_Clock`.
+    pub fn write_rx<Inst: GetSet>(
+        &self,
+        inst: &mut Inst,
+        rx_data: &[u8],
+    ) -> Result<(), FmiLsBusError> {
+        inst.set_binary(&[self.rx_data], &[rx_data])
+            .map_err(fmi::Error::from)?;
+        inst.set_clock(&[self.rx_clock], &[true])
+            .map_err(fmi::Error::from)?;
+        Ok(())
+    }
+}
@@ -2,10 +2,11 @@ use crate::{
     FmiLsBus,
     can::{
         LsBusCanArbitrationLostBehavior, LsBusCanErrorCode, LsBusCanErrorFlag, LsBusCanOp,
-        LsBusCanStatusKind,
+        LsBusCanStatusKind, simulation::CanBusSimulation,
     },
 };
 
+use fmi_sys::ls_bus;
 use std::borrow::Cow;
 
 fn cow_bytes<'a>(data: &'a Cow<'a, [u8]>) -> &'a [u8] {
@@ -165,6 +166,112 @@ fn test_can_baudrate_config() {
     }
 }
 
+#[test]
+fn test_can_fd_xl_baudrate_config() {
+    let mut buffer = vec![0u8; 1024];
+    let mut bus = FmiLsBus::new();
+
+    bus.write_operation(LsBusCanOp::ConfigFdBaudrate(2_000_000), &mut buffer)
+        .unwrap();
+    bus.write_operation(LsBusCanOp::ConfigXlBaudrate(10_000_000), &mut buffer)
+        .unwrap();
+
+    let operation: Option<LsBusCanOp> = bus.read_next_operation(&buffer).unwrap();
+    assert!(matches!(
+        operation,
+        Some(LsBusCanOp::ConfigFdBaudrate(2_000_000))
+    ));
+    let operation: Option<LsBusCanOp> = bus.read_next_operation(&buffer).unwrap();
+    assert!(matches!(
+        operation,
+        Some(LsBusCanOp::ConfigXlBaudrate(10_000_000))
+    ));
+}
+
+/// Serialize a configuration operation the way the `FMI3_LS_BUS_CAN_CREATE_OP_CONFIGURATION_*`
+/// macros of `fmi3LsBusUtilCan.h` do: fill in the packed struct and copy `header.length` bytes.
+fn c_configuration_op(
+    parameter_type: ls_bus::fmi3LsBusCanConfigParameterType,
+    parameter: ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1,
+    parameter_size: usize,
+) -> Vec<u8> {
+    let op = ls_bus::fmi3LsBusCanOperationConfiguration {
+        header: ls_bus::fmi3LsBusOperationHeader {
+            opCode: ls_bus::FMI3_LS_BUS_CAN_OP_CONFIGURATION,
+            length: (std::mem::size_of::<ls_bus::fmi3LsBusOperationHeader>()
+                + std::mem::size_of::<ls_bus::fmi3LsBusCanConfigParameterType>()
+                + parameter_size) as u32,
+        },
+        parameterType: parameter_type,
+        __bindgen_anon_1: parameter,
+    };
+    let length = op.header.length as usize;
+    let bytes = unsafe { std::slice::from_raw_parts(&op as *const _ as *const u8, length) };
+    bytes.to_vec()
+}
+
+#[test]
+fn test_can_configuration_matches_c_macros() {
+    let baud_rate_size = std::mem::size_of::<ls_bus::fmi3LsBusCanBaudrate>();
+    let cases = [
+        (
+            LsBusCanOp::ConfigBaudrate(500_000),
+            c_configuration_op(
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CAN_BAUDRATE,
+                ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 { baudrate: 500_000 },
+                baud_rate_size,
+            ),
+        ),
+        (
+            LsBusCanOp::ConfigFdBaudrate(2_000_000),
+            c_configuration_op(
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANFD_BAUDRATE,
+                ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 {
+                    baudrate: 2_000_000,
+                },
+                baud_rate_size,
+            ),
+        ),
+        (
+            LsBusCanOp::ConfigXlBaudrate(10_000_000),
+            c_configuration_op(
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_CANXL_BAUDRATE,
+                ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 {
+                    baudrate: 10_000_000,
+                },
+                baud_rate_size,
+            ),
+        ),
+        (
+            LsBusCanOp::ConfigArbitrationLost(LsBusCanArbitrationLostBehavior::DiscardAndNotify),
+            c_configuration_op(
+                ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_TYPE_ARBITRATION_LOST_BEHAVIOR,
+                ls_bus::fmi3LsBusCanOperationConfiguration__bindgen_ty_1 {
+                    arbitrationLostBehavior:
+                        ls_bus::FMI3_LS_BUS_CAN_CONFIG_PARAM_ARBITRATION_LOST_BEHAVIOR_DISCARD_AND_NOTIFY,
+                },
+                std::mem::size_of::<ls_bus::fmi3LsBusCanArbitrationLostBehavior>(),
+            ),
+        ),
+    ];
+
+    for (op, c_bytes) in cases {
+        let expected = format!("{op:?}");
+
+        // Serialized like the C macro
+        let mut buffer = vec![0u8; 64];
+        let mut bus = FmiLsBus::new();
+        bus.write_operation(op, &mut buffer).unwrap();
+        assert_eq!(&buffer[..bus.write_pos], &c_bytes[..], "{expected}");
+
+        // Read back from exactly the bytes the C macro writes
+        let mut bus = FmiLsBus::new();
+        let operation: Option<LsBusCanOp> = bus.read_next_operation(&c_bytes).unwrap();
+        assert_eq!(format!("{:?}", operation.unwrap()), expected);
+        assert_eq!(bus.read_pos, c_bytes.len());
+    }
+}
+
 #[test]
 fn test_can_config_arbitration_lost_operation() {
     let mut buffer = vec![0u8; 1024];
@@ -696,3 +803,191 @@ fn test_simplified_transmit_interface() {
         _ => panic!("Expected Transmit operation"),
     }
 }
+
+/// Serialize operations into a `Tx_Data` buffer
+fn tx_buffer(ops: Vec<LsBusCanOp>) -> Vec<u8> {
+    let mut buffer = vec![0u8; 1024];
+    let mut bus = FmiLsBus::new();
+    for op in ops {
+        bus.write_operation(op, &mut buffer).unwrap();
+    }
+    buffer.truncate(bus.write_pos);
+    buffer
+}
+
+fn transmit(id: u32, data: &'static [u8]) -> LsBusCanOp<'static> {
+    LsBusCanOp::Transmit {
+        id,
+        ide: 0,
+        rtr: 0,
+        data: Cow::Borrowed(data),
+    }
+}
+
+/// Read all operations delivered to a node
+fn rx_ops(sim: &mut CanBusSimulation, node: usize) -> Vec<LsBusCanOp<'static>> {
+    let Some(rx_data) = sim.take_rx(node) else {
+        return Vec::new();
+    };
+    let mut bus = FmiLsBus::new();
+    let mut ops = Vec::new();
+    while let Some(op) = bus.read_next_operation::<LsBusCanOp>(&rx_data).unwrap() {
+        ops.push(op.into_owned());
+    }
+    ops
+}
+
+#[test]
+fn test_simulation_delivers_after_transmission_time() {
+    let mut sim = CanBusSimulation::new(500_000);
+    let sender = sim.add_node();
+    let receiver = sim.add_node();
+
+    sim.process_tx(sender, &tx_buffer(vec![transmit(0x123, b"abcd")]))
+        .unwrap();
+    sim.update(1.0).unwrap();
+
+    // 47 bits of a standard frame and 32 data bits at 500 kbit/s
+    let end_time = sim.next_event_time().unwrap();
+    assert!((end_time - (1.0 + 79.0 / 500_000.0)).abs() < 1e-12);
+    assert!(sim.take_rx(receiver).is_none());
+
+    sim.update(end_time).unwrap();
+    assert!(sim.next_event_time().is_none());
+    match &rx_ops(&mut sim, receiver)[..] {
+        [LsBusCanOp::Transmit { id, data, .. }] => {
+            assert_eq!(*id, 0x123);
+            assert_eq!(cow_bytes(data), b"abcd");
+        }
+        ops => panic!("Expected Transmit operation, got {ops:?}"),
+    }
+    assert!(matches!(
+        &rx_ops(&mut sim, sender)[..],
+        [LsBusCanOp::Confirm(0x123)]
+    ));
+}
+
+#[test]
+fn test_simulation_arbitration_by_priority() {
+    let mut sim = CanBusSimulation::new(0);
+    let low = sim.add_node();
+    let high = sim.add_node();
+    let listener = sim.add_node();
+
+    sim.process_tx(low, &tx_buffer(vec![transmit(0x200, b"low")]))
+        .unwrap();
+    sim.process_tx(high, &tx_buffer(vec![transmit(0x100, b"high")]))
+        .unwrap();
+    sim.update(0.0).unwrap();
+
+    // Without delay both frames are transmitted, the higher priority one first
+    let ids: Vec<_> = rx_ops(&mut sim, listener)
+        .iter()
+        .map(|op| match op {
+            LsBusCanOp::Transmit { id, .. } => *id,
+            op => panic!("Unexpected operation {op:?}"),
+        })
+        .collect();
+    assert_eq!(ids, vec![0x100, 0x200]);
+}
+
+#[test]
+fn test_simulation_arbitration_lost_discard_and_notify() {
+    let mut sim = CanBusSimulation::new(125_000);
+    let winner = sim.add_node();
+    let loser = sim.add_node();
+
+    sim.process_tx(
+        loser,
+        &tx_buffer(vec![
+            LsBusCanOp::ConfigArbitrationLost(LsBusCanArbitrationLostBehavior::DiscardAndNotify),
+            transmit(0x300, b"x"),
+        ]),
+    )
+    .unwrap();
+    sim.process_tx(winner, &tx_buffer(vec![transmit(0x010, b"y")]))
+        .unwrap();
+    sim.update(0.0).unwrap();
+    let end_time = sim.next_event_time().unwrap();
+    sim.update(end_time).unwrap();
+
+    let ops = rx_ops(&mut sim, loser);
+    assert!(matches!(
+        &ops[..],
+        [
+            LsBusCanOp::ArbitrationLost { id: 0x300 },
+            LsBusCanOp::Transmit { id: 0x010, .. }
+        ]
+    ));
+    // The discarded frame is not retransmitted
+    assert!(sim.next_event_time().is_none());
+}
+
+#[test]
+fn test_simulation_same_identifier_bus_error() {
+    let mut sim = CanBusSimulation::new(500_000);
+    let a = sim.add_node();
+    let b = sim.add_node();
+    let listener = sim.add_node();
+
+    sim.process_tx(a, &tx_buffer(vec![transmit(0x42, b"a")]))
+        .unwrap();
+    sim.process_tx(b, &tx_buffer(vec![transmit(0x42, b"b")]))
+        .unwrap();
+    sim.update(0.0).unwrap();
+
+    assert!(sim.next_event_time().is_none());
+    assert!(matches!(
+        &rx_ops(&mut sim, a)[..],
+        [LsBusCanOp::BusError {
+            id: 0x42,
+            error_code: LsBusCanErrorCode::BitError,
+            error_flags: LsBusCanErrorFlag::Primary,
+            is_sender: true,
+        }]
+    ));
+    assert!(matches!(
+        &rx_ops(&mut sim, listener)[..],
+        [LsBusCanOp::BusError {
+            error_flags: LsBusCanErrorFlag::Secondary,
+            is_sender: false,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_simulation_config_baudrate() {
+    let mut sim = CanBusSimulation::new(500_000);
+    let node = sim.add_node();
+    sim.process_tx(node, &tx_buffer(vec![LsBusCanOp::ConfigBaudrate(250_000)]))
+        .unwrap();
+    assert_eq!(sim.baud_rate(), 250_000);
+    assert!(sim.take_rx(node).is_none());
+}
+
+#[test]
+fn test_simulation_fd_data_phase_baudrate() {
+    let mut sim = CanBusSimulation::new(500_000);
+    let node = sim.add_node();
+    sim.process_tx(
+        node,
+        &tx_buffer(vec![
+            LsBusCanOp::ConfigFdBaudrate(2_000_000),
+            LsBusCanOp::FdTransmit {
+                id: 0x10,
+                ide: 0,
+                brs: 1,
+                esi: 0,
+                data: Cow::Borrowed(&[0u8; 64]),
+            },
+        ]),
+    )
+    .unwrap();
+    assert_eq!(sim.fd_baud_rate(), 2_000_000);
+    sim.update(0.0).unwrap();
+
+    // 30 arbitration phase bits at 500 kbit/s, 30 + 512 data phase bits at 2 Mbit/s
+    let expected = 30.0 / 500_000.0 + 542.0 / 2_000_000.0;
+    assert!((sim.next_event_time().unwrap() - expected).abs() < 1e-12);
+}
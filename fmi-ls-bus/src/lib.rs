@@ -19,6 +19,10 @@ pub enum FmiLsBusError {
     InvalidVariant(u32),
     #[error("Invalid operation code or size mismatch: {0}")]
     InvalidOperation(ls_bus::fmi3LsBusOperationCode),
+    #[error("Variable not found: {0}")]
+    UnknownVariable(String),
+    #[error(transparent)]
+    Fmi(#[from] fmi::Error),
 }
 
 pub trait LsBusOperation<'a>: Sized {
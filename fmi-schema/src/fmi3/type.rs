@@ -221,6 +221,29 @@ pub enum TypeDefinition {
     Clock(ClockType),
 }
 
+impl TypeDefinition {
+    /// The name of the type, as referenced by the `declaredType` attribute of variables.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Float32(t) => &t.name,
+            Self::Float64(t) => &t.name,
+            Self::Int8(t) => &t.name,
+            Self::UInt8(t) => &t.name,
+            Self::Int16(t) => &t.name,
+            Self::UInt16(t) => &t.name,
+            Self::Int32(t) => &t.name,
+            Self::UInt32(t) => &t.name,
+            Self::Int64(t) => &t.name,
+            Self::UInt64(t) => &t.name,
+            Self::Boolean(t) => &t.name,
+            Self::String(t) => &t.name,
+            Self::Binary(t) => &t.name,
+            Self::Enumeration(t) => &t.name,
+            Self::Clock(t) => &t.name,
+        }
+    }
+}
+
 #[derive(Default, PartialEq, Debug, hard_xml::XmlRead, hard_xml::XmlWrite)]
 #[xml(tag = "TypeDefinitions", strict(unknown_attribute, unknown_element))]
 pub struct TypeDefinitions {
@@ -254,4 +277,5 @@ fn test_type_definitions() {
 
     let types: TypeDefinitions = hard_xml::XmlRead::from_str(xml).unwrap();
     assert_eq!(types.type_definitions.len(), 3);
+    assert_eq!(types.type_definitions[2].name(), "Position");
 }
@@ -277,10 +277,22 @@ macro_rules! impl_float_type {
             pub start: Option<AttrList<$type>>,
             #[xml(attr = "initial")]
             pub initial: Option<Initial>,
+            #[xml(attr = "quantity")]
+            pub quantity: Option<String>,
+            #[xml(attr = "unit")]
+            pub unit: Option<String>,
+            #[xml(attr = "displayUnit")]
+            pub display_unit: Option<String>,
+            #[xml(attr = "relativeQuantity")]
+            pub relative_quantity: Option<bool>,
+            #[xml(attr = "unbounded")]
+            pub unbounded: Option<bool>,
             #[xml(attr = "min")]
             pub min: Option<$type>,
             #[xml(attr = "max")]
             pub max: Option<$type>,
+            #[xml(attr = "nominal")]
+            pub nominal: Option<$type>,
             #[xml(attr = "derivative")]
             pub derivative: Option<u32>,
             #[xml(attr = "reinit")]
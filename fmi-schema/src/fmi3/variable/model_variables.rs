@@ -1,7 +1,7 @@
 use super::{
-    AbstractVariableTrait, FmiBinary, FmiBoolean, FmiClock, FmiFloat32, FmiFloat64, FmiInt8,
-    FmiInt16, FmiInt32, FmiInt64, FmiString, FmiUInt8, FmiUInt16, FmiUInt32, FmiUInt64,
-    TypedArrayableVariableTrait,
+    AbstractVariableTrait, ArrayableVariableTrait, Dimension, FmiBinary, FmiBoolean, FmiClock,
+    FmiFloat32, FmiFloat64, FmiInt8, FmiInt16, FmiInt32, FmiInt64, FmiString, FmiUInt8, FmiUInt16,
+    FmiUInt32, FmiUInt64, TypedArrayableVariableTrait,
 };
 
 #[derive(hard_xml::XmlRead, hard_xml::XmlWrite, Debug, PartialEq)]
@@ -36,6 +36,49 @@ pub enum Variable {
     Clock(FmiClock),
 }
 
+impl Variable {
+    /// Returns the variable as an AbstractVariable
+    pub fn as_abstract(&self) -> &dyn AbstractVariableTrait {
+        match self {
+            Variable::Int8(var) => var as &dyn AbstractVariableTrait,
+            Variable::UInt8(var) => var as &dyn AbstractVariableTrait,
+            Variable::Int16(var) => var as &dyn AbstractVariableTrait,
+            Variable::UInt16(var) => var as &dyn AbstractVariableTrait,
+            Variable::Int32(var) => var as &dyn AbstractVariableTrait,
+            Variable::UInt32(var) => var as &dyn AbstractVariableTrait,
+            Variable::Int64(var) => var as &dyn AbstractVariableTrait,
+            Variable::UInt64(var) => var as &dyn AbstractVariableTrait,
+            Variable::Float32(var) => var as &dyn AbstractVariableTrait,
+            Variable::Float64(var) => var as &dyn AbstractVariableTrait,
+            Variable::Boolean(var) => var as &dyn AbstractVariableTrait,
+            Variable::String(var) => var as &dyn AbstractVariableTrait,
+            Variable::Binary(var) => var as &dyn AbstractVariableTrait,
+            Variable::Clock(var) => var as &dyn AbstractVariableTrait,
+        }
+    }
+
+    /// Returns the array dimensions of the variable, which are empty for scalar variables and
+    /// Clocks
+    pub fn dimensions(&self) -> &[Dimension] {
+        match self {
+            Variable::Int8(var) => var.dimensions(),
+            Variable::UInt8(var) => var.dimensions(),
+            Variable::Int16(var) => var.dimensions(),
+            Variable::UInt16(var) => var.dimensions(),
+            Variable::Int32(var) => var.dimensions(),
+            Variable::UInt32(var) => var.dimensions(),
+            Variable::Int64(var) => var.dimensions(),
+            Variable::UInt64(var) => var.dimensions(),
+            Variable::Float32(var) => var.dimensions(),
+            Variable::Float64(var) => var.dimensions(),
+            Variable::Boolean(var) => var.dimensions(),
+            Variable::String(var) => var.dimensions(),
+            Variable::Binary(var) => var.dimensions(),
+            Variable::Clock(_) => &[],
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Default, hard_xml::XmlRead, hard_xml::XmlWrite)]
 #[xml(tag = "ModelVariables")]
 pub struct ModelVariables {
@@ -70,22 +113,7 @@ impl ModelVariables {
 
     /// Returns an iterator over all the AbstractVariables in the model description
     pub fn iter_abstract(&self) -> impl Iterator<Item = &dyn AbstractVariableTrait> {
-        self.variables.iter().map(|v| match v {
-            Variable::Int8(var) => var as &dyn AbstractVariableTrait,
-            Variable::UInt8(var) => var as &dyn AbstractVariableTrait,
-            Variable::Int16(var) => var as &dyn AbstractVariableTrait,
-            Variable::UInt16(var) => var as &dyn AbstractVariableTrait,
-            Variable::Int32(var) => var as &dyn AbstractVariableTrait,
-            Variable::UInt32(var) => var as &dyn AbstractVariableTrait,
-            Variable::Int64(var) => var as &dyn AbstractVariableTrait,
-            Variable::UInt64(var) => var as &dyn AbstractVariableTrait,
-            Variable::Float32(var) => var as &dyn AbstractVariableTrait,
-            Variable::Float64(var) => var as &dyn AbstractVariableTrait,
-            Variable::Boolean(var) => var as &dyn AbstractVariableTrait,
-            Variable::String(var) => var as &dyn AbstractVariableTrait,
-            Variable::Binary(var) => var as &dyn AbstractVariableTrait,
-            Variable::Clock(var) => var as &dyn AbstractVariableTrait,
-        })
+        self.variables.iter().map(Variable::as_abstract)
     }
 
     /// Returns an iterator over all the float32 and float64 variables in the model description
@@ -244,6 +272,17 @@ impl ModelVariables {
             })
             .collect()
     }
+
+    /// Returns a vector of all Clock variables
+    pub fn clock(&self) -> Vec<&FmiClock> {
+        self.variables
+            .iter()
+            .filter_map(|v| match v {
+                Variable::Clock(var) => Some(var),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 /// Append a variable to the given `ModelVariables` struct
@@ -253,6 +253,27 @@ fn test_variable_with_all_attributes() {
     assert_eq!(var.dimensions()[0].as_fixed(), Some(2));
 }
 
+#[test]
+fn test_float_unit_attributes() {
+    let xml = r#"<Float64
+            name="v"
+            valueReference="5"
+            quantity="Velocity"
+            unit="m/s"
+            displayUnit="km/h"
+            relativeQuantity="false"
+            unbounded="true"
+            nominal="10"/>"#;
+
+    let var: FmiFloat64 = FmiFloat64::from_str(xml).unwrap();
+    assert_eq!(var.quantity.as_deref(), Some("Velocity"));
+    assert_eq!(var.unit.as_deref(), Some("m/s"));
+    assert_eq!(var.display_unit.as_deref(), Some("km/h"));
+    assert_eq!(var.relative_quantity, Some(false));
+    assert_eq!(var.unbounded, Some(true));
+    assert_eq!(var.nominal, Some(10.0));
+}
+
 #[test]
 fn test_dimension_with_value_reference() {
     let xml = r#"<Float32
@@ -20,6 +20,18 @@ fn main() -> anyhow::Result<()> {
         stats.num_events
     );
 
+    // CSV has no list type, so array outputs are written as one column per element
+    let outputs = if options.output_file.is_some() {
+        fmi_sim::sim::util::flatten_arrays(&outputs)?
+    } else {
+        outputs
+    };
+    let outputs = if options.mangle_names {
+        fmi_sim::sim::util::mangle_names(&outputs)?
+    } else {
+        outputs
+    };
+
     if let Some(output_file) = options.output_file {
         let file = std::fs::File::create(output_file).unwrap();
         arrow::csv::writer::WriterBuilder::new()
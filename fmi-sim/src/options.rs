@@ -6,6 +6,18 @@ pub enum SolverArg {
     #[clap(name = "euler")]
     #[default]
     Euler,
+
+    /// Variable-order BDF solver for stiff models
+    #[clap(name = "bdf")]
+    Bdf,
+
+    /// Adaptive Dormand-Prince 5(4) solver for non-stiff models
+    #[clap(name = "dopri5")]
+    Dopri5,
+
+    /// Classical Runge-Kutta solver of order 4 with a fixed step size (see `--step`)
+    #[clap(name = "rk4")]
+    Rk4,
 }
 
 #[derive(Default, Debug, clap::Args)]
@@ -34,6 +46,19 @@ pub struct CoSimulationOptions {
     pub early_return_allowed: bool,
 }
 
+#[derive(Default, Debug, clap::Args)]
+/// Perform a ScheduledExecution simulation
+pub struct ScheduledExecutionOptions {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    /// Connect an output Clock to a triggered input Clock, such that the model partition of the
+    /// input Clock is activated whenever the output Clock ticks. The format is
+    /// "outputClock=inputClock".
+    #[arg(long = "connect-clock")]
+    pub clock_connections: Vec<String>,
+}
+
 #[derive(Debug, clap::Subcommand)]
 pub enum Interface {
     #[cfg(feature = "me")]
@@ -44,9 +69,9 @@ pub enum Interface {
     #[command(alias = "cs")]
     CoSimulation(CoSimulationOptions),
 
-    /// Perform a ScheduledExecution simulation
     #[cfg(feature = "se")]
-    ScheduledExecution(CommonOptions),
+    #[command(alias = "se")]
+    ScheduledExecution(ScheduledExecutionOptions),
 }
 
 impl Default for Interface {
@@ -86,11 +111,12 @@ impl Display for Interface {
 
 #[derive(Default, Debug, clap::Args)]
 pub struct CommonOptions {
-    /// File containing initial serialized FMU state.
+    /// File containing initial serialized FMU state. The state is restored after initialization,
+    /// and the start time should match the time at which the state was saved.
     #[arg(long)]
     pub initial_fmu_state_file: Option<std::path::PathBuf>,
 
-    /// File to write final serialized FMU state.
+    /// File to write final serialized FMU state. The state is saved before the FMU is terminated.
     #[arg(long)]
     pub final_fmu_state_file: Option<std::path::PathBuf>,
 
@@ -110,7 +136,7 @@ pub struct CommonOptions {
     #[arg(long = "print-all")]
     pub print_all_variables: bool,
 
-    /// For ME simulation: Decides step size to use in forward Euler.
+    /// For ME simulation: Decides step size to use in forward Euler and RK4.
     /// For CS simulation: Decides communication step size for the stepping.
     /// Observe that if a small stepSize is used the number of saved outputs will still be limited
     /// by the number of output points. Default is to calculated a step size from the number of
@@ -118,14 +144,16 @@ pub struct CommonOptions {
     #[arg(long = "step")]
     pub step_size: Option<f64>,
 
+    /// Interval between output points. Takes precedence over -n.
     #[arg(long = "output-interval")]
     pub output_interval: Option<f64>,
 
     /// Maximum number of output points. "-n 0" means output at every step and the number of
-    /// outputs are decided by the -h option. Observe that no interpolation is used, output points
-    /// are taken at the steps.
-    #[arg(short = 'n', default_value = "500")]
-    pub num_steps: usize,
+    /// outputs are decided by the --step option. Observe that no interpolation is used, output
+    /// points are taken at the steps. Default is to use the output interval, the step size of the
+    /// 'DefaultExperiment', or 500 output points.
+    #[arg(short = 'n')]
+    pub num_steps: Option<usize>,
 
     /// Simulation start time, default is to use information from 'DefaultExperiment' as specified
     /// in the model description XML.
@@ -5,7 +5,7 @@ use fmi::{
         import::Fmi2Import,
         instance::{CoSimulation, InstanceCS},
     },
-    traits::FmiInstance,
+    traits::{FmiImport, FmiInstance},
 };
 
 use crate::{
@@ -14,8 +14,11 @@ use crate::{
         InputState, RecorderState, SimState, SimStateTrait, SimStats,
         interpolation::Linear,
         io::StartValues,
+        multi::{CoSimImport, CoSimSlave, Slave, SlaveStep, StepResult},
         params::SimParams,
-        traits::{InstRecordValues, InstSetValues, SimApplyStartValues},
+        traits::{
+            ImportSchemaBuilder, InstRecordValues, InstSetValues, SimApplyStartValues, SimFmuState,
+        },
     },
 };
 
@@ -43,25 +46,28 @@ impl SimApplyStartValues<InstanceCS> for SimState<InstanceCS> {
         &mut self,
         start_values: &StartValues<<InstanceCS as FmiInstance>::ValueRef>,
     ) -> Result<(), Error> {
-        start_values.variables.iter().for_each(|(vr, ary)| {
-            self.inst.set_array(&[*vr], ary);
-        });
+        for (vr, ary) in &start_values.variables {
+            self.inst.set_array(&[*vr], ary)?;
+        }
         Ok(())
     }
 }
 
 impl SimState<InstanceCS> {
     /// Main loop of the co-simulation
-    pub fn main_loop(&mut self) -> Result<SimStats, Fmi2Error> {
+    pub fn main_loop(&mut self) -> Result<SimStats, Error> {
         let mut stats = SimStats::default();
 
         loop {
-            let time = self.sim_params.start_time
-                + stats.num_steps as f64 * self.sim_params.output_interval;
+            let time =
+                self.sim_params.start_time + stats.num_steps as f64 * self.sim_params.step_size;
 
-            self.inst
-                .record_outputs(time, &mut self.recorder_state)
-                .expect("Failed to record outputs");
+            let output_due = self.recorder_state.output_due(time);
+            if output_due || time >= self.sim_params.stop_time {
+                self.inst
+                    .record_outputs(time, &mut self.recorder_state)
+                    .expect("Failed to record outputs");
+            }
 
             self.input_state
                 .apply_input::<Linear>(time, &mut self.inst, true, true, false)
@@ -72,13 +78,10 @@ impl SimState<InstanceCS> {
                 break;
             }
 
-            match self
-                .inst
-                .do_step(time, self.sim_params.output_interval, true)
-            {
+            match self.inst.do_step(time, self.sim_params.step_size, true) {
                 Err(Fmi2Error::Discard) => {
-                    if self.inst.terminated()? {
-                        let time = self.inst.last_successful_time()?;
+                    if self.inst.terminated().map_err(fmi::Error::from)? {
+                        let time = self.inst.last_successful_time().map_err(fmi::Error::from)?;
 
                         self.inst
                             .record_outputs(time, &mut self.recorder_state)
@@ -88,17 +91,101 @@ impl SimState<InstanceCS> {
                         break;
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(fmi::Error::from(e).into()),
                 _ => {}
             }
 
             stats.num_steps += 1;
         }
 
-        //TODO save final FMU state
+        if let Some(path) = self.sim_params.final_fmu_state_file.clone() {
+            self.save_fmu_state(&path)?;
+        }
 
-        self.inst.terminate().map_err(Into::into)?;
+        self.inst.terminate().map_err(fmi::Error::from)?;
 
         Ok(stats)
     }
 }
+
+impl SlaveStep for SimState<InstanceCS> {
+    fn slave_exit_initialization_mode(&mut self) -> Result<(), Error> {
+        self.inst
+            .exit_initialization_mode()
+            .map_err(fmi::Error::from)?;
+        Ok(())
+    }
+
+    fn slave_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error> {
+        let terminate = match self.inst.do_step(time, step_size, true) {
+            Ok(_) => false,
+            Err(Fmi2Error::Discard) if self.inst.terminated().map_err(fmi::Error::from)? => true,
+            Err(e) => return Err(fmi::Error::from(e).into()),
+        };
+        Ok(StepResult {
+            terminate,
+            ..Default::default()
+        })
+    }
+
+    /// FMI 2.0 Co-Simulation has no Event Mode, so no events are ever encountered.
+    fn slave_handle_events(&mut self, _time: f64) -> Result<bool, Error> {
+        Ok(false)
+    }
+}
+
+impl CoSimImport for Fmi2Import {
+    fn instantiate_slave(
+        &self,
+        name: &str,
+        start_values: &[String],
+    ) -> Result<Box<dyn CoSimSlave>, Error> {
+        let model_description = self.model_description();
+        let sim_params =
+            SimParams::new_from_options(&Default::default(), model_description, false, false);
+        let start_values = self.parse_start_values(start_values)?;
+
+        let mut sim_state = SimState {
+            input_state: InputState::new(self, None)?,
+            recorder_state: RecorderState::new(self, &sim_params),
+            inst: self.instantiate_cs(name, false, true)?,
+            sim_params,
+            event_flags: EventFlags::default(),
+        };
+        sim_state.apply_start_values(&start_values)?;
+
+        // Variable indices start at 1 in the modelDescription
+        let vr = |index: u32| {
+            let index = (index as usize).checked_sub(1)?;
+            model_description
+                .model_variables
+                .variables
+                .get(index)
+                .map(|variable| variable.value_reference)
+        };
+        // An empty dependency list can't be told apart from a missing one, so it is taken to
+        // depend on all inputs.
+        let dependencies = model_description
+            .model_structure
+            .outputs
+            .unknowns
+            .iter()
+            .filter_map(|unknown| {
+                let dependencies = (!unknown.dependencies.is_empty())
+                    .then(|| unknown.dependencies.iter().filter_map(|&i| vr(i)).collect());
+                Some((vr(unknown.index)?, dependencies))
+            })
+            .collect();
+
+        Ok(Box::new(Slave {
+            name: name.to_owned(),
+            sim_state,
+            inputs: self
+                .continuous_inputs()
+                .chain(self.discrete_inputs())
+                .collect(),
+            outputs: self.outputs().collect(),
+            dependencies,
+        }))
+    }
+}
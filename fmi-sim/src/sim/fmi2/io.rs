@@ -1,9 +1,11 @@
 //! FMI2-specific input and output implementation
 
+use std::sync::Arc;
+
 use arrow::{
     array::{
-        ArrayRef, AsArray, BooleanBuilder, Float64Array, Float64Builder, Int32Builder,
-        StringBuilder, downcast_array,
+        ArrayRef, AsArray, BooleanArray, BooleanBuilder, Float64Array, Float64Builder, Int32Array,
+        Int32Builder, StringArray, StringBuilder, downcast_array,
     },
     datatypes::{DataType, Float64Type, Int32Type},
 };
@@ -14,7 +16,7 @@ use crate::sim::{
     RecorderState,
     interpolation::{Interpolate, PreLookup},
     io::Recorder,
-    traits::{InstRecordValues, InstSetValues},
+    traits::{InstClocks, InstGetValues, InstRecordValues, InstSetValues},
 };
 
 macro_rules! impl_recorder {
@@ -97,10 +99,53 @@ macro_rules! impl_record_values {
     };
 }
 
+macro_rules! impl_get_values {
+    ($inst:ty) => {
+        impl InstGetValues for $inst {
+            fn get_array(
+                &mut self,
+                vr: <Self as FmiInstance>::ValueRef,
+                data_type: &DataType,
+            ) -> anyhow::Result<ArrayRef> {
+                let array: ArrayRef = match data_type {
+                    DataType::Boolean => {
+                        let mut value = [0];
+                        self.get_boolean(&[vr], &mut value)?;
+                        Arc::new(BooleanArray::from(vec![value[0] > 0]))
+                    }
+                    DataType::Int32 => {
+                        let mut value = [0];
+                        self.get_integer(&[vr], &mut value)?;
+                        Arc::new(Int32Array::from(value.to_vec()))
+                    }
+                    DataType::Float64 => {
+                        let mut value = [0.0];
+                        self.get_real(&[vr], &mut value)?;
+                        Arc::new(Float64Array::from(value.to_vec()))
+                    }
+                    DataType::Utf8 => {
+                        let mut value = [std::ffi::CString::default()];
+                        self.get_string(&[vr], &mut value)?;
+                        Arc::new(StringArray::from(vec![
+                            value[0].to_string_lossy().into_owned(),
+                        ]))
+                    }
+                    _ => anyhow::bail!("Unsupported data type: {data_type:?}"),
+                };
+                Ok(array)
+            }
+        }
+    };
+}
+
 macro_rules! impl_set_values {
     ($t:ty) => {
         impl InstSetValues for $t {
-            fn set_array(&mut self, vrs: &[Self::ValueRef], values: &ArrayRef) {
+            fn set_array(
+                &mut self,
+                vrs: &[Self::ValueRef],
+                values: &ArrayRef,
+            ) -> anyhow::Result<()> {
                 match values.data_type() {
                     DataType::Boolean => {
                         let values = values
@@ -108,13 +153,13 @@ macro_rules! impl_set_values {
                             .iter()
                             .map(|x| x.unwrap() as i32)
                             .collect_vec();
-                        let _ = self.set_boolean(vrs, &values);
+                        self.set_boolean(vrs, &values)?;
                     }
                     DataType::Int32 => {
-                        let _ = self.set_integer(vrs, values.as_primitive::<Int32Type>().values());
+                        self.set_integer(vrs, values.as_primitive::<Int32Type>().values())?;
                     }
                     DataType::Float64 => {
-                        let _ = self.set_real(vrs, values.as_primitive::<Float64Type>().values());
+                        self.set_real(vrs, values.as_primitive::<Float64Type>().values())?;
                     }
                     DataType::Utf8 => {
                         let cstrings: Vec<std::ffi::CString> = values
@@ -123,10 +168,11 @@ macro_rules! impl_set_values {
                             .flatten()
                             .map(|s| std::ffi::CString::new(s).unwrap())
                             .collect();
-                        let _ = self.set_string(vrs, &cstrings);
+                        self.set_string(vrs, &cstrings)?;
                     }
                     _ => unimplemented!("Unsupported data type"),
                 }
+                Ok(())
             }
 
             fn set_interpolated<I: Interpolate>(
@@ -155,10 +201,17 @@ macro_rules! impl_set_values {
     };
 }
 
+#[cfg(feature = "cs")]
+impl InstClocks for fmi::fmi2::instance::InstanceCS {}
+#[cfg(feature = "me")]
+impl InstClocks for fmi::fmi2::instance::InstanceME {}
+
 #[cfg(feature = "cs")]
 impl_set_values!(fmi::fmi2::instance::InstanceCS);
 #[cfg(feature = "cs")]
 impl_record_values!(fmi::fmi2::instance::InstanceCS);
+#[cfg(feature = "cs")]
+impl_get_values!(fmi::fmi2::instance::InstanceCS);
 
 #[cfg(feature = "me")]
 impl_set_values!(fmi::fmi2::instance::InstanceME);
@@ -1,13 +1,16 @@
+use std::path::Path;
+
+use anyhow::Context;
 use arrow::array::RecordBatch;
 
-use fmi::fmi2::import::Fmi2Import;
+use fmi::{fmi2::import::Fmi2Import, traits::FmiStatus};
 
 use crate::{
     Error,
     options::{CoSimulationOptions, ModelExchangeOptions},
     sim::{
         InputState, RecorderState, SimState, SimStateTrait,
-        traits::{ImportSchemaBuilder, SimInitialize},
+        traits::{ImportSchemaBuilder, SimFmuState, SimInitialize},
     },
 };
 
@@ -20,6 +23,98 @@ mod io;
 mod me;
 mod schema;
 
+macro_rules! impl_sim_fmu_state {
+    ($inst:ty) => {
+        impl SimFmuState for SimState<$inst> {
+            fn restore_fmu_state(&mut self, path: &Path) -> Result<(), Error> {
+                let buffer = std::fs::read(path)
+                    .with_context(|| format!("Reading FMU state from {}", path.display()))?;
+                let state = self
+                    .inst
+                    .deserialize_fmu_state(&buffer)
+                    .map_err(fmi::Error::from)?;
+                self.inst
+                    .set_fmu_state(&state)
+                    .ok()
+                    .map_err(fmi::Error::from)?;
+                log::debug!("Restored FMU state from {}", path.display());
+                Ok(())
+            }
+
+            fn save_fmu_state(&mut self, path: &Path) -> Result<(), Error> {
+                let state = self.inst.get_fmu_state().map_err(fmi::Error::from)?;
+                let buffer = self
+                    .inst
+                    .serialize_fmu_state(&state)
+                    .map_err(fmi::Error::from)?;
+                std::fs::write(path, buffer)
+                    .with_context(|| format!("Writing FMU state to {}", path.display()))?;
+                log::debug!("Saved FMU state to {}", path.display());
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "me")]
+impl_sim_fmu_state!(fmi::fmi2::instance::InstanceME);
+#[cfg(feature = "cs")]
+impl_sim_fmu_state!(fmi::fmi2::instance::InstanceCS);
+
+/// Parameters for the BDF solver, which builds its Jacobian from directional derivatives if the FMU
+/// provides them.
+#[cfg(feature = "me")]
+fn bdf_params(import: &Fmi2Import) -> super::solver::BdfParams<fmi::fmi2::instance::InstanceME> {
+    use fmi::{fmi2::instance::Common, schema::fmi2::ScalarVariableElement, traits::FmiImport};
+
+    let model_description = import.model_description();
+    let provides_directional_derivative = model_description
+        .model_exchange
+        .as_ref()
+        .and_then(|me| me.provides_directional_derivative)
+        .unwrap_or(false);
+    if !provides_directional_derivative {
+        return Default::default();
+    }
+
+    // Value references of the state derivatives, and of the states they belong to. The model
+    // structure and the `derivative` attribute refer to variables by their 1-based index.
+    let variables = &model_description.model_variables.variables;
+    let by_index = |idx: u32| variables.get((idx as usize).checked_sub(1)?);
+    let pairs: Option<Vec<(u32, u32)>> = model_description
+        .model_structure
+        .derivatives
+        .unknowns
+        .iter()
+        .map(|unknown| {
+            let der = by_index(unknown.index)?;
+            let ScalarVariableElement::Real(real) = &der.elem else {
+                return None;
+            };
+            let state = by_index(real.derivative?)?;
+            Some((der.value_reference, state.value_reference))
+        })
+        .collect();
+    let Some((derivatives, states)): Option<(Vec<u32>, Vec<u32>)> =
+        pairs.map(|pairs| pairs.into_iter().unzip())
+    else {
+        log::warn!("Could not resolve the continuous states, using finite differences");
+        return Default::default();
+    };
+
+    log::debug!("Using directional derivatives for the BDF Jacobian");
+    super::solver::BdfParams {
+        directional_derivative: Some(Box::new(move |inst, seed, sensitivity| {
+            inst.get_directional_derivative(&derivatives, &states, seed, sensitivity)
+                .map(|_| ())
+                .map_err(|e| {
+                    log::error!("Directional derivative failed: {e}");
+                    super::solver::SolverError::StepError
+                })
+        })),
+    }
+}
+
 impl FmiSim for Fmi2Import {
     #[cfg(feature = "me")]
     fn simulate_me(
@@ -27,7 +122,10 @@ impl FmiSim for Fmi2Import {
         options: &ModelExchangeOptions,
         input_data: Option<RecordBatch>,
     ) -> Result<(RecordBatch, SimStats), Error> {
-        use crate::sim::{solver, traits::SimMe};
+        use crate::{
+            options::SolverArg,
+            sim::{solver, traits::SimMe},
+        };
         use fmi::{fmi2::instance::InstanceME, traits::FmiImport};
 
         let sim_params =
@@ -41,19 +139,38 @@ impl FmiSim for Fmi2Import {
         let nx = self.model_description().num_states();
         let nz = self.model_description().num_event_indicators();
 
-        let solver: solver::Euler = solver::Solver::<InstanceME>::new(
-            sim_params.start_time,
-            sim_params.tolerance.unwrap_or_default(),
-            nx,
-            nz,
-            (),
-        );
+        let start_time = sim_params.start_time;
+        let tol = sim_params.tolerance.unwrap_or_default();
 
         let mut sim_state =
             SimState::<InstanceME>::new(self, sim_params, input_state, recorder_state)?;
         sim_state.initialize(start_values, options.common.initial_fmu_state_file.as_ref())?;
 
-        let stats = sim_state.main_loop(solver)?;
+        let stats = match options.solver {
+            SolverArg::Euler => {
+                let solver: solver::Euler =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, ());
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Bdf => {
+                let solver: solver::Bdf<InstanceME> =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, bdf_params(self));
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Dopri5 => {
+                let solver: solver::Dopri5 =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, ());
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Rk4 => {
+                let params = solver::Rk4Params {
+                    step_size: options.common.step_size,
+                };
+                let solver: solver::Rk4 =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, params);
+                sim_state.main_loop(solver)?
+            }
+        };
 
         Ok((sim_state.recorder_state.finish(), stats))
     }
@@ -80,7 +197,7 @@ impl FmiSim for Fmi2Import {
         let mut sim_state =
             SimState::<InstanceCS>::new(self, sim_params, input_state, recorder_state)?;
         sim_state.initialize(start_values, options.common.initial_fmu_state_file.as_ref())?;
-        let stats = sim_state.main_loop().map_err(fmi::Error::from)?;
+        let stats = sim_state.main_loop()?;
 
         Ok((sim_state.recorder_state.finish(), stats))
     }
@@ -93,6 +93,20 @@ where
             })
     }
 
+    fn variables(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
+        self.model_description()
+            .model_variables
+            .variables
+            .iter()
+            .filter(|v| v.causality != Causality::Independent)
+            .map(|v| {
+                (
+                    Field::new(&v.name, v.elem.data_type(), false),
+                    v.value_reference,
+                )
+            })
+    }
+
     fn parse_start_values(
         &self,
         start_values: &[String],
@@ -2,7 +2,7 @@ use anyhow::Context;
 use fmi::{
     EventFlags,
     fmi3::{CoSimulation, Fmi3Model, import::Fmi3Import, instance::InstanceCS},
-    traits::FmiInstance,
+    traits::{FmiImport, FmiInstance},
 };
 
 use crate::{
@@ -10,8 +10,12 @@ use crate::{
     sim::{
         InputState, RecorderState, SimState, SimStateTrait, SimStats,
         interpolation::Linear,
+        multi::{CoSimImport, CoSimSlave, Slave, SlaveStep, StepResult},
         params::SimParams,
-        traits::{InstRecordValues, SimHandleEvents},
+        traits::{
+            ImportSchemaBuilder, InstRecordValues, SimApplyStartValues, SimFmuState,
+            SimHandleEvents,
+        },
     },
 };
 
@@ -50,9 +54,15 @@ impl SimState<InstanceCS> {
         }
 
         let mut time = self.sim_params.start_time;
+        // Whether an event has been handled at `time`
+        let mut event_handled = false;
 
         loop {
-            self.inst.record_outputs(time, &mut self.recorder_state)?;
+            let output_due = self.recorder_state.output_due(time);
+            if output_due || event_handled || time >= self.sim_params.stop_time {
+                self.inst.record_outputs(time, &mut self.recorder_state)?;
+            }
+            event_handled = false;
 
             if time >= self.sim_params.stop_time {
                 break;
@@ -60,11 +70,11 @@ impl SimState<InstanceCS> {
 
             // calculate next time point
             let next_regular_point = self.sim_params.start_time
-                + (stats.num_steps + 1) as f64 * self.sim_params.output_interval;
-            let next_input_event_time = self.input_state.next_input_event(time);
+                + (stats.num_steps + 1) as f64 * self.sim_params.step_size;
+            let next_input_event_time = self.input_state.next_input_event(time)?;
             // use `next_input_event` if it is earlier than `next_regular_point`
             let next_communication_point = next_input_event_time.min(next_regular_point);
-            let input_event = next_regular_point > next_input_event_time;
+            let input_event = next_regular_point >= next_input_event_time;
 
             let step_size = next_communication_point - time;
 
@@ -115,6 +125,7 @@ impl SimState<InstanceCS> {
             if self.sim_params.event_mode_used && (input_event || event_encountered) {
                 log::trace!("Event encountered at t = {time}");
                 let (_reset_solver, terminate) = self.handle_events(time, input_event)?;
+                event_handled = true;
 
                 if terminate {
                     break;
@@ -127,9 +138,114 @@ impl SimState<InstanceCS> {
             }
         }
 
+        if let Some(path) = self.sim_params.final_fmu_state_file.clone() {
+            self.save_fmu_state(&path)?;
+        }
+
         self.inst.terminate().ok().context("terminate")?;
 
         stats.end_time = time;
         Ok(stats)
     }
 }
+
+impl SlaveStep for SimState<InstanceCS> {
+    fn slave_exit_initialization_mode(&mut self) -> Result<(), Error> {
+        self.inst
+            .exit_initialization_mode()
+            .map_err(fmi::Error::from)?;
+        self.recorder_state.resolve_shapes(&mut self.inst)?;
+        if self.sim_params.event_mode_used {
+            self.inst.enter_step_mode().map_err(fmi::Error::from)?;
+        }
+        Ok(())
+    }
+
+    fn slave_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error> {
+        let mut event_encountered = false;
+        let mut terminate_simulation = false;
+        let mut early_return = false;
+        let mut last_successful_time = 0.0;
+        self.inst
+            .do_step(
+                time,
+                step_size,
+                true,
+                &mut event_encountered,
+                &mut terminate_simulation,
+                &mut early_return,
+                &mut last_successful_time,
+            )
+            .ok()
+            .context("do_step")?;
+        Ok(StepResult {
+            terminate: terminate_simulation,
+            event_encountered: event_encountered && self.sim_params.event_mode_used,
+            early_return: early_return.then_some(last_successful_time),
+        })
+    }
+
+    fn slave_handle_events(&mut self, time: f64) -> Result<bool, Error> {
+        let (_reset_solver, terminate) = self.handle_events(time, false)?;
+        if !terminate {
+            self.inst
+                .enter_step_mode()
+                .ok()
+                .context("enter_step_mode")?;
+        }
+        Ok(terminate)
+    }
+}
+
+impl CoSimImport for Fmi3Import {
+    fn instantiate_slave(
+        &self,
+        name: &str,
+        start_values: &[String],
+    ) -> Result<Box<dyn CoSimSlave>, Error> {
+        let model_description = self.model_description();
+        // Use Event Mode if the FMU supports it, so that events can be handled at communication
+        // points
+        let event_mode_used = model_description
+            .co_simulation
+            .as_ref()
+            .and_then(|cs| cs.has_event_mode)
+            .unwrap_or(false);
+        let sim_params = SimParams::new_from_options(
+            &Default::default(),
+            model_description,
+            event_mode_used,
+            false,
+        );
+        let start_values = self.parse_start_values(start_values)?;
+
+        let mut sim_state = SimState {
+            input_state: InputState::new(self, None)?,
+            recorder_state: RecorderState::new(self, &sim_params),
+            inst: self.instantiate_cs(name, false, true, event_mode_used, false, &[])?,
+            sim_params,
+            event_flags: EventFlags::default(),
+        };
+        sim_state.apply_start_values(&start_values)?;
+
+        let dependencies = model_description
+            .model_structure
+            .outputs()
+            .map(|unknown| {
+                let dependencies = unknown.dependencies.as_ref().map(|deps| deps.to_vec());
+                (unknown.value_reference, dependencies)
+            })
+            .collect();
+
+        Ok(Box::new(Slave {
+            name: name.to_owned(),
+            sim_state,
+            inputs: self
+                .continuous_inputs()
+                .chain(self.discrete_inputs())
+                .collect(),
+            outputs: self.outputs().collect(),
+            dependencies,
+        }))
+    }
+}
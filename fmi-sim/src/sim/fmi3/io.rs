@@ -1,29 +1,38 @@
 //! FMI3-specific input and output implementation
 
+use std::sync::Arc;
+
 use anyhow::Context;
 use arrow::{
     array::{
-        ArrayRef, AsArray, BinaryBuilder, BooleanBuilder, Float32Array, Float32Builder,
-        Float64Array, Float64Builder, Int8Builder, Int16Builder, Int32Builder, Int64Builder,
-        StringBuilder, UInt8Array, UInt8Builder, UInt16Array, UInt16Builder, UInt32Array,
-        UInt32Builder, UInt64Array, UInt64Builder, downcast_array,
+        Array, ArrayBuilder, ArrayRef, AsArray, BinaryArray, BinaryBuilder, BooleanArray,
+        BooleanBuilder, FixedSizeListBuilder, Float32Array, Float32Builder, Float64Array,
+        Float64Builder, Int8Array, Int8Builder, Int16Array, Int16Builder, Int32Array, Int32Builder,
+        Int64Array, Int64Builder, ListBuilder, PrimitiveArray, StringArray, StringBuilder,
+        UInt8Array, UInt8Builder, UInt16Array, UInt16Builder, UInt32Array, UInt32Builder,
+        UInt64Array, UInt64Builder, downcast_array,
     },
     datatypes::{
-        DataType, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type, UInt8Type,
-        UInt16Type, UInt32Type, UInt64Type,
+        ArrowPrimitiveType, DataType, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type,
+        Int64Type, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
     },
 };
+use num_traits::NumCast;
 
 use crate::sim::{
     RecorderState,
     interpolation::{Interpolate, PreLookup},
     io::Recorder,
-    traits::{InstRecordValues, InstSetValues},
+    traits::{InstClocks, InstGetValues, InstRecordValues, InstSetValues},
+    util::list_offsets,
 };
 
-use fmi::{fmi3::GetSet, traits::FmiInstance};
+use fmi::{
+    fmi3::{Common, GetSet, instance::Instance},
+    traits::FmiInstance,
+};
 
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 
 const DEFAULT_BINARY_BUFFER_SIZE: usize = 1024;
 
@@ -39,6 +48,67 @@ macro_rules! impl_recorder {
     }};
 }
 
+macro_rules! impl_array_recorder {
+    ($getter:ident, $builder_type:ident, $inst:expr, $vr:ident, $builder:ident, $len:expr) => {{
+        let mut values = vec![std::default::Default::default(); $len];
+        $inst.$getter(&[*$vr], &mut values)?;
+        append_list_row::<$builder_type>($builder, |items| items.append_slice(&values));
+    }};
+}
+
+/// Append one row of array elements to the builder of a `FixedSizeList` or `List` column.
+fn append_list_row<B: ArrayBuilder>(
+    builder: &mut Box<dyn ArrayBuilder>,
+    append: impl FnOnce(&mut B),
+) {
+    let builder = builder.as_any_mut();
+    if let Some(list) = builder.downcast_mut::<FixedSizeListBuilder<Box<dyn ArrayBuilder>>>() {
+        append(
+            list.values()
+                .as_any_mut()
+                .downcast_mut::<B>()
+                .expect("unexpected list item type"),
+        );
+        list.append(true);
+    } else {
+        let list = builder
+            .downcast_mut::<ListBuilder<Box<dyn ArrayBuilder>>>()
+            .expect("column is not a list");
+        append(
+            list.values()
+                .as_any_mut()
+                .downcast_mut::<B>()
+                .expect("unexpected list item type"),
+        );
+        list.append(true);
+    }
+}
+
+/// Interpolate each element of an array input, whose rows are lists of equal length.
+fn interpolate_elements<I: Interpolate, T: ArrowPrimitiveType>(
+    pl: &PreLookup,
+    array: &ArrayRef,
+) -> anyhow::Result<Vec<T::Native>>
+where
+    T::Native: NumCast,
+{
+    let (values, offsets) = list_offsets(array).context("Array input is not a list")?;
+    let len = match array.data_type() {
+        DataType::FixedSizeList(_, size) => *size as usize,
+        _ => array.as_list::<i32>().value_length(0) as usize,
+    };
+    let values = values.as_primitive::<T>();
+
+    Ok((0..len)
+        .map(|element| {
+            let column = PrimitiveArray::<T>::from_iter_values(
+                offsets.iter().map(|offset| values.value(offset + element)),
+            );
+            I::interpolate(pl, &column)
+        })
+        .collect())
+}
+
 macro_rules! impl_record_values {
     ($inst:ty) => {
         impl InstRecordValues for $inst {
@@ -55,14 +125,124 @@ macro_rules! impl_record_values {
                     value_reference: vr,
                     builder,
                     binary_max_size,
+                    clock,
+                    shape,
+                    ..
                 } in &mut recorder.recorders
                 {
+                    if *clock {
+                        builder
+                            .as_any_mut()
+                            .downcast_mut::<BooleanBuilder>()
+                            .expect("column is not Boolean")
+                            .append_value(recorder.active_clocks.contains(vr));
+                        continue;
+                    }
+
                     log::trace!(
                         "Recording variable VR={} of type {:?}",
                         vr,
                         field.data_type()
                     );
                     match field.data_type() {
+                        DataType::FixedSizeList(item, _) | DataType::List(item) => {
+                            let len = shape
+                                .as_ref()
+                                .context("Array shape has not been resolved")?
+                                .iter()
+                                .product::<usize>();
+                            match item.data_type() {
+                                DataType::Boolean => impl_array_recorder!(
+                                    get_boolean,
+                                    BooleanBuilder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Int8 => impl_array_recorder!(
+                                    get_int8,
+                                    Int8Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Int16 => impl_array_recorder!(
+                                    get_int16,
+                                    Int16Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Int32 => impl_array_recorder!(
+                                    get_int32,
+                                    Int32Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Int64 => impl_array_recorder!(
+                                    get_int64,
+                                    Int64Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::UInt8 => impl_array_recorder!(
+                                    get_uint8,
+                                    UInt8Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::UInt16 => impl_array_recorder!(
+                                    get_uint16,
+                                    UInt16Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::UInt32 => impl_array_recorder!(
+                                    get_uint32,
+                                    UInt32Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::UInt64 => impl_array_recorder!(
+                                    get_uint64,
+                                    UInt64Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Float32 => impl_array_recorder!(
+                                    get_float32,
+                                    Float32Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                DataType::Float64 => impl_array_recorder!(
+                                    get_float64,
+                                    Float64Builder,
+                                    self,
+                                    vr,
+                                    builder,
+                                    len
+                                ),
+                                t => anyhow::bail!("Unsupported array data type: {t:?}"),
+                            }
+                        }
                         DataType::Boolean => {
                             impl_recorder!(get_boolean, BooleanBuilder, self, vr, builder)
                         }
@@ -130,47 +310,99 @@ macro_rules! impl_record_values {
     };
 }
 
+macro_rules! impl_getter {
+    ($getter:ident, $array_type:ident, $inst:expr, $vr:expr) => {{
+        let mut value = [std::default::Default::default()];
+        $inst.$getter(&[$vr], &mut value)?;
+        Arc::new($array_type::from(value.to_vec())) as ArrayRef
+    }};
+}
+
+macro_rules! impl_get_values {
+    ($inst:ty) => {
+        impl InstGetValues for $inst {
+            fn get_array(
+                &mut self,
+                vr: <Self as FmiInstance>::ValueRef,
+                data_type: &DataType,
+            ) -> anyhow::Result<ArrayRef> {
+                let array = match data_type {
+                    DataType::Boolean => impl_getter!(get_boolean, BooleanArray, self, vr),
+                    DataType::Int8 => impl_getter!(get_int8, Int8Array, self, vr),
+                    DataType::Int16 => impl_getter!(get_int16, Int16Array, self, vr),
+                    DataType::Int32 => impl_getter!(get_int32, Int32Array, self, vr),
+                    DataType::Int64 => impl_getter!(get_int64, Int64Array, self, vr),
+                    DataType::UInt8 => impl_getter!(get_uint8, UInt8Array, self, vr),
+                    DataType::UInt16 => impl_getter!(get_uint16, UInt16Array, self, vr),
+                    DataType::UInt32 => impl_getter!(get_uint32, UInt32Array, self, vr),
+                    DataType::UInt64 => impl_getter!(get_uint64, UInt64Array, self, vr),
+                    DataType::Float32 => impl_getter!(get_float32, Float32Array, self, vr),
+                    DataType::Float64 => impl_getter!(get_float64, Float64Array, self, vr),
+                    DataType::Binary => {
+                        let mut data = vec![0u8; DEFAULT_BINARY_BUFFER_SIZE];
+                        let mut value = [data.as_mut_slice()];
+                        let sizes = self
+                            .get_binary(&[vr], &mut value)
+                            .context("Failed to get binary data")?;
+                        data.truncate(sizes.first().copied().unwrap_or(0));
+                        Arc::new(BinaryArray::from_vec(vec![data.as_slice()]))
+                    }
+                    DataType::Utf8 => {
+                        let mut value = [std::ffi::CString::default()];
+                        self.get_string(&[vr], &mut value)?;
+                        Arc::new(StringArray::from(vec![
+                            value[0].to_string_lossy().into_owned(),
+                        ]))
+                    }
+                    _ => anyhow::bail!("Unsupported data type: {data_type:?}"),
+                };
+                Ok(array)
+            }
+        }
+    };
+}
+
 macro_rules! impl_set_values {
     ($t:ty) => {
         impl InstSetValues for $t {
-            fn set_array(&mut self, vrs: &[Self::ValueRef], values: &ArrayRef) {
+            fn set_array(&mut self, vrs: &[Self::ValueRef], values: &ArrayRef) -> anyhow::Result<()> {
                 match values.data_type() {
                     DataType::Boolean => {
                         let values = values.as_boolean().iter().map(|x| x.unwrap()).collect_vec();
-                        self.set_boolean(vrs, &values).unwrap();
+                        self.set_boolean(vrs, &values)?;
                     }
                     DataType::Int8 => {
-                        self.set_int8(vrs, values.as_primitive::<Int8Type>().values()).unwrap();
+                        self.set_int8(vrs, values.as_primitive::<Int8Type>().values())?;
                     }
                     DataType::Int16 => {
-                        self.set_int16(vrs, values.as_primitive::<Int16Type>().values()).unwrap();
+                        self.set_int16(vrs, values.as_primitive::<Int16Type>().values())?;
                     }
                     DataType::Int32 => {
-                        self.set_int32(vrs, values.as_primitive::<Int32Type>().values()).unwrap();
+                        self.set_int32(vrs, values.as_primitive::<Int32Type>().values())?;
                     }
                     DataType::Int64 => {
-                        self.set_int64(vrs, values.as_primitive::<Int64Type>().values()).unwrap();
+                        self.set_int64(vrs, values.as_primitive::<Int64Type>().values())?;
                     }
                     DataType::UInt8 => {
-                        self.set_uint8(vrs, values.as_primitive::<UInt8Type>().values()).unwrap();
+                        self.set_uint8(vrs, values.as_primitive::<UInt8Type>().values())?;
                     }
                     DataType::UInt16 => {
-                        self.set_uint16(vrs, values.as_primitive::<UInt16Type>().values()).unwrap();
+                        self.set_uint16(vrs, values.as_primitive::<UInt16Type>().values())?;
                     }
                     DataType::UInt32 => {
-                        self.set_uint32(vrs, values.as_primitive::<UInt32Type>().values()).unwrap();
+                        self.set_uint32(vrs, values.as_primitive::<UInt32Type>().values())?;
                     }
                     DataType::UInt64 => {
-                        self.set_uint64(vrs, values.as_primitive::<UInt64Type>().values()).unwrap();
+                        self.set_uint64(vrs, values.as_primitive::<UInt64Type>().values())?;
                     }
                     DataType::Float16 => {
                         unimplemented!()
                     }
                     DataType::Float32 => {
-                        self.set_float32(vrs, values.as_primitive::<Float32Type>().values()).unwrap();
+                        self.set_float32(vrs, values.as_primitive::<Float32Type>().values())?;
                     }
                     DataType::Float64 => {
-                        self.set_float64(vrs, values.as_primitive::<Float64Type>().values()).unwrap();
+                        self.set_float64(vrs, values.as_primitive::<Float64Type>().values())?;
                     }
                     DataType::Binary => {
                         let binary_refs: Vec<&[u8]> = values
@@ -178,7 +410,7 @@ macro_rules! impl_set_values {
                             .iter()
                             .filter_map(|opt| opt) // Filter out None values
                             .collect();
-                        let _ = self.set_binary(vrs, &binary_refs);
+                        self.set_binary(vrs, &binary_refs)?;
                     }
                     DataType::FixedSizeBinary(_) => todo!(),
                     DataType::LargeBinary => todo!(),
@@ -189,11 +421,19 @@ macro_rules! impl_set_values {
                             .filter_map(|opt| opt) // Filter out None values
                             .map(|s| std::ffi::CString::new(s).unwrap())
                             .collect();
-                        let _ = self.set_string(vrs, &string_values);
+                        self.set_string(vrs, &string_values)?;
                     }
                     DataType::LargeUtf8 => todo!(),
+                    // Array variables take the elements of the first row
+                    DataType::FixedSizeList(..) => {
+                        self.set_array(vrs, &values.as_fixed_size_list().value(0))?;
+                    }
+                    DataType::List(_) => {
+                        self.set_array(vrs, &values.as_list::<i32>().value(0))?;
+                    }
                     _ => unimplemented!("Unsupported data type"),
                 }
+                Ok(())
             }
 
             fn set_interpolated<I: Interpolate>(
@@ -254,6 +494,19 @@ macro_rules! impl_set_values {
                         let value = I::interpolate(pl, &array);
                         self.set_float64(&[vr], &[value])?;
                     }
+                    DataType::FixedSizeList(item, _) | DataType::List(item) => {
+                        match item.data_type() {
+                            DataType::Float32 => {
+                                let values = interpolate_elements::<I, Float32Type>(pl, array)?;
+                                self.set_float32(&[vr], &values)?;
+                            }
+                            DataType::Float64 => {
+                                let values = interpolate_elements::<I, Float64Type>(pl, array)?;
+                                self.set_float64(&[vr], &values)?;
+                            }
+                            t => anyhow::bail!("Unsupported continuous array data type: {t:?}"),
+                        }
+                    }
                     DataType::Binary => todo!(),
                     DataType::Utf8 => {
                         // For string interpolation, we use the next index value (no real interpolation for strings)
@@ -272,12 +525,77 @@ macro_rules! impl_set_values {
     };
 }
 
+impl<Tag> InstClocks for Instance<Tag>
+where
+    Instance<Tag>: GetSet + FmiInstance<ValueRef = u32>,
+{
+    fn activate_clocks(&mut self, vrs: &[Self::ValueRef]) -> anyhow::Result<()> {
+        if !vrs.is_empty() {
+            self.set_clock(vrs, &vec![true; vrs.len()])
+                .ok()
+                .context("set_clock")?;
+        }
+        Ok(())
+    }
+
+    fn ticked_clocks(&mut self, vrs: &[Self::ValueRef]) -> anyhow::Result<Vec<Self::ValueRef>> {
+        if vrs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut values = vec![false; vrs.len()];
+        self.get_clock(vrs, &mut values).ok().context("get_clock")?;
+        Ok(vrs
+            .iter()
+            .zip(values)
+            .filter_map(|(vr, active)| active.then_some(*vr))
+            .collect())
+    }
+}
+
+impl<Tag> RecorderState<Instance<Tag>>
+where
+    Instance<Tag>: Common + FmiInstance<ValueRef = u32>,
+{
+    /// Resolve the shapes of the array outputs. This must happen after initialization, since
+    /// dimensions may be given by structural parameters.
+    pub(crate) fn resolve_shapes(&mut self, inst: &mut Instance<Tag>) -> anyhow::Result<()> {
+        for recorder in &mut self.recorders {
+            if recorder.dimensions.is_empty() {
+                continue;
+            }
+            let shape = recorder
+                .dimensions
+                .iter()
+                .map(|dim| match dim {
+                    Either::Left(size) => Ok(*size),
+                    Either::Right(vr) => {
+                        let mut size = [0];
+                        inst.get_uint64(&[*vr], &mut size)
+                            .ok()
+                            .with_context(|| format!("get_uint64 of dimension VR={vr}"))?;
+                        Ok(size[0] as usize)
+                    }
+                })
+                .collect::<anyhow::Result<_>>()?;
+            recorder.shape = Some(shape);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(feature = "cs")]
 impl_set_values!(fmi::fmi3::instance::InstanceCS);
 #[cfg(feature = "cs")]
 impl_record_values!(fmi::fmi3::instance::InstanceCS);
+#[cfg(feature = "cs")]
+impl_get_values!(fmi::fmi3::instance::InstanceCS);
 
 #[cfg(feature = "me")]
 impl_set_values!(fmi::fmi3::instance::InstanceME);
 #[cfg(feature = "me")]
 impl_record_values!(fmi::fmi3::instance::InstanceME);
+
+#[cfg(feature = "se")]
+impl_set_values!(fmi::fmi3::instance::InstanceSE);
+#[cfg(feature = "se")]
+impl_record_values!(fmi::fmi3::instance::InstanceSE);
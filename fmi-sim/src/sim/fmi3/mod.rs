@@ -1,3 +1,6 @@
+use std::path::Path;
+
+use anyhow::Context;
 use arrow::array::RecordBatch;
 
 use fmi::{
@@ -11,7 +14,7 @@ use crate::{
     sim::{
         InputState, RecorderState, SimState, SimStateTrait,
         params::SimParams,
-        traits::{ImportSchemaBuilder, SimInitialize},
+        traits::{ImportSchemaBuilder, SimFmuState, SimInitialize},
     },
 };
 
@@ -27,6 +30,8 @@ mod io;
 #[cfg(feature = "me")]
 mod me;
 mod schema;
+#[cfg(feature = "se")]
+mod se;
 
 macro_rules! impl_sim_apply_start_values {
     ($inst:ty) => {
@@ -41,16 +46,16 @@ macro_rules! impl_sim_apply_start_values {
                         .map_err(fmi::Error::from)?;
                     for (vr, ary) in &start_values.structural_parameters {
                         //log::trace!("Setting structural parameter `{}`", (*vr).into());
-                        self.inst.set_array(&[(*vr)], ary);
+                        self.inst.set_array(&[(*vr)], ary)?;
                     }
                     self.inst
                         .exit_configuration_mode()
                         .map_err(fmi::Error::from)?;
                 }
 
-                start_values.variables.iter().for_each(|(vr, ary)| {
-                    self.inst.set_array(&[*vr], ary);
-                });
+                for (vr, ary) in &start_values.variables {
+                    self.inst.set_array(&[*vr], ary)?;
+                }
 
                 Ok(())
             }
@@ -62,6 +67,104 @@ macro_rules! impl_sim_apply_start_values {
 impl_sim_apply_start_values!(fmi::fmi3::instance::InstanceME);
 #[cfg(feature = "cs")]
 impl_sim_apply_start_values!(fmi::fmi3::instance::InstanceCS);
+#[cfg(feature = "se")]
+impl_sim_apply_start_values!(fmi::fmi3::instance::InstanceSE);
+
+macro_rules! impl_sim_fmu_state {
+    ($inst:ty) => {
+        impl SimFmuState for SimState<$inst> {
+            fn restore_fmu_state(&mut self, path: &Path) -> Result<(), Error> {
+                let buffer = std::fs::read(path)
+                    .with_context(|| format!("Reading FMU state from {}", path.display()))?;
+                let state = self
+                    .inst
+                    .deserialize_fmu_state(&buffer)
+                    .map_err(fmi::Error::from)?;
+                self.inst
+                    .set_fmu_state(&state)
+                    .and_then(|_| self.inst.free_fmu_state(state))
+                    .map_err(fmi::Error::from)?;
+                log::debug!("Restored FMU state from {}", path.display());
+                Ok(())
+            }
+
+            fn save_fmu_state(&mut self, path: &Path) -> Result<(), Error> {
+                let state = self.inst.get_fmu_state().map_err(fmi::Error::from)?;
+                let buffer = self
+                    .inst
+                    .serialize_fmu_state(&state)
+                    .map_err(fmi::Error::from)?;
+                self.inst.free_fmu_state(state).map_err(fmi::Error::from)?;
+                std::fs::write(path, buffer)
+                    .with_context(|| format!("Writing FMU state to {}", path.display()))?;
+                log::debug!("Saved FMU state to {}", path.display());
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "me")]
+impl_sim_fmu_state!(fmi::fmi3::instance::InstanceME);
+#[cfg(feature = "cs")]
+impl_sim_fmu_state!(fmi::fmi3::instance::InstanceCS);
+#[cfg(feature = "se")]
+impl_sim_fmu_state!(fmi::fmi3::instance::InstanceSE);
+
+/// Parameters for the BDF solver, which builds its Jacobian from directional derivatives if the FMU
+/// provides them.
+#[cfg(feature = "me")]
+fn bdf_params(import: &Fmi3Import) -> super::solver::BdfParams<fmi::fmi3::instance::InstanceME> {
+    let model_description = import.model_description();
+    let provides_directional_derivatives = model_description
+        .model_exchange
+        .as_ref()
+        .and_then(|me| me.provides_directional_derivatives)
+        .unwrap_or(false);
+    if !provides_directional_derivatives {
+        return Default::default();
+    }
+
+    // Value references of the state derivatives, and of the states they belong to
+    let derivatives: Vec<u32> = model_description
+        .model_structure
+        .continuous_state_derivatives()
+        .map(|unknown| unknown.value_reference)
+        .collect();
+    let float64 = model_description.model_variables.float64();
+    let float32 = model_description.model_variables.float32();
+    let states: Option<Vec<u32>> = derivatives
+        .iter()
+        .map(|vr| {
+            float64
+                .iter()
+                .find(|v| v.value_reference == *vr)
+                .and_then(|v| v.derivative())
+                .or_else(|| {
+                    float32
+                        .iter()
+                        .find(|v| v.value_reference == *vr)
+                        .and_then(|v| v.derivative())
+                })
+        })
+        .collect();
+    let Some(states) = states else {
+        log::warn!("Could not resolve the continuous states, using finite differences");
+        return Default::default();
+    };
+
+    log::debug!("Using directional derivatives for the BDF Jacobian");
+    super::solver::BdfParams {
+        directional_derivative: Some(Box::new(move |inst, seed, sensitivity| {
+            inst.get_directional_derivative(&derivatives, &states, seed, sensitivity)
+                .map(|_| ())
+                .map_err(|e| {
+                    log::error!("Directional derivative failed: {e}");
+                    super::solver::SolverError::StepError
+                })
+        })),
+    }
+}
 
 impl FmiSim for Fmi3Import {
     #[cfg(feature = "me")]
@@ -70,7 +173,10 @@ impl FmiSim for Fmi3Import {
         options: &ModelExchangeOptions,
         input_data: Option<RecordBatch>,
     ) -> Result<(RecordBatch, SimStats), Error> {
-        use crate::sim::{solver, traits::SimMe};
+        use crate::{
+            options::SolverArg,
+            sim::{solver, traits::SimMe},
+        };
         use fmi::fmi3::{ModelExchange, instance::InstanceME};
 
         let sim_params =
@@ -95,10 +201,36 @@ impl FmiSim for Fmi3Import {
             .get_number_of_event_indicators()
             .map_err(|e| Error::from(fmi::Error::from(e)))?;
 
-        let solver: solver::Euler = solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, ());
-
         sim_state.initialize(start_values, options.common.initial_fmu_state_file.as_ref())?;
-        let stats = sim_state.main_loop(solver)?;
+        sim_state
+            .recorder_state
+            .resolve_shapes(&mut sim_state.inst)?;
+
+        let stats = match options.solver {
+            SolverArg::Euler => {
+                let solver: solver::Euler =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, ());
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Bdf => {
+                let solver: solver::Bdf<InstanceME> =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, bdf_params(self));
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Dopri5 => {
+                let solver: solver::Dopri5 =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, ());
+                sim_state.main_loop(solver)?
+            }
+            SolverArg::Rk4 => {
+                let params = solver::Rk4Params {
+                    step_size: options.common.step_size,
+                };
+                let solver: solver::Rk4 =
+                    solver::Solver::<InstanceME>::new(start_time, tol, nx, nz, params);
+                sim_state.main_loop(solver)?
+            }
+        };
 
         Ok((sim_state.recorder_state.finish(), stats))
     }
@@ -125,8 +257,38 @@ impl FmiSim for Fmi3Import {
         let mut sim_state =
             SimState::<InstanceCS>::new(self, sim_params, input_state, output_state)?;
         sim_state.initialize(start_values, options.common.initial_fmu_state_file.as_ref())?;
+        sim_state
+            .recorder_state
+            .resolve_shapes(&mut sim_state.inst)?;
         let stats = sim_state.main_loop()?;
 
         Ok((sim_state.recorder_state.finish(), stats))
     }
+
+    #[cfg(feature = "se")]
+    fn simulate_se(
+        &self,
+        options: &crate::options::ScheduledExecutionOptions,
+        input_data: Option<RecordBatch>,
+    ) -> Result<(RecordBatch, SimStats), Error> {
+        use fmi::fmi3::instance::InstanceSE;
+
+        let sim_params =
+            SimParams::new_from_options(&options.common, self.model_description(), false, false);
+
+        let start_values = self.parse_start_values(&options.common.initial_values)?;
+        let input_state = InputState::new(self, input_data)?;
+        let output_state = RecorderState::new(self, &sim_params);
+        let schedule = se::Schedule::new(self, &options.clock_connections)?;
+
+        let mut sim_state =
+            SimState::<InstanceSE>::new(self, sim_params, input_state, output_state)?;
+        sim_state.initialize(start_values, options.common.initial_fmu_state_file.as_ref())?;
+        sim_state
+            .recorder_state
+            .resolve_shapes(&mut sim_state.inst)?;
+        let stats = sim_state.main_loop(schedule)?;
+
+        Ok((sim_state.recorder_state.finish(), stats))
+    }
 }
@@ -1,15 +1,43 @@
+use std::sync::Arc;
+
 use arrow::{
     array::{ArrayRef, StringArray},
     datatypes::{DataType, Field, Fields, Schema},
 };
 use fmi::{
     fmi3::{import::Fmi3Import, schema::Causality},
-    schema::fmi3::{Variability, Variable},
+    schema::fmi3::{Dimension, Variability, Variable},
     traits::FmiImport,
 };
 
+use itertools::Either;
+
 use crate::sim::{io::StartValues, traits::ImportSchemaBuilder};
 
+/// Build the Arrow field for a variable.
+///
+/// Array variables hold their elements flattened in row-major order, as a `FixedSizeList` if all
+/// dimensions are fixed, or as a `List` if any dimension is given by a structural parameter.
+fn variable_field(variable: &Variable) -> Field {
+    let var = variable.as_abstract();
+    let data_type: DataType = var.data_type().into();
+    let dimensions = variable.dimensions();
+    if dimensions.is_empty() {
+        return Field::new(var.name(), data_type, false);
+    }
+
+    let item = Arc::new(Field::new_list_field(data_type, false));
+    let fixed_len = dimensions
+        .iter()
+        .map(Dimension::as_fixed)
+        .product::<Option<u64>>();
+    let data_type = match fixed_len {
+        Some(len) => DataType::FixedSizeList(item, len as i32),
+        None => DataType::List(item),
+    };
+    Field::new(var.name(), data_type, false)
+}
+
 impl ImportSchemaBuilder for Fmi3Import
 where
     Self::ValueRef: From<u32>,
@@ -18,9 +46,10 @@ where
         let input_fields = self
             .model_description()
             .model_variables
-            .iter_abstract()
-            .filter(|v| v.causality() == Causality::Input)
-            .map(|v| Field::new(v.name(), v.data_type().into(), false))
+            .variables
+            .iter()
+            .filter(|v| v.as_abstract().causality() == Causality::Input)
+            .map(variable_field)
             .collect::<Fields>();
 
         Schema::new(input_fields)
@@ -31,9 +60,10 @@ where
         let output_fields = self
             .model_description()
             .model_variables
-            .iter_abstract()
-            .filter(|v| v.causality() == Causality::Output)
-            .map(|v| Field::new(v.name(), v.data_type().into(), false))
+            .variables
+            .iter()
+            .filter(|v| v.as_abstract().causality() == Causality::Output)
+            .map(variable_field)
             .chain(std::iter::once(time))
             .collect::<Fields>();
 
@@ -43,47 +73,47 @@ where
     fn continuous_inputs(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
         self.model_description()
             .model_variables
-            .iter_abstract()
+            .variables
+            .iter()
             .filter(|v| {
+                let v = v.as_abstract();
                 v.causality() == Causality::Input
                     && v.variability() == fmi::fmi3::schema::Variability::Continuous
             })
-            .map(|v| {
-                (
-                    Field::new(v.name(), v.data_type().into(), false),
-                    v.value_reference(),
-                )
-            })
+            .map(|v| (variable_field(v), v.as_abstract().value_reference()))
     }
 
     fn discrete_inputs(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
         self.model_description()
             .model_variables
-            .iter_abstract()
+            .variables
+            .iter()
+            .filter(|v| !matches!(v, Variable::Clock(_)))
             .filter(|v| {
+                let v = v.as_abstract();
                 v.causality() == Causality::Input
                     && (v.variability() == Variability::Discrete
                         || v.variability() == Variability::Tunable)
             })
-            .map(|v| {
-                (
-                    Field::new(v.name(), v.data_type().into(), false),
-                    v.value_reference(),
-                )
-            })
+            .map(|v| (variable_field(v), v.as_abstract().value_reference()))
     }
 
     fn outputs(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> {
         self.model_description()
             .model_variables
-            .iter_abstract()
-            .filter(|v| v.causality() == Causality::Output)
-            .map(|v| {
-                (
-                    Field::new(v.name(), v.data_type().into(), false),
-                    v.value_reference(),
-                )
-            })
+            .variables
+            .iter()
+            .filter(|v| v.as_abstract().causality() == Causality::Output)
+            .map(|v| (variable_field(v), v.as_abstract().value_reference()))
+    }
+
+    fn variables(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
+        self.model_description()
+            .model_variables
+            .variables
+            .iter()
+            .filter(|v| v.as_abstract().causality() != Causality::Independent)
+            .map(|v| (variable_field(v), v.as_abstract().value_reference()))
     }
 
     fn parse_start_values(
@@ -145,4 +175,52 @@ where
                 _ => None,
             })
     }
+
+    fn dimensions(&self, vr: Self::ValueRef) -> Vec<Either<usize, Self::ValueRef>> {
+        self.model_description()
+            .model_variables
+            .variables
+            .iter()
+            .find(|v| v.as_abstract().value_reference() == vr)
+            .map(|v| {
+                v.dimensions()
+                    .iter()
+                    .map(|dim| match dim {
+                        Dimension::Fixed(size) => Either::Left(*size as usize),
+                        Dimension::Variable(vr) => Either::Right(*vr),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn is_clock(&self, vr: Self::ValueRef) -> bool {
+        self.model_description()
+            .model_variables
+            .clock()
+            .iter()
+            .any(|clock| clock.value_reference == vr)
+    }
+
+    fn input_clocks(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
+        self.model_description()
+            .model_variables
+            .variables
+            .iter()
+            .filter(|v| {
+                matches!(v, Variable::Clock(_)) && v.as_abstract().causality() == Causality::Input
+            })
+            .map(|v| (variable_field(v), v.as_abstract().value_reference()))
+    }
+
+    fn output_clocks(&self) -> impl Iterator<Item = Self::ValueRef> + '_ {
+        self.model_description()
+            .model_variables
+            .variables
+            .iter()
+            .filter(|v| {
+                matches!(v, Variable::Clock(_)) && v.as_abstract().causality() == Causality::Output
+            })
+            .map(|v| v.as_abstract().value_reference())
+    }
 }
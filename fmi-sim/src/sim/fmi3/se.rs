@@ -0,0 +1,399 @@
+//! Scheduled Execution master for FMI 3.0.
+//!
+//! Input Clocks are read from the model description and their model partitions are activated in
+//! time order, and by priority at the same time instant. After each activation the output Clocks
+//! are read, triggering the input Clocks connected to them, and the outputs are recorded.
+
+use anyhow::Context;
+use fmi::{
+    EventFlags,
+    fmi3::{
+        Common, Fmi3Model, GetSet, ScheduledExecution, binding,
+        import::Fmi3Import,
+        instance::InstanceSE,
+        schema::{
+            AbstractVariableTrait, Causality, Fmi3ModelDescription, FmiClock, IntervalVariability,
+            TypeDefinition,
+        },
+    },
+    traits::FmiImport,
+};
+
+use crate::{
+    Error,
+    sim::{
+        InputState, RecorderState, SimState, SimStateTrait, SimStats,
+        interpolation::Linear,
+        params::SimParams,
+        traits::{InstRecordValues, SimFmuState},
+    },
+};
+
+impl SimStateTrait<InstanceSE, Fmi3Import> for SimState<InstanceSE> {
+    fn new(
+        import: &Fmi3Import,
+        sim_params: SimParams,
+        input_state: InputState<InstanceSE>,
+        output_state: RecorderState<InstanceSE>,
+    ) -> Result<Self, Error> {
+        let inst = import.instantiate_se("inst1", true, true)?;
+        Ok(Self {
+            sim_params,
+            input_state,
+            recorder_state: output_state,
+            inst,
+            event_flags: EventFlags::default(),
+        })
+    }
+}
+
+/// An input Clock, whose model partition is activated by the master
+#[derive(Debug)]
+struct InputClock {
+    name: String,
+    vr: binding::fmi3ValueReference,
+    /// Partitions with a lower value are activated first at the same time instant
+    priority: u32,
+    interval_variability: IntervalVariability,
+    /// Interval of a periodic Clock
+    interval: Option<f64>,
+    /// Time of the first tick of a periodic Clock with the current interval
+    base_time: f64,
+    /// Number of ticks since `base_time`
+    ticks: u64,
+    /// Time of the next activation, if one is scheduled
+    next_time: Option<f64>,
+}
+
+impl InputClock {
+    fn new(clock: &FmiClock, model_description: &Fmi3ModelDescription) -> Self {
+        let clock_type = clock.declared_type.as_ref().and_then(|declared_type| {
+            model_description
+                .type_definitions
+                .as_ref()?
+                .type_definitions
+                .iter()
+                .find_map(|td| match td {
+                    TypeDefinition::Clock(ct) if &ct.name == declared_type => Some(ct),
+                    _ => None,
+                })
+        });
+
+        let interval_variability = clock
+            .interval_variability
+            .or_else(|| {
+                clock_type
+                    .and_then(|ct| ct.interval_variability.as_deref())
+                    .and_then(|iv| iv.parse().ok())
+            })
+            .unwrap_or_default();
+        let interval = clock
+            .interval_decimal
+            .or_else(|| clock_type.and_then(|ct| ct.interval_decimal));
+        let shift = clock
+            .shift_decimal
+            .or_else(|| clock_type.and_then(|ct| ct.shift_decimal))
+            .unwrap_or(0.0);
+        let priority = clock
+            .priority
+            .map(|p| p.max(0) as u32)
+            .or_else(|| clock_type.and_then(|ct| ct.priority))
+            .unwrap_or(u32::MAX);
+
+        Self {
+            name: clock.name.clone(),
+            vr: clock.value_reference,
+            priority,
+            interval_variability,
+            interval,
+            base_time: shift,
+            ticks: 0,
+            next_time: None,
+        }
+    }
+
+    /// Whether the FMU reports the interval of the Clock at runtime
+    fn queries_interval(&self) -> bool {
+        match self.interval_variability {
+            IntervalVariability::Constant => self.interval.is_none(),
+            IntervalVariability::Fixed
+            | IntervalVariability::Tunable
+            | IntervalVariability::Changing
+            | IntervalVariability::Countdown => true,
+            IntervalVariability::Triggered => false,
+        }
+    }
+
+    /// Schedule the next tick of a periodic Clock
+    fn schedule_periodic(&mut self) {
+        self.next_time = self
+            .interval
+            .filter(|interval| *interval > 0.0)
+            .map(|interval| self.base_time + self.ticks as f64 * interval);
+    }
+}
+
+/// Activation schedule of the model partitions of an FMU
+pub struct Schedule {
+    input_clocks: Vec<InputClock>,
+    /// Value references of the output Clocks
+    output_clocks: Vec<binding::fmi3ValueReference>,
+    /// Connections from output Clocks to the indices of the input Clocks they trigger
+    connections: Vec<(binding::fmi3ValueReference, usize)>,
+}
+
+impl Schedule {
+    /// Build the schedule from the Clocks in the model description.
+    ///
+    /// `clock_connections` are "outputClock=inputClock" pairs of Clock names.
+    pub fn new(import: &Fmi3Import, clock_connections: &[String]) -> anyhow::Result<Self> {
+        let model_description = import.model_description();
+        let clocks = model_description.model_variables.clock();
+
+        let input_clocks: Vec<_> = clocks
+            .iter()
+            .filter(|clock| clock.causality() == Causality::Input)
+            .map(|clock| InputClock::new(clock, model_description))
+            .collect();
+        let output_clocks: Vec<_> = clocks
+            .iter()
+            .filter(|clock| clock.causality() == Causality::Output)
+            .map(|clock| clock.value_reference)
+            .collect();
+
+        let connections = clock_connections
+            .iter()
+            .map(|connection| {
+                let (output, input) = connection
+                    .split_once('=')
+                    .with_context(|| format!("Invalid Clock connection: {connection}"))?;
+                let output = clocks
+                    .iter()
+                    .find(|clock| clock.name == output && clock.causality() == Causality::Output)
+                    .with_context(|| format!("Unknown output Clock: {output}"))?;
+                let input = input_clocks
+                    .iter()
+                    .position(|clock| clock.name == input)
+                    .with_context(|| format!("Unknown input Clock: {input}"))?;
+                Ok((output.value_reference, input))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        for clock in &input_clocks {
+            log::debug!(
+                "Input Clock '{}': {:?}, interval: {:?}, priority: {}",
+                clock.name,
+                clock.interval_variability,
+                clock.interval,
+                clock.priority
+            );
+        }
+
+        Ok(Self {
+            input_clocks,
+            output_clocks,
+            connections,
+        })
+    }
+
+    /// Schedule the first ticks of the periodic Clocks.
+    fn init(&mut self, inst: &mut InstanceSE, start_time: f64) -> anyhow::Result<()> {
+        for clock in &mut self.input_clocks {
+            clock.base_time += start_time;
+        }
+
+        let (vrs, mut shifts): (Vec<_>, Vec<_>) = self
+            .input_clocks
+            .iter()
+            .filter(|clock| {
+                matches!(
+                    clock.interval_variability,
+                    IntervalVariability::Fixed | IntervalVariability::Tunable
+                )
+            })
+            .map(|clock| (clock.vr, clock.base_time - start_time))
+            .unzip();
+        if !vrs.is_empty() {
+            inst.get_shift_decimal(&vrs, &mut shifts)
+                .ok()
+                .context("get_shift_decimal")?;
+            for (vr, shift) in vrs.iter().zip(&shifts) {
+                if let Some(clock) = self.input_clocks.iter_mut().find(|c| c.vr == *vr) {
+                    clock.base_time = start_time + shift;
+                }
+            }
+        }
+
+        for clock in &mut self.input_clocks {
+            clock.schedule_periodic();
+        }
+        self.update_intervals(inst, start_time)
+    }
+
+    /// Query the intervals the FMU reports at runtime, and reschedule the Clocks whose interval
+    /// changed.
+    fn update_intervals(&mut self, inst: &mut InstanceSE, time: f64) -> anyhow::Result<()> {
+        let vrs: Vec<_> = self
+            .input_clocks
+            .iter()
+            .filter(|clock| clock.queries_interval())
+            .map(|clock| clock.vr)
+            .collect();
+        if vrs.is_empty() {
+            return Ok(());
+        }
+
+        let mut intervals = vec![0.0; vrs.len()];
+        let mut qualifiers =
+            vec![binding::fmi3IntervalQualifier_fmi3IntervalNotYetKnown; vrs.len()];
+        inst.get_interval_decimal(&vrs, &mut intervals, &mut qualifiers)
+            .ok()
+            .context("get_interval_decimal")?;
+
+        for ((vr, interval), qualifier) in vrs.iter().zip(intervals).zip(qualifiers) {
+            if qualifier != binding::fmi3IntervalQualifier_fmi3IntervalChanged {
+                continue;
+            }
+            let Some(clock) = self.input_clocks.iter_mut().find(|c| c.vr == *vr) else {
+                continue;
+            };
+            log::trace!("Interval of Clock '{}' changed to {interval}", clock.name);
+
+            if clock.interval_variability == IntervalVariability::Countdown {
+                clock.next_time = Some(time + interval);
+            } else {
+                // Keep the pending tick if there is one, and continue with the new interval
+                if clock.ticks > 0 {
+                    clock.base_time = clock.next_time.unwrap_or(time);
+                    clock.ticks = 0;
+                }
+                clock.interval = Some(interval);
+                clock.schedule_periodic();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Time of the next scheduled activation
+    fn next_time(&self) -> Option<f64> {
+        self.input_clocks
+            .iter()
+            .filter_map(|clock| clock.next_time)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Indices of the input Clocks scheduled at `time`, in the order they are to be activated.
+    fn due(&self, time: f64) -> Vec<usize> {
+        let eps = 1e-12 * time.abs().max(1.0);
+        let mut due: Vec<_> = (0..self.input_clocks.len())
+            .filter(|&i| {
+                self.input_clocks[i]
+                    .next_time
+                    .is_some_and(|next_time| next_time <= time + eps)
+            })
+            .collect();
+        due.sort_by_key(|&i| self.input_clocks[i].priority);
+        due
+    }
+
+    /// Mark an input Clock as activated, and schedule its next tick.
+    fn activated(&mut self, index: usize) {
+        let clock = &mut self.input_clocks[index];
+        match clock.interval_variability {
+            IntervalVariability::Countdown | IntervalVariability::Triggered => {
+                clock.next_time = None;
+            }
+            _ => {
+                clock.ticks += 1;
+                clock.schedule_periodic();
+            }
+        }
+    }
+
+    /// Read the output Clocks, returning those that ticked.
+    fn read_output_clocks(
+        &self,
+        inst: &mut InstanceSE,
+    ) -> anyhow::Result<Vec<binding::fmi3ValueReference>> {
+        if self.output_clocks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut values = vec![false; self.output_clocks.len()];
+        inst.get_clock(&self.output_clocks, &mut values)
+            .ok()
+            .context("get_clock")?;
+        Ok(self
+            .output_clocks
+            .iter()
+            .zip(values)
+            .filter_map(|(vr, active)| active.then_some(*vr))
+            .collect())
+    }
+}
+
+impl SimState<InstanceSE> {
+    /// Main loop of the scheduled execution
+    pub fn main_loop(&mut self, mut schedule: Schedule) -> Result<SimStats, Error> {
+        let mut stats = SimStats::default();
+        let mut time = self.sim_params.start_time;
+
+        schedule.init(&mut self.inst, time)?;
+        self.inst.record_outputs(time, &mut self.recorder_state)?;
+
+        while let Some(next_time) = schedule.next_time() {
+            if next_time > self.sim_params.stop_time {
+                break;
+            }
+            time = next_time;
+
+            let mut due = schedule.due(time);
+            // Triggered Clocks are activated at most once per time instant
+            let mut triggered = vec![];
+
+            while !due.is_empty() {
+                let index = due.remove(0);
+                let vr = schedule.input_clocks[index].vr;
+                log::trace!(
+                    "Activating partition of Clock '{}' at t = {time}",
+                    schedule.input_clocks[index].name
+                );
+
+                self.input_state
+                    .apply_input::<Linear>(time, &mut self.inst, true, true, false)?;
+                self.inst
+                    .activate_model_partition(vr, time)
+                    .ok()
+                    .context("activate_model_partition")?;
+                schedule.activated(index);
+                stats.num_steps += 1;
+
+                let ticked = schedule.read_output_clocks(&mut self.inst)?;
+                for (output, input) in &schedule.connections {
+                    if ticked.contains(output) && !triggered.contains(input) {
+                        triggered.push(*input);
+                        due.push(*input);
+                    }
+                }
+                let input_clocks = &schedule.input_clocks;
+                due.sort_by_key(|&i| input_clocks[i].priority);
+                stats.num_events += ticked.len();
+
+                self.recorder_state.active_clocks = ticked;
+                self.inst.record_outputs(time, &mut self.recorder_state)?;
+                self.recorder_state.active_clocks.clear();
+
+                schedule.update_intervals(&mut self.inst, time)?;
+            }
+        }
+
+        if let Some(path) = self.sim_params.final_fmu_state_file.clone() {
+            self.save_fmu_state(&path)?;
+        }
+
+        self.inst.terminate().ok().context("terminate")?;
+
+        stats.end_time = time;
+        Ok(stats)
+    }
+}
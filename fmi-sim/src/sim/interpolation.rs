@@ -48,7 +48,7 @@ where
             .iter()
             .position(|&x| x > value)
             .map(|i| (i + row).saturating_sub(1))
-            .unwrap_or(row)
+            .unwrap_or(array.len().saturating_sub(1))
     } else {
         row
     };
@@ -161,7 +161,7 @@ impl Interpolate for Cubic {
 
 #[cfg(test)]
 mod tests {
-    use super::{Interpolate, Linear, PreLookup};
+    use super::{Interpolate, Linear, PreLookup, find_index};
     use arrow::{array::PrimitiveArray, datatypes::Int32Type};
 
     #[test]
@@ -181,6 +181,19 @@ mod tests {
         assert_eq!(PreLookup::new(&array, 5.0, false), PreLookup(4, 1.0));
     }
 
+    #[test]
+    fn test_find_index() {
+        let array = PrimitiveArray::from(vec![0.0, 1.0, 1.0, 2.0]);
+
+        assert_eq!(find_index(&array, 0.5, false), 0);
+        assert_eq!(find_index(&array, 1.0, false), 0);
+        assert_eq!(find_index(&array, 1.0, true), 2);
+        assert_eq!(find_index(&array, 2.0, false), 2);
+        // an event at the last row
+        assert_eq!(find_index(&array, 2.0, true), 3);
+        assert_eq!(find_index(&array, 3.0, true), 3);
+    }
+
     #[test]
     fn test_interpolation() {
         let time = PrimitiveArray::from(vec![0.0, 2.0, 3.0]);
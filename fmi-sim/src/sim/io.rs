@@ -1,21 +1,25 @@
-use std::{fmt::Display, sync::Arc};
+use std::{collections::HashMap, fmt::Display, sync::Arc};
 
 use anyhow::Context;
 use arrow::{
-    array::{ArrayBuilder, ArrayRef, Float64Array, Float64Builder, downcast_array, make_builder},
+    array::{
+        Array, ArrayBuilder, ArrayRef, AsArray, Float64Array, Float64Builder, downcast_array,
+        make_builder,
+    },
     datatypes::{DataType, Field, Schema},
     downcast_primitive_array,
     record_batch::RecordBatch,
 };
 use fmi::traits::FmiInstance;
+use itertools::{Either, Itertools};
 
 use crate::Error;
 
 use super::{
     interpolation::{Interpolate, PreLookup, find_index},
     params::SimParams,
-    traits::{ImportSchemaBuilder, InstSetValues},
-    util::project_input_data,
+    traits::{ImportSchemaBuilder, InstClocks, InstSetValues},
+    util::{SHAPE_METADATA_KEY, project_input_data},
 };
 
 /// Container for holding initial values for the FMU.
@@ -30,6 +34,8 @@ pub struct InputState<Inst: FmiInstance> {
     pub(crate) continuous_inputs: Vec<(Field, Inst::ValueRef)>,
     // Map schema column index to ValueReference
     pub(crate) discrete_inputs: Vec<(Field, Inst::ValueRef)>,
+    /// Input Clocks, which tick at the rows where their Boolean column is `true`
+    pub(crate) clock_inputs: Vec<(Field, Inst::ValueRef)>,
 }
 
 impl<Inst> Display for InputState<Inst>
@@ -62,6 +68,14 @@ where
         }
         writeln!(f, "continuous_inputs: {continuous_inputs:?}")?;
         writeln!(f, "discrete_inputs: {discrete_inputs:?}")?;
+        writeln!(
+            f,
+            "clock_inputs: {:?}",
+            self.clock_inputs
+                .iter()
+                .map(|(field, _)| field.name())
+                .collect::<Vec<_>>()
+        )?;
         write!(f, "}}")
     }
 }
@@ -77,6 +91,7 @@ where
         let model_input_schema = Arc::new(import.inputs_schema());
         let continuous_inputs = import.continuous_inputs().collect();
         let discrete_inputs = import.discrete_inputs().collect();
+        let clock_inputs = import.input_clocks().collect();
 
         let input_data = input_data
             .map(|input_data| project_input_data(&input_data, model_input_schema.clone()))
@@ -86,6 +101,7 @@ where
             input_data,
             continuous_inputs,
             discrete_inputs,
+            clock_inputs,
         })
     }
 }
@@ -138,7 +154,7 @@ where
 
                         //log::trace!( "Applying discrete input {}={values:#?} at time {time:.2}", field.name());
 
-                        inst.set_array(&[*vr], values);
+                        inst.set_array(&[*vr], values)?;
                     }
                 }
             }
@@ -147,12 +163,51 @@ where
         Ok(())
     }
 
+    /// Activate the input Clocks that tick at exactly `time`, and return them. Must be called in
+    /// Event Mode.
+    pub fn apply_input_clocks(
+        &self,
+        time: f64,
+        inst: &mut Inst,
+    ) -> anyhow::Result<Vec<Inst::ValueRef>>
+    where
+        Inst: InstClocks,
+    {
+        let Some(input_data) = &self.input_data else {
+            return Ok(Vec::new());
+        };
+        let time_array: Float64Array = downcast_array(
+            input_data
+                .column_by_name("time")
+                .context("Input data must have a column named 'time' with the time values")?,
+        );
+        let input_idx = find_index(&time_array, time, true);
+        if time_array.value(input_idx) != time {
+            return Ok(Vec::new());
+        }
+
+        let mut ticked = Vec::new();
+        for (field, vr) in &self.clock_inputs {
+            if let Some(input_col) = input_data.column_by_name(field.name())
+                && clock_ticks(field, input_col, input_idx)?
+            {
+                ticked.push(*vr);
+            }
+        }
+
+        inst.activate_clocks(&ticked)?;
+        Ok(ticked)
+    }
+
     /// Get the time of the next input event after the given time.
     /// If no such event exists, returns `f64::INFINITY`.
-    pub fn next_input_event(&self, time: f64) -> f64 {
+    pub fn next_input_event(&self, time: f64) -> anyhow::Result<f64> {
         if let Some(input_data) = &self.input_data {
-            let time_array: Float64Array =
-                downcast_array(input_data.column_by_name("time").unwrap());
+            let time_array: Float64Array = downcast_array(
+                input_data
+                    .column_by_name("time")
+                    .context("Input data must have a column named 'time' with the time values")?,
+            );
 
             for i in 0..(time_array.len() - 1) {
                 let t0 = time_array.value(i);
@@ -163,7 +218,7 @@ where
                 }
 
                 if t0 == t1 {
-                    return t0; // discrete change of a continuous variable
+                    return Ok(t0); // discrete change of a continuous variable
                 }
 
                 // TODO: This could be computed once and cached
@@ -173,28 +228,61 @@ where
                     if let Some(input_col) = input_data.column_by_name(field.name()) {
                         if downcast_primitive_array!(
                             input_col => input_col.value(i) != input_col.value(i + 1),
-                            t => panic!("Unsupported datatype {}", t)
+                            _ => input_col.slice(i, 1).to_data() != input_col.slice(i + 1, 1).to_data()
                         ) {
-                            return t1;
+                            return Ok(t1);
                         }
                     }
                 }
+
+                // every tick of an input Clock is an event
+                for (field, _vr) in &self.clock_inputs {
+                    if let Some(input_col) = input_data.column_by_name(field.name())
+                        && clock_ticks(field, input_col, i + 1)?
+                    {
+                        return Ok(t1);
+                    }
+                }
             }
         }
-        f64::INFINITY
+        Ok(f64::INFINITY)
     }
 }
 
+/// Whether the input Clock ticks at the given row of its column. Null entries do not tick.
+fn clock_ticks(field: &Field, input_col: &ArrayRef, index: usize) -> anyhow::Result<bool> {
+    let input_col = input_col
+        .as_boolean_opt()
+        .with_context(|| format!("Input Clock '{}' must be a Boolean column", field.name()))?;
+    Ok(input_col.is_valid(index) && input_col.value(index))
+}
+
 pub struct Recorder<Inst: FmiInstance> {
     pub(crate) field: Field,
     pub(crate) value_reference: Inst::ValueRef,
     pub(crate) builder: Box<dyn ArrayBuilder>,
     pub(crate) binary_max_size: Option<usize>,
+    /// Whether the variable is a Clock, which is recorded from `RecorderState::active_clocks`
+    pub(crate) clock: bool,
+    /// Size of each dimension of an array variable, fully resolved after initialization
+    pub(crate) shape: Option<Vec<usize>>,
+    /// Dimensions of an array variable, each either a fixed size or a structural parameter
+    pub(crate) dimensions: Vec<Either<usize, Inst::ValueRef>>,
 }
 
 pub struct RecorderState<Inst: FmiInstance> {
     pub(crate) time: Float64Builder,
     pub(crate) recorders: Vec<Recorder<Inst>>,
+    /// Clocks that are active at the time instant being recorded
+    pub(crate) active_clocks: Vec<Inst::ValueRef>,
+    /// Output Clocks, which are read in Event Mode
+    pub(crate) output_clocks: Vec<Inst::ValueRef>,
+    /// Start time of the output points
+    start_time: f64,
+    /// Interval between output points, or `None` to record at every step
+    output_interval: Option<f64>,
+    /// Index of the next output point
+    next_output: usize,
 }
 
 impl<Inst> RecorderState<Inst>
@@ -211,8 +299,13 @@ where
 
         let time = Float64Builder::with_capacity(num_points);
 
-        let recorders = import
-            .outputs()
+        let variables = if sim_params.print_all_variables {
+            Either::Left(import.variables())
+        } else {
+            Either::Right(import.outputs())
+        };
+
+        let recorders = variables
             .map(|(field, vr)| {
                 let builder = make_builder(field.data_type(), num_points);
                 let binary_max_size = if field.data_type() == &DataType::Binary {
@@ -220,16 +313,48 @@ where
                 } else {
                     None
                 };
+                // Dimensions given by structural parameters are only known after initialization
+                let shape = match field.data_type() {
+                    DataType::FixedSizeList(_, len) => Some(vec![*len as usize]),
+                    _ => None,
+                };
                 Recorder {
+                    clock: import.is_clock(vr),
+                    dimensions: import.dimensions(vr),
                     field,
                     value_reference: vr,
                     builder,
                     binary_max_size,
+                    shape,
                 }
             })
             .collect();
 
-        Self { time, recorders }
+        Self {
+            time,
+            recorders,
+            active_clocks: Vec::new(),
+            output_clocks: import.output_clocks().collect(),
+            start_time: sim_params.start_time,
+            output_interval: (!sim_params.output_every_step).then_some(sim_params.output_interval),
+            next_output: 0,
+        }
+    }
+
+    /// Whether the next output point has been reached at `time`. Output points are taken at the
+    /// steps, so a step that passes one or more output points is recorded once.
+    pub(crate) fn output_due(&mut self, time: f64) -> bool {
+        let Some(interval) = self.output_interval else {
+            return true;
+        };
+        // Tolerate round-off between the step and output time grids
+        let eps = interval * 1e-9;
+        let next = self.start_time + self.next_output as f64 * interval;
+        if time + eps < next {
+            return false;
+        }
+        self.next_output = ((time + eps - self.start_time) / interval).floor() as usize + 1;
+        true
     }
 
     /// Finish the output state and return the RecordBatch.
@@ -237,6 +362,7 @@ where
         let Self {
             mut time,
             recorders,
+            ..
         } = self;
 
         let recorders = recorders.into_iter().map(
@@ -244,8 +370,18 @@ where
                  field,
                  value_reference: _,
                  mut builder,
+                 shape,
                  ..
-             }| { (field, builder.finish()) },
+             }| {
+                let field = match shape {
+                    Some(shape) => field.with_metadata(HashMap::from([(
+                        SHAPE_METADATA_KEY.to_string(),
+                        shape.iter().join(","),
+                    )])),
+                    None => field,
+                };
+                (field, builder.finish())
+            },
         );
 
         let time = std::iter::once((
@@ -258,3 +394,42 @@ where
         RecordBatch::try_new(schema, columns).unwrap()
     }
 }
+
+#[cfg(all(test, feature = "fmi3"))]
+mod tests {
+    use super::*;
+    use arrow::array::{BooleanArray, Int32Array};
+    use fmi::fmi3::instance::InstanceCS;
+
+    fn clock_input_state(rx_clock: ArrayRef) -> InputState<InstanceCS> {
+        let field = Field::new("Rx_Clock", rx_clock.data_type().clone(), true);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("time", DataType::Float64, false),
+            field.clone(),
+        ]));
+        let time = Arc::new(Float64Array::from(vec![0.0, 0.2, 0.4, 0.6]));
+        InputState {
+            input_data: Some(RecordBatch::try_new(schema, vec![time, rx_clock]).unwrap()),
+            continuous_inputs: Vec::new(),
+            discrete_inputs: Vec::new(),
+            clock_inputs: vec![(field, 0)],
+        }
+    }
+
+    #[test]
+    fn next_input_event_skips_null_clock_ticks() {
+        let rx_clock = BooleanArray::from(vec![Some(false), None, Some(true), Some(false)]);
+        let input_state = clock_input_state(Arc::new(rx_clock));
+
+        assert_eq!(input_state.next_input_event(0.0).unwrap(), 0.4);
+        assert_eq!(input_state.next_input_event(0.4).unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn next_input_event_non_boolean_clock() {
+        let input_state = clock_input_state(Arc::new(Int32Array::from(vec![0, 1, 0, 1])));
+
+        let err = input_state.next_input_event(0.0).unwrap_err();
+        assert!(err.to_string().contains("Rx_Clock"));
+    }
+}
@@ -8,12 +8,18 @@ use super::{
     SimState, SimStats,
     interpolation::Linear,
     solver::Solver,
-    traits::{InstRecordValues, InstSetValues, SimHandleEvents, SimMe},
+    traits::{InstClocks, InstRecordValues, InstSetValues, SimFmuState, SimHandleEvents, SimMe},
 };
 
 impl<Inst> SimMe<Inst> for SimState<Inst>
 where
-    Inst: FmiInstance + FmiModelExchange + InstSetValues + InstRecordValues + FmiEventHandler,
+    Inst: FmiInstance
+        + FmiModelExchange
+        + InstSetValues
+        + InstRecordValues
+        + InstClocks
+        + FmiEventHandler,
+    Self: SimFmuState,
 {
     fn main_loop<S>(&mut self, mut solver: S) -> Result<SimStats, Error>
     where
@@ -23,9 +29,15 @@ where
         self.inst.enter_continuous_time_mode().map_err(Into::into)?;
 
         let mut time = self.sim_params.start_time;
+        // Whether an event has been handled at `time`
+        let mut event_handled = false;
 
         loop {
-            self.inst.record_outputs(time, &mut self.recorder_state)?;
+            let output_due = self.recorder_state.output_due(time);
+            if output_due || event_handled || time >= self.sim_params.stop_time {
+                self.inst.record_outputs(time, &mut self.recorder_state)?;
+            }
+            event_handled = false;
 
             if time >= self.sim_params.stop_time {
                 break;
@@ -33,8 +45,8 @@ where
 
             // calculate next time point
             let next_regular_point = self.sim_params.start_time
-                + (stats.num_steps + 1) as f64 * self.sim_params.output_interval;
-            let next_input_event_time = self.input_state.next_input_event(time);
+                + (stats.num_steps + 1) as f64 * self.sim_params.step_size;
+            let next_input_event_time = self.input_state.next_input_event(time)?;
 
             let input_event = next_regular_point >= next_input_event_time;
             let time_event = next_regular_point >= self.next_event_time();
@@ -50,6 +62,10 @@ where
                 solver.step(&mut self.inst, next_communication_point)?;
             time = time_reached;
 
+            // The solver may return early at a state event, before the input or time event
+            let input_event = time >= next_input_event_time;
+            let time_event = time >= self.next_event_time();
+
             self.inst.set_time(time).map_err(Into::into)?;
 
             self.input_state
@@ -77,6 +93,7 @@ where
                 );
                 stats.num_events += 1;
                 let (reset_solver, terminate) = self.handle_events(time, input_event)?;
+                event_handled = true;
 
                 if terminate {
                     break;
@@ -90,6 +107,10 @@ where
             }
         }
 
+        if let Some(path) = self.sim_params.final_fmu_state_file.clone() {
+            self.save_fmu_state(&path)?;
+        }
+
         self.inst.terminate().map_err(Into::into)?;
 
         Ok(stats)
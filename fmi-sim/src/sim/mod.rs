@@ -13,7 +13,9 @@ use crate::{Error, options};
 use self::{
     interpolation::Linear,
     params::SimParams,
-    traits::{FmiSim, InstRecordValues, InstSetValues, SimDefaultInitialize, SimHandleEvents},
+    traits::{
+        FmiSim, InstClocks, InstRecordValues, InstSetValues, SimDefaultInitialize, SimHandleEvents,
+    },
 };
 
 #[cfg(feature = "fmi2")]
@@ -23,6 +25,8 @@ pub mod fmi3;
 mod interpolation;
 mod io;
 mod me;
+#[cfg(feature = "cs")]
+pub mod multi;
 pub mod params;
 pub mod solver;
 pub mod traits;
@@ -57,17 +61,44 @@ pub trait SimStateTrait<Inst: FmiInstance, Import: FmiImport> {
         Self: Sized;
 }
 
+impl<Inst: InstRecordValues> SimState<Inst> {
+    /// Record a row for the active Clocks at `time`. Clocked variables are only valid while their
+    /// Clock is active, so this happens in Event Mode, before the discrete states are updated.
+    fn record_clock_ticks(
+        &mut self,
+        time: f64,
+        active_clocks: Vec<Inst::ValueRef>,
+    ) -> Result<(), Error> {
+        if !active_clocks.is_empty() {
+            self.recorder_state.active_clocks = active_clocks;
+            self.inst.record_outputs(time, &mut self.recorder_state)?;
+            self.recorder_state.active_clocks.clear();
+        }
+        Ok(())
+    }
+}
+
 impl<Inst> SimHandleEvents for SimState<Inst>
 where
-    Inst: FmiEventHandler + InstSetValues + InstRecordValues,
+    Inst: FmiEventHandler + InstSetValues + InstRecordValues + InstClocks,
 {
     fn handle_events(&mut self, time: f64, input_event: bool) -> Result<(bool, bool), Error> {
-        self.inst.record_outputs(time, &mut self.recorder_state)?;
+        if self.sim_params.print_left_limit {
+            self.inst.record_outputs(time, &mut self.recorder_state)?;
+        }
         self.inst.enter_event_mode().map_err(Into::into)?;
+        let mut active_clocks = Vec::new();
         if input_event {
             self.input_state
                 .apply_input::<Linear>(time, &mut self.inst, true, true, true)?;
+            active_clocks = self.input_state.apply_input_clocks(time, &mut self.inst)?;
         }
+        active_clocks.extend(
+            self.inst
+                .ticked_clocks(&self.recorder_state.output_clocks)?,
+        );
+        self.record_clock_ticks(time, active_clocks)?;
+
         let mut reset_solver = false;
 
         self.event_flags.discrete_states_need_update = true;
@@ -107,7 +138,7 @@ pub fn simulate_with<Imp: FmiSim>(
         #[cfg(feature = "cs")]
         options::Interface::CoSimulation(options) => import.simulate_cs(options, input_data),
         #[cfg(feature = "se")]
-        options::Interface::ScheduledExecution(options) => unimplemented!(),
+        options::Interface::ScheduledExecution(options) => import.simulate_se(options, input_data),
         #[cfg(any(not(feature = "me"), not(feature = "cs")))]
         _ => Err(fmi::Error::UnsupportedInterface(format!("{}", interface)).into()),
     }
@@ -130,6 +161,12 @@ macro_rules! impl_sim_default_initialize {
                     .map_err(fmi::Error::from)?;
 
                 if self.sim_params.event_mode_used {
+                    // Output Clocks may tick during initialization
+                    let ticked = self
+                        .inst
+                        .ticked_clocks(&self.recorder_state.output_clocks)?;
+                    self.record_clock_ticks(self.sim_params.start_time, ticked)?;
+
                     // update discrete states
                     self.event_flags.discrete_states_need_update = true;
                     while self.event_flags.discrete_states_need_update {
@@ -174,6 +211,24 @@ impl SimDefaultInitialize for SimState<fmi::fmi2::instance::InstanceCS> {
 impl_sim_default_initialize!(fmi::fmi3::instance::InstanceME);
 #[cfg(feature = "cs")]
 impl_sim_default_initialize!(fmi::fmi3::instance::InstanceCS);
+#[cfg(feature = "se")]
+impl SimDefaultInitialize for SimState<fmi::fmi3::instance::InstanceSE> {
+    fn default_initialize(&mut self) -> Result<(), Error> {
+        self.inst
+            .enter_initialization_mode(
+                self.sim_params.tolerance,
+                self.sim_params.start_time,
+                Some(self.sim_params.stop_time),
+            )
+            .map_err(fmi::Error::from)?;
+        // Leaves the FMU in Clock Activation Mode
+        self.inst
+            .exit_initialization_mode()
+            .map_err(fmi::Error::from)?;
+
+        Ok(())
+    }
+}
 
 macro_rules! impl_sim_initialize {
     ($inst:ty) => {
@@ -183,11 +238,6 @@ macro_rules! impl_sim_initialize {
                 start_values: io::StartValues<<$inst as FmiInstance>::ValueRef>,
                 initial_fmu_state_file: Option<P>,
             ) -> Result<(), Error> {
-                if let Some(_initial_state_file) = &initial_fmu_state_file {
-                    unimplemented!("initial_fmu_state_file");
-                    // self.inst.restore_fmu_state_from_file(initial_state_file)?;
-                }
-
                 // set start values
                 traits::SimApplyStartValues::apply_start_values(self, &start_values)?;
 
@@ -199,9 +249,12 @@ macro_rules! impl_sim_initialize {
                     false,
                 )?;
 
-                // Default initialization
-                if initial_fmu_state_file.is_none() {
-                    self.default_initialize()?;
+                self.default_initialize()?;
+
+                // The restored state replaces the initialized one, but leaves the instance in the
+                // mode reached by the default initialization.
+                if let Some(initial_state_file) = &initial_fmu_state_file {
+                    traits::SimFmuState::restore_fmu_state(self, initial_state_file.as_ref())?;
                 }
 
                 Ok(())
@@ -218,3 +271,5 @@ impl_sim_initialize!(fmi::fmi3::instance::InstanceME);
 impl_sim_initialize!(fmi::fmi2::instance::InstanceCS);
 #[cfg(feature = "cs")]
 impl_sim_initialize!(fmi::fmi3::instance::InstanceCS);
+#[cfg(feature = "se")]
+impl_sim_initialize!(fmi::fmi3::instance::InstanceSE);
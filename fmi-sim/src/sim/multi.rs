@@ -0,0 +1,883 @@
+//! Co-Simulation of several connected FMUs.
+//!
+//! A [`MultiSim`] owns a set of Co-Simulation slaves and a list of connections from the outputs of
+//! one slave to the inputs of another. The connected variables are initialized in topological
+//! order of the direct feedthrough declared in the `ModelStructure` of each FMU, after which all
+//! slaves are stepped with a common communication step, using either a Jacobi or a Gauss-Seidel
+//! coupling scheme. Slaves that encounter an event during a step enter Event Mode at the following
+//! communication point. The outputs of all slaves are recorded into a single [`RecordBatch`] with
+//! columns named `instance.variable`.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use arrow::{
+    array::{ArrayRef, Float64Array, Float64Builder, RecordBatch},
+    datatypes::{DataType, Field, Schema},
+};
+use fmi::traits::FmiInstance;
+
+use crate::Error;
+
+use super::{
+    SimState, SimStats,
+    traits::{InstGetValues, InstRecordValues, InstSetValues},
+};
+
+/// A Co-Simulation FMU instance taking part in a [`MultiSim`].
+pub trait CoSimSlave {
+    /// Name of the instance, which qualifies its variables in connections and results.
+    fn name(&self) -> &str;
+
+    /// Value reference and data type of the input with the given name.
+    fn input(&self, name: &str) -> Option<(u32, DataType)>;
+
+    /// Value reference and data type of the output with the given name.
+    fn output(&self, name: &str) -> Option<(u32, DataType)>;
+
+    /// Whether the output directly depends on the input (direct feedthrough).
+    fn depends_on(&self, output: u32, input: u32) -> bool;
+
+    fn enter_initialization_mode(
+        &mut self,
+        tolerance: Option<f64>,
+        start_time: f64,
+        stop_time: f64,
+    ) -> Result<(), Error>;
+
+    fn exit_initialization_mode(&mut self) -> Result<(), Error>;
+
+    /// Get the value of a variable as a one-element array.
+    fn get_value(&mut self, vr: u32, data_type: &DataType) -> Result<ArrayRef, Error>;
+
+    /// Set the value of a variable from a one-element array.
+    fn set_value(&mut self, vr: u32, value: &ArrayRef) -> Result<(), Error>;
+
+    /// Advance the slave from `time` by `step_size`.
+    fn do_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error>;
+
+    /// Handle the event encountered in the last step at `time`, and return to Step Mode.
+    ///
+    /// # Returns
+    /// Whether the slave requested the simulation to terminate.
+    fn handle_events(&mut self, time: f64) -> Result<bool, Error>;
+
+    /// Record the outputs of the slave at the given time.
+    fn record_outputs(&mut self, time: f64) -> Result<(), Error>;
+
+    fn terminate(&mut self) -> Result<(), Error>;
+
+    /// Finish recording and return the recorded outputs, including a `time` column.
+    fn finish(self: Box<Self>) -> RecordBatch;
+}
+
+/// Imports that can be instantiated as a [`CoSimSlave`].
+pub trait CoSimImport {
+    /// Instantiate the FMU for Co-Simulation under the given instance name.
+    ///
+    /// `start_values` are "variableName=value" pairs, see
+    /// [`crate::options::CommonOptions::initial_values`].
+    fn instantiate_slave(
+        &self,
+        name: &str,
+        start_values: &[String],
+    ) -> Result<Box<dyn CoSimSlave>, Error>;
+}
+
+/// Outcome of a communication step of a [`CoSimSlave`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StepResult {
+    /// The slave requested the simulation to terminate.
+    pub terminate: bool,
+    /// The slave needs to enter Event Mode at the end of the step.
+    pub event_encountered: bool,
+    /// The slave returned early, at the given time.
+    pub early_return: Option<f64>,
+}
+
+/// Mode changes and stepping of a Co-Simulation instance, implemented per FMI version.
+pub(crate) trait SlaveStep {
+    /// Leave Initialization Mode, and enter Step Mode if Event Mode is used.
+    fn slave_exit_initialization_mode(&mut self) -> Result<(), Error>;
+
+    /// Advance the instance.
+    fn slave_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error>;
+
+    /// Handle an event at `time` in Event Mode and return to Step Mode, returning whether the
+    /// instance requested termination.
+    fn slave_handle_events(&mut self, time: f64) -> Result<bool, Error>;
+}
+
+/// An FMU instance wrapped as a [`CoSimSlave`].
+pub(crate) struct Slave<Inst: FmiInstance> {
+    pub(crate) name: String,
+    pub(crate) sim_state: SimState<Inst>,
+    pub(crate) inputs: Vec<(Field, u32)>,
+    pub(crate) outputs: Vec<(Field, u32)>,
+    /// Inputs each output directly depends on, or `None` if it may depend on all of them
+    pub(crate) dependencies: HashMap<u32, Option<Vec<u32>>>,
+}
+
+fn find_variable(variables: &[(Field, u32)], name: &str) -> Option<(u32, DataType)> {
+    variables
+        .iter()
+        .find(|(field, _)| field.name() == name)
+        .map(|(field, vr)| (*vr, field.data_type().clone()))
+}
+
+impl<Inst> CoSimSlave for Slave<Inst>
+where
+    Inst: InstGetValues + InstSetValues + InstRecordValues,
+    SimState<Inst>: SlaveStep,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn input(&self, name: &str) -> Option<(u32, DataType)> {
+        find_variable(&self.inputs, name)
+    }
+
+    fn output(&self, name: &str) -> Option<(u32, DataType)> {
+        find_variable(&self.outputs, name)
+    }
+
+    fn depends_on(&self, output: u32, input: u32) -> bool {
+        match self.dependencies.get(&output) {
+            Some(Some(dependencies)) => dependencies.contains(&input),
+            _ => true,
+        }
+    }
+
+    fn enter_initialization_mode(
+        &mut self,
+        tolerance: Option<f64>,
+        start_time: f64,
+        stop_time: f64,
+    ) -> Result<(), Error> {
+        self.sim_state
+            .inst
+            .enter_initialization_mode(tolerance, start_time, Some(stop_time))
+            .map_err(Into::into)?;
+        Ok(())
+    }
+
+    fn exit_initialization_mode(&mut self) -> Result<(), Error> {
+        self.sim_state.slave_exit_initialization_mode()
+    }
+
+    fn get_value(&mut self, vr: u32, data_type: &DataType) -> Result<ArrayRef, Error> {
+        Ok(self.sim_state.inst.get_array(vr.into(), data_type)?)
+    }
+
+    fn set_value(&mut self, vr: u32, value: &ArrayRef) -> Result<(), Error> {
+        self.sim_state.inst.set_array(&[vr.into()], value)?;
+        Ok(())
+    }
+
+    fn do_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error> {
+        self.sim_state.slave_step(time, step_size)
+    }
+
+    fn handle_events(&mut self, time: f64) -> Result<bool, Error> {
+        self.sim_state.slave_handle_events(time)
+    }
+
+    fn record_outputs(&mut self, time: f64) -> Result<(), Error> {
+        self.sim_state
+            .inst
+            .record_outputs(time, &mut self.sim_state.recorder_state)?;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<(), Error> {
+        self.sim_state.inst.terminate().map_err(Into::into)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> RecordBatch {
+        self.sim_state.recorder_state.finish()
+    }
+}
+
+/// Order in which the slaves exchange values during a communication step.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CouplingScheme {
+    /// All slaves are stepped with the inputs from the last communication point.
+    #[default]
+    Jacobi,
+    /// Slaves are stepped one after another in the order they were added, each using the outputs
+    /// of the slaves already stepped to the next communication point.
+    GaussSeidel,
+}
+
+/// Communication step size control.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommunicationStep {
+    /// Constant communication step size
+    Fixed(f64),
+    /// Step size adapted to the change of the connected variables over the last step.
+    ///
+    /// Steps are never rejected; the error estimate only sizes the next step.
+    Adaptive {
+        /// Size of the first step
+        initial: f64,
+        min: f64,
+        max: f64,
+        /// Relative and absolute tolerance on the change of a connected variable per step
+        tolerance: f64,
+    },
+}
+
+impl Default for CommunicationStep {
+    fn default() -> Self {
+        Self::Fixed(1e-2)
+    }
+}
+
+/// Parameters of a [`MultiSim`].
+#[derive(Debug, Clone)]
+pub struct MultiSimParams {
+    /// Start time of the simulation
+    pub start_time: f64,
+    /// Stop time of the simulation
+    pub stop_time: f64,
+    /// Tolerance passed on to the slaves
+    pub tolerance: Option<f64>,
+    pub scheme: CouplingScheme,
+    pub step: CommunicationStep,
+}
+
+impl Default for MultiSimParams {
+    fn default() -> Self {
+        Self {
+            start_time: 0.0,
+            stop_time: 1.0,
+            tolerance: None,
+            scheme: CouplingScheme::default(),
+            step: CommunicationStep::default(),
+        }
+    }
+}
+
+/// A connection from the output of one slave to the input of another
+struct Connection {
+    /// Source slave index and output value reference
+    from: (usize, u32),
+    from_type: DataType,
+    /// Destination slave index and input value reference
+    to: (usize, u32),
+    to_type: DataType,
+    /// Value at the last communication point, used for step size control
+    last_value: Option<f64>,
+}
+
+/// Master for the Co-Simulation of several connected FMUs.
+pub struct MultiSim {
+    params: MultiSimParams,
+    slaves: Vec<Box<dyn CoSimSlave>>,
+    connections: Vec<Connection>,
+}
+
+impl MultiSim {
+    pub fn new(params: MultiSimParams) -> Self {
+        Self {
+            params,
+            slaves: Vec::new(),
+            connections: Vec::new(),
+        }
+    }
+
+    /// Add a slave to the simulation. Instance names must be unique.
+    pub fn add_slave(&mut self, slave: Box<dyn CoSimSlave>) -> Result<(), Error> {
+        if self.slave_index(slave.name()).is_some() {
+            return Err(anyhow::anyhow!("Duplicate instance name: {}", slave.name()).into());
+        }
+        self.slaves.push(slave);
+        Ok(())
+    }
+
+    /// Instantiate an FMU and add it to the simulation under the given instance name.
+    pub fn add_fmu<Import: CoSimImport>(
+        &mut self,
+        import: &Import,
+        name: &str,
+        start_values: &[String],
+    ) -> Result<(), Error> {
+        let slave = import.instantiate_slave(name, start_values)?;
+        self.add_slave(slave)
+    }
+
+    /// Connect an output to an input, both given as "instance.variable".
+    pub fn connect(&mut self, output: &str, input: &str) -> Result<(), Error> {
+        let (from_slave, from_var) = self.split_name(output)?;
+        let (to_slave, to_var) = self.split_name(input)?;
+
+        let (from_vr, from_type) = self.slaves[from_slave]
+            .output(from_var)
+            .with_context(|| format!("Unknown output: {output}"))?;
+        let (to_vr, to_type) = self.slaves[to_slave]
+            .input(to_var)
+            .with_context(|| format!("Unknown input: {input}"))?;
+
+        if !arrow::compute::can_cast_types(&from_type, &to_type) {
+            return Err(anyhow::anyhow!(
+                "Cannot connect {output} of type {from_type} to {input} of type {to_type}"
+            )
+            .into());
+        }
+        if self.connections.iter().any(|c| c.to == (to_slave, to_vr)) {
+            return Err(anyhow::anyhow!("Input {input} is already connected").into());
+        }
+
+        self.connections.push(Connection {
+            from: (from_slave, from_vr),
+            from_type,
+            to: (to_slave, to_vr),
+            to_type,
+            last_value: None,
+        });
+        Ok(())
+    }
+
+    fn slave_index(&self, name: &str) -> Option<usize> {
+        self.slaves.iter().position(|slave| slave.name() == name)
+    }
+
+    /// Split "instance.variable" into the slave index and the variable name.
+    fn split_name<'a>(&self, name: &'a str) -> anyhow::Result<(usize, &'a str)> {
+        let (instance, variable) = name
+            .split_once('.')
+            .with_context(|| format!("Expected \"instance.variable\", got {name}"))?;
+        let index = self
+            .slave_index(instance)
+            .with_context(|| format!("Unknown instance: {instance}"))?;
+        Ok((index, variable))
+    }
+
+    /// Order of the connected outputs such that each output is only read after all connected
+    /// inputs it directly depends on have been set.
+    fn initialization_order(&self) -> Vec<(usize, u32)> {
+        let mut ports: Vec<(usize, u32)> = Vec::new();
+        for connection in &self.connections {
+            if !ports.contains(&connection.from) {
+                ports.push(connection.from);
+            }
+        }
+
+        // Output `port` depends on output `source` if `source` is connected to an input of the
+        // same slave that `port` has direct feedthrough from.
+        let depends = |port: (usize, u32), source: (usize, u32)| {
+            self.connections.iter().any(|c| {
+                c.from == source
+                    && c.to.0 == port.0
+                    && self.slaves[port.0].depends_on(port.1, c.to.1)
+            })
+        };
+
+        let mut in_degree: Vec<usize> = ports
+            .iter()
+            .map(|&port| {
+                ports
+                    .iter()
+                    .filter(|&&source| depends(port, source))
+                    .count()
+            })
+            .collect();
+        let mut done = vec![false; ports.len()];
+        let mut order = Vec::with_capacity(ports.len());
+
+        while let Some(next) = (0..ports.len()).find(|&i| !done[i] && in_degree[i] == 0) {
+            done[next] = true;
+            order.push(ports[next]);
+            for (i, &port) in ports.iter().enumerate() {
+                if !done[i] && depends(port, ports[next]) {
+                    in_degree[i] -= 1;
+                }
+            }
+        }
+
+        if order.len() < ports.len() {
+            let remaining: Vec<_> = (0..ports.len()).filter(|&i| !done[i]).collect();
+            log::warn!(
+                "Algebraic loop between {} connected outputs, initializing them in connection order",
+                remaining.len()
+            );
+            order.extend(remaining.into_iter().map(|i| ports[i]));
+        }
+
+        order
+    }
+
+    /// Transfer the value of a connection from its output to its input.
+    fn transfer(
+        slaves: &mut [Box<dyn CoSimSlave>],
+        connection: &Connection,
+    ) -> Result<ArrayRef, Error> {
+        let (from_slave, from_vr) = connection.from;
+        let (to_slave, to_vr) = connection.to;
+        let mut value = slaves[from_slave].get_value(from_vr, &connection.from_type)?;
+        if connection.from_type != connection.to_type {
+            value = arrow::compute::cast(&value, &connection.to_type)?;
+        }
+        slaves[to_slave].set_value(to_vr, &value)?;
+        Ok(value)
+    }
+
+    /// Transfer all connections, reading the outputs in the given order.
+    ///
+    /// # Returns
+    /// The largest change of a numeric connected variable since the last exchange, weighted by
+    /// the tolerance.
+    fn exchange(&mut self, order: &[(usize, u32)], tolerance: f64) -> Result<f64, Error> {
+        let mut error: f64 = 0.0;
+        for port in order {
+            for connection in self.connections.iter_mut().filter(|c| c.from == *port) {
+                let value = Self::transfer(&mut self.slaves, connection)?;
+                let value = arrow::compute::cast(&value, &DataType::Float64)
+                    .ok()
+                    .and_then(|value| {
+                        value
+                            .as_any()
+                            .downcast_ref::<Float64Array>()
+                            .map(|value| value.value(0))
+                    });
+                if let (Some(new), Some(old)) = (value, connection.last_value) {
+                    let scale = tolerance * (1.0 + new.abs().max(old.abs()));
+                    error = error.max((new - old).abs() / scale);
+                }
+                connection.last_value = value;
+            }
+        }
+        Ok(error)
+    }
+
+    fn record_outputs(
+        &mut self,
+        time: f64,
+        time_builder: &mut Float64Builder,
+    ) -> Result<(), Error> {
+        time_builder.append_value(time);
+        for slave in &mut self.slaves {
+            slave.record_outputs(time)?;
+        }
+        Ok(())
+    }
+
+    /// Run the simulation.
+    ///
+    /// # Returns
+    /// The outputs of all slaves in a single record batch, and the statistics of the simulation.
+    pub fn simulate(mut self) -> Result<(RecordBatch, SimStats), Error> {
+        let MultiSimParams {
+            start_time,
+            stop_time,
+            tolerance,
+            scheme,
+            step,
+        } = self.params.clone();
+
+        let (mut step_size, step_tolerance) = match step {
+            CommunicationStep::Fixed(h) => (h, 1.0),
+            CommunicationStep::Adaptive {
+                initial, tolerance, ..
+            } => (initial, tolerance),
+        };
+        if step_size <= 0.0 {
+            return Err(anyhow::anyhow!("Communication step size must be positive").into());
+        }
+
+        let mut stats = SimStats::default();
+        let mut time_builder = Float64Builder::new();
+        let order = self.initialization_order();
+
+        for slave in &mut self.slaves {
+            slave.enter_initialization_mode(tolerance, start_time, stop_time)?;
+        }
+        self.exchange(&order, step_tolerance)?;
+        for slave in &mut self.slaves {
+            slave.exit_initialization_mode()?;
+        }
+        // Outputs may change when leaving initialization mode
+        self.exchange(&order, step_tolerance)?;
+
+        let mut time = start_time;
+        self.record_outputs(time, &mut time_builder)?;
+
+        while time < stop_time {
+            let h = step_size.min(stop_time - time);
+            let mut results = Vec::with_capacity(self.slaves.len());
+
+            match scheme {
+                CouplingScheme::Jacobi => {
+                    for slave in &mut self.slaves {
+                        results.push(slave.do_step(time, h)?);
+                    }
+                }
+                CouplingScheme::GaussSeidel => {
+                    for index in 0..self.slaves.len() {
+                        for connection in self.connections.iter().filter(|c| c.to.0 == index) {
+                            Self::transfer(&mut self.slaves, connection)?;
+                        }
+                        results.push(self.slaves[index].do_step(time, h)?);
+                    }
+                }
+            }
+            stats.num_steps += 1;
+
+            // The slaves are instantiated without early return, and the other slaves have already
+            // completed the step, so there is no common time to continue from.
+            if let Some((slave, result)) = self
+                .slaves
+                .iter()
+                .zip(&results)
+                .find(|(_, result)| result.early_return.is_some())
+            {
+                return Err(anyhow::anyhow!(
+                    "{} returned early at t = {}, which is not supported",
+                    slave.name(),
+                    result.early_return.unwrap_or(time)
+                )
+                .into());
+            }
+            let mut terminate = results.iter().any(|result| result.terminate);
+
+            time = match step {
+                CommunicationStep::Fixed(h) => start_time + stats.num_steps as f64 * h,
+                CommunicationStep::Adaptive { .. } => time + h,
+            };
+            // Don't leave a sliver of the interval to round-off
+            if time + h * 1e-8 >= stop_time {
+                time = stop_time;
+            }
+
+            let mut error = self.exchange(&order, step_tolerance)?;
+
+            if !terminate && results.iter().any(|result| result.event_encountered) {
+                log::trace!("Event encountered at t = {time}");
+                stats.num_events += 1;
+                for (slave, result) in self.slaves.iter_mut().zip(&results) {
+                    if result.event_encountered {
+                        terminate |= slave.handle_events(time)?;
+                    }
+                }
+                // Outputs may change in Event Mode
+                error = error.max(self.exchange(&order, step_tolerance)?);
+            }
+
+            self.record_outputs(time, &mut time_builder)?;
+
+            if terminate {
+                log::info!("Simulation terminated by a slave at t = {time}");
+                break;
+            }
+
+            if let CommunicationStep::Adaptive { min, max, .. } = step {
+                let factor = if error > 0.0 { 0.9 / error } else { 2.0 };
+                step_size = (h * factor.clamp(0.2, 2.0)).clamp(min, max);
+                log::trace!("Communication step size at t = {time}: {step_size}");
+            }
+        }
+
+        for slave in &mut self.slaves {
+            slave.terminate()?;
+        }
+        stats.end_time = time;
+
+        Ok((combine_results(time_builder, self.slaves)?, stats))
+    }
+}
+
+/// Combine the recorded outputs of all slaves into one record batch with a common time column.
+fn combine_results(
+    mut time: Float64Builder,
+    slaves: Vec<Box<dyn CoSimSlave>>,
+) -> Result<RecordBatch, Error> {
+    let mut fields = vec![Field::new("time", DataType::Float64, false)];
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(time.finish())];
+
+    for slave in slaves {
+        let name = slave.name().to_owned();
+        let batch = slave.finish();
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            if field.name() == "time" {
+                continue;
+            }
+            fields.push(
+                field
+                    .as_ref()
+                    .clone()
+                    .with_name(format!("{name}.{}", field.name())),
+            );
+            columns.push(column.clone());
+        }
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::AsArray, datatypes::Float64Type};
+
+    use super::*;
+
+    /// A slave with Float64 inputs `u` and outputs `y`, computed by `f(x, u)` from a state `x`
+    /// that is integrated with explicit Euler as `x' = g(x, u)`.
+    struct TestSlave {
+        name: String,
+        feedthrough: bool,
+        f: fn(f64, f64) -> f64,
+        g: fn(f64, f64) -> f64,
+        /// Encounter an event once `x` reaches this value, and reset `x` to zero in Event Mode
+        reset_at: Option<f64>,
+        x: f64,
+        u: f64,
+        time: Float64Builder,
+        y: Float64Builder,
+    }
+
+    impl TestSlave {
+        fn new(
+            name: &str,
+            feedthrough: bool,
+            f: fn(f64, f64) -> f64,
+            g: fn(f64, f64) -> f64,
+        ) -> Box<Self> {
+            Box::new(Self {
+                name: name.to_owned(),
+                feedthrough,
+                f,
+                g,
+                reset_at: None,
+                x: 0.0,
+                u: 0.0,
+                time: Float64Builder::new(),
+                y: Float64Builder::new(),
+            })
+        }
+
+        fn with_reset_at(mut self: Box<Self>, reset_at: f64) -> Box<Self> {
+            self.reset_at = Some(reset_at);
+            self
+        }
+
+        fn y(&self) -> f64 {
+            (self.f)(self.x, self.u)
+        }
+    }
+
+    impl CoSimSlave for TestSlave {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn input(&self, name: &str) -> Option<(u32, DataType)> {
+            (name == "u").then_some((0, DataType::Float64))
+        }
+
+        fn output(&self, name: &str) -> Option<(u32, DataType)> {
+            (name == "y").then_some((1, DataType::Float64))
+        }
+
+        fn depends_on(&self, _output: u32, _input: u32) -> bool {
+            self.feedthrough
+        }
+
+        fn enter_initialization_mode(
+            &mut self,
+            _: Option<f64>,
+            _: f64,
+            _: f64,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn exit_initialization_mode(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_value(&mut self, _vr: u32, _data_type: &DataType) -> Result<ArrayRef, Error> {
+            Ok(Arc::new(Float64Array::from(vec![self.y()])))
+        }
+
+        fn set_value(&mut self, _vr: u32, value: &ArrayRef) -> Result<(), Error> {
+            self.u = value.as_primitive::<Float64Type>().value(0);
+            Ok(())
+        }
+
+        fn do_step(&mut self, time: f64, step_size: f64) -> Result<StepResult, Error> {
+            self.x += step_size * (self.g)(time, self.u);
+            Ok(StepResult {
+                event_encountered: self
+                    .reset_at
+                    .is_some_and(|reset_at| self.x >= reset_at - 1e-9),
+                ..Default::default()
+            })
+        }
+
+        fn handle_events(&mut self, _time: f64) -> Result<bool, Error> {
+            self.x = 0.0;
+            Ok(false)
+        }
+
+        fn record_outputs(&mut self, time: f64) -> Result<(), Error> {
+            self.time.append_value(time);
+            self.y.append_value(self.y());
+            Ok(())
+        }
+
+        fn terminate(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> RecordBatch {
+            RecordBatch::try_new(
+                Arc::new(Schema::new(vec![
+                    Field::new("time", DataType::Float64, false),
+                    Field::new("y", DataType::Float64, false),
+                ])),
+                vec![Arc::new(self.time.finish()), Arc::new(self.y.finish())],
+            )
+            .unwrap()
+        }
+    }
+
+    fn column(batch: &RecordBatch, name: &str) -> Vec<f64> {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_primitive::<Float64Type>()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_initialization_order() {
+        let mut sim = MultiSim::new(MultiSimParams {
+            stop_time: 0.1,
+            step: CommunicationStep::Fixed(0.1),
+            ..Default::default()
+        });
+        // Added in reverse order of the feedthrough chain source -> gain1 -> gain2
+        sim.add_slave(TestSlave::new("gain2", true, |_, u| 2.0 * u, |_, _| 0.0))
+            .unwrap();
+        sim.add_slave(TestSlave::new("gain1", true, |_, u| 3.0 * u, |_, _| 0.0))
+            .unwrap();
+        sim.add_slave(TestSlave::new("source", false, |_, _| 2.0, |_, _| 0.0))
+            .unwrap();
+        sim.connect("gain1.y", "gain2.u").unwrap();
+        sim.connect("source.y", "gain1.u").unwrap();
+
+        let (output, stats) = sim.simulate().unwrap();
+        assert_eq!(stats.num_steps, 1);
+        assert_eq!(column(&output, "time"), vec![0.0, 0.1]);
+        assert_eq!(column(&output, "gain2.y")[0], 12.0);
+    }
+
+    #[test]
+    fn test_coupling_schemes() {
+        // A ramp `y = t` integrated by a slave connected to it
+        let simulate = |scheme| {
+            let mut sim = MultiSim::new(MultiSimParams {
+                stop_time: 1.0,
+                scheme,
+                step: CommunicationStep::Fixed(0.1),
+                ..Default::default()
+            });
+            sim.add_slave(TestSlave::new("ramp", false, |x, _| x, |_, _| 1.0))
+                .unwrap();
+            sim.add_slave(TestSlave::new("integrator", false, |x, _| x, |_, u| u))
+                .unwrap();
+            sim.connect("ramp.y", "integrator.u").unwrap();
+            let (output, stats) = sim.simulate().unwrap();
+            assert_eq!(stats.num_steps, 10);
+            *column(&output, "integrator.y").last().unwrap()
+        };
+
+        // Jacobi uses the ramp at the start of each step, Gauss-Seidel at its end
+        float_cmp::assert_approx_eq!(f64, simulate(CouplingScheme::Jacobi), 0.45, epsilon = 1e-12);
+        float_cmp::assert_approx_eq!(
+            f64,
+            simulate(CouplingScheme::GaussSeidel),
+            0.55,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let mut sim = MultiSim::new(MultiSimParams {
+            stop_time: 1.0,
+            step: CommunicationStep::Fixed(0.1),
+            ..Default::default()
+        });
+        sim.add_slave(TestSlave::new("sawtooth", false, |x, _| x, |_, _| 1.0).with_reset_at(0.5))
+            .unwrap();
+        sim.add_slave(TestSlave::new("gain", true, |_, u| 2.0 * u, |_, _| 0.0))
+            .unwrap();
+        sim.connect("sawtooth.y", "gain.u").unwrap();
+
+        let (output, stats) = sim.simulate().unwrap();
+        assert_eq!(stats.num_events, 2);
+
+        // The outputs after the events are recorded and exchanged at the communication point
+        let expected = [0.0, 0.1, 0.2, 0.3, 0.4, 0.0, 0.1, 0.2, 0.3, 0.4, 0.0];
+        for (e, a) in expected.iter().zip(column(&output, "sawtooth.y")) {
+            float_cmp::assert_approx_eq!(f64, *e, a, epsilon = 1e-9);
+        }
+        for (e, a) in expected.iter().zip(column(&output, "gain.y")) {
+            float_cmp::assert_approx_eq!(f64, 2.0 * e, a, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_step() {
+        let mut sim = MultiSim::new(MultiSimParams {
+            stop_time: 1.0,
+            step: CommunicationStep::Adaptive {
+                initial: 1e-3,
+                min: 1e-4,
+                max: 0.25,
+                tolerance: 1e-2,
+            },
+            ..Default::default()
+        });
+        sim.add_slave(TestSlave::new("ramp", false, |x, _| x, |_, _| 1.0))
+            .unwrap();
+        sim.add_slave(TestSlave::new("integrator", false, |x, _| x, |_, u| u))
+            .unwrap();
+        sim.connect("ramp.y", "integrator.u").unwrap();
+
+        let (output, stats) = sim.simulate().unwrap();
+        let time = column(&output, "time");
+        assert_eq!(stats.end_time, 1.0);
+        assert_eq!(*time.last().unwrap(), 1.0);
+        assert!(time.windows(2).all(|w| w[0] < w[1]));
+        // The step size grows from the initial step
+        assert!(stats.num_steps < 100);
+    }
+
+    #[test]
+    fn test_connection_errors() {
+        let mut sim = MultiSim::new(MultiSimParams::default());
+        sim.add_slave(TestSlave::new("a", false, |x, _| x, |_, _| 0.0))
+            .unwrap();
+        sim.add_slave(TestSlave::new("b", false, |x, _| x, |_, _| 0.0))
+            .unwrap();
+
+        assert!(
+            sim.add_slave(TestSlave::new("a", false, |x, _| x, |_, _| 0.0))
+                .is_err()
+        );
+        assert!(sim.connect("a.y", "c.u").is_err());
+        assert!(sim.connect("a.u", "b.u").is_err());
+        assert!(sim.connect("a.y", "b.y").is_err());
+        assert!(sim.connect("ay", "b.u").is_err());
+        sim.connect("a.y", "b.u").unwrap();
+        assert!(sim.connect("b.y", "b.u").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use fmi::schema::traits::DefaultExperiment;
 
 use crate::options::CommonOptions;
@@ -10,12 +12,22 @@ pub struct SimParams {
     pub stop_time: f64,
     /// Output interval
     pub output_interval: f64,
+    /// Step size of the Euler solver, and communication step size of Co-Simulation
+    pub step_size: f64,
+    /// Record outputs at every step instead of at the output interval
+    pub output_every_step: bool,
+    /// Also record the left limit values before handling an event
+    pub print_left_limit: bool,
+    /// Record all variables instead of only the outputs
+    pub print_all_variables: bool,
     /// Tolerance
     pub tolerance: Option<f64>,
     /// Use event mode
     pub event_mode_used: bool,
     /// Support early-return in Co-Simulation.
     pub early_return_allowed: bool,
+    /// File to write the final serialized FMU state to
+    pub final_fmu_state_file: Option<PathBuf>,
 }
 
 impl SimParams {
@@ -43,6 +55,10 @@ impl SimParams {
 
         let output_interval = options
             .output_interval
+            .or(options
+                .num_steps
+                .filter(|n| *n > 0)
+                .map(|n| (stop_time - start_time) / n as f64))
             .or(default_experiment.step_size())
             .unwrap_or_else(|| (stop_time - start_time) / 500.0);
 
@@ -50,15 +66,26 @@ impl SimParams {
             panic!("`output_interval` must be positive.");
         }
 
+        let step_size = options.step_size.unwrap_or(output_interval);
+
+        if step_size <= 0.0 {
+            panic!("`step_size` must be positive.");
+        }
+
         let tolerance = options.tolerance.or(default_experiment.tolerance());
 
         Self {
             start_time,
             stop_time,
             output_interval,
+            step_size,
+            output_every_step: options.num_steps == Some(0),
+            print_left_limit: options.print_left_limit,
+            print_all_variables: options.print_all_variables,
             tolerance,
             event_mode_used,
             early_return_allowed,
+            final_fmu_state_file: options.final_fmu_state_file.clone(),
         }
     }
 }
@@ -0,0 +1,720 @@
+//! Variable-order, variable-step BDF solver for stiff systems.
+//!
+//! The implementation follows the quasi-constant step size formulation of Shampine & Reichelt,
+//! "The MATLAB ODE Suite" (1997), which stores the backward differences of the interpolating
+//! polynomial and rescales them whenever the step size changes.
+
+use super::{
+    DenseOutput, Model, Solver, SolverError,
+    common::{ZeroCrossings, absolute_tolerances, eval, rms_norm, select_initial_step},
+};
+
+/// Maximum order of the method
+const MAX_ORDER: usize = 5;
+/// Maximum number of Newton iterations per step
+const NEWTON_MAXITER: usize = 4;
+/// Bounds of the step size change factor
+const MIN_FACTOR: f64 = 0.2;
+const MAX_FACTOR: f64 = 10.0;
+/// Relative tolerance used if the simulation doesn't specify one
+const DEFAULT_TOLERANCE: f64 = 1e-4;
+
+/// Computes `sensitivity = J * seed`, where `J` is the Jacobian of the continuous state
+/// derivatives with respect to the continuous states, at the time and states currently set in
+/// the model.
+pub type DirectionalDerivative<M> =
+    Box<dyn FnMut(&mut M, &[f64], &mut [f64]) -> Result<(), SolverError>>;
+
+/// Parameters of the [`Bdf`] solver
+pub struct BdfParams<M> {
+    /// Directional derivatives provided by the model. If `None`, the Jacobian is built with
+    /// finite differences.
+    pub directional_derivative: Option<DirectionalDerivative<M>>,
+}
+
+impl<M> Default for BdfParams<M> {
+    fn default() -> Self {
+        Self {
+            directional_derivative: None,
+        }
+    }
+}
+
+/// Backward Differentiation Formula solver with orders 1 to 5, suited for stiff models.
+pub struct Bdf<M> {
+    params: BdfParams<M>,
+    /// Relative tolerance
+    rtol: f64,
+    /// Absolute tolerances, the relative tolerance scaled by the nominals of the states
+    atol: Vec<f64>,
+    /// Convergence tolerance of the Newton iteration
+    newton_tol: f64,
+    /// Current time
+    time: f64,
+    /// Start time of the last step
+    time_old: f64,
+    /// Size of the last step, at whose end the interpolating polynomial is anchored
+    h_last: f64,
+    /// Backward differences of the interpolating polynomial, where `d[0]` holds the states
+    d: Vec<Vec<f64>>,
+    /// Current order of the method
+    order: usize,
+    /// Current step size
+    h_abs: f64,
+    /// Step size to resume with after a step was shortened to reach a communication point
+    h_abs_resume: Option<f64>,
+    /// Number of steps taken with the current step size and order
+    n_equal_steps: usize,
+    /// Jacobian of the state derivatives, row-major
+    jac: Vec<f64>,
+    /// Factorized iteration matrix `I - c * J`
+    lu: Option<Lu>,
+    /// State event detection
+    zero_crossings: ZeroCrossings,
+    /// Whether the history has to be (re-)initialized from the model
+    needs_init: bool,
+    /// BDF coefficients
+    gamma: [f64; MAX_ORDER + 1],
+    error_const: [f64; MAX_ORDER + 1],
+}
+
+impl<M: Model> Solver<M> for Bdf<M> {
+    type Params = BdfParams<M>;
+
+    fn new(start_time: f64, tol: f64, nx: usize, nz: usize, params: Self::Params) -> Self {
+        let rtol = if tol > 0.0 { tol } else { DEFAULT_TOLERANCE };
+
+        let mut gamma = [0.0; MAX_ORDER + 1];
+        let mut error_const = [0.0; MAX_ORDER + 1];
+        for k in 0..=MAX_ORDER {
+            if k > 0 {
+                gamma[k] = gamma[k - 1] + 1.0 / k as f64;
+            }
+            error_const[k] = 1.0 / (k + 1) as f64;
+        }
+
+        Self {
+            params,
+            rtol,
+            atol: vec![rtol; nx],
+            newton_tol: (10.0 * f64::EPSILON / rtol).max(rtol.sqrt().min(0.03)),
+            time: start_time,
+            time_old: start_time,
+            h_last: 0.0,
+            d: vec![vec![0.0; nx]; MAX_ORDER + 3],
+            order: 1,
+            h_abs: 0.0,
+            h_abs_resume: None,
+            n_equal_steps: 0,
+            jac: vec![0.0; nx * nx],
+            lu: None,
+            zero_crossings: ZeroCrossings::new(nz),
+            needs_init: true,
+            gamma,
+            error_const,
+        }
+    }
+
+    fn step(&mut self, model: &mut M, next_time: f64) -> Result<(f64, bool), SolverError> {
+        if next_time <= self.time {
+            return Ok((self.time, false));
+        }
+
+        if self.needs_init {
+            self.init(model, next_time)?;
+        }
+
+        if self.atol.is_empty() {
+            self.time_old = self.time;
+            self.time = next_time;
+            model.set_time(self.time);
+            let state_event = self.locate_event(model).is_some();
+            return Ok((self.time, state_event));
+        }
+
+        if let Some(h_abs) = self.h_abs_resume.take() {
+            let factor = (h_abs / self.h_abs).min(MAX_FACTOR);
+            change_d(&mut self.d, self.order, factor);
+            self.h_abs *= factor;
+            self.n_equal_steps = 0;
+            self.lu = None;
+        }
+
+        while self.time < next_time {
+            self.step_impl(model, next_time)?;
+
+            // Leave the model at the accepted solution
+            model.set_time(self.time);
+            model.set_continuous_states(&self.d[0]);
+
+            if let Some(event_time) = self.locate_event(model) {
+                return Ok((event_time, true));
+            }
+        }
+
+        Ok((self.time, false))
+    }
+
+    fn reset(&mut self, _model: &mut M, time: f64) -> Result<(), SolverError> {
+        self.time = time;
+        self.needs_init = true;
+        Ok(())
+    }
+}
+
+impl<M: Model> Bdf<M> {
+    /// Initialize the history from the current model states with a first-order step.
+    fn init(&mut self, model: &mut M, t_bound: f64) -> Result<(), SolverError> {
+        self.needs_init = false;
+        self.zero_crossings.init(model);
+        let nx = self.atol.len();
+        if nx == 0 {
+            return Ok(());
+        }
+
+        let mut x = vec![0.0; nx];
+        model.get_continuous_states(&mut x);
+        absolute_tolerances(model, self.rtol, &mut self.atol);
+
+        let mut f = vec![0.0; nx];
+        eval(model, self.time, &x, &mut f);
+        self.h_abs =
+            select_initial_step(model, self.time, &x, &f, t_bound, 1, self.rtol, &self.atol);
+
+        for row in self.d.iter_mut() {
+            row.fill(0.0);
+        }
+        self.d[0].copy_from_slice(&x);
+        for (d1, f) in self.d[1].iter_mut().zip(&f) {
+            *d1 = f * self.h_abs;
+        }
+        self.order = 1;
+        self.n_equal_steps = 0;
+        self.h_abs_resume = None;
+        self.lu = None;
+        self.update_jacobian(model, self.time, &x)
+    }
+
+    /// Evaluate the Jacobian of the state derivatives at `(t, x)`.
+    fn update_jacobian(&mut self, model: &mut M, t: f64, x: &[f64]) -> Result<(), SolverError> {
+        let nx = x.len();
+        let mut f0 = vec![0.0; nx];
+        eval(model, t, x, &mut f0);
+
+        let mut column = vec![0.0; nx];
+        if let Some(directional_derivative) = &mut self.params.directional_derivative {
+            let mut seed = vec![0.0; nx];
+            for j in 0..nx {
+                seed[j] = 1.0;
+                directional_derivative(model, &seed, &mut column)?;
+                seed[j] = 0.0;
+                for (i, c) in column.iter().enumerate() {
+                    self.jac[i * nx + j] = *c;
+                }
+            }
+        } else {
+            let mut xp = x.to_vec();
+            for j in 0..nx {
+                let delta = f64::EPSILON.sqrt() * x[j].abs().max(self.atol[j] / self.rtol);
+                xp[j] = x[j] + delta;
+                eval(model, t, &xp, &mut column);
+                xp[j] = x[j];
+                for i in 0..nx {
+                    self.jac[i * nx + j] = (column[i] - f0[i]) / delta;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Factorize the iteration matrix `I - c * J`.
+    fn factorize(&self, c: f64) -> Option<Lu> {
+        let nx = self.atol.len();
+        let mut a: Vec<f64> = self.jac.iter().map(|j| -c * j).collect();
+        for i in 0..nx {
+            a[i * nx + i] += 1.0;
+        }
+        Lu::factorize(a, nx)
+    }
+
+    /// Solve the implicit BDF system with a simplified Newton iteration.
+    ///
+    /// # Returns
+    /// A tuple of (`converged`, `iterations`, `x_new`, `d`), where `d` is the correction applied
+    /// to the predicted states.
+    #[allow(clippy::too_many_arguments)]
+    fn solve_bdf_system(
+        &self,
+        model: &mut M,
+        t_new: f64,
+        x_predict: &[f64],
+        c: f64,
+        psi: &[f64],
+        lu: &Lu,
+        scale: &[f64],
+    ) -> (bool, usize, Vec<f64>, Vec<f64>) {
+        let nx = x_predict.len();
+        let mut x = x_predict.to_vec();
+        let mut d = vec![0.0; nx];
+        let mut f = vec![0.0; nx];
+        let mut dx = vec![0.0; nx];
+        let mut dx_norm_old: Option<f64> = None;
+        let mut converged = false;
+        let mut k = 0;
+
+        while k < NEWTON_MAXITER {
+            eval(model, t_new, &x, &mut f);
+            if !f.iter().all(|f| f.is_finite()) {
+                break;
+            }
+
+            for i in 0..nx {
+                dx[i] = c * f[i] - psi[i] - d[i];
+            }
+            lu.solve(&mut dx);
+            let dx_norm = rms_norm(dx.iter().zip(scale).map(|(dx, s)| dx / s));
+
+            let rate = dx_norm_old.map(|old| dx_norm / old);
+            if let Some(rate) = rate
+                && (rate >= 1.0
+                    || rate.powi((NEWTON_MAXITER - k) as i32) / (1.0 - rate) * dx_norm
+                        > self.newton_tol)
+            {
+                break;
+            }
+
+            for i in 0..nx {
+                x[i] += dx[i];
+                d[i] += dx[i];
+            }
+
+            if dx_norm == 0.0
+                || rate.is_some_and(|rate| rate / (1.0 - rate) * dx_norm < self.newton_tol)
+            {
+                converged = true;
+                break;
+            }
+
+            dx_norm_old = Some(dx_norm);
+            k += 1;
+        }
+
+        (converged, k + 1, x, d)
+    }
+
+    /// Locate a state event within the last step, and if there is one, cut the step short at it.
+    /// The history is then restarted from the states at the event.
+    fn locate_event(&mut self, model: &mut M) -> Option<f64> {
+        let mut zero_crossings = std::mem::take(&mut self.zero_crossings);
+        let event_time =
+            zero_crossings.locate(model, self, self.atol.len(), self.time_old, self.time);
+        self.zero_crossings = zero_crossings;
+
+        if let Some(event_time) = event_time {
+            self.time = event_time;
+            self.needs_init = true;
+        }
+        event_time
+    }
+
+    /// Take a single accepted step, shortened if needed to end exactly at `t_bound`.
+    fn step_impl(&mut self, model: &mut M, t_bound: f64) -> Result<(), SolverError> {
+        let t = self.time;
+        let nx = self.atol.len();
+        let min_step = 10.0 * (t.next_up() - t);
+
+        let mut h_abs = self.h_abs;
+        if h_abs < min_step {
+            h_abs = min_step;
+            change_d(&mut self.d, self.order, min_step / self.h_abs);
+            self.n_equal_steps = 0;
+        }
+
+        let order = self.order;
+        let mut current_jac = false;
+
+        let (t_new, d, safety, error_norm, scale) = loop {
+            if h_abs < min_step {
+                return Err(SolverError::StepSizeTooSmall(t));
+            }
+
+            let mut t_new = t + h_abs;
+            let mut h_abs_resume = None;
+            if t_new >= t_bound {
+                t_new = t_bound;
+                change_d(&mut self.d, order, (t_new - t) / h_abs);
+                self.n_equal_steps = 0;
+                self.lu = None;
+                h_abs_resume = Some(h_abs);
+            }
+            h_abs = t_new - t;
+
+            let mut x_predict = vec![0.0; nx];
+            let mut psi = vec![0.0; nx];
+            for k in 0..=order {
+                for i in 0..nx {
+                    x_predict[i] += self.d[k][i];
+                    if k > 0 {
+                        psi[i] += self.d[k][i] * self.gamma[k] / self.gamma[order];
+                    }
+                }
+            }
+            let scale: Vec<f64> = x_predict
+                .iter()
+                .zip(&self.atol)
+                .map(|(x, atol)| atol + self.rtol * x.abs())
+                .collect();
+
+            let c = h_abs / self.gamma[order];
+            let mut solution = None;
+            loop {
+                if self.lu.is_none() {
+                    self.lu = self.factorize(c);
+                }
+                if let Some(lu) = &self.lu {
+                    let (converged, n_iter, x_new, d) =
+                        self.solve_bdf_system(model, t_new, &x_predict, c, &psi, lu, &scale);
+                    if converged {
+                        solution = Some((n_iter, x_new, d));
+                        break;
+                    }
+                }
+                if current_jac {
+                    break;
+                }
+                self.update_jacobian(model, t_new, &x_predict)?;
+                self.lu = None;
+                current_jac = true;
+            }
+
+            let Some((n_iter, x_new, d)) = solution else {
+                log::trace!("BDF Newton iteration failed at t = {t_new}, h = {h_abs}");
+                h_abs *= 0.5;
+                change_d(&mut self.d, order, 0.5);
+                self.n_equal_steps = 0;
+                self.lu = None;
+                continue;
+            };
+
+            let safety =
+                0.9 * (2 * NEWTON_MAXITER + 1) as f64 / (2 * NEWTON_MAXITER + n_iter) as f64;
+            let scale: Vec<f64> = x_new
+                .iter()
+                .zip(&self.atol)
+                .map(|(x, atol)| atol + self.rtol * x.abs())
+                .collect();
+            let error_norm = rms_norm(
+                d.iter()
+                    .zip(&scale)
+                    .map(|(d, s)| self.error_const[order] * d / s),
+            );
+
+            if error_norm > 1.0 {
+                let factor = MIN_FACTOR.max(safety * error_norm.powf(-1.0 / (order + 1) as f64));
+                h_abs *= factor;
+                change_d(&mut self.d, order, factor);
+                self.n_equal_steps = 0;
+                continue;
+            }
+
+            self.h_abs_resume = h_abs_resume;
+            break (t_new, d, safety, error_norm, scale);
+        };
+
+        self.n_equal_steps += 1;
+        self.time_old = t;
+        self.h_last = t_new - t;
+        self.time = t_new;
+        self.h_abs = h_abs;
+
+        // Update the differences, using `d` = D^{order + 1} x_new
+        for (i, d) in d.iter().enumerate() {
+            self.d[order + 2][i] = d - self.d[order + 1][i];
+            self.d[order + 1][i] = *d;
+        }
+        for k in (0..=order).rev() {
+            for i in 0..nx {
+                self.d[k][i] += self.d[k + 1][i];
+            }
+        }
+
+        if self.n_equal_steps < order + 1 {
+            return Ok(());
+        }
+
+        // Select the order with the largest step size for the next step
+        let error_m_norm = if order > 1 {
+            rms_norm(
+                self.d[order]
+                    .iter()
+                    .zip(&scale)
+                    .map(|(d, s)| self.error_const[order - 1] * d / s),
+            )
+        } else {
+            f64::INFINITY
+        };
+        let error_p_norm = if order < MAX_ORDER {
+            rms_norm(
+                self.d[order + 2]
+                    .iter()
+                    .zip(&scale)
+                    .map(|(d, s)| self.error_const[order + 1] * d / s),
+            )
+        } else {
+            f64::INFINITY
+        };
+
+        let factors = [error_m_norm, error_norm, error_p_norm]
+            .iter()
+            .enumerate()
+            .map(|(i, norm)| norm.powf(-1.0 / (order + i) as f64))
+            .collect::<Vec<_>>();
+        let (delta_order, max_factor) =
+            factors
+                .iter()
+                .enumerate()
+                .fold((0, f64::NEG_INFINITY), |(best, max), (i, f)| {
+                    if *f > max { (i, *f) } else { (best, max) }
+                });
+
+        self.order = order + delta_order - 1;
+        let factor = MAX_FACTOR.min(safety * max_factor);
+        self.h_abs *= factor;
+        change_d(&mut self.d, self.order, factor);
+        self.n_equal_steps = 0;
+        self.lu = None;
+
+        Ok(())
+    }
+}
+
+impl<M> DenseOutput for Bdf<M> {
+    fn interpolate(&self, time: f64, x: &mut [f64]) {
+        x.copy_from_slice(&self.d[0]);
+        let h = self.h_abs;
+        let time_last = self.time_old + self.h_last;
+        let mut p = 1.0;
+        for j in 0..self.order {
+            p *= (time - (time_last - h * j as f64)) / (h * (j + 1) as f64);
+            for (x, d) in x.iter_mut().zip(&self.d[j + 1]) {
+                *x += d * p;
+            }
+        }
+    }
+}
+
+/// Transformation matrix to rescale the differences of an order `order` polynomial by `factor`.
+fn compute_r(order: usize, factor: f64) -> Vec<Vec<f64>> {
+    let mut r = vec![vec![1.0; order + 1]; order + 1];
+    for i in 1..=order {
+        let (prev, rest) = r.split_at_mut(i);
+        rest[0][0] = 0.0;
+        for j in 1..=order {
+            rest[0][j] = prev[i - 1][j] * (i as f64 - 1.0 - factor * j as f64) / i as f64;
+        }
+    }
+    r
+}
+
+/// Rescale the differences `d` after the step size changed by `factor`.
+fn change_d(d: &mut [Vec<f64>], order: usize, factor: f64) {
+    let r = compute_r(order, factor);
+    let u = compute_r(order, 1.0);
+    let nx = d[0].len();
+
+    // RU = R * U, and the new differences are RU^T * D
+    let mut ru = vec![vec![0.0; order + 1]; order + 1];
+    for i in 0..=order {
+        for j in 0..=order {
+            ru[i][j] = (0..=order).map(|k| r[i][k] * u[k][j]).sum();
+        }
+    }
+
+    let old: Vec<Vec<f64>> = d[..=order].to_vec();
+    for (j, row) in d[..=order].iter_mut().enumerate() {
+        for i in 0..nx {
+            row[i] = (0..=order).map(|k| ru[k][j] * old[k][i]).sum();
+        }
+    }
+}
+
+/// LU factorization with partial pivoting of a dense, row-major matrix.
+struct Lu {
+    lu: Vec<f64>,
+    pivots: Vec<usize>,
+    n: usize,
+}
+
+impl Lu {
+    /// Factorize `a`, returning `None` if it is singular.
+    fn factorize(mut a: Vec<f64>, n: usize) -> Option<Self> {
+        let mut pivots = vec![0; n];
+        for k in 0..n {
+            let p = (k..n).max_by(|&i, &j| a[i * n + k].abs().total_cmp(&a[j * n + k].abs()))?;
+            if a[p * n + k] == 0.0 || !a[p * n + k].is_finite() {
+                return None;
+            }
+            pivots[k] = p;
+            if p != k {
+                for j in 0..n {
+                    a.swap(k * n + j, p * n + j);
+                }
+            }
+            for i in k + 1..n {
+                let l = a[i * n + k] / a[k * n + k];
+                a[i * n + k] = l;
+                for j in k + 1..n {
+                    a[i * n + j] -= l * a[k * n + j];
+                }
+            }
+        }
+        Some(Self { lu: a, pivots, n })
+    }
+
+    /// Solve `A x = b` in place.
+    fn solve(&self, b: &mut [f64]) {
+        let n = self.n;
+        for k in 0..n {
+            b.swap(k, self.pivots[k]);
+        }
+        for i in 0..n {
+            for j in 0..i {
+                b[i] -= self.lu[i * n + j] * b[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                b[i] -= self.lu[i * n + j] * b[j];
+            }
+            b[i] /= self.lu[i * n + i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    /// `dx/dt = -k * (x - cos(t)) - sin(t)`, with the solution `x = cos(t)` for `x(0) = 1`
+    struct StiffModel {
+        time: f64,
+        x: f64,
+        k: f64,
+    }
+
+    impl Model for StiffModel {
+        fn get_continuous_states(&mut self, x: &mut [f64]) {
+            x[0] = self.x;
+        }
+
+        fn set_continuous_states(&mut self, states: &[f64]) {
+            self.x = states[0];
+        }
+
+        fn get_continuous_state_derivatives(&mut self, dx: &mut [f64]) {
+            dx[0] = -self.k * (self.x - self.time.cos()) - self.time.sin();
+        }
+
+        fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]) {
+            nominals[0] = 1.0;
+        }
+
+        fn get_event_indicators(&mut self, z: &mut [f64]) {
+            // crosses zero at t = pi / 2
+            z[0] = self.x;
+        }
+
+        fn set_time(&mut self, time: f64) {
+            self.time = time;
+        }
+    }
+
+    #[test]
+    fn test_bdf_stiff() {
+        let mut model = StiffModel {
+            time: 0.0,
+            x: 1.0,
+            k: 1e4,
+        };
+        let mut bdf =
+            <Bdf<StiffModel> as Solver<StiffModel>>::new(0.0, 1e-6, 1, 0, BdfParams::default());
+
+        for i in 1..=10 {
+            let next_time = 0.1 * i as f64;
+            let (time, state_event) = bdf.step(&mut model, next_time).unwrap();
+            assert_eq!(time, next_time);
+            assert!(!state_event);
+            assert!(
+                (model.x - time.cos()).abs() < 1e-4,
+                "x({time}) = {}, expected {}",
+                model.x,
+                time.cos()
+            );
+        }
+        assert!(bdf.order > 1);
+    }
+
+    #[test]
+    fn test_bdf_directional_derivative() {
+        let calls = Rc::new(Cell::new(0));
+        let params = BdfParams {
+            directional_derivative: Some(Box::new({
+                let calls = calls.clone();
+                move |model: &mut StiffModel, seed: &[f64], sensitivity: &mut [f64]| {
+                    calls.set(calls.get() + 1);
+                    sensitivity[0] = -model.k * seed[0];
+                    Ok(())
+                }
+            })),
+        };
+
+        let mut model = StiffModel {
+            time: 0.0,
+            x: 1.0,
+            k: 1e3,
+        };
+        let mut bdf = <Bdf<StiffModel> as Solver<StiffModel>>::new(0.0, 1e-6, 1, 0, params);
+        let (time, _) = bdf.step(&mut model, 1.0).unwrap();
+
+        assert_eq!(time, 1.0);
+        assert!((model.x - 1f64.cos()).abs() < 1e-4);
+        assert!(calls.get() > 0);
+    }
+
+    #[test]
+    fn test_bdf_state_event() {
+        let mut model = StiffModel {
+            time: 0.0,
+            x: 1.0,
+            k: 1e3,
+        };
+        let mut bdf =
+            <Bdf<StiffModel> as Solver<StiffModel>>::new(0.0, 1e-6, 1, 1, BdfParams::default());
+
+        let (time, state_event) = bdf.step(&mut model, 1.0).unwrap();
+        assert_eq!(time, 1.0);
+        assert!(!state_event);
+
+        let (time, state_event) = bdf.step(&mut model, 2.0).unwrap();
+        assert!(state_event);
+        assert!((time - std::f64::consts::FRAC_PI_2).abs() < 1e-2);
+        assert!(model.x <= 0.0 && model.x > -1e-6);
+        assert_eq!(model.time, time);
+
+        bdf.reset(&mut model, time).unwrap();
+        let (time, state_event) = bdf.step(&mut model, 2.0).unwrap();
+        assert_eq!(time, 2.0);
+        assert!(!state_event);
+    }
+
+    #[test]
+    fn test_change_d_identity() {
+        let mut d = vec![vec![1.0], vec![2.0], vec![3.0]];
+        change_d(&mut d, 2, 1.0);
+        for (row, expected) in d.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((row[0] - expected).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Building blocks shared by the solvers.
+
+use super::{DenseOutput, Model};
+
+/// Evaluate the state derivatives `f` of the model at `(t, x)`.
+pub(super) fn eval<M: Model>(model: &mut M, t: f64, x: &[f64], f: &mut [f64]) {
+    model.set_time(t);
+    model.set_continuous_states(x);
+    model.get_continuous_state_derivatives(f);
+}
+
+/// Root-mean-square norm
+pub(super) fn rms_norm(values: impl ExactSizeIterator<Item = f64>) -> f64 {
+    let n = values.len();
+    if n == 0 {
+        return 0.0;
+    }
+    (values.map(|v| v * v).sum::<f64>() / n as f64).sqrt()
+}
+
+/// Absolute tolerances of the states, the relative tolerance scaled by their nominal values.
+pub(super) fn absolute_tolerances<M: Model>(model: &mut M, rtol: f64, atol: &mut [f64]) {
+    let mut nominals = vec![1.0; atol.len()];
+    model.get_nominals_of_continuous_states(&mut nominals);
+    for (atol, nominal) in atol.iter_mut().zip(&nominals) {
+        let nominal = if nominal.is_finite() && *nominal > 0.0 {
+            *nominal
+        } else {
+            1.0
+        };
+        *atol = rtol * nominal;
+    }
+}
+
+/// Empirical initial step size selection for a method with an error estimator of the given
+/// `order`, see Hairer, Nørsett & Wanner, "Solving Ordinary Differential Equations I", Sec. II.4.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn select_initial_step<M: Model>(
+    model: &mut M,
+    t0: f64,
+    x0: &[f64],
+    f0: &[f64],
+    t_bound: f64,
+    order: usize,
+    rtol: f64,
+    atol: &[f64],
+) -> f64 {
+    let interval_length = t_bound - t0;
+    let scale: Vec<f64> = x0
+        .iter()
+        .zip(atol)
+        .map(|(x, atol)| atol + rtol * x.abs())
+        .collect();
+
+    let d0 = rms_norm(x0.iter().zip(&scale).map(|(x, s)| x / s));
+    let d1 = rms_norm(f0.iter().zip(&scale).map(|(f, s)| f / s));
+    let h0 = if d0 < 1e-5 || d1 < 1e-5 {
+        1e-6
+    } else {
+        0.01 * d0 / d1
+    }
+    .min(interval_length);
+
+    let x1: Vec<f64> = x0.iter().zip(f0).map(|(x, f)| x + h0 * f).collect();
+    let mut f1 = vec![0.0; x0.len()];
+    eval(model, t0 + h0, &x1, &mut f1);
+    let d2 = rms_norm(
+        f1.iter()
+            .zip(f0)
+            .zip(&scale)
+            .map(|((f1, f0), s)| (f1 - f0) / s),
+    ) / h0;
+
+    let h1 = if d1 <= 1e-15 && d2 <= 1e-15 {
+        (h0 * 1e-3).max(1e-6)
+    } else {
+        (0.01 / d1.max(d2)).powf(1.0 / (order + 1) as f64)
+    };
+
+    (100.0 * h0).min(h1).min(interval_length)
+}
+
+/// Maximum number of iterations to locate a zero crossing
+const MAX_ROOT_ITER: usize = 100;
+
+/// Detects sign changes of the event indicators between steps and locates the earliest one.
+#[derive(Default)]
+pub(super) struct ZeroCrossings {
+    /// Event indicators
+    z: Vec<f64>,
+    /// Previous event indicators
+    prez: Vec<f64>,
+}
+
+impl ZeroCrossings {
+    pub(super) fn new(nz: usize) -> Self {
+        Self {
+            z: vec![0.0; nz],
+            prez: vec![0.0; nz],
+        }
+    }
+
+    /// Record the event indicators at the current model state without checking them.
+    pub(super) fn init<M: Model>(&mut self, model: &mut M) {
+        if !self.prez.is_empty() {
+            model.get_event_indicators(&mut self.prez);
+        }
+    }
+
+    /// Check the event indicators for sign changes over the last step `[t_old, t_new]`, with the
+    /// model currently at `t_new`.
+    ///
+    /// If an indicator changed sign, the earliest crossing is located with the Illinois method
+    /// on the `interpolant` of the states, and its time is returned. The model is then left just
+    /// past the crossing, such that the indicator has already changed sign.
+    pub(super) fn locate<M: Model>(
+        &mut self,
+        model: &mut M,
+        interpolant: &impl DenseOutput,
+        nx: usize,
+        t_old: f64,
+        t_new: f64,
+    ) -> Option<f64> {
+        if self.z.is_empty() {
+            return None;
+        }
+        model.get_event_indicators(&mut self.z);
+
+        if !any_crossed(&self.prez, &self.z) {
+            self.prez.copy_from_slice(&self.z);
+            return None;
+        }
+
+        let (mut t_lo, mut t_hi) = (t_old, t_new);
+        let mut z_lo = self.prez.clone();
+        let mut z_mid = vec![0.0; self.z.len()];
+        let mut x = vec![0.0; nx];
+        // Weights of the bracket ends, halved when an end is retained twice in a row
+        let (mut w_lo, mut w_hi) = (1.0, 1.0);
+        let mut lo_retained = None;
+        let tol = 100.0 * f64::EPSILON * t_hi.abs().max(t_hi - t_lo);
+
+        for _ in 0..MAX_ROOT_ITER {
+            if t_hi - t_lo <= tol {
+                break;
+            }
+
+            // Secant estimate of the earliest crossing among the indicators
+            let mut t_mid = t_hi;
+            for (lo, hi) in z_lo.iter().zip(&self.z) {
+                if crossed(*lo, *hi) {
+                    let (lo, hi) = (w_lo * lo, w_hi * hi);
+                    t_mid = t_mid.min(t_hi - hi * (t_hi - t_lo) / (hi - lo));
+                }
+            }
+            let t_mid = t_mid.clamp(t_lo + 0.5 * tol, t_hi - 0.5 * tol);
+
+            interpolant.interpolate(t_mid, &mut x);
+            model.set_time(t_mid);
+            if nx > 0 {
+                model.set_continuous_states(&x);
+            }
+            model.get_event_indicators(&mut z_mid);
+
+            if any_crossed(&z_lo, &z_mid) {
+                t_hi = t_mid;
+                self.z.copy_from_slice(&z_mid);
+                w_hi = 1.0;
+                if lo_retained == Some(true) {
+                    w_lo *= 0.5;
+                }
+                lo_retained = Some(true);
+            } else {
+                t_lo = t_mid;
+                z_lo.copy_from_slice(&z_mid);
+                w_lo = 1.0;
+                if lo_retained == Some(false) {
+                    w_hi *= 0.5;
+                }
+                lo_retained = Some(false);
+            }
+        }
+
+        interpolant.interpolate(t_hi, &mut x);
+        model.set_time(t_hi);
+        if nx > 0 {
+            model.set_continuous_states(&x);
+        }
+        self.prez.copy_from_slice(&self.z);
+
+        Some(t_hi)
+    }
+}
+
+/// Whether an event indicator crossed zero going positive or negative.
+fn crossed(prez: f64, z: f64) -> bool {
+    (prez <= 0.0 && z > 0.0) || (prez > 0.0 && z <= 0.0)
+}
+
+fn any_crossed(prez: &[f64], z: &[f64]) -> bool {
+    prez.iter().zip(z).any(|(prez, z)| crossed(*prez, *z))
+}
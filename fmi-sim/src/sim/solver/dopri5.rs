@@ -0,0 +1,394 @@
+//! Dormand-Prince 5(4) explicit Runge-Kutta solver with adaptive step size.
+//!
+//! See Hairer, Nørsett & Wanner, "Solving Ordinary Differential Equations I", Sec. II.5, for the
+//! coefficients and the continuous extension used for dense output.
+
+use super::{
+    DenseOutput, Model, Solver, SolverError,
+    common::{ZeroCrossings, absolute_tolerances, eval, rms_norm, select_initial_step},
+};
+
+const C2: f64 = 1.0 / 5.0;
+const C3: f64 = 3.0 / 10.0;
+const C4: f64 = 4.0 / 5.0;
+const C5: f64 = 8.0 / 9.0;
+
+const A21: f64 = 1.0 / 5.0;
+const A31: f64 = 3.0 / 40.0;
+const A32: f64 = 9.0 / 40.0;
+const A41: f64 = 44.0 / 45.0;
+const A42: f64 = -56.0 / 15.0;
+const A43: f64 = 32.0 / 9.0;
+const A51: f64 = 19372.0 / 6561.0;
+const A52: f64 = -25360.0 / 2187.0;
+const A53: f64 = 64448.0 / 6561.0;
+const A54: f64 = -212.0 / 729.0;
+const A61: f64 = 9017.0 / 3168.0;
+const A62: f64 = -355.0 / 33.0;
+const A63: f64 = 46732.0 / 5247.0;
+const A64: f64 = 49.0 / 176.0;
+const A65: f64 = -5103.0 / 18656.0;
+const A71: f64 = 35.0 / 384.0;
+const A73: f64 = 500.0 / 1113.0;
+const A74: f64 = 125.0 / 192.0;
+const A75: f64 = -2187.0 / 6784.0;
+const A76: f64 = 11.0 / 84.0;
+
+/// Coefficients of the embedded error estimate
+const E1: f64 = 71.0 / 57600.0;
+const E3: f64 = -71.0 / 16695.0;
+const E4: f64 = 71.0 / 1920.0;
+const E5: f64 = -17253.0 / 339200.0;
+const E6: f64 = 22.0 / 525.0;
+const E7: f64 = -1.0 / 40.0;
+
+/// Coefficients of the continuous extension
+const D1: f64 = -12715105075.0 / 11282082432.0;
+const D3: f64 = 87487479700.0 / 32700410799.0;
+const D4: f64 = -10690763975.0 / 1880347072.0;
+const D5: f64 = 701980252875.0 / 199316789632.0;
+const D6: f64 = -1453857185.0 / 822651844.0;
+const D7: f64 = 69997945.0 / 29380423.0;
+
+/// Order of the embedded error estimator
+const ERROR_ESTIMATOR_ORDER: usize = 4;
+/// Bounds of the step size change factor
+const MIN_FACTOR: f64 = 0.2;
+const MAX_FACTOR: f64 = 10.0;
+const SAFETY: f64 = 0.9;
+/// Relative tolerance used if the simulation doesn't specify one
+const DEFAULT_TOLERANCE: f64 = 1e-4;
+
+/// Dormand-Prince 5(4) solver, suited for non-stiff models.
+pub struct Dopri5 {
+    /// Relative tolerance
+    rtol: f64,
+    /// Absolute tolerances, the relative tolerance scaled by the nominals of the states
+    atol: Vec<f64>,
+    /// Current time
+    time: f64,
+    /// Start time of the last step
+    time_old: f64,
+    /// Size of the last step, over which the dense output is defined
+    h_last: f64,
+    /// Continuous states
+    x: Vec<f64>,
+    /// Proposed size of the next step
+    h: f64,
+    /// Stage derivatives
+    k: [Vec<f64>; 7],
+    /// Coefficients of the dense output polynomial over the last step
+    rcont: [Vec<f64>; 5],
+    /// State event detection
+    zero_crossings: ZeroCrossings,
+    /// Whether the step size and event indicators have to be (re-)initialized from the model
+    needs_init: bool,
+}
+
+impl<M: Model> Solver<M> for Dopri5 {
+    type Params = ();
+
+    fn new(start_time: f64, tol: f64, nx: usize, nz: usize, _params: Self::Params) -> Self {
+        let rtol = if tol > 0.0 { tol } else { DEFAULT_TOLERANCE };
+        Self {
+            rtol,
+            atol: vec![rtol; nx],
+            time: start_time,
+            time_old: start_time,
+            h_last: 0.0,
+            x: vec![0.0; nx],
+            h: 0.0,
+            k: std::array::from_fn(|_| vec![0.0; nx]),
+            rcont: std::array::from_fn(|_| vec![0.0; nx]),
+            zero_crossings: ZeroCrossings::new(nz),
+            needs_init: true,
+        }
+    }
+
+    fn step(&mut self, model: &mut M, next_time: f64) -> Result<(f64, bool), SolverError> {
+        if next_time <= self.time {
+            return Ok((self.time, false));
+        }
+
+        if self.needs_init {
+            self.zero_crossings.init(model);
+        }
+
+        if self.x.is_empty() {
+            self.needs_init = false;
+            self.time_old = self.time;
+            self.time = next_time;
+            model.set_time(self.time);
+            let state_event = self.locate_event(model).is_some();
+            return Ok((self.time, state_event));
+        }
+
+        // Inputs may have changed since the last step, so start from a fresh derivative
+        model.get_continuous_states(&mut self.x);
+        eval(model, self.time, &self.x, &mut self.k[0]);
+
+        if self.needs_init {
+            self.needs_init = false;
+            absolute_tolerances(model, self.rtol, &mut self.atol);
+            self.h = select_initial_step(
+                model,
+                self.time,
+                &self.x,
+                &self.k[0],
+                next_time,
+                ERROR_ESTIMATOR_ORDER,
+                self.rtol,
+                &self.atol,
+            );
+        }
+
+        while self.time < next_time {
+            self.step_impl(model, next_time)?;
+
+            // Leave the model at the accepted solution
+            model.set_time(self.time);
+            model.set_continuous_states(&self.x);
+
+            if let Some(event_time) = self.locate_event(model) {
+                return Ok((event_time, true));
+            }
+        }
+
+        Ok((self.time, false))
+    }
+
+    fn reset(&mut self, _model: &mut M, time: f64) -> Result<(), SolverError> {
+        self.time = time;
+        self.time_old = time;
+        self.needs_init = true;
+        Ok(())
+    }
+}
+
+impl Dopri5 {
+    /// Locate a state event within the last step, and if there is one, cut the step short at it.
+    fn locate_event<M: Model>(&mut self, model: &mut M) -> Option<f64> {
+        let mut zero_crossings = std::mem::take(&mut self.zero_crossings);
+        let event_time = zero_crossings.locate(model, self, self.x.len(), self.time_old, self.time);
+        self.zero_crossings = zero_crossings;
+
+        if let Some(event_time) = event_time {
+            self.time = event_time;
+            model.get_continuous_states(&mut self.x);
+        }
+        event_time
+    }
+
+    /// Take a single accepted step, shortened if needed to end exactly at `t_bound`. Expects
+    /// `k[0]` to hold the derivatives at the current states.
+    fn step_impl<M: Model>(&mut self, model: &mut M, t_bound: f64) -> Result<(), SolverError> {
+        let t = self.time;
+        let nx = self.x.len();
+        let min_step = 10.0 * (t.next_up() - t);
+
+        let mut x_new = vec![0.0; nx];
+        let mut x_stage = vec![0.0; nx];
+        let mut h = self.h;
+
+        loop {
+            if h < min_step {
+                return Err(SolverError::StepSizeTooSmall(t));
+            }
+            let (t_new, h_proposed) = if t + h * (1.0 + 1e-8) >= t_bound {
+                (t_bound, Some(h))
+            } else {
+                (t + h, None)
+            };
+            h = t_new - t;
+
+            let [k1, k2, k3, k4, k5, k6, k7] = &mut self.k;
+            let x = &self.x;
+
+            for i in 0..nx {
+                x_stage[i] = x[i] + h * A21 * k1[i];
+            }
+            eval(model, t + C2 * h, &x_stage, k2);
+            for i in 0..nx {
+                x_stage[i] = x[i] + h * (A31 * k1[i] + A32 * k2[i]);
+            }
+            eval(model, t + C3 * h, &x_stage, k3);
+            for i in 0..nx {
+                x_stage[i] = x[i] + h * (A41 * k1[i] + A42 * k2[i] + A43 * k3[i]);
+            }
+            eval(model, t + C4 * h, &x_stage, k4);
+            for i in 0..nx {
+                x_stage[i] = x[i] + h * (A51 * k1[i] + A52 * k2[i] + A53 * k3[i] + A54 * k4[i]);
+            }
+            eval(model, t + C5 * h, &x_stage, k5);
+            for i in 0..nx {
+                x_stage[i] = x[i]
+                    + h * (A61 * k1[i] + A62 * k2[i] + A63 * k3[i] + A64 * k4[i] + A65 * k5[i]);
+            }
+            eval(model, t_new, &x_stage, k6);
+            for i in 0..nx {
+                x_new[i] = x[i]
+                    + h * (A71 * k1[i] + A73 * k3[i] + A74 * k4[i] + A75 * k5[i] + A76 * k6[i]);
+            }
+            eval(model, t_new, &x_new, k7);
+
+            let error_norm = rms_norm((0..nx).map(|i| {
+                let error = h
+                    * (E1 * k1[i] + E3 * k3[i] + E4 * k4[i] + E5 * k5[i] + E6 * k6[i] + E7 * k7[i]);
+                let scale = self.atol[i] + self.rtol * x[i].abs().max(x_new[i].abs());
+                error / scale
+            }));
+
+            if !error_norm.is_finite() || error_norm > 1.0 {
+                let factor = if error_norm.is_finite() {
+                    MIN_FACTOR.max(SAFETY * error_norm.powf(-1.0 / 5.0))
+                } else {
+                    MIN_FACTOR
+                };
+                h *= factor;
+                continue;
+            }
+
+            // Dense output coefficients over [t, t_new]
+            let [r1, r2, r3, r4, r5] = &mut self.rcont;
+            for i in 0..nx {
+                let x_diff = x_new[i] - x[i];
+                let b_spl = h * k1[i] - x_diff;
+                r1[i] = x[i];
+                r2[i] = x_diff;
+                r3[i] = b_spl;
+                r4[i] = x_diff - h * k7[i] - b_spl;
+                r5[i] = h
+                    * (D1 * k1[i] + D3 * k3[i] + D4 * k4[i] + D5 * k5[i] + D6 * k6[i] + D7 * k7[i]);
+            }
+
+            let factor = if error_norm == 0.0 {
+                MAX_FACTOR
+            } else {
+                MAX_FACTOR.min(SAFETY * error_norm.powf(-1.0 / 5.0))
+            };
+            // Don't let a step shortened to reach `t_bound` slow down the following steps
+            self.h = (h * factor).max(h_proposed.unwrap_or(0.0));
+            self.h_last = h;
+            self.time_old = t;
+            self.time = t_new;
+            self.x.copy_from_slice(&x_new);
+
+            // First same as last
+            let [k1, .., k7] = &mut self.k;
+            k1.copy_from_slice(k7);
+
+            return Ok(());
+        }
+    }
+}
+
+impl DenseOutput for Dopri5 {
+    fn interpolate(&self, time: f64, x: &mut [f64]) {
+        let h = self.h_last;
+        if h <= 0.0 {
+            x.copy_from_slice(&self.x);
+            return;
+        }
+        let theta = (time - self.time_old) / h;
+        let theta1 = 1.0 - theta;
+        let [r1, r2, r3, r4, r5] = &self.rcont;
+        for (i, x) in x.iter_mut().enumerate() {
+            *x = r1[i] + theta * (r2[i] + theta1 * (r3[i] + theta * (r4[i] + theta1 * r5[i])));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Harmonic oscillator `x'' = -x`, with the solution `x = cos(t)` for `x(0) = 1, x'(0) = 0`
+    struct Oscillator {
+        time: f64,
+        x: [f64; 2],
+        evaluations: usize,
+    }
+
+    impl Model for Oscillator {
+        fn get_continuous_states(&mut self, x: &mut [f64]) {
+            x.copy_from_slice(&self.x);
+        }
+
+        fn set_continuous_states(&mut self, states: &[f64]) {
+            self.x.copy_from_slice(states);
+        }
+
+        fn get_continuous_state_derivatives(&mut self, dx: &mut [f64]) {
+            self.evaluations += 1;
+            dx[0] = self.x[1];
+            dx[1] = -self.x[0];
+        }
+
+        fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]) {
+            nominals.fill(1.0);
+        }
+
+        fn get_event_indicators(&mut self, z: &mut [f64]) {
+            // crosses zero at t = pi / 2
+            z[0] = self.x[0];
+        }
+
+        fn set_time(&mut self, time: f64) {
+            self.time = time;
+        }
+    }
+
+    fn oscillator() -> Oscillator {
+        Oscillator {
+            time: 0.0,
+            x: [1.0, 0.0],
+            evaluations: 0,
+        }
+    }
+
+    #[test]
+    fn test_dopri5_accuracy() {
+        let mut model = oscillator();
+        let mut solver = <Dopri5 as Solver<Oscillator>>::new(0.0, 1e-8, 2, 0, ());
+
+        for i in 1..=10 {
+            let next_time = i as f64;
+            let (time, state_event) = solver.step(&mut model, next_time).unwrap();
+            assert_eq!(time, next_time);
+            assert!(!state_event);
+            assert!((model.x[0] - time.cos()).abs() < 1e-6);
+            assert_eq!(model.time, time);
+        }
+
+        // A loose tolerance needs fewer evaluations
+        let evaluations = model.evaluations;
+        let mut model = oscillator();
+        let mut solver = <Dopri5 as Solver<Oscillator>>::new(0.0, 1e-3, 2, 0, ());
+        for i in 1..=10 {
+            solver.step(&mut model, i as f64).unwrap();
+        }
+        assert!(model.evaluations < evaluations);
+        assert!((model.x[0] - 10f64.cos()).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_dopri5_dense_output() {
+        let mut model = oscillator();
+        let mut solver = <Dopri5 as Solver<Oscillator>>::new(0.0, 1e-8, 2, 1, ());
+
+        let (time, state_event) = solver.step(&mut model, 10.0).unwrap();
+        assert!(state_event);
+        assert!((time - std::f64::consts::FRAC_PI_2).abs() < 1e-8);
+        assert!(model.x[0] <= 0.0);
+        assert_eq!(model.time, time);
+
+        let mut x = [0.0; 2];
+        let t = 0.5 * (solver.time_old + time);
+        solver.interpolate(t, &mut x);
+        assert!((x[0] - t.cos()).abs() < 1e-6);
+        assert!((x[1] + t.sin()).abs() < 1e-6);
+
+        solver.interpolate(time, &mut x);
+        assert_eq!(x, model.x);
+    }
+}
@@ -1,16 +1,20 @@
-use super::{Model, Solver, SolverError};
+use super::{DenseOutput, Model, Solver, SolverError, common::ZeroCrossings};
 
 pub struct Euler {
     /// Current time
     time: f64,
+    /// Start time of the last step
+    time_old: f64,
     /// Continuous states
     x: Vec<f64>,
+    /// Continuous states at the start of the last step
+    x_old: Vec<f64>,
     /// Derivatives of continuous states
     dx: Vec<f64>,
-    /// Event indicators
-    z: Vec<f64>,
-    /// Previous event indicators
-    prez: Vec<f64>,
+    /// State event detection
+    zero_crossings: ZeroCrossings,
+    /// Whether the event indicators have to be (re-)initialized from the model
+    needs_init: bool,
 }
 
 impl<M: Model> Solver<M> for Euler {
@@ -19,19 +23,27 @@ impl<M: Model> Solver<M> for Euler {
     fn new(start_time: f64, _tol: f64, nx: usize, nz: usize, _solver_params: Self::Params) -> Self {
         Self {
             time: start_time,
+            time_old: start_time,
             x: vec![0.0; nx],
+            x_old: vec![0.0; nx],
             dx: vec![0.0; nx],
-            z: vec![0.0; nz],
-            prez: vec![0.0; nz],
+            zero_crossings: ZeroCrossings::new(nz),
+            needs_init: true,
         }
     }
 
     fn step(&mut self, model: &mut M, next_time: f64) -> Result<(f64, bool), SolverError> {
+        if self.needs_init {
+            self.needs_init = false;
+            self.zero_crossings.init(model);
+        }
+
         let dt = next_time - self.time;
 
         if !self.x.is_empty() {
             model.get_continuous_states(&mut self.x);
             model.get_continuous_state_derivatives(&mut self.dx);
+            self.x_old.copy_from_slice(&self.x);
 
             for i in 0..self.x.len() {
                 self.x[i] += self.dx[i] * dt;
@@ -40,36 +52,36 @@ impl<M: Model> Solver<M> for Euler {
             model.set_continuous_states(&self.x);
         }
 
-        let mut state_event = false;
-
-        if !self.z.is_empty() {
-            model.get_event_indicators(&mut self.z);
-
-            self.z
-                .iter()
-                .zip(self.prez.iter_mut())
-                .for_each(|(z, prez)| {
-                    // crossed zero going positive
-                    let cross_pos = *prez <= 0.0 && *z > 0.0;
-                    // crossed zero going negative
-                    let cross_neg = *prez > 0.0 && *z <= 0.0;
-                    state_event = cross_pos || cross_neg || state_event;
-                    *prez = *z;
-                });
-        }
+        self.time_old = self.time;
         self.time = next_time;
 
-        Ok((self.time, state_event))
-    }
+        let mut zero_crossings = std::mem::take(&mut self.zero_crossings);
+        let event_time = zero_crossings.locate(model, self, self.x.len(), self.time_old, self.time);
+        self.zero_crossings = zero_crossings;
 
-    fn reset(&mut self, model: &mut M, _time: f64) -> Result<(), SolverError> {
-        if !self.z.is_empty() {
-            model.get_event_indicators(&mut self.z);
+        if let Some(event_time) = event_time {
+            self.time = event_time;
         }
+
+        Ok((self.time, event_time.is_some()))
+    }
+
+    fn reset(&mut self, _model: &mut M, time: f64) -> Result<(), SolverError> {
+        self.time = time;
+        self.needs_init = true;
         Ok(())
     }
 }
 
+impl DenseOutput for Euler {
+    fn interpolate(&self, time: f64, x: &mut [f64]) {
+        let dt = time - self.time_old;
+        for ((x, x_old), dx) in x.iter_mut().zip(&self.x_old).zip(&self.dx) {
+            *x = x_old + dx * dt;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,9 +101,15 @@ mod tests {
             dx[0] = 1.0;
         }
 
+        fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]) {
+            nominals[0] = 1.0;
+        }
+
         fn get_event_indicators(&mut self, z: &mut [f64]) {
             z[0] = 0.0;
         }
+
+        fn set_time(&mut self, _time: f64) {}
     }
 
     #[test]
@@ -1,13 +1,22 @@
+mod bdf;
+mod common;
+mod dopri5;
 mod euler;
+mod rk4;
 
+pub use bdf::{Bdf, BdfParams, DirectionalDerivative};
+pub use dopri5::Dopri5;
 pub use euler::Euler;
 use fmi::traits::FmiModelExchange;
+pub use rk4::{Rk4, Rk4Params};
 
 pub trait Model {
     fn get_continuous_states(&mut self, x: &mut [f64]);
     fn set_continuous_states(&mut self, states: &[f64]);
     fn get_continuous_state_derivatives(&mut self, dx: &mut [f64]);
+    fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]);
     fn get_event_indicators(&mut self, z: &mut [f64]);
+    fn set_time(&mut self, time: f64);
 }
 
 impl<Inst: FmiModelExchange> Model for Inst {
@@ -23,15 +32,26 @@ impl<Inst: FmiModelExchange> Model for Inst {
         let _ = FmiModelExchange::get_continuous_state_derivatives(self, dx);
     }
 
+    fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]) {
+        let _ = FmiModelExchange::get_nominals_of_continuous_states(self, nominals);
+    }
+
     fn get_event_indicators(&mut self, z: &mut [f64]) {
         let _ = FmiModelExchange::get_event_indicators(self, z);
     }
+
+    fn set_time(&mut self, time: f64) {
+        let _ = FmiModelExchange::set_time(self, time);
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum SolverError {
     #[error("Step error")]
     StepError,
+
+    #[error("Step size became too small at t = {0}")]
+    StepSizeTooSmall(f64),
 }
 
 pub trait Solver<M> {
@@ -58,6 +78,12 @@ pub trait Solver<M> {
     fn reset(&mut self, model: &mut M, time: f64) -> Result<(), SolverError>;
 }
 
+/// A solver that can evaluate a continuous approximation of the states over its last step.
+pub trait DenseOutput {
+    /// Interpolate the continuous states `x` at `time`, which must lie within the last step.
+    fn interpolate(&self, time: f64, x: &mut [f64]);
+}
+
 /// A dummy solver that does nothing.
 pub struct DummySolver;
 
@@ -0,0 +1,252 @@
+//! Classical fourth-order Runge-Kutta solver with a fixed step size.
+
+use super::{
+    DenseOutput, Model, Solver, SolverError,
+    common::{ZeroCrossings, eval},
+};
+
+/// Parameters of the [`Rk4`] solver
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rk4Params {
+    /// Fixed integration step size. If `None`, a single step is taken per communication interval.
+    pub step_size: Option<f64>,
+}
+
+/// Classical Runge-Kutta solver of order 4.
+pub struct Rk4 {
+    step_size: Option<f64>,
+    /// Current time
+    time: f64,
+    /// Start time of the last step
+    time_old: f64,
+    /// Size of the last step, over which the dense output is defined
+    h_last: f64,
+    /// Continuous states at the end and start of the last step
+    x: Vec<f64>,
+    x_old: Vec<f64>,
+    /// Derivatives of the continuous states at the end and start of the last step
+    dx: Vec<f64>,
+    dx_old: Vec<f64>,
+    /// State event detection
+    zero_crossings: ZeroCrossings,
+    /// Whether the event indicators have to be (re-)initialized from the model
+    needs_init: bool,
+}
+
+impl<M: Model> Solver<M> for Rk4 {
+    type Params = Rk4Params;
+
+    fn new(start_time: f64, _tol: f64, nx: usize, nz: usize, params: Self::Params) -> Self {
+        Self {
+            step_size: params.step_size.filter(|h| *h > 0.0),
+            time: start_time,
+            time_old: start_time,
+            h_last: 0.0,
+            x: vec![0.0; nx],
+            x_old: vec![0.0; nx],
+            dx: vec![0.0; nx],
+            dx_old: vec![0.0; nx],
+            zero_crossings: ZeroCrossings::new(nz),
+            needs_init: true,
+        }
+    }
+
+    fn step(&mut self, model: &mut M, next_time: f64) -> Result<(f64, bool), SolverError> {
+        if next_time <= self.time {
+            return Ok((self.time, false));
+        }
+
+        if self.needs_init {
+            self.needs_init = false;
+            self.zero_crossings.init(model);
+        }
+
+        if self.x.is_empty() {
+            self.time_old = self.time;
+            self.time = next_time;
+            model.set_time(self.time);
+            let state_event = self.locate_event(model).is_some();
+            return Ok((self.time, state_event));
+        }
+
+        // Inputs may have changed since the last step, so start from a fresh derivative
+        model.get_continuous_states(&mut self.x);
+        eval(model, self.time, &self.x, &mut self.dx);
+
+        while self.time < next_time {
+            let h = self
+                .step_size
+                .map_or(next_time - self.time, |h| h.min(next_time - self.time));
+            // Don't leave a sliver of the interval to round-off
+            let t_new = if self.time + h * (1.0 + 1e-8) >= next_time {
+                next_time
+            } else {
+                self.time + h
+            };
+            self.step_impl(model, t_new);
+
+            if let Some(event_time) = self.locate_event(model) {
+                return Ok((event_time, true));
+            }
+        }
+
+        Ok((self.time, false))
+    }
+
+    fn reset(&mut self, _model: &mut M, time: f64) -> Result<(), SolverError> {
+        self.time = time;
+        self.time_old = time;
+        self.needs_init = true;
+        Ok(())
+    }
+}
+
+impl Rk4 {
+    /// Locate a state event within the last step, and if there is one, cut the step short at it.
+    fn locate_event<M: Model>(&mut self, model: &mut M) -> Option<f64> {
+        let mut zero_crossings = std::mem::take(&mut self.zero_crossings);
+        let event_time = zero_crossings.locate(model, self, self.x.len(), self.time_old, self.time);
+        self.zero_crossings = zero_crossings;
+
+        // The states are read back from the model at the start of the next step
+        if let Some(event_time) = event_time {
+            self.time = event_time;
+        }
+        event_time
+    }
+
+    /// Take a single step to `t_new`, leaving the model at the new states. Expects `dx` to hold
+    /// the derivatives at the current states.
+    fn step_impl<M: Model>(&mut self, model: &mut M, t_new: f64) {
+        let t = self.time;
+        let h = t_new - t;
+        let nx = self.x.len();
+
+        let mut k2 = vec![0.0; nx];
+        let mut k3 = vec![0.0; nx];
+        let mut k4 = vec![0.0; nx];
+        let mut x_stage = vec![0.0; nx];
+
+        let (x, k1) = (&self.x, &self.dx);
+        for i in 0..nx {
+            x_stage[i] = x[i] + 0.5 * h * k1[i];
+        }
+        eval(model, t + 0.5 * h, &x_stage, &mut k2);
+        for i in 0..nx {
+            x_stage[i] = x[i] + 0.5 * h * k2[i];
+        }
+        eval(model, t + 0.5 * h, &x_stage, &mut k3);
+        for i in 0..nx {
+            x_stage[i] = x[i] + h * k3[i];
+        }
+        eval(model, t_new, &x_stage, &mut k4);
+
+        std::mem::swap(&mut self.x, &mut self.x_old);
+        std::mem::swap(&mut self.dx, &mut self.dx_old);
+        for i in 0..nx {
+            self.x[i] =
+                self.x_old[i] + h / 6.0 * (self.dx_old[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+        }
+        self.h_last = h;
+        self.time_old = t;
+        self.time = t_new;
+
+        // Also leaves the model at the new states
+        eval(model, self.time, &self.x, &mut self.dx);
+    }
+}
+
+impl DenseOutput for Rk4 {
+    /// Cubic Hermite interpolation between the states and derivatives at both ends of the step.
+    fn interpolate(&self, time: f64, x: &mut [f64]) {
+        let h = self.h_last;
+        if h <= 0.0 {
+            x.copy_from_slice(&self.x);
+            return;
+        }
+        let theta = (time - self.time_old) / h;
+        let h00 = (1.0 + 2.0 * theta) * (1.0 - theta) * (1.0 - theta);
+        let h10 = theta * (1.0 - theta) * (1.0 - theta);
+        let h01 = theta * theta * (3.0 - 2.0 * theta);
+        let h11 = theta * theta * (theta - 1.0);
+        for (i, x) in x.iter_mut().enumerate() {
+            *x = h00 * self.x_old[i]
+                + h10 * h * self.dx_old[i]
+                + h01 * self.x[i]
+                + h11 * h * self.dx[i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `dx/dt = -x`, with the solution `x = exp(-t)` for `x(0) = 1`
+    struct Decay {
+        time: f64,
+        x: f64,
+    }
+
+    impl Model for Decay {
+        fn get_continuous_states(&mut self, x: &mut [f64]) {
+            x[0] = self.x;
+        }
+
+        fn set_continuous_states(&mut self, states: &[f64]) {
+            self.x = states[0];
+        }
+
+        fn get_continuous_state_derivatives(&mut self, dx: &mut [f64]) {
+            dx[0] = -self.x;
+        }
+
+        fn get_nominals_of_continuous_states(&mut self, nominals: &mut [f64]) {
+            nominals[0] = 1.0;
+        }
+
+        fn get_event_indicators(&mut self, z: &mut [f64]) {
+            // crosses zero at t = ln(2)
+            z[0] = self.x - 0.5;
+        }
+
+        fn set_time(&mut self, time: f64) {
+            self.time = time;
+        }
+    }
+
+    #[test]
+    fn test_rk4() {
+        let mut model = Decay { time: 0.0, x: 1.0 };
+        let params = Rk4Params {
+            step_size: Some(0.1),
+        };
+        let mut solver = <Rk4 as Solver<Decay>>::new(0.0, 0.0, 1, 0, params);
+
+        let (time, state_event) = solver.step(&mut model, 1.0).unwrap();
+        assert_eq!(time, 1.0);
+        assert_eq!(model.time, 1.0);
+        assert!(!state_event);
+        assert!((model.x - (-1f64).exp()).abs() < 1e-6);
+
+        // Dense output within the last step
+        let mut x = [0.0];
+        solver.interpolate(0.95, &mut x);
+        assert!((x[0] - (-0.95f64).exp()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rk4_state_event() {
+        let mut model = Decay { time: 0.0, x: 1.0 };
+        let params = Rk4Params {
+            step_size: Some(0.1),
+        };
+        let mut solver = <Rk4 as Solver<Decay>>::new(0.0, 0.0, 1, 1, params);
+
+        let (time, state_event) = solver.step(&mut model, 1.0).unwrap();
+        assert!(state_event);
+        assert!((time - 2f64.ln()).abs() < 1e-6);
+        assert_eq!(model.time, time);
+        assert!(model.x <= 0.5);
+    }
+}
@@ -2,10 +2,13 @@ use std::path::Path;
 
 use arrow::{
     array::{ArrayRef, RecordBatch},
-    datatypes::{Field, Schema},
+    datatypes::{DataType, Field, Schema},
 };
 use fmi::traits::{FmiImport, FmiInstance};
+use itertools::Either;
 
+#[cfg(feature = "se")]
+use crate::options::ScheduledExecutionOptions;
 use crate::{
     Error,
     options::{CoSimulationOptions, ModelExchangeOptions},
@@ -30,6 +33,8 @@ pub trait ImportSchemaBuilder: FmiImport {
     fn discrete_inputs(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_;
     /// Build a list of Schema column (index, ValueReference) for the outputs.
     fn outputs(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_;
+    /// Build a list of (Field, ValueReference) for all variables except the independent one.
+    fn variables(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_;
     /// Parse a list of "var=value" strings.
     ///
     /// # Returns
@@ -46,6 +51,34 @@ pub trait ImportSchemaBuilder: FmiImport {
     fn binary_max_size(&self, _vr: Self::ValueRef) -> Option<usize> {
         None
     }
+
+    /// Dimensions of an array variable, each either a fixed size or the value reference of the
+    /// structural parameter holding it.
+    ///
+    /// Defaults to none, for FMI versions without array variables.
+    fn dimensions(&self, _vr: Self::ValueRef) -> Vec<Either<usize, Self::ValueRef>> {
+        Vec::new()
+    }
+
+    /// Whether the variable with the given value reference is a Clock.
+    fn is_clock(&self, _vr: Self::ValueRef) -> bool {
+        false
+    }
+
+    /// Build a list of (Field, ValueReference) for the input Clocks, which are activated from
+    /// the input data.
+    ///
+    /// Defaults to none, for FMI versions without Clocks.
+    fn input_clocks(&self) -> impl Iterator<Item = (Field, Self::ValueRef)> + '_ {
+        std::iter::empty()
+    }
+
+    /// Value references of the output Clocks, which are read in Event Mode.
+    ///
+    /// Defaults to none, for FMI versions without Clocks.
+    fn output_clocks(&self) -> impl Iterator<Item = Self::ValueRef> + '_ {
+        std::iter::empty()
+    }
 }
 
 pub trait InstSetValues: FmiInstance {
@@ -53,7 +86,7 @@ pub trait InstSetValues: FmiInstance {
         &mut self,
         vrs: &[<Self as FmiInstance>::ValueRef],
         values: &arrow::array::ArrayRef,
-    );
+    ) -> anyhow::Result<()>;
     fn set_interpolated<I: Interpolate>(
         &mut self,
         vr: <Self as FmiInstance>::ValueRef,
@@ -62,6 +95,29 @@ pub trait InstSetValues: FmiInstance {
     ) -> anyhow::Result<()>;
 }
 
+pub trait InstGetValues: FmiInstance {
+    /// Get the value of a single variable as a one-element array of the given type.
+    fn get_array(
+        &mut self,
+        vr: <Self as FmiInstance>::ValueRef,
+        data_type: &DataType,
+    ) -> anyhow::Result<ArrayRef>;
+}
+
+/// Clock handling of an instance in Event Mode. FMI 2.0 has no Clocks, so the defaults do
+/// nothing.
+pub trait InstClocks: FmiInstance {
+    /// Activate the given input Clocks.
+    fn activate_clocks(&mut self, _vrs: &[Self::ValueRef]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Read the given output Clocks, returning those that ticked.
+    fn ticked_clocks(&mut self, _vrs: &[Self::ValueRef]) -> anyhow::Result<Vec<Self::ValueRef>> {
+        Ok(Vec::new())
+    }
+}
+
 pub trait InstRecordValues: FmiInstance + Sized {
     fn record_outputs(
         &mut self,
@@ -98,6 +154,15 @@ pub trait SimApplyStartValues<Inst: FmiInstance> {
     ) -> Result<(), Error>;
 }
 
+/// Saving and restoring of serialized FMU states.
+pub trait SimFmuState {
+    /// Restore the FMU state from a file written by [`SimFmuState::save_fmu_state`].
+    fn restore_fmu_state(&mut self, path: &Path) -> Result<(), Error>;
+
+    /// Serialize the current FMU state to a file.
+    fn save_fmu_state(&mut self, path: &Path) -> Result<(), Error>;
+}
+
 pub trait SimInitialize<Inst: FmiInstance>: SimDefaultInitialize {
     fn initialize<P: AsRef<Path>>(
         &mut self,
@@ -122,4 +187,14 @@ pub trait FmiSim: FmiImport + ImportSchemaBuilder {
         options: &CoSimulationOptions,
         input_data: Option<RecordBatch>,
     ) -> Result<(RecordBatch, SimStats), Error>;
+
+    /// Simulate the model using Scheduled Execution.
+    #[cfg(feature = "se")]
+    fn simulate_se(
+        &self,
+        _options: &ScheduledExecutionOptions,
+        _input_data: Option<RecordBatch>,
+    ) -> Result<(RecordBatch, SimStats), Error> {
+        Err(fmi::Error::UnsupportedInterface("ScheduledExecution".to_owned()).into())
+    }
 }
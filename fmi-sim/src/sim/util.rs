@@ -5,13 +5,17 @@ use std::{
 };
 
 use arrow::{
+    array::{Array, ArrayRef, AsArray, FixedSizeListArray, UInt32Array},
     csv::{ReaderBuilder, reader::Format},
-    datatypes::{Field, Schema, SchemaRef},
+    datatypes::{DataType, Field, Schema, SchemaRef},
     record_batch::RecordBatch,
 };
 use comfy_table::Table;
 use itertools::Itertools;
 
+/// Field metadata key of array outputs, holding the comma-separated size of each dimension
+pub const SHAPE_METADATA_KEY: &str = "fmi.shape";
+
 pub fn read_csv_file<P: AsRef<Path>>(path: P) -> anyhow::Result<RecordBatch> {
     let mut file = std::fs::File::open(&path)?;
     log::debug!("Reading CSV file {:?}", path.as_ref());
@@ -113,12 +117,15 @@ pub fn project_input_data(
     let fields_iter = std::iter::once(&time_field).chain(model_input_schema.fields().iter());
 
     let (projected_fields, projected_columns): (Vec<_>, Vec<_>) = fields_iter
-        .filter_map(|field| {
-            input_data.column_by_name(field.name()).map(|col| {
+        .filter_map(|field| match input_data.column_by_name(field.name()) {
+            Some(col) => Some(
                 arrow::compute::cast(col, field.data_type())
                     .map(|col| (field.clone(), col))
-                    .map_err(|_| anyhow::anyhow!("Error casting type"))
-            })
+                    .map_err(|_| anyhow::anyhow!("Error casting type")),
+            ),
+            None => collect_flattened(input_data, field)
+                .map(|col| col.map(|col| (field.clone(), col)))
+                .transpose(),
         })
         .process_results(|pairs| pairs.unzip())?;
 
@@ -130,3 +137,375 @@ pub fn project_input_data(
     let input_data_schema = Arc::new(Schema::new(projected_fields));
     RecordBatch::try_new(input_data_schema, projected_columns).map_err(anyhow::Error::from)
 }
+
+/// Parse the 1-based indices of a flattened array column named `name[i,j,...]`.
+fn parse_element_indices(column_name: &str, name: &str) -> Option<Vec<usize>> {
+    let indices = column_name
+        .strip_prefix(name)?
+        .strip_prefix('[')?
+        .strip_suffix(']')?;
+    indices
+        .split(',')
+        .map(|index| index.trim().parse().ok())
+        .collect()
+}
+
+/// Combine the flattened columns `name[i,j,...]` of an array input into a single list column.
+///
+/// Elements are ordered row-major, and the shape is given by the largest index of each
+/// dimension. Returns `None` if `field` is not an array or there are no such columns.
+fn collect_flattened(input_data: &RecordBatch, field: &Field) -> anyhow::Result<Option<ArrayRef>> {
+    let (DataType::FixedSizeList(item, _) | DataType::List(item)) = field.data_type() else {
+        return Ok(None);
+    };
+
+    let schema = input_data.schema();
+    let elements = schema
+        .fields()
+        .iter()
+        .zip(input_data.columns())
+        .filter_map(|(f, col)| parse_element_indices(f.name(), field.name()).map(|i| (i, col)))
+        .collect_vec();
+    let Some((first, _)) = elements.first() else {
+        return Ok(None);
+    };
+
+    let rank = first.len();
+    if elements
+        .iter()
+        .any(|(indices, _)| indices.len() != rank || indices.contains(&0))
+    {
+        anyhow::bail!(
+            "Columns of array input '{}' must all have {rank} 1-based indices",
+            field.name()
+        );
+    }
+    let shape = (0..rank)
+        .map(|dim| {
+            elements
+                .iter()
+                .map(|(indices, _)| indices[dim])
+                .max()
+                .unwrap()
+        })
+        .collect_vec();
+    let len = shape.iter().product::<usize>();
+    if let DataType::FixedSizeList(_, expected) = field.data_type()
+        && len != *expected as usize
+    {
+        anyhow::bail!(
+            "Array input '{}' has {len} elements with shape {shape:?}, expected {expected}",
+            field.name()
+        );
+    }
+
+    let mut columns: Vec<Option<ArrayRef>> = vec![None; len];
+    for (indices, col) in &elements {
+        let flat = indices
+            .iter()
+            .zip(&shape)
+            .fold(0, |flat, (index, size)| flat * size + index - 1);
+        columns[flat] = Some(
+            arrow::compute::cast(col, item.data_type())
+                .map_err(|_| anyhow::anyhow!("Error casting type"))?,
+        );
+    }
+    let columns = columns
+        .iter()
+        .map(|col| col.as_deref())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Array input '{}' with shape {shape:?} is missing element columns",
+                field.name()
+            )
+        })?;
+
+    let indices = (0..input_data.num_rows())
+        .flat_map(|row| (0..len).map(move |element| (element, row)))
+        .collect_vec();
+    let values = arrow::compute::interleave(&columns, &indices)?;
+    let list = FixedSizeListArray::try_new(item.clone(), len as i32, values, None)?;
+    Ok(Some(arrow::compute::cast(&list, field.data_type())?))
+}
+
+/// Split a `FixedSizeList` or `List` column into its values and the offset of each row into
+/// them. Returns `None` for other columns.
+pub(crate) fn list_offsets(array: &ArrayRef) -> Option<(&ArrayRef, Vec<usize>)> {
+    match array.data_type() {
+        DataType::FixedSizeList(..) => {
+            let list = array.as_fixed_size_list();
+            let offsets = (0..list.len())
+                .map(|row| list.value_offset(row) as usize)
+                .collect();
+            Some((list.values(), offsets))
+        }
+        DataType::List(_) => {
+            let list = array.as_list::<i32>();
+            let offsets = list.value_offsets()[..list.len()]
+                .iter()
+                .map(|offset| *offset as usize)
+                .collect();
+            Some((list.values(), offsets))
+        }
+        _ => None,
+    }
+}
+
+/// Flatten the list columns of array outputs into one column per element, named `name[i,j,...]`
+/// with 1-based indices in row-major order, e.g. to write them to CSV.
+///
+/// The shape is read from the [`SHAPE_METADATA_KEY`] field metadata, and defaults to a single
+/// dimension.
+pub fn flatten_arrays(batch: &RecordBatch) -> anyhow::Result<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields = vec![];
+    let mut columns = vec![];
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let Some((values, offsets)) = list_offsets(column) else {
+            fields.push(field.clone());
+            columns.push(column.clone());
+            continue;
+        };
+
+        let len = match column.data_type() {
+            DataType::FixedSizeList(_, size) => *size as usize,
+            _ => {
+                // Every row needs the same number of elements to get one column per element
+                let list = column.as_list::<i32>();
+                let lengths = (0..list.len())
+                    .filter(|&row| list.is_valid(row))
+                    .map(|row| list.value_length(row) as usize)
+                    .dedup()
+                    .collect_vec();
+                match lengths.as_slice() {
+                    [] => 0,
+                    [len] => *len,
+                    _ => anyhow::bail!(
+                        "Array '{}' can not be flattened, since its rows have different lengths",
+                        field.name()
+                    ),
+                }
+            }
+        };
+        let shape = field
+            .metadata()
+            .get(SHAPE_METADATA_KEY)
+            .and_then(|shape| {
+                shape
+                    .split(',')
+                    .map(|size| size.parse().ok())
+                    .collect::<Option<Vec<usize>>>()
+            })
+            .filter(|shape| shape.iter().product::<usize>() == len)
+            .unwrap_or_else(|| vec![len]);
+
+        for element in 0..len {
+            let mut indices = vec![0; shape.len()];
+            let mut rest = element;
+            for (index, size) in indices.iter_mut().zip(&shape).rev() {
+                *index = rest % size + 1;
+                rest /= size;
+            }
+
+            // Null rows give null elements
+            let take = UInt32Array::from_iter(
+                offsets
+                    .iter()
+                    .enumerate()
+                    .map(|(row, offset)| column.is_valid(row).then(|| (offset + element) as u32)),
+            );
+            let name = format!("{}[{}]", field.name(), indices.iter().join(","));
+            let element_column = arrow::compute::take(values, &take, None)?;
+            fields.push(Arc::new(Field::new(
+                name,
+                element_column.data_type().clone(),
+                element_column.null_count() > 0,
+            )));
+            columns.push(element_column);
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(anyhow::Error::from)
+}
+
+/// Mangle the column names to avoid quoting in CSV, by replacing every character other than
+/// ASCII alphanumerics and `_` with `_`, e.g. `der(x[1])` becomes `der_x_1__`.
+pub fn mangle_names(batch: &RecordBatch) -> anyhow::Result<RecordBatch> {
+    let fields = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            let name = field
+                .name()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>();
+            field.as_ref().clone().with_name(name)
+        })
+        .collect_vec();
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), batch.columns().to_vec())
+        .map_err(anyhow::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array};
+
+    fn array_field(name: &str, data_type: DataType) -> Field {
+        let item = Arc::new(Field::new_list_field(data_type, false));
+        Field::new(name, DataType::FixedSizeList(item, 6), false)
+    }
+
+    #[test]
+    fn project_flattened_array_input() {
+        let schema = Arc::new(Schema::new(
+            std::iter::once(Field::new("time", DataType::Float64, false))
+                .chain((1..=2).flat_map(|i| {
+                    (1..=3).map(move |j| Field::new(format!("x[{i},{j}]"), DataType::Int64, false))
+                }))
+                .collect::<Vec<_>>(),
+        ));
+        let columns = std::iter::once(Arc::new(Float64Array::from(vec![0.0, 1.0])) as ArrayRef)
+            .chain(
+                (0..6)
+                    .map(|k| Arc::new(arrow::array::Int64Array::from(vec![k, 10 + k])) as ArrayRef),
+            )
+            .collect();
+        let input_data = RecordBatch::try_new(schema, columns).unwrap();
+
+        let model_schema = Arc::new(Schema::new(vec![array_field("x", DataType::Int32)]));
+        let projected = project_input_data(&input_data, model_schema).unwrap();
+
+        let x = projected.column_by_name("x").unwrap().as_fixed_size_list();
+        assert_eq!(x.len(), 2);
+        assert_eq!(
+            x.value(1).as_primitive::<arrow::datatypes::Int32Type>(),
+            &Int32Array::from(vec![10, 11, 12, 13, 14, 15])
+        );
+    }
+
+    #[test]
+    fn project_flattened_array_input_shape_mismatch() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("time", DataType::Float64, false),
+            Field::new("x[1]", DataType::Float64, false),
+            Field::new("x[2]", DataType::Float64, false),
+        ]));
+        let columns = (0..3)
+            .map(|_| Arc::new(Float64Array::from(vec![0.0])) as ArrayRef)
+            .collect();
+        let input_data = RecordBatch::try_new(schema, columns).unwrap();
+
+        let model_schema = Arc::new(Schema::new(vec![array_field("x", DataType::Float64)]));
+        assert!(project_input_data(&input_data, model_schema).is_err());
+    }
+
+    #[test]
+    fn flatten_array_outputs() {
+        let values = Arc::new(Float64Array::from_iter_values((0..12).map(f64::from)));
+        let item = Arc::new(Field::new_list_field(DataType::Float64, false));
+        let list = FixedSizeListArray::try_new(item.clone(), 6, values, None).unwrap();
+        let field = Field::new("x", DataType::FixedSizeList(item, 6), false)
+            .with_metadata([(SHAPE_METADATA_KEY.to_string(), "2,3".to_string())].into());
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("time", DataType::Float64, false),
+                field,
+            ])),
+            vec![Arc::new(Float64Array::from(vec![0.0, 1.0])), Arc::new(list)],
+        )
+        .unwrap();
+
+        let flattened = flatten_arrays(&batch).unwrap();
+        let names = flattened
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect_vec();
+        assert_eq!(
+            names,
+            [
+                "time", "x[1,1]", "x[1,2]", "x[1,3]", "x[2,1]", "x[2,2]", "x[2,3]"
+            ]
+        );
+        assert_eq!(
+            flattened
+                .column(5)
+                .as_primitive::<arrow::datatypes::Float64Type>(),
+            &Float64Array::from(vec![4.0, 10.0])
+        );
+    }
+
+    #[test]
+    fn flatten_variable_size_array_outputs() {
+        let list_batch = |rows: Vec<Option<Vec<Option<f64>>>>| {
+            let list = arrow::array::ListArray::from_iter_primitive::<
+                arrow::datatypes::Float64Type,
+                _,
+                _,
+            >(rows);
+            RecordBatch::try_new(
+                Arc::new(Schema::new(vec![Field::new(
+                    "x",
+                    list.data_type().clone(),
+                    true,
+                )])),
+                vec![Arc::new(list)],
+            )
+            .unwrap()
+        };
+
+        // Null rows give null elements
+        let batch = list_batch(vec![
+            Some(vec![Some(1.0), Some(2.0)]),
+            None,
+            Some(vec![Some(3.0), Some(4.0)]),
+        ]);
+        let flattened = flatten_arrays(&batch).unwrap();
+        assert_eq!(flattened.num_columns(), 2);
+        assert_eq!(
+            flattened
+                .column_by_name("x[2]")
+                .unwrap()
+                .as_primitive::<arrow::datatypes::Float64Type>(),
+            &Float64Array::from(vec![Some(2.0), None, Some(4.0)])
+        );
+
+        // Rows of different lengths can not be split into one column per element
+        let batch = list_batch(vec![
+            Some(vec![Some(1.0), Some(2.0)]),
+            Some(vec![Some(3.0), Some(4.0), Some(5.0)]),
+        ]);
+        assert!(flatten_arrays(&batch).is_err());
+    }
+
+    #[test]
+    fn mangle_column_names() {
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("time", DataType::Float64, false),
+                Field::new("der(x[1])", DataType::Float64, false),
+                Field::new("a.b c", DataType::Float64, false),
+            ])),
+            (0..3)
+                .map(|_| Arc::new(Float64Array::from(vec![0.0])) as ArrayRef)
+                .collect(),
+        )
+        .unwrap();
+
+        let mangled = mangle_names(&batch).unwrap();
+        let names = mangled
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect_vec();
+        assert_eq!(names, ["time", "der_x_1__", "a_b_c"]);
+    }
+}
@@ -5,7 +5,7 @@
 use std::{io::Cursor, path::PathBuf, str::FromStr};
 
 use arrow::{
-    array::{AsArray, Float64Array},
+    array::{Array, AsArray, Float64Array},
     datatypes::{
         ArrowPrimitiveType, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type,
         UInt8Type, UInt16Type, UInt32Type, UInt64Type,
@@ -13,7 +13,10 @@ use arrow::{
 };
 use fmi::{fmi2::import::Fmi2Import, fmi3::import::Fmi3Import, schema::MajorVersion};
 use fmi_sim::{
-    options::{CoSimulationOptions, CommonOptions, FmiSimOptions, Interface, ModelExchangeOptions},
+    options::{
+        CoSimulationOptions, CommonOptions, FmiSimOptions, Interface, ModelExchangeOptions,
+        SolverArg,
+    },
     sim::traits::FmiSim,
 };
 
@@ -92,6 +95,66 @@ fn test_stop_time(
     assert_eq!(time.value(time.len() - 1), 0.5,);
 }
 
+#[rstest::rstest]
+#[trace]
+#[test]
+fn test_fmu_state_files(
+    ref_fmus: fmi_test_data::ReferenceFmus,
+    #[values(MajorVersion::FMI2, MajorVersion::FMI3)] fmi_version: MajorVersion,
+) {
+    let mut ref_fmus = ref_fmus;
+    if cfg!(target_os = "macos") && fmi_version == MajorVersion::FMI2 {
+        return;
+    }
+
+    let fmu_file = ref_fmus
+        .extract_reference_fmu("BouncingBall", fmi_version)
+        .unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+    let state_file = state_dir.path().join("state.bin");
+
+    let simulate = |common: CommonOptions| {
+        let options = FmiSimOptions {
+            interface: Interface::CoSimulation(CoSimulationOptions {
+                common: CommonOptions {
+                    output_interval: Some(0.1),
+                    ..common
+                },
+                ..Default::default()
+            }),
+            model: fmu_file.path().to_path_buf(),
+            ..Default::default()
+        };
+        let (output, _) = fmi_sim::simulate(&options).unwrap();
+        let h = output
+            .column_by_name("h")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        h.value(h.len() - 1)
+    };
+
+    let expected = simulate(CommonOptions {
+        stop_time: Some(1.0),
+        ..Default::default()
+    });
+
+    // Checkpoint half-way, and resume from the checkpoint
+    simulate(CommonOptions {
+        stop_time: Some(0.5),
+        final_fmu_state_file: Some(state_file.clone()),
+        ..Default::default()
+    });
+    assert!(state_file.exists());
+    let resumed = simulate(CommonOptions {
+        start_time: Some(0.5),
+        stop_time: Some(1.0),
+        initial_fmu_state_file: Some(state_file),
+        ..Default::default()
+    });
+
+    float_cmp::assert_approx_eq!(f64, resumed, expected, epsilon = 1e-12);
+}
+
 #[test]
 fn test_start_value_types() {
     flexi_logger::init();
@@ -267,6 +330,329 @@ fn compare_f64_column_by_name(
     }
 }
 
+/// The variable-step solvers converge to the same solution as the tolerance is tightened.
+#[rstest::rstest]
+#[trace]
+#[test]
+fn test_variable_step_solvers(
+    ref_fmus: fmi_test_data::ReferenceFmus,
+    #[values(MajorVersion::FMI2, MajorVersion::FMI3)] fmi_version: MajorVersion,
+    #[values(SolverArg::Bdf, SolverArg::Dopri5)] solver: SolverArg,
+) {
+    let mut ref_fmus = ref_fmus;
+    if cfg!(target_os = "macos") && fmi_version == MajorVersion::FMI2 {
+        return;
+    }
+
+    let fmu_file = ref_fmus
+        .extract_reference_fmu("VanDerPol", fmi_version)
+        .unwrap();
+
+    let final_x0 = |tolerance: f64| {
+        let options = FmiSimOptions {
+            interface: Interface::ModelExchange(ModelExchangeOptions {
+                common: CommonOptions {
+                    tolerance: Some(tolerance),
+                    ..Default::default()
+                },
+                solver: solver.clone(),
+            }),
+            model: fmu_file.path().to_path_buf(),
+            ..Default::default()
+        };
+        let (output, _) = fmi_sim::simulate(&options).unwrap();
+        let x0 = output
+            .column_by_name("x0")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        x0.value(x0.len() - 1)
+    };
+
+    float_cmp::assert_approx_eq!(f64, final_x0(1e-6), final_x0(1e-9), epsilon = 1e-4);
+}
+
+/// State events are located within the solver step, so the first bounce happens at the analytic
+/// time of impact rather than at the next output point.
+#[rstest::rstest]
+#[trace]
+#[test]
+fn test_state_event_location(
+    ref_fmus: fmi_test_data::ReferenceFmus,
+    #[values(MajorVersion::FMI2, MajorVersion::FMI3)] fmi_version: MajorVersion,
+) {
+    let mut ref_fmus = ref_fmus;
+    if cfg!(target_os = "macos") && fmi_version == MajorVersion::FMI2 {
+        return;
+    }
+
+    let fmu_file = ref_fmus
+        .extract_reference_fmu("BouncingBall", fmi_version)
+        .unwrap();
+
+    let options = FmiSimOptions {
+        interface: Interface::ModelExchange(ModelExchangeOptions {
+            common: CommonOptions {
+                tolerance: Some(1e-8),
+                output_interval: Some(0.1),
+                ..Default::default()
+            },
+            solver: SolverArg::Dopri5,
+        }),
+        model: fmu_file.path().to_path_buf(),
+        ..Default::default()
+    };
+    let (output, _) = fmi_sim::simulate(&options).unwrap();
+
+    let time = output
+        .column_by_name("time")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let v = output
+        .column_by_name("v")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let bounce = (1..v.len()).find(|&i| v.value(i) > 0.0).unwrap();
+
+    // h(t) = 1 - g t^2 / 2
+    let expected = (2.0 / 9.81f64).sqrt();
+    float_cmp::assert_approx_eq!(f64, time.value(bounce), expected, epsilon = 1e-6);
+}
+
+/// A state event located before a pending input event is handled on its own, and the input event
+/// is still handled at its own time.
+#[rstest::rstest]
+#[trace]
+#[test]
+fn test_state_event_before_input_event(
+    ref_fmus: fmi_test_data::ReferenceFmus,
+    #[values(MajorVersion::FMI2, MajorVersion::FMI3)] fmi_version: MajorVersion,
+) {
+    let mut ref_fmus = ref_fmus;
+    if cfg!(target_os = "macos") && fmi_version == MajorVersion::FMI2 {
+        return;
+    }
+
+    // The repeated time point is a discontinuity of the input data, i.e. an input event at 0.7
+    let input_data =
+        fmi_sim::sim::util::read_csv(&mut Cursor::new("time\n0.0\n0.7\n0.7\n1.0\n")).unwrap();
+    let interface = Interface::ModelExchange(ModelExchangeOptions {
+        common: CommonOptions {
+            stop_time: Some(1.0),
+            tolerance: Some(1e-8),
+            output_interval: Some(1.0),
+            ..Default::default()
+        },
+        solver: SolverArg::Dopri5,
+    });
+
+    let (output, stats) = match fmi_version {
+        MajorVersion::FMI1 => unimplemented!(),
+        MajorVersion::FMI2 => {
+            let import: Fmi2Import = ref_fmus.get_reference_fmu("BouncingBall").unwrap();
+            fmi_sim::sim::simulate_with(Some(input_data), &interface, import).unwrap()
+        }
+        MajorVersion::FMI3 => {
+            let import: Fmi3Import = ref_fmus.get_reference_fmu("BouncingBall").unwrap();
+            fmi_sim::sim::simulate_with(Some(input_data), &interface, import).unwrap()
+        }
+    };
+
+    // The first bounce, then the input event
+    assert_eq!(stats.num_events, 2);
+
+    let time = output
+        .column_by_name("time")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let v = output
+        .column_by_name("v")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let bounce = (1..v.len()).find(|&i| v.value(i) > 0.0).unwrap();
+    float_cmp::assert_approx_eq!(
+        f64,
+        time.value(bounce),
+        (2.0 / 9.81f64).sqrt(),
+        epsilon = 1e-6
+    );
+    assert!(time.values().contains(&0.7));
+}
+
+/// Simulate the FMI 3.0 BouncingBall from 0 to 1 with the given interface.
+fn simulate_bouncing_ball(
+    ref_fmus: &mut fmi_test_data::ReferenceFmus,
+    interface: Interface,
+) -> (arrow::record_batch::RecordBatch, fmi_sim::sim::SimStats) {
+    let fmu_file = ref_fmus
+        .extract_reference_fmu("BouncingBall", MajorVersion::FMI3)
+        .unwrap();
+    let options = FmiSimOptions {
+        interface,
+        model: fmu_file.path().to_path_buf(),
+        ..Default::default()
+    };
+    fmi_sim::simulate(&options).unwrap()
+}
+
+/// With `-d`, the left limit is recorded before each event, so the bounce appears twice in the
+/// time column: first falling, then rising.
+#[rstest::rstest]
+#[test]
+fn test_print_left_limit(mut ref_fmus: fmi_test_data::ReferenceFmus) {
+    let interface = |print_left_limit| {
+        Interface::ModelExchange(ModelExchangeOptions {
+            common: CommonOptions {
+                stop_time: Some(1.0),
+                output_interval: Some(0.1),
+                print_left_limit,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    };
+
+    let (output, _) = simulate_bouncing_ball(&mut ref_fmus, interface(false));
+    let time = output
+        .column_by_name("time")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    assert!(time.values().windows(2).all(|t| t[0] < t[1]));
+
+    let (output, _) = simulate_bouncing_ball(&mut ref_fmus, interface(true));
+    let time = output
+        .column_by_name("time")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let v = output
+        .column_by_name("v")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    let events = (1..time.len())
+        .filter(|&i| time.value(i - 1) == time.value(i))
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+    assert!(v.value(events[0] - 1) < 0.0);
+    assert!(v.value(events[0]) > 0.0);
+}
+
+/// `--print-all` records the parameters in addition to the outputs.
+#[rstest::rstest]
+#[test]
+fn test_print_all_variables(mut ref_fmus: fmi_test_data::ReferenceFmus) {
+    let interface = |print_all_variables| {
+        Interface::CoSimulation(CoSimulationOptions {
+            common: CommonOptions {
+                stop_time: Some(1.0),
+                output_interval: Some(0.1),
+                print_all_variables,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    };
+
+    let (output, _) = simulate_bouncing_ball(&mut ref_fmus, interface(false));
+    assert!(output.column_by_name("h").is_some());
+    assert!(output.column_by_name("g").is_none());
+    assert!(output.column_by_name("e").is_none());
+
+    let (output, _) = simulate_bouncing_ball(&mut ref_fmus, interface(true));
+    assert!(output.column_by_name("h").is_some());
+    assert!(output.column_by_name("g").is_some());
+    assert!(output.column_by_name("e").is_some());
+}
+
+/// `-n 0` records the outputs at every communication step.
+#[rstest::rstest]
+#[test]
+fn test_output_every_step(mut ref_fmus: fmi_test_data::ReferenceFmus) {
+    let (output, stats) = simulate_bouncing_ball(
+        &mut ref_fmus,
+        Interface::CoSimulation(CoSimulationOptions {
+            common: CommonOptions {
+                stop_time: Some(1.0),
+                step_size: Some(0.05),
+                num_steps: Some(0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    );
+
+    assert_eq!(stats.num_steps, 20);
+    assert_eq!(output.num_rows(), stats.num_steps + 1);
+}
+
+/// `--step` sets the integration step size, while the outputs are still recorded at the output
+/// interval.
+#[rstest::rstest]
+#[test]
+fn test_step_size(mut ref_fmus: fmi_test_data::ReferenceFmus) {
+    let interface = |step_size| {
+        Interface::ModelExchange(ModelExchangeOptions {
+            common: CommonOptions {
+                stop_time: Some(0.4),
+                output_interval: Some(0.1),
+                step_size,
+                ..Default::default()
+            },
+            solver: SolverArg::Euler,
+        })
+    };
+
+    let (coarse, coarse_stats) = simulate_bouncing_ball(&mut ref_fmus, interface(None));
+    let (fine, fine_stats) = simulate_bouncing_ball(&mut ref_fmus, interface(Some(0.01)));
+
+    assert_eq!(coarse_stats.num_steps, 4);
+    assert_eq!(fine_stats.num_steps, 40);
+    assert_eq!(coarse.num_rows(), 5);
+    assert_eq!(fine.num_rows(), 5);
+
+    // The forward Euler error shrinks with the step size: h(t) = 1 - g t^2 / 2
+    let final_h = |output: &arrow::record_batch::RecordBatch| {
+        let h = output
+            .column_by_name("h")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        h.value(h.len() - 1)
+    };
+    let expected = 1.0 - 9.81 * 0.4f64.powi(2) / 2.0;
+    assert!((final_h(&fine) - expected).abs() < (final_h(&coarse) - expected).abs());
+}
+
+#[cfg(feature = "se")]
+#[test]
+fn test_scheduled_execution() {
+    let mut ref_fmus = fmi_test_data::ReferenceFmus::new().unwrap();
+    let fmu_file = ref_fmus
+        .extract_reference_fmu("Clocks", MajorVersion::FMI3)
+        .unwrap();
+
+    let options = FmiSimOptions {
+        interface: Interface::ScheduledExecution(fmi_sim::options::ScheduledExecutionOptions {
+            common: CommonOptions {
+                stop_time: Some(3.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        model: fmu_file.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    let (output, stats) = fmi_sim::simulate(&options).unwrap();
+    assert!(stats.num_steps > 0);
+    assert!(stats.end_time <= 3.0);
+
+    // One row for the initial outputs, and one per partition activation
+    assert_eq!(output.num_rows(), stats.num_steps + 1);
+    let time = output
+        .column_by_name("time")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    assert!(time.values().windows(2).all(|w| w[0] <= w[1]));
+}
+
 #[cfg(false)]
 #[test]
 fn test_bouncing_ball() {
@@ -399,3 +785,31 @@ fn test_input_data(
     // extrapolation
     assert_eq!(f64_cts_out.value(f64_cts_out.len() - 1), 3.0);
 }
+
+/// Array outputs of a multi-FMU slave, whose dimensions are given by structural parameters, are
+/// recorded with their shape resolved after initialization.
+#[rstest::rstest]
+#[test]
+fn test_multi_sim_array_outputs(mut ref_fmus: fmi_test_data::ReferenceFmus) {
+    use fmi_sim::sim::{
+        multi::{MultiSim, MultiSimParams},
+        util::SHAPE_METADATA_KEY,
+    };
+
+    let import: Fmi3Import = ref_fmus.get_reference_fmu("StateSpace").unwrap();
+
+    let mut multi = MultiSim::new(MultiSimParams::default());
+    multi.add_fmu(&import, "ss", &[]).unwrap();
+    let (output, _) = multi.simulate().unwrap();
+
+    let schema = output.schema();
+    let field = schema.field_with_name("ss.y").unwrap();
+    assert_eq!(
+        field.metadata().get(SHAPE_METADATA_KEY).map(String::as_str),
+        Some("3")
+    );
+
+    let y = output.column_by_name("ss.y").unwrap().as_list::<i32>();
+    assert_eq!(y.len(), output.num_rows());
+    assert!((0..y.len()).all(|row| y.value_length(row) == 3));
+}
@@ -116,6 +116,14 @@ impl Instance<CS> {
             binding,
             ptr: instance,
             name,
+            provides_directional_derivatives: co_simulation
+                .provides_directional_derivatives()
+                .unwrap_or(false),
+            provides_adjoint_derivatives: co_simulation
+                .provides_adjoint_derivatives()
+                .unwrap_or(false),
+            instance_id: super::next_instance_id(),
+            saved_states: Vec::new(),
             _tag: std::marker::PhantomData,
         })
     }
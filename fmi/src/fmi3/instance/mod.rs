@@ -1,12 +1,14 @@
 //! FMI 3.0 instance interface
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::{
-    CS, InterfaceType, ME, SE,
+    CS, Error, InterfaceType, ME, SE,
     fmi3::{
         Fmi3Error, Fmi3Res,
         traits::{Common, GetSet},
     },
-    traits::{FmiImport, FmiInstance, InstanceTag},
+    traits::{FmiImport, FmiInstance, FmiStatus, InstanceTag},
 };
 
 use super::{Fmi3Status, binding, import::Fmi3Import, schema};
@@ -20,6 +22,12 @@ pub type InstanceME = Instance<ME>;
 pub type InstanceCS = Instance<CS>;
 pub type InstanceSE = Instance<SE>;
 
+/// Returns a new unique id for an [`Instance`]
+fn next_instance_id() -> usize {
+    static NEXT_INSTANCE_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 /// An imported FMI 3.0 instance
 pub struct Instance<Tag> {
     /// Raw FMI 3.0 bindings
@@ -28,12 +36,26 @@ pub struct Instance<Tag> {
     ptr: binding::fmi3Instance,
     /// Instance name
     name: String,
+    /// Whether the FMU provides `fmi3GetDirectionalDerivative`
+    provides_directional_derivatives: bool,
+    /// Whether the FMU provides `fmi3GetAdjointDerivative`
+    provides_adjoint_derivatives: bool,
+    /// Unique id of this instance, checked against the [`FmuState`] handles passed to it
+    instance_id: usize,
+    /// Allocated FMU states, null once freed, in which case the slot is reused
+    saved_states: Vec<binding::fmi3FMUState>,
     _tag: std::marker::PhantomData<Tag>,
 }
 
 impl<Tag> Drop for Instance<Tag> {
     fn drop(&mut self) {
         unsafe {
+            for state in &mut self.saved_states {
+                if !state.is_null() {
+                    log::trace!("Freeing state {:?}", state);
+                    self.binding.fmi3FreeFMUState(self.ptr, state);
+                }
+            }
             log::trace!("Freeing instance {:?}", self.ptr);
             self.binding.fmi3FreeInstance(self.ptr);
         }
@@ -53,30 +75,170 @@ where
         &mut self,
         model_description: &schema::Fmi3ModelDescription,
         var_refs: &[u32],
-    ) -> usize {
-        let var_dims = var_refs.iter().map(|vr| {
-            model_description
-                .model_variables
-                .iter_floating()
-                .find(|fv| fv.value_reference() == *vr)
-                .map(|fv| fv.dimensions().iter())
-                .expect("Variable not found")
-        });
-
-        var_dims
-            .map(|dims| {
-                dims.map(|dim| match dim {
-                    schema::Dimension::Fixed(start) => *start as usize,
-                    schema::Dimension::Variable(vr) => {
-                        let mut dim_val = [0];
-                        self.get_uint64(&[*vr], &mut dim_val)
-                            .expect("Error getting dimension");
-                        dim_val[0] as usize
-                    }
-                })
-                .product::<usize>()
+    ) -> Result<usize, Error> {
+        var_refs.iter().try_fold(0, |sum, vr| {
+            let shape = self.get_variable_shape(model_description, *vr)?;
+            Ok(sum + shape.iter().product::<usize>())
+        })
+    }
+
+    /// Get the size of each dimension of the variable with the given value reference. Dimensions
+    /// given by a structural parameter are resolved to its current value. The shape of a scalar
+    /// variable is empty.
+    ///
+    /// Returns [`Error::UnknownVariable`] if no variable has the value reference.
+    ///
+    /// # Arguments
+    /// * `model_description` - The model description to look up variable information
+    /// * `var_ref` - Value reference of the variable
+    pub fn get_variable_shape(
+        &mut self,
+        model_description: &schema::Fmi3ModelDescription,
+        var_ref: u32,
+    ) -> Result<Vec<usize>, Error> {
+        model_description
+            .model_variables
+            .variables
+            .iter()
+            .find(|v| v.as_abstract().value_reference() == var_ref)
+            .ok_or_else(|| Error::UnknownVariable {
+                name: format!("value reference {var_ref}"),
+            })?
+            .dimensions()
+            .iter()
+            .map(|dim| match dim {
+                schema::Dimension::Fixed(start) => Ok(*start as usize),
+                schema::Dimension::Variable(vr) => {
+                    let mut dim_val = [0];
+                    self.get_uint64(&[*vr], &mut dim_val)?;
+                    Ok(dim_val[0] as usize)
+                }
             })
-            .sum()
+            .collect()
+    }
+}
+
+impl<Tag> Instance<Tag> {
+    /// Computes the directional derivative `sensitivity = J * seed` of the `unknowns` with
+    /// respect to the `knowns`, where `J` is their partial derivative matrix.
+    ///
+    /// `seed` must contain one entry per (array) element of the knowns, and `sensitivity` one
+    /// entry per element of the unknowns.
+    ///
+    /// Returns [`Error::UnsupportedInterface`] if the FMU does not declare
+    /// `providesDirectionalDerivatives`.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetDirectionalDerivative>
+    pub fn get_directional_derivative(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<Fmi3Res, Error> {
+        if !self.provides_directional_derivatives {
+            return Err(Error::UnsupportedInterface(
+                "Directional derivatives are not provided by this FMU".to_string(),
+            ));
+        }
+        Fmi3Status::from(unsafe {
+            self.binding.fmi3GetDirectionalDerivative(
+                self.ptr,
+                unknowns.as_ptr(),
+                unknowns.len() as _,
+                knowns.as_ptr(),
+                knowns.len() as _,
+                seed.as_ptr(),
+                seed.len() as _,
+                sensitivity.as_mut_ptr(),
+                sensitivity.len() as _,
+            )
+        })
+        .ok()
+        .map_err(Error::from)
+    }
+
+    /// Computes the adjoint derivative `sensitivity = seed^T * J` of the `unknowns` with respect
+    /// to the `knowns`, where `J` is their partial derivative matrix.
+    ///
+    /// `seed` must contain one entry per (array) element of the unknowns, and `sensitivity` one
+    /// entry per element of the knowns.
+    ///
+    /// Returns [`Error::UnsupportedInterface`] if the FMU does not declare
+    /// `providesAdjointDerivatives`.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetAdjointDerivative>
+    pub fn get_adjoint_derivative(
+        &mut self,
+        unknowns: &[binding::fmi3ValueReference],
+        knowns: &[binding::fmi3ValueReference],
+        seed: &[f64],
+        sensitivity: &mut [f64],
+    ) -> Result<Fmi3Res, Error> {
+        if !self.provides_adjoint_derivatives {
+            return Err(Error::UnsupportedInterface(
+                "Adjoint derivatives are not provided by this FMU".to_string(),
+            ));
+        }
+        Fmi3Status::from(unsafe {
+            self.binding.fmi3GetAdjointDerivative(
+                self.ptr,
+                unknowns.as_ptr(),
+                unknowns.len() as _,
+                knowns.as_ptr(),
+                knowns.len() as _,
+                seed.as_ptr(),
+                seed.len() as _,
+                sensitivity.as_mut_ptr(),
+                sensitivity.len() as _,
+            )
+        })
+        .ok()
+        .map_err(Error::from)
+    }
+
+    /// Get the intervals of the given Clocks. For each Clock, the qualifier tells whether the
+    /// interval is not yet known, unchanged or changed since the last call.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetIntervalDecimal>
+    pub fn get_interval_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        intervals: &mut [f64],
+        qualifiers: &mut [binding::fmi3IntervalQualifier],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        assert_eq!(vrs.len(), intervals.len());
+        assert_eq!(vrs.len(), qualifiers.len());
+        Fmi3Status::from(unsafe {
+            self.binding.fmi3GetIntervalDecimal(
+                self.ptr,
+                vrs.as_ptr(),
+                vrs.len() as _,
+                intervals.as_mut_ptr(),
+                qualifiers.as_mut_ptr(),
+            )
+        })
+        .ok()
+    }
+
+    /// Get the shifts of the given Clocks.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetShiftDecimal>
+    pub fn get_shift_decimal(
+        &mut self,
+        vrs: &[binding::fmi3ValueReference],
+        shifts: &mut [f64],
+    ) -> Result<Fmi3Res, Fmi3Error> {
+        assert_eq!(vrs.len(), shifts.len());
+        Fmi3Status::from(unsafe {
+            self.binding.fmi3GetShiftDecimal(
+                self.ptr,
+                vrs.as_ptr(),
+                vrs.len() as _,
+                shifts.as_mut_ptr(),
+            )
+        })
+        .ok()
     }
 }
 
@@ -127,19 +289,144 @@ impl<Tag: InstanceTag> FmiInstance for Instance<Tag> {
     }
 }
 
-pub struct Fmu3State<Tag> {
-    instance: Instance<Tag>,
-    /// Pointer to the raw FMI 3.0 state
-    state: binding::fmi3FMUState,
+/// Handle to an FMU state saved in an [`Instance`], retrieved with [`Instance::get_fmu_state`] or
+/// [`Instance::deserialize_fmu_state`].
+///
+/// An instance can hold any number of saved states, e.g. one checkpoint per communication step of
+/// a rollback master. A handle is only valid for the instance that returned it, and passing it to
+/// another instance is an error. The state is freed with [`Instance::free_fmu_state`], which
+/// consumes the handle and makes its slot available to the next saved state, or when the instance
+/// is dropped.
+///
+/// See <https://fmi-standard.org/docs/3.0.1/#get-set-fmu-state>
+#[derive(Debug, PartialEq, Eq)]
+pub struct FmuState {
+    instance_id: usize,
+    index: usize,
 }
 
-impl<Tag> Drop for Fmu3State<Tag> {
-    fn drop(&mut self) {
-        unsafe {
-            log::trace!("Freeing state {:?}", self.state);
-            self.instance
-                .binding
-                .fmi3FreeFMUState(self.instance.ptr, &mut self.state);
+impl<Tag> Instance<Tag> {
+    /// The raw FMU state of a handle, or an error if it belongs to another instance or has
+    /// already been freed
+    fn saved_state(&self, state: &FmuState) -> Result<binding::fmi3FMUState, Fmi3Error> {
+        if state.instance_id != self.instance_id {
+            log::error!("FMU state handle belongs to another instance");
+            return Err(Fmi3Error::Error);
+        }
+        match self.saved_states.get(state.index) {
+            Some(raw) if !raw.is_null() => Ok(*raw),
+            _ => {
+                log::error!("Invalid FMU state handle {}", state.index);
+                Err(Fmi3Error::Error)
+            }
+        }
+    }
+
+    /// Store a state returned by the FMU in the first free slot and return its handle
+    fn push_state(&mut self, state: binding::fmi3FMUState) -> Result<FmuState, Fmi3Error> {
+        if state.is_null() {
+            log::error!("FMU returned a null state");
+            return Err(Fmi3Error::Fatal);
         }
+        let index = match self.saved_states.iter().position(|raw| raw.is_null()) {
+            Some(index) => {
+                self.saved_states[index] = state;
+                index
+            }
+            None => {
+                self.saved_states.push(state);
+                self.saved_states.len() - 1
+            }
+        };
+        Ok(FmuState {
+            instance_id: self.instance_id,
+            index,
+        })
+    }
+
+    /// Makes a copy of the internal FMU state and returns a handle to it.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetFMUState>
+    pub fn get_fmu_state(&mut self) -> Result<FmuState, Fmi3Error> {
+        let mut state = std::ptr::null_mut();
+        Fmi3Status::from(unsafe { self.binding.fmi3GetFMUState(self.ptr, &mut state) }).ok()?;
+        self.push_state(state)
+    }
+
+    /// Overwrites a saved state with a copy of the current internal FMU state, re-using the
+    /// memory allocated for it.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetFMUState>
+    pub fn update_fmu_state(&mut self, state: &FmuState) -> Result<Fmi3Res, Fmi3Error> {
+        let mut raw = self.saved_state(state)?;
+        let res = Fmi3Status::from(unsafe { self.binding.fmi3GetFMUState(self.ptr, &mut raw) });
+        self.saved_states[state.index] = raw;
+        res.ok()
+    }
+
+    /// Copies a saved state back into the instance.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3SetFMUState>
+    pub fn set_fmu_state(&mut self, state: &FmuState) -> Result<Fmi3Res, Fmi3Error> {
+        let raw = self.saved_state(state)?;
+        Fmi3Status::from(unsafe { self.binding.fmi3SetFMUState(self.ptr, raw) }).ok()
+    }
+
+    /// Frees a saved state.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3FreeFMUState>
+    pub fn free_fmu_state(&mut self, state: FmuState) -> Result<Fmi3Res, Fmi3Error> {
+        let mut raw = self.saved_state(&state)?;
+        let res = Fmi3Status::from(unsafe { self.binding.fmi3FreeFMUState(self.ptr, &mut raw) });
+        self.saved_states[state.index] = std::ptr::null_mut();
+        res.ok()
+    }
+
+    /// Returns the size of the byte vector needed to store a saved state.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3SerializedFMUStateSize>
+    pub fn serialized_fmu_state_size(&mut self, state: &FmuState) -> Result<usize, Fmi3Error> {
+        let raw = self.saved_state(state)?;
+        let mut size = 0;
+        Fmi3Status::from(unsafe {
+            self.binding
+                .fmi3SerializedFMUStateSize(self.ptr, raw, &mut size)
+        })
+        .ok()?;
+        Ok(size)
+    }
+
+    /// Serializes a saved state into a byte vector, which can be restored with
+    /// [`Instance::deserialize_fmu_state`].
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3SerializeFMUState>
+    pub fn serialize_fmu_state(&mut self, state: &FmuState) -> Result<Vec<u8>, Fmi3Error> {
+        let size = self.serialized_fmu_state_size(state)?;
+        let raw = self.saved_state(state)?;
+        let mut buffer: Vec<u8> = vec![0; size];
+        Fmi3Status::from(unsafe {
+            self.binding
+                .fmi3SerializeFMUState(self.ptr, raw, buffer.as_mut_ptr(), size)
+        })
+        .ok()?;
+        Ok(buffer)
+    }
+
+    /// Deserializes a byte vector previously returned by [`Instance::serialize_fmu_state`] into a
+    /// new saved state.
+    ///
+    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3DeserializeFMUState>
+    pub fn deserialize_fmu_state(&mut self, buffer: &[u8]) -> Result<FmuState, Fmi3Error> {
+        let mut state = std::ptr::null_mut();
+        Fmi3Status::from(unsafe {
+            self.binding.fmi3DeserializeFMUState(
+                self.ptr,
+                buffer.as_ptr(),
+                buffer.len() as _,
+                &mut state,
+            )
+        })
+        .ok()?;
+        self.push_state(state)
     }
 }
@@ -59,6 +59,14 @@ impl Instance<ME> {
             binding,
             ptr: instance,
             name,
+            provides_directional_derivatives: model_exchange
+                .provides_directional_derivatives()
+                .unwrap_or(false),
+            provides_adjoint_derivatives: model_exchange
+                .provides_adjoint_derivatives()
+                .unwrap_or(false),
+            instance_id: super::next_instance_id(),
+            saved_states: Vec::new(),
             _tag: std::marker::PhantomData,
         })
     }
@@ -10,16 +10,17 @@ use crate::{
 
 use super::{Instance, SE};
 
+// The importer polls the output and countdown Clocks after each call to
+// `fmi3ActivateModelPartition`, so there is nothing to do when the FMU signals a Clock update.
 unsafe extern "C" fn clock_update(_instance_environment: binding::fmi3InstanceEnvironment) {
-    todo!();
-}
-unsafe extern "C" fn lock_preemption() {
-    todo!();
-}
-unsafe extern "C" fn unlock_preemption() {
-    todo!();
+    log::trace!("Clock update signaled");
 }
 
+// Model partitions are activated one at a time from a single thread, so they never preempt each
+// other.
+unsafe extern "C" fn lock_preemption() {}
+unsafe extern "C" fn unlock_preemption() {}
+
 impl Instance<SE> {
     pub fn new(
         import: &import::Fmi3Import,
@@ -37,7 +38,7 @@ impl Instance<SE> {
             .ok_or(Error::UnsupportedFmuType("ScheduledExecution".to_owned()))?;
 
         log::debug!(
-            "Instantiating ME: {} '{name}'",
+            "Instantiating SE: {} '{name}'",
             scheduled_execution.model_identifier()
         );
 
@@ -72,6 +73,14 @@ impl Instance<SE> {
             binding,
             ptr: instance,
             name,
+            provides_directional_derivatives: scheduled_execution
+                .provides_directional_derivatives()
+                .unwrap_or(false),
+            provides_adjoint_derivatives: scheduled_execution
+                .provides_adjoint_derivatives()
+                .unwrap_or(false),
+            instance_id: super::next_instance_id(),
+            saved_states: Vec::new(),
             _tag: std::marker::PhantomData,
         })
     }
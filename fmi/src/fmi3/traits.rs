@@ -97,17 +97,6 @@ pub trait GetSet {
         unimplemented!()
     }
 
-    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3GetFMUState>
-    #[cfg(false)]
-    fn get_fmu_state<Tag>(
-        &mut self,
-        state: Option<Fmu3State<'_, Tag>>,
-    ) -> Result<Fmu3State<'_, Tag>, Error>;
-
-    /// See <https://fmi-standard.org/docs/3.0.1/#fmi3SetFMUState>
-    #[cfg(false)]
-    fn set_fmu_state<Tag>(&mut self, state: &Fmu3State<'_, Tag>) -> Fmi3Status;
-
     fn get_clock(
         &mut self,
         vrs: &[binding::fmi3ValueReference],
@@ -9,6 +9,8 @@ enum SimMode {
     ModelExchange,
     CoSimulation,
     Both,
+    /// Co-Simulation in Event Mode, checking that the output Clock in the given column ticks
+    ClockedCoSimulation(&'static str),
     Skip,
 }
 
@@ -26,13 +28,82 @@ fn metadata() -> Metadata {
 #[case::vanderpol("vanderpol", SimMode::ModelExchange)]
 #[case::dahlquist("dahlquist", SimMode::Both)]
 #[case::stair("stair", SimMode::ModelExchange)]
-#[case::can_triggered_output("can-triggered-output", SimMode::Skip)]
+#[case::can_triggered_output(
+    "can-triggered-output",
+    SimMode::ClockedCoSimulation("CanChannel.Tx_Clock")
+)]
 fn examples_export_and_simulate(
     metadata: Metadata,
     #[case] package: &'static str,
     #[case] sim: SimMode,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let package = find_package(&metadata, package)?;
+    let fmu_path = export_fmu(&metadata, package)?;
+
+    match sim {
+        SimMode::ModelExchange => {
+            run_simulation("model-exchange", &[], None, &fmu_path)?;
+        }
+        SimMode::CoSimulation => {
+            run_simulation("co-simulation", &[], None, &fmu_path)?;
+        }
+        SimMode::Both => {
+            run_simulation("model-exchange", &[], None, &fmu_path)?;
+            run_simulation("co-simulation", &[], None, &fmu_path)?;
+        }
+        SimMode::ClockedCoSimulation(clock) => {
+            let output = run_simulation("co-simulation", &["--event-mode-used"], None, &fmu_path)?;
+            if clock_ticks(&output, clock)?.is_empty() {
+                return Err(format!("No ticks of {clock} recorded in output:\n{output}").into());
+            }
+        }
+        SimMode::Skip => {}
+    }
+
+    Ok(())
+}
+
+/// An input Clock ticked from the input data is recorded as active at the time of its tick.
+#[rstest]
+fn examples_input_clock(metadata: Metadata) -> Result<(), Box<dyn std::error::Error>> {
+    let fmu_path = export_fmu(&metadata, "can-triggered-output")?;
+
+    let output = run_simulation(
+        "co-simulation",
+        &["--event-mode-used", "--print-all"],
+        Some("time,CanChannel.Rx_Clock\n0.0,false\n0.4,true\n"),
+        &fmu_path,
+    )?;
+
+    let ticks = clock_ticks(&output, "CanChannel.Rx_Clock")?;
+    if ticks != [0.4] {
+        return Err(format!("Expected a single Rx_Clock tick at 0.4, got {ticks:?}").into());
+    }
+
+    Ok(())
+}
+
+/// Times at which the Clock in the given column of the output CSV is active
+fn clock_ticks(output: &str, clock: &str) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    let mut lines = output.lines();
+    let header = lines.next().ok_or("Empty output")?;
+    let column = header
+        .split(',')
+        .position(|name| name == clock)
+        .ok_or_else(|| format!("No {clock} column in output:\n{output}"))?;
+    let ticks = lines
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .filter(|row| row.get(column) == Some(&"true"))
+        .map(|row| row[0].parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ticks)
+}
+
+/// Bundle the example package with cargo-fmi and return the path of the FMU
+fn export_fmu(
+    metadata: &Metadata,
+    package: &str,
+) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let package = find_package(metadata, package)?;
     let model_identifier = cdylib_target_name(package)?;
     let fmu_path = metadata
         .target_directory
@@ -56,18 +127,7 @@ fn examples_export_and_simulate(
         return Err(format!("Expected FMU at {}", fmu_path.display()).into());
     }
 
-    match sim {
-        SimMode::ModelExchange => run_simulation("model-exchange", &fmu_path)?,
-        SimMode::CoSimulation => run_simulation("co-simulation", &fmu_path)?,
-        SimMode::Both => {
-            run_simulation("model-exchange", &fmu_path)?;
-            run_simulation("co-simulation", &fmu_path)?;
-        }
-        // Binary CAN payloads are not yet supported by fmi-sim's FMI3 binary IO path.
-        SimMode::Skip => {}
-    }
-
-    Ok(())
+    Ok(fmu_path)
 }
 
 fn find_package<'a>(metadata: &'a Metadata, name: &str) -> Result<&'a Package, String> {
@@ -88,7 +148,13 @@ fn cdylib_target_name(package: &Package) -> Result<String, String> {
     Ok(target.name.clone())
 }
 
-fn run_simulation(interface: &str, fmu_path: &std::path::Path) -> Result<(), String> {
+/// Simulate the FMU with fmi-sim, optionally with the given input CSV, and return the output CSV
+fn run_simulation(
+    interface: &str,
+    args: &[&str],
+    input: Option<&str>,
+    fmu_path: &std::path::Path,
+) -> Result<String, String> {
     let tempdir = tempfile::tempdir().map_err(|err| err.to_string())?;
     let output_path = tempdir.path().join("output.csv");
 
@@ -101,11 +167,15 @@ fn run_simulation(interface: &str, fmu_path: &std::path::Path) -> Result<(), Str
         .arg("--model")
         .arg(fmu_path)
         .arg("-o")
-        .arg(output_path)
-        .arg(interface)
-        .arg("-n")
-        .arg("25");
-    run_command(sim_cmd)
+        .arg(&output_path);
+    if let Some(input) = input {
+        let input_path = tempdir.path().join("input.csv");
+        std::fs::write(&input_path, input).map_err(|err| err.to_string())?;
+        sim_cmd.arg("-i").arg(input_path);
+    }
+    sim_cmd.arg(interface).args(args).arg("-n").arg("25");
+    run_command(sim_cmd)?;
+    std::fs::read_to_string(&output_path).map_err(|err| err.to_string())
 }
 
 fn cargo_path() -> String {
@@ -1,7 +1,7 @@
 //! Test the FMI3.0 instance API.
 
 use fmi::{
-    fmi3::{Common, Fmi3Model, GetSet, ModelExchange, import::Fmi3Import},
+    fmi3::{CoSimulation, Common, Fmi3Error, Fmi3Model, GetSet, ModelExchange, import::Fmi3Import},
     schema::fmi3::{AbstractVariableTrait, InitializableVariableTrait},
     traits::FmiImport as _,
 };
@@ -143,3 +143,146 @@ fn test_instance_feedthrough_binary() {
     // compare my_binary to the new value
     assert_eq!(&my_binary[..values_sizes[0]], b"New Binary Value");
 }
+
+/// Test getting, restoring and (de)serializing the FMU state with the `Dahlquist` FMU
+#[test]
+fn test_instance_dahlquist_fmu_state() {
+    let mut ref_fmus = ReferenceFmus::new().unwrap();
+    let import: Fmi3Import = ref_fmus.get_reference_fmu("Dahlquist").unwrap();
+    let mut inst1 = import
+        .instantiate_cs("inst1", false, true, false, false, &[])
+        .unwrap();
+
+    let x_vr = import
+        .model_description()
+        .model_variables
+        .find_by_name("x")
+        .expect("Variable x not found")
+        .value_reference();
+
+    inst1
+        .enter_initialization_mode(None, 0.0, None)
+        .ok()
+        .unwrap();
+    inst1.exit_initialization_mode().ok().unwrap();
+
+    let mut x0 = [0.0];
+    inst1.get_float64(&[x_vr], &mut x0).unwrap();
+
+    let state0 = inst1.get_fmu_state().unwrap();
+
+    let mut x = [0.0];
+    let (mut event_handling_needed, mut terminate, mut early_return, mut last_time) =
+        (false, false, false, 0.0);
+    let mut step = |inst: &mut fmi::fmi3::instance::InstanceCS, time: f64| {
+        inst.do_step(
+            time,
+            0.1,
+            false,
+            &mut event_handling_needed,
+            &mut terminate,
+            &mut early_return,
+            &mut last_time,
+        )
+        .unwrap();
+    };
+
+    step(&mut inst1, 0.0);
+    let mut x1 = [0.0];
+    inst1.get_float64(&[x_vr], &mut x1).unwrap();
+    assert_ne!(x1, x0);
+
+    // Several checkpoints can be kept at the same time
+    let state1 = inst1.get_fmu_state().unwrap();
+    step(&mut inst1, 0.1);
+
+    inst1.set_fmu_state(&state1).unwrap();
+    inst1.get_float64(&[x_vr], &mut x).unwrap();
+    assert_eq!(x, x1);
+
+    // Rolling back restores the value of `x`
+    inst1.set_fmu_state(&state0).unwrap();
+    inst1.get_float64(&[x_vr], &mut x).unwrap();
+    assert_eq!(x, x0);
+
+    let serialized = inst1.serialize_fmu_state(&state0).unwrap();
+    assert_eq!(
+        serialized.len(),
+        inst1.serialized_fmu_state_size(&state0).unwrap()
+    );
+    inst1.free_fmu_state(state0).unwrap();
+    inst1.free_fmu_state(state1).unwrap();
+
+    step(&mut inst1, 0.0);
+
+    let state = inst1.deserialize_fmu_state(&serialized).unwrap();
+    inst1.set_fmu_state(&state).unwrap();
+    inst1.get_float64(&[x_vr], &mut x).unwrap();
+    assert_eq!(x, x0);
+
+    // A handle is rejected by any other instance
+    let mut inst2 = import
+        .instantiate_cs("inst2", false, true, false, false, &[])
+        .unwrap();
+    let state2 = inst2.get_fmu_state().unwrap();
+    assert_eq!(inst2.set_fmu_state(&state), Err(Fmi3Error::Error));
+    assert_eq!(inst2.serialize_fmu_state(&state), Err(Fmi3Error::Error));
+    assert_eq!(inst1.set_fmu_state(&state2), Err(Fmi3Error::Error));
+    assert_eq!(inst2.free_fmu_state(state), Err(Fmi3Error::Error));
+    inst2.free_fmu_state(state2).unwrap();
+}
+
+/// Test directional and adjoint derivatives with the `VanDerPol` FMU
+#[test]
+fn test_instance_van_der_pol_derivatives() {
+    let mut ref_fmus = ReferenceFmus::new().unwrap();
+    let import: Fmi3Import = ref_fmus.get_reference_fmu("VanDerPol").unwrap();
+    let mut inst1 = import.instantiate_me("inst1", false, true).unwrap();
+
+    let vr = |name: &str| {
+        import
+            .model_description()
+            .model_variables
+            .find_by_name(name)
+            .unwrap_or_else(|| panic!("Variable {name} not found"))
+            .value_reference()
+    };
+    let (x0, x1, der_x1) = (vr("x0"), vr("x1"), vr("der(x1)"));
+
+    inst1
+        .enter_initialization_mode(None, 0.0, None)
+        .ok()
+        .unwrap();
+    inst1.exit_initialization_mode().ok().unwrap();
+
+    // der(x1) = mu * (1 - x0^2) * x1 - x0, evaluated at x0 = 2, x1 = 0, mu = 1
+    let mut sensitivity = [0.0];
+    inst1
+        .get_directional_derivative(&[der_x1], &[x0], &[1.0], &mut sensitivity)
+        .unwrap();
+    assert_eq!(sensitivity, [-1.0]);
+
+    let mut sensitivity = [0.0; 2];
+    inst1
+        .get_adjoint_derivative(&[der_x1], &[x0, x1], &[1.0], &mut sensitivity)
+        .unwrap();
+    assert_eq!(sensitivity, [-1.0, -3.0]);
+}
+
+/// Derivatives are rejected for FMUs that don't declare them, such as `Dahlquist`
+#[test]
+fn test_instance_derivatives_unsupported() {
+    let mut ref_fmus = ReferenceFmus::new().unwrap();
+    let import: Fmi3Import = ref_fmus.get_reference_fmu("Dahlquist").unwrap();
+    let mut inst1 = import.instantiate_me("inst1", false, true).unwrap();
+
+    let mut sensitivity = [0.0];
+    assert!(matches!(
+        inst1.get_directional_derivative(&[2], &[1], &[1.0], &mut sensitivity),
+        Err(fmi::Error::UnsupportedInterface(_))
+    ));
+    assert!(matches!(
+        inst1.get_adjoint_derivative(&[2], &[1], &[1.0], &mut sensitivity),
+        Err(fmi::Error::UnsupportedInterface(_))
+    ));
+}